@@ -1,14 +1,53 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
+use clap::Parser;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
+use illef_workflow::common::paths::{self, PathOverrides};
+use illef_workflow::runner::RunOptions;
+
+#[derive(Parser)]
+#[command(name = "illef-workflow-runner", about = "illef-workflow scheduler daemon")]
+struct Args {
+    /// Reject mutating RPCs (trigger, delete, restore) with PermissionDenied.
+    #[arg(long)]
+    read_only: bool,
+    /// Stop the runner that is already running and take over once it has shut down.
+    #[arg(long)]
+    replace: bool,
+    /// Directory of config.yaml, scripts/ and plugins/ [default: $XDG_CONFIG_HOME/illef-workflow]
+    #[arg(long, env = "ILLEF_WORKFLOW_CONFIG_DIR", value_name = "DIR")]
+    config_dir: Option<PathBuf>,
+    /// Directory of the database and logs [default: $XDG_CACHE_HOME/illef-workflow]
+    #[arg(long, env = "ILLEF_WORKFLOW_CACHE_DIR", value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+    /// The runner's Unix socket [default: $XDG_RUNTIME_DIR/illef-workflow.sock]
+    #[arg(long, env = "ILLEF_WORKFLOW_SOCKET", value_name = "PATH")]
+    socket: Option<PathBuf>,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args = Args::parse();
+    paths::init(PathOverrides {
+        config_dir: args.config_dir,
+        cache_dir: args.cache_dir,
+        socket: args.socket,
+    });
+
     tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive("info".parse()?))
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
         .init();
 
     info!("illef-workflow runner starting");
 
-    illef_workflow::runner::run().await
+    illef_workflow::runner::run(RunOptions {
+        read_only: args.read_only,
+        replace: args.replace,
+    })
+    .await
 }
@@ -1,6 +1,380 @@
-use anyhow::Result;
+use std::io::Read;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{Local, TimeZone};
+use clap::{Parser, Subcommand};
+
+use illef_workflow::common::duration::parse_duration;
+use illef_workflow::common::paths::{self, PathOverrides};
+use illef_workflow::common::types::TriggerSource;
+use illef_workflow::proto::{AdhocRequest, DryRunPlan};
+use illef_workflow::runner::server::MAX_STDIN;
+use illef_workflow::tui::ViewOptions;
+use illef_workflow::tui::client::{self, ConnectOptions};
+use illef_workflow::tui::health;
+use illef_workflow::tui::report::{self, ReportOptions};
+use illef_workflow::tui::schedule_override::{self, OverrideOptions};
+use illef_workflow::tui::status::{self, StatusOptions};
+
+#[derive(Parser)]
+#[command(name = "illef-workflow-tui", about = "illef-workflow terminal UI")]
+struct Args {
+    /// Runner TCP address (e.g. http://server:50051). Defaults to the local Unix socket.
+    #[arg(long, env = "ILLEF_WORKFLOW_ADDR")]
+    addr: Option<String>,
+    /// API token for the TCP listener.
+    #[arg(long, env = "ILLEF_WORKFLOW_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    /// Show logs without ANSI colors instead of rendering them. Also set by NO_COLOR.
+    #[arg(long)]
+    strip_ansi: bool,
+    /// Directory of config.yaml, scripts/ and plugins/ [default: $XDG_CONFIG_HOME/illef-workflow]
+    #[arg(long, env = "ILLEF_WORKFLOW_CONFIG_DIR", value_name = "DIR")]
+    config_dir: Option<PathBuf>,
+    /// Directory of the database and logs [default: $XDG_CACHE_HOME/illef-workflow]
+    #[arg(long, env = "ILLEF_WORKFLOW_CACHE_DIR", value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+    /// The runner's Unix socket [default: $XDG_RUNTIME_DIR/illef-workflow.sock]
+    #[arg(long, env = "ILLEF_WORKFLOW_SOCKET", value_name = "PATH")]
+    socket: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a Markdown or HTML report of past executions instead of starting the UI.
+    Report {
+        /// Start of the range: a duration ago (7d, 12h), a date (2024-05-01) or RFC 3339.
+        #[arg(long, default_value = "7d")]
+        since: String,
+        /// End of the range, same formats as --since. Defaults to now.
+        #[arg(long)]
+        until: Option<String>,
+        /// markdown or html.
+        #[arg(long, default_value = "markdown")]
+        format: String,
+        /// Only include this workflow; repeatable. Defaults to all.
+        #[arg(long = "workflow")]
+        workflows: Vec<String>,
+        /// Write the report to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print the latest status of each workflow, one per line.
+    Status {
+        /// Only show this workflow, and exit with 1 if its latest execution failed (2 if the
+        /// status can't be read), for shell prompts and status lines.
+        #[arg(long, value_name = "WORKFLOW")]
+        exit_code_from: Option<String>,
+    },
+    /// Print the runner's health; exit with 1 if its scheduler or database doesn't answer (2 if
+    /// the runner can't be reached), for monitoring.
+    Health,
+    /// Trigger a workflow now, as `r` does in the UI.
+    Run {
+        workflow: String,
+        /// Feed this file to the script on stdin instead of its `stdin_file`; `-` reads this
+        /// command's own stdin.
+        #[arg(long)]
+        stdin_file: Option<PathBuf>,
+        /// Print what would run, with its working directory and environment, instead of running
+        /// it; exit with 1 if the run would fail to start.
+        #[arg(long)]
+        dry_run: bool,
+        /// Arguments replacing the workflow's configured `args`.
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Run a script that isn't in the config through the runner, as a workflow of its own, for
+    /// its log, history and notifications.
+    RunAdhoc {
+        /// The script; a relative path is taken from the current directory.
+        #[arg(long)]
+        script: PathBuf,
+        /// The workflow name to run it under. Defaults to the script's file name without its
+        /// extension.
+        #[arg(long)]
+        name: Option<String>,
+        /// Drop the workflow from the runner's list once the run ends; its history stays.
+        #[arg(long)]
+        forget: bool,
+        /// Feed this file to the script on stdin; `-` reads this command's own stdin.
+        #[arg(long)]
+        stdin_file: Option<PathBuf>,
+        /// Arguments for the script.
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Skip a workflow's next scheduled run, and only that one; manual triggers still run.
+    Snooze {
+        workflow: String,
+        /// Refuse if the next run is further off than this, e.g. 12h, so a run days away isn't
+        /// skipped by mistake.
+        within: Option<String>,
+    },
+    /// Pin a successful run as the workflow's baseline; later runs that take much longer or log
+    /// many more errors than it are flagged.
+    Baseline {
+        workflow: String,
+        /// The run to pin; the latest successful one if left out.
+        execution_id: Option<String>,
+        /// Unpin the baseline instead.
+        #[arg(long, conflicts_with = "execution_id")]
+        clear: bool,
+    },
+    /// Change a workflow's schedule for a while without editing the config: run it at another
+    /// interval, or snooze it, until the override runs out.
+    Override {
+        workflow: String,
+        /// Run at this interval in place of the schedule, e.g. 10m.
+        #[arg(long, conflicts_with = "snooze")]
+        every: Option<String>,
+        /// Skip scheduled runs; manual triggers still run.
+        #[arg(long)]
+        snooze: bool,
+        /// How long the override lasts, e.g. 2h.
+        #[arg(long = "for", value_name = "DURATION", conflicts_with = "until")]
+        duration: Option<String>,
+        /// When the override ends: a date (2024-05-06, local midnight), a local time
+        /// (2024-05-06 09:00) or RFC 3339.
+        #[arg(long)]
+        until: Option<String>,
+        /// Drop the override now and go back to the schedule.
+        #[arg(long, conflicts_with_all = ["every", "snooze", "duration", "until"])]
+        clear: bool,
+    },
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    illef_workflow::tui::run().await
+    let args = Args::parse();
+    paths::init(PathOverrides {
+        config_dir: args.config_dir,
+        cache_dir: args.cache_dir,
+        socket: args.socket,
+    });
+    let options = ConnectOptions {
+        addr: args.addr,
+        token: args.token,
+    };
+    match args.command {
+        Some(Command::Report {
+            since,
+            until,
+            format,
+            workflows,
+            output,
+        }) => {
+            let report = ReportOptions {
+                since,
+                until,
+                format,
+                workflows,
+                output,
+            };
+            report::run(&options, report).await
+        }
+        Some(Command::Status { exit_code_from }) => {
+            match status::run(&options, StatusOptions { exit_code_from }).await {
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("Error: {:#}", e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        Some(Command::Health) => match health::run(&options).await {
+            Ok(code) => std::process::exit(code),
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                std::process::exit(2);
+            }
+        },
+        Some(Command::Run {
+            workflow,
+            stdin_file,
+            dry_run,
+            args,
+        }) => {
+            let stdin = read_stdin_file(stdin_file)?;
+            let mut client = client::connect(&options)?;
+            if dry_run {
+                let response =
+                    client::dry_run(&mut client, &workflow, args, stdin, TriggerSource::Cli)
+                        .await
+                        .map_err(without_envelope)?;
+                println!("{}", response.message);
+                let plan = response.dry_run.unwrap_or_default();
+                print_dry_run(&plan);
+                std::process::exit(if plan.problems.is_empty() { 0 } else { 1 });
+            }
+            let response =
+                client::trigger_workflow(&mut client, &workflow, args, stdin, TriggerSource::Cli)
+                    .await
+                    .map_err(without_envelope)?;
+            println!("{}", response.message);
+            Ok(())
+        }
+        Some(Command::RunAdhoc {
+            script,
+            name,
+            forget,
+            stdin_file,
+            args,
+        }) => {
+            let script = std::path::absolute(&script)
+                .with_context(|| format!("invalid script path {}", script.display()))?;
+            let name = match name {
+                Some(name) => name,
+                None => script
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .ok_or_else(|| anyhow!("no name in {}; pass --name", script.display()))?,
+            };
+            // in the caller's directory, as if it were run from this shell
+            let cwd = std::env::current_dir().map(|d| d.to_string_lossy().to_string());
+            let request = AdhocRequest {
+                name,
+                script: script.to_string_lossy().to_string(),
+                args,
+                stdin: read_stdin_file(stdin_file)?,
+                cwd: cwd.unwrap_or_default(),
+                forget,
+                source: TriggerSource::Cli.as_str().to_string(),
+            };
+            let mut client = client::connect(&options)?;
+            let response = client::run_adhoc(&mut client, request).await.map_err(without_envelope)?;
+            println!("{}", response.message);
+            Ok(())
+        }
+        Some(Command::Snooze { workflow, within }) => {
+            let within = within.as_deref().map(parse_duration).transpose()?.unwrap_or_default();
+            let mut client = client::connect(&options)?;
+            let response = client::snooze_next_run(&mut client, &workflow, within)
+                .await
+                .map_err(without_envelope)?;
+            let skipped = Local.timestamp_opt(response.skipped_at, 0).unwrap();
+            println!(
+                "{}: skipping the run at {} ({})",
+                workflow,
+                skipped.format("%Y-%m-%d %H:%M"),
+                response.schedule_override
+            );
+            Ok(())
+        }
+        Some(Command::Baseline { workflow, execution_id, clear }) => {
+            let mut client = client::connect(&options)?;
+            let id = execution_id.unwrap_or_default();
+            let response = client::set_baseline(&mut client, &workflow, &id, clear)
+                .await
+                .map_err(without_envelope)?;
+            match (clear, response.changed) {
+                (true, true) => println!("{}: baseline unpinned", workflow),
+                (true, false) => println!("{}: no baseline was pinned", workflow),
+                (false, true) => {
+                    println!("{}: pinned {} as the baseline", workflow, response.execution_id)
+                }
+                (false, false) => {
+                    println!("{}: {} is already the baseline", workflow, response.execution_id)
+                }
+            }
+            Ok(())
+        }
+        Some(Command::Override {
+            workflow,
+            every,
+            snooze,
+            duration,
+            until,
+            clear,
+        }) => {
+            let change = OverrideOptions {
+                workflow,
+                every,
+                snooze,
+                duration,
+                until,
+                clear,
+            };
+            schedule_override::run(&options, change).await.map_err(without_envelope)
+        }
+        None => {
+            let view = ViewOptions {
+                strip_ansi: args.strip_ansi
+                    || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()),
+            };
+            illef_workflow::tui::run(options, view).await
+        }
+    }
+}
+
+/// The bytes of `--stdin-file`: a file, this command's stdin for `-`, or nothing.
+fn read_stdin_file(path: Option<PathBuf>) -> Result<Vec<u8>> {
+    let mut input = Vec::new();
+    // one byte past the limit tells a file that fits from one that doesn't
+    let limit = MAX_STDIN as u64 + 1;
+    match path {
+        Some(path) if path.as_os_str() == "-" => {
+            std::io::stdin()
+                .take(limit)
+                .read_to_end(&mut input)
+                .context("failed to read stdin")?;
+        }
+        Some(path) => {
+            std::fs::File::open(&path)
+                .and_then(|file| file.take(limit).read_to_end(&mut input))
+                .with_context(|| format!("failed to read {}", path.display()))?;
+        }
+        None => {}
+    }
+    if input.len() > MAX_STDIN {
+        bail!("--stdin-file is larger than {} MiB", MAX_STDIN / (1024 * 1024));
+    }
+    Ok(input)
+}
+
+/// Each command of a dry run with where and how it would run, then what would stop it.
+fn print_dry_run(plan: &DryRunPlan) {
+    for command in &plan.commands {
+        let argv: Vec<String> = command
+            .argv
+            .iter()
+            .map(|a| match a.is_empty() || a.contains(char::is_whitespace) {
+                true => format!("{:?}", a),
+                false => a.clone(),
+            })
+            .collect();
+        println!();
+        if !command.step.is_empty() {
+            println!("step      {}", command.step);
+        }
+        println!("command   {}", argv.join(" "));
+        let cwd = match command.cwd.as_str() {
+            "" => "the runner's working directory",
+            cwd => cwd,
+        };
+        println!("cwd       {}", cwd);
+        for (i, var) in command.env.iter().enumerate() {
+            println!("{:<10}{}", if i == 0 { "env" } else { "" }, var);
+        }
+    }
+    if !plan.stdin.is_empty() {
+        println!("\nstdin     {}", plan.stdin);
+    }
+    if !plan.problems.is_empty() {
+        println!();
+    }
+    for problem in &plan.problems {
+        println!("problem   {}", problem);
+    }
+}
+
+/// Messages like "ambiguous name ...: matches ..." read better without the gRPC envelope.
+fn without_envelope(e: anyhow::Error) -> anyhow::Error {
+    match e.downcast_ref::<tonic::Status>() {
+        Some(status) => anyhow!("{}", status.message()),
+        None => e,
+    }
 }
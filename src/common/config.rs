@@ -1,40 +1,196 @@
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::de::DeserializeOwned;
 use tokio::sync::mpsc;
+use tracing::warn;
 
+use super::{discover, include, paths, template, upgrade};
 use super::types::AppConfig;
 
+/// `config.yaml`, or `config.toml` when only that one exists.
 pub fn config_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/root".to_string());
-    PathBuf::from(home)
-        .join(".config")
-        .join("illef-workflow")
-        .join("config.yaml")
+    let (yaml, toml) = config_files();
+    if !yaml.exists() && toml.exists() {
+        toml
+    } else {
+        yaml
+    }
+}
+
+fn config_files() -> (PathBuf, PathBuf) {
+    let dir = &paths::get().config_dir;
+    (dir.join("config.yaml"), dir.join("config.toml"))
+}
+
+/// The config file's name, for messages.
+pub fn config_name() -> String {
+    config_path().file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
 }
 
 pub fn scripts_dir() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/root".to_string());
-    PathBuf::from(home)
-        .join(".config")
-        .join("illef-workflow")
-        .join("scripts")
+    paths::get().config_dir.join("scripts")
+}
+
+/// This machine's name, as recorded with executions and distributed locks.
+pub fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|h| h.trim().to_string())
+        .ok()
+        .filter(|h| !h.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Where plugins are looked up by name.
+pub fn plugins_dir() -> PathBuf {
+    paths::get().config_dir.join("plugins")
+}
+
+/// Expand a leading `~` and resolve relative paths against the scripts directory.
+pub fn resolve_path(path: &str) -> PathBuf {
+    let expanded = expand_home(path);
+    if expanded.is_absolute() {
+        expanded
+    } else {
+        scripts_dir().join(expanded)
+    }
+}
+
+/// Expand a leading `~` and resolve relative paths against the config directory.
+pub fn config_dir_path(path: &str) -> PathBuf {
+    let expanded = expand_home(path);
+    if expanded.is_absolute() {
+        expanded
+    } else {
+        paths::get().config_dir.join(expanded)
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~") {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            PathBuf::from(format!("{}{}", paths::home().display(), rest))
+        }
+        _ => PathBuf::from(path),
+    }
 }
 
 pub fn load_config() -> Result<AppConfig> {
+    let (yaml, toml) = config_files();
+    if yaml.exists() && toml.exists() {
+        let dir = paths::get().config_dir.display();
+        bail!("both config.yaml and config.toml are in {}; keep one", dir);
+    }
     let path = config_path();
+    let name = config_name();
     let content = std::fs::read_to_string(&path)
         .with_context(|| format!("failed to read config file: {}", path.display()))?;
-    let config: AppConfig =
-        serde_yaml::from_str(&content).with_context(|| "failed to parse config.yaml")?;
+    let is_toml = path == toml;
+    let mut value: serde_yaml::Value =
+        parse(&content, is_toml).with_context(|| format!("failed to parse {}", name))?;
+    let version =
+        upgrade::upgrade(&mut value).with_context(|| format!("failed to upgrade {}", name))?;
+    // parsed from the text when nothing changed, so errors point at their line
+    let parsed = if version == upgrade::CONFIG_VERSION {
+        parse(&content, is_toml)
+    } else {
+        serde_yaml::from_value(value).map_err(Into::into)
+    };
+    let mut config: AppConfig = parsed.with_context(|| format!("failed to parse {}", name))?;
+    for path in include::files(&config.include)? {
+        let included = include::load(&path, version)?;
+        config.workflows.extend(included.workflows);
+        config.templates.extend(included.templates);
+    }
+    let expanded = template::expand(&config.templates)?;
+    config.workflows.extend(expanded);
+    if config.discover_scripts {
+        let taken: Vec<&str> = config.workflows.iter().flat_map(|wf| wf.scripts()).collect();
+        let discovered = discover::scan(&scripts_dir(), &taken);
+        config.workflows.extend(discovered);
+    }
+    template::check_names(&config.workflows)?;
+    for wf in &config.workflows {
+        let set = [!wf.script.is_empty(), wf.script_inline.is_some(), !wf.steps.is_empty()];
+        match set.iter().filter(|s| **s).count() {
+            0 => bail!("workflow {}: set `script`, `script_inline` or `steps`", wf.name),
+            1 => {}
+            _ => bail!(
+                "workflow {}: set only one of `script`, `script_inline` and `steps`",
+                wf.name
+            ),
+        }
+        let limits = std::iter::once(&wf.limits).chain(wf.steps.iter().map(|s| &s.limits));
+        if limits.filter_map(|l| l.nice).any(|n| !(-20..=19).contains(&n)) {
+            bail!("workflow {}: `nice` must be between -20 and 19", wf.name);
+        }
+    }
     Ok(config)
 }
 
-pub fn watch_config(tx: mpsc::Sender<()>) -> Result<RecommendedWatcher> {
+/// The same structs read either way; TOML has no null, so optional keys are left out instead.
+pub fn parse<T: DeserializeOwned>(content: &str, toml: bool) -> Result<T> {
+    if toml {
+        Ok(toml::from_str(content)?)
+    } else {
+        Ok(serde_yaml::from_str(content)?)
+    }
+}
+
+/// Watches the config file and what it includes; see [`watch_config`].
+pub struct ConfigWatcher {
+    watcher: RecommendedWatcher,
+    /// Directories of `include` patterns being watched.
+    includes: Vec<(PathBuf, RecursiveMode)>,
+}
+
+impl ConfigWatcher {
+    /// Watch the directories of `config`'s includes, and stop watching those it no longer has.
+    /// One that doesn't exist yet is picked up on the reload its creation causes.
+    pub fn update(&mut self, config: &AppConfig) {
+        let wanted: Vec<_> = include::watch_dirs(&config.include)
+            .into_iter()
+            .filter(|(dir, mode)| {
+                !(*dir == paths::get().config_dir && *mode == RecursiveMode::NonRecursive)
+            })
+            .filter(|(dir, _)| dir.is_dir())
+            .collect();
+        for (dir, _) in self.includes.iter().filter(|w| !wanted.contains(w)) {
+            let _ = self.watcher.unwatch(dir);
+        }
+        self.includes.retain(|w| wanted.contains(w));
+        for (dir, mode) in wanted {
+            if self.includes.contains(&(dir.clone(), mode)) {
+                continue;
+            }
+            match self.watcher.watch(&dir, mode) {
+                Ok(()) => self.includes.push((dir, mode)),
+                Err(e) => warn!(path = %dir.display(), error = %e, "failed to watch includes"),
+            }
+        }
+    }
+}
+
+/// Signal `tx` when the config file or an included one changes, or a script while `discover`
+/// is set (for `discover_scripts`).
+pub fn watch_config(
+    tx: mpsc::Sender<()>,
+    discover: Arc<AtomicBool>,
+    config: &AppConfig,
+) -> Result<ConfigWatcher> {
     let path = config_path();
+    let scripts = scripts_dir();
+    let scripts_clone = scripts.clone();
     let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
         if let Ok(event) = res {
+            let script_only = event.paths.iter().all(|p| p.starts_with(&scripts_clone));
+            if script_only && !discover.load(Ordering::Relaxed) {
+                return;
+            }
             if matches!(
                 event.kind,
                 EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
@@ -47,6 +203,14 @@ pub fn watch_config(tx: mpsc::Sender<()>) -> Result<RecommendedWatcher> {
     if let Some(parent) = path.parent() {
         watcher.watch(parent, RecursiveMode::NonRecursive)?;
     }
+    if scripts.is_dir() {
+        watcher.watch(&scripts, RecursiveMode::NonRecursive)?;
+    }
 
+    let mut watcher = ConfigWatcher {
+        watcher,
+        includes: Vec::new(),
+    };
+    watcher.update(config);
     Ok(watcher)
 }
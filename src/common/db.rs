@@ -1,40 +1,101 @@
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Row, params};
+use tokio::sync::Semaphore;
+use tracing::warn;
 
-use super::types::{Execution, ExecutionStatus};
+use super::paths;
+use super::storage::Storage;
+use super::types::{
+    CollapsedRepeat, ConfigChange, DbStats, DurationSample, Execution, ExecutionStatus,
+    LogSeverity, MaintenanceReport, ScheduleOverride, StepResult,
+};
 
 pub fn db_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/root".to_string());
-    PathBuf::from(home)
-        .join(".cache")
-        .join("illef-workflow")
-        .join("storage.sqlite")
+    paths::get().cache_dir.join("storage.sqlite")
 }
 
 pub fn logs_dir() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/root".to_string());
-    PathBuf::from(home)
-        .join(".cache")
-        .join("illef-workflow")
-        .join("logs")
+    paths::get().cache_dir.join("logs")
 }
 
+/// Connections kept open by [`SqliteStorage`]. In WAL mode readers never wait for a writer,
+/// so listing workflows stays quick while executions are being recorded.
+const POOL_SIZE: usize = 4;
+
+/// How long a write waits for another connection's write to finish before failing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub fn open_db() -> Result<Connection> {
     let path = db_path();
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    let conn = Connection::open(&path)
-        .with_context(|| format!("failed to open database: {}", path.display()))?;
+    let conn = open_connection(&path)?;
     init_schema(&conn)?;
     Ok(conn)
 }
 
+fn open_connection(path: &std::path::Path) -> Result<Connection> {
+    let conn = Connection::open(path)
+        .with_context(|| format!("failed to open database: {}", path.display()))?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    Ok(conn)
+}
+
+/// One step per schema change: `MIGRATIONS[i]` takes a database from version `i` to `i + 1`,
+/// noted in `PRAGMA user_version`. Append new steps; never change released ones.
+const MIGRATIONS: &[fn(&Connection) -> Result<()>] = &[
+    baseline,
+    schedule_overrides_table,
+    baselines_table,
+    config_changes_tables,
+    repeats_column,
+];
+
+/// The schema version this build reads and writes.
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// Run the migrations the database hasn't had, each in a transaction of its own.
 fn init_schema(conn: &Connection) -> Result<()> {
+    let version = schema_version(conn)?;
+    for (step, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let tx = conn.unchecked_transaction()?;
+        migrate(&tx)
+            .with_context(|| format!("failed to migrate the database to version {}", step + 1))?;
+        tx.pragma_update(None, "user_version", step as i64 + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// The database's schema version, refusing one written by a newer build that the queries
+/// here would misread.
+fn schema_version(conn: &Connection) -> Result<i64> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        bail!(
+            "the database is at schema version {}, but this illef-workflow only knows up to {}; \
+             upgrade illef-workflow",
+            version,
+            SCHEMA_VERSION
+        );
+    }
+    Ok(version)
+}
+
+/// The schema as of versioning. Databases made before it may be at any earlier layout, so
+/// this brings whatever it finds up to date.
+fn baseline(conn: &Connection) -> Result<()> {
     conn.execute_batch(
         "CREATE TABLE IF NOT EXISTS executions (
             id          TEXT PRIMARY KEY,
@@ -46,15 +107,137 @@ fn init_schema(conn: &Connection) -> Result<()> {
             log_path    TEXT NOT NULL
         );
         CREATE INDEX IF NOT EXISTS idx_executions_workflow
-            ON executions(workflow, started_at DESC);",
+            ON executions(workflow, started_at DESC);
+        CREATE TABLE IF NOT EXISTS paused_workflows (
+            workflow    TEXT PRIMARY KEY,
+            paused_at   INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS maintenance_runs (
+            ran_at      INTEGER NOT NULL,
+            size_before INTEGER NOT NULL,
+            size_after  INTEGER NOT NULL
+        );",
+    )?;
+    ensure_column(conn, "executions", "deleted_at", "INTEGER")?;
+    ensure_column(conn, "executions", "args", "TEXT NOT NULL DEFAULT '[]'")?;
+    ensure_column(conn, "executions", "result_json", "TEXT")?;
+    ensure_column(conn, "executions", "error_count", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "executions", "warning_count", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "executions", "first_error_line", "INTEGER")?;
+    ensure_column(conn, "executions", "trigger_source", "TEXT")?;
+    ensure_column(conn, "executions", "binary_output", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "executions", "steps_json", "TEXT")?;
+    ensure_column(conn, "executions", "host", "TEXT")?;
+    Ok(())
+}
+
+/// Temporary schedule changes, one per workflow.
+fn schedule_overrides_table(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE schedule_overrides (
+            workflow    TEXT PRIMARY KEY,
+            every_ms    INTEGER,
+            until_at    INTEGER NOT NULL,
+            created_at  INTEGER NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+/// Pinned baseline executions, one per workflow, and how each run strayed from its baseline.
+fn baselines_table(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE baselines (
+            workflow     TEXT PRIMARY KEY,
+            execution_id TEXT NOT NULL,
+            pinned_at    INTEGER NOT NULL
+        );
+        ALTER TABLE executions ADD COLUMN baseline_deviation TEXT;",
+    )?;
+    Ok(())
+}
+
+/// Each workflow's config as last loaded, and the changes noticed between loads.
+fn config_changes_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE config_snapshots (
+            workflow    TEXT PRIMARY KEY,
+            config      TEXT NOT NULL
+        );
+        CREATE TABLE config_changes (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            workflow    TEXT NOT NULL,
+            changed_at  INTEGER NOT NULL,
+            summary     TEXT NOT NULL
+        );
+        CREATE INDEX idx_config_changes_workflow ON config_changes(workflow, changed_at DESC);",
     )?;
     Ok(())
 }
 
+/// The lines `collapse_repeats` dropped from each execution's log.
+fn repeats_column(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE executions ADD COLUMN repeats_json TEXT;")?;
+    Ok(())
+}
+
+/// Add a column to an existing table if it is missing (databases created by older versions).
+fn ensure_column(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|c| c.ok())
+        .any(|c| c == column);
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl))?;
+    }
+    Ok(())
+}
+
+const EXECUTION_COLUMNS: &str = "id, workflow, status, started_at, finished_at, exit_code, \
+    log_path, args, result_json, error_count, warning_count, first_error_line, trigger_source, \
+    binary_output, steps_json, host, baseline_deviation, repeats_json";
+
+fn execution_from_row(row: &Row) -> rusqlite::Result<Execution> {
+    let status_str: String = row.get(2)?;
+    let started_ts: i64 = row.get(3)?;
+    let finished_ts: Option<i64> = row.get(4)?;
+    let args_json: String = row.get(7)?;
+    Ok(Execution {
+        id: row.get(0)?,
+        workflow: row.get(1)?,
+        status: ExecutionStatus::from_str(&status_str).unwrap_or(ExecutionStatus::Failed),
+        started_at: Utc.timestamp_opt(started_ts, 0).unwrap(),
+        finished_at: finished_ts.map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
+        exit_code: row.get(5)?,
+        log_path: row.get(6)?,
+        args: serde_json::from_str(&args_json).unwrap_or_default(),
+        result_json: row.get(8)?,
+        severity: LogSeverity {
+            errors: row.get(9)?,
+            warnings: row.get(10)?,
+            first_error_line: row.get(11)?,
+        },
+        trigger: row.get::<_, Option<String>>(12)?.and_then(|s| s.parse().ok()),
+        binary_output: row.get(13)?,
+        steps: row
+            .get::<_, Option<String>>(14)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        host: row.get(15)?,
+        deviation: row.get(16)?,
+        repeats: row
+            .get::<_, Option<String>>(17)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+    })
+}
+
 pub fn insert_execution(conn: &Connection, exec: &Execution) -> Result<()> {
     conn.execute(
-        "INSERT INTO executions (id, workflow, status, started_at, finished_at, exit_code, log_path)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO executions (id, workflow, status, started_at, finished_at, exit_code, log_path, args,
+                                 trigger_source, host)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
         params![
             exec.id,
             exec.workflow,
@@ -63,6 +246,9 @@ pub fn insert_execution(conn: &Connection, exec: &Execution) -> Result<()> {
             exec.finished_at.map(|t| t.timestamp()),
             exec.exit_code,
             exec.log_path,
+            serde_json::to_string(&exec.args)?,
+            exec.trigger.map(|t| t.as_str()),
+            exec.host,
         ],
     )?;
     Ok(())
@@ -87,43 +273,88 @@ pub fn update_execution_finished(
     Ok(())
 }
 
+pub fn set_execution_result(conn: &Connection, id: &str, result_json: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE executions SET result_json = ?1 WHERE id = ?2",
+        params![result_json, id],
+    )?;
+    Ok(())
+}
+
+pub fn set_execution_severity(conn: &Connection, id: &str, severity: &LogSeverity) -> Result<()> {
+    conn.execute(
+        "UPDATE executions SET error_count = ?1, warning_count = ?2, first_error_line = ?3
+         WHERE id = ?4",
+        params![severity.errors, severity.warnings, severity.first_error_line, id],
+    )?;
+    Ok(())
+}
+
+pub fn set_execution_binary_output(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute("UPDATE executions SET binary_output = 1 WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn set_execution_deviation(conn: &Connection, id: &str, deviation: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE executions SET baseline_deviation = ?1 WHERE id = ?2",
+        params![deviation, id],
+    )?;
+    Ok(())
+}
+
+pub fn set_execution_steps(conn: &Connection, id: &str, steps: &[StepResult]) -> Result<()> {
+    conn.execute(
+        "UPDATE executions SET steps_json = ?1 WHERE id = ?2",
+        params![serde_json::to_string(steps)?, id],
+    )?;
+    Ok(())
+}
+
+pub fn set_execution_repeats(
+    conn: &Connection,
+    id: &str,
+    repeats: &[CollapsedRepeat],
+) -> Result<()> {
+    conn.execute(
+        "UPDATE executions SET repeats_json = ?1 WHERE id = ?2",
+        params![serde_json::to_string(repeats)?, id],
+    )?;
+    Ok(())
+}
+
 pub fn get_executions(conn: &Connection, workflow: &str, limit: usize) -> Result<Vec<Execution>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM executions
+         WHERE workflow = ?1 AND deleted_at IS NULL
+         ORDER BY started_at DESC
+         LIMIT ?2",
+        EXECUTION_COLUMNS
+    ))?;
+
+    let rows = stmt.query_map(params![workflow, limit as i64], execution_from_row)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn get_durations(conn: &Connection, workflow: &str, limit: usize) -> Result<Vec<DurationSample>> {
     let mut stmt = conn.prepare(
-        "SELECT id, workflow, status, started_at, finished_at, exit_code, log_path
+        "SELECT started_at, finished_at - started_at, status
          FROM executions
-         WHERE workflow = ?1
+         WHERE workflow = ?1 AND finished_at IS NOT NULL AND deleted_at IS NULL
          ORDER BY started_at DESC
          LIMIT ?2",
     )?;
 
     let rows = stmt.query_map(params![workflow, limit as i64], |row| {
-        Ok((
-            row.get::<_, String>(0)?,
-            row.get::<_, String>(1)?,
-            row.get::<_, String>(2)?,
-            row.get::<_, i64>(3)?,
-            row.get::<_, Option<i64>>(4)?,
-            row.get::<_, Option<i32>>(5)?,
-            row.get::<_, String>(6)?,
-        ))
+        let status_str: String = row.get(2)?;
+        Ok(DurationSample {
+            started_at: Utc.timestamp_opt(row.get(0)?, 0).unwrap(),
+            duration_secs: row.get(1)?,
+            status: ExecutionStatus::from_str(&status_str).unwrap_or(ExecutionStatus::Failed),
+        })
     })?;
-
-    let mut executions = Vec::new();
-    for row in rows {
-        let (id, workflow, status_str, started_ts, finished_ts, exit_code, log_path) = row?;
-        executions.push(Execution {
-            id,
-            workflow,
-            status: ExecutionStatus::from_str(&status_str)
-                .unwrap_or(ExecutionStatus::Failed),
-            started_at: Utc.timestamp_opt(started_ts, 0).unwrap(),
-            finished_at: finished_ts.map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
-            exit_code,
-            log_path,
-        });
-    }
-
-    Ok(executions)
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
 }
 
 pub fn get_last_execution(conn: &Connection, workflow: &str) -> Result<Option<Execution>> {
@@ -132,35 +363,546 @@ pub fn get_last_execution(conn: &Connection, workflow: &str) -> Result<Option<Ex
 }
 
 pub fn get_execution_by_id(conn: &Connection, id: &str) -> Result<Option<Execution>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM executions WHERE id = ?1 AND deleted_at IS NULL",
+        EXECUTION_COLUMNS
+    ))?;
+
+    Ok(stmt.query_row(params![id], execution_from_row).optional()?)
+}
+
+/// Executions still marked running, deleted or not.
+pub fn running_executions(conn: &Connection) -> Result<Vec<Execution>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM executions WHERE status = 'running' ORDER BY started_at",
+        EXECUTION_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], execution_from_row)?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Hide an execution from history. The row and its log are kept until the grace period expires.
+pub fn soft_delete_execution(conn: &Connection, id: &str, deleted_at: DateTime<Utc>) -> Result<bool> {
+    let changed = conn.execute(
+        "UPDATE executions SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+        params![deleted_at.timestamp(), id],
+    )?;
+    Ok(changed > 0)
+}
+
+pub fn restore_execution(conn: &Connection, id: &str, workflow: &str) -> Result<bool> {
+    let changed = conn.execute(
+        "UPDATE executions SET deleted_at = NULL \
+         WHERE id = ?1 AND deleted_at IS NOT NULL AND (?2 = '' OR workflow = ?2)",
+        params![id, workflow],
+    )?;
+    Ok(changed > 0)
+}
+
+/// Restore every soft-deleted execution of a workflow that has not been purged yet.
+pub fn restore_workflow_executions(conn: &Connection, workflow: &str) -> Result<usize> {
+    let changed = conn.execute(
+        "UPDATE executions SET deleted_at = NULL WHERE workflow = ?1 AND deleted_at IS NOT NULL",
+        params![workflow],
+    )?;
+    Ok(changed)
+}
+
+/// Permanently remove executions soft-deleted before `cutoff`, returning their log paths.
+pub fn purge_deleted(conn: &Connection, cutoff: DateTime<Utc>) -> Result<Vec<String>> {
     let mut stmt = conn.prepare(
-        "SELECT id, workflow, status, started_at, finished_at, exit_code, log_path
-         FROM executions WHERE id = ?1",
-    )?;
-
-    let mut rows = stmt.query_map(params![id], |row| {
-        Ok((
-            row.get::<_, String>(0)?,
-            row.get::<_, String>(1)?,
-            row.get::<_, String>(2)?,
-            row.get::<_, i64>(3)?,
-            row.get::<_, Option<i64>>(4)?,
-            row.get::<_, Option<i32>>(5)?,
-            row.get::<_, String>(6)?,
-        ))
-    })?;
+        "DELETE FROM executions WHERE deleted_at IS NOT NULL AND deleted_at < ?1 RETURNING log_path",
+    )?;
+    let rows = stmt.query_map(params![cutoff.timestamp()], |row| row.get::<_, String>(0))?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
 
-    if let Some(row) = rows.next() {
-        let (id, workflow, status_str, started_ts, finished_ts, exit_code, log_path) = row?;
-        Ok(Some(Execution {
-            id,
-            workflow,
-            status: ExecutionStatus::from_str(&status_str).unwrap_or(ExecutionStatus::Failed),
-            started_at: Utc.timestamp_opt(started_ts, 0).unwrap(),
-            finished_at: finished_ts.map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
-            exit_code,
-            log_path,
-        }))
+pub fn prune_executions(
+    conn: &Connection,
+    keep: Option<usize>,
+    cutoff: Option<DateTime<Utc>>,
+) -> Result<Vec<String>> {
+    let keep = keep.map_or(i64::MAX, |k| k as i64);
+    let cutoff = cutoff.map_or(i64::MIN, |c| c.timestamp());
+    let mut stmt = conn.prepare(
+        "DELETE FROM executions WHERE id IN (
+            SELECT id FROM (
+                SELECT id, started_at, finished_at,
+                       ROW_NUMBER() OVER (PARTITION BY workflow ORDER BY started_at DESC)
+                           AS row_num
+                FROM executions
+            )
+            WHERE finished_at IS NOT NULL AND (row_num > ?1 OR started_at < ?2)
+        ) AND id NOT IN (SELECT execution_id FROM baselines) RETURNING log_path",
+    )?;
+    let rows = stmt.query_map(params![keep, cutoff], |row| row.get::<_, String>(0))?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn prune_config_changes(
+    conn: &Connection,
+    keep: Option<usize>,
+    cutoff: Option<DateTime<Utc>>,
+) -> Result<usize> {
+    let keep = keep.map_or(i64::MAX, |k| k as i64);
+    let cutoff = cutoff.map_or(i64::MIN, |c| c.timestamp());
+    Ok(conn.execute(
+        "DELETE FROM config_changes WHERE id IN (
+            SELECT id FROM (
+                SELECT id, changed_at,
+                       ROW_NUMBER() OVER (PARTITION BY workflow ORDER BY changed_at DESC, id DESC)
+                           AS row_num
+                FROM config_changes
+            )
+            WHERE row_num > ?1 OR changed_at < ?2
+        )",
+        params![keep, cutoff],
+    )?)
+}
+
+fn db_size(conn: &Connection) -> Result<i64> {
+    let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    Ok(page_count * page_size)
+}
+
+/// Checkpoint the WAL, reclaim free pages and refresh query planner statistics.
+pub fn run_maintenance(conn: &Connection) -> Result<MaintenanceReport> {
+    let size_before = db_size(conn)?;
+
+    // 0 = NONE, 1 = FULL, 2 = INCREMENTAL. Switching to incremental needs one full VACUUM.
+    let auto_vacuum: i64 = conn.query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+    if auto_vacuum != 2 {
+        conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")?;
     } else {
-        Ok(None)
+        conn.execute_batch("PRAGMA incremental_vacuum;")?;
+    }
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    conn.execute_batch("PRAGMA optimize;")?;
+
+    let report = MaintenanceReport {
+        ran_at: Utc::now(),
+        size_before,
+        size_after: db_size(conn)?,
+    };
+    conn.execute(
+        "INSERT INTO maintenance_runs (ran_at, size_before, size_after) VALUES (?1, ?2, ?3)",
+        params![report.ran_at.timestamp(), report.size_before, report.size_after],
+    )?;
+    Ok(report)
+}
+
+pub fn get_db_stats(conn: &Connection) -> Result<DbStats> {
+    let (execution_count, deleted_count): (i64, i64) = conn.query_row(
+        "SELECT COUNT(*), COUNT(deleted_at) FROM executions",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let freelist_count: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+
+    let last_maintenance = conn
+        .query_row(
+            "SELECT ran_at, size_before, size_after FROM maintenance_runs
+             ORDER BY ran_at DESC LIMIT 1",
+            [],
+            |row| {
+                Ok(MaintenanceReport {
+                    ran_at: Utc.timestamp_opt(row.get(0)?, 0).unwrap(),
+                    size_before: row.get(1)?,
+                    size_after: row.get(2)?,
+                })
+            },
+        )
+        .optional()?;
+    let total_reclaimed: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(MAX(size_before - size_after, 0)), 0) FROM maintenance_runs",
+        [],
+        |row| row.get(0),
+    )?;
+
+    Ok(DbStats {
+        size_bytes: db_size(conn)?,
+        free_bytes: freelist_count * page_size,
+        execution_count,
+        deleted_count,
+        last_maintenance,
+        total_reclaimed_bytes: total_reclaimed,
+    })
+}
+
+pub fn set_paused(conn: &Connection, workflow: &str, paused: bool) -> Result<bool> {
+    let changed = if paused {
+        conn.execute(
+            "INSERT OR IGNORE INTO paused_workflows (workflow, paused_at) VALUES (?1, ?2)",
+            params![workflow, Utc::now().timestamp()],
+        )?
+    } else {
+        conn.execute("DELETE FROM paused_workflows WHERE workflow = ?1", params![workflow])?
+    };
+    Ok(changed > 0)
+}
+
+pub fn paused_workflows(conn: &Connection) -> Result<HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT workflow FROM paused_workflows")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    Ok(rows.collect::<rusqlite::Result<HashSet<_>>>()?)
+}
+
+pub fn set_schedule_override(conn: &Connection, o: &ScheduleOverride) -> Result<()> {
+    // overrides that ran out go whenever a new one is set
+    conn.execute(
+        "DELETE FROM schedule_overrides WHERE until_at <= ?1",
+        params![Utc::now().timestamp()],
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO schedule_overrides (workflow, every_ms, until_at, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            o.workflow,
+            o.every.map(|d| d.as_millis() as i64),
+            o.until.timestamp(),
+            o.created_at.timestamp()
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn clear_schedule_override(conn: &Connection, workflow: &str) -> Result<bool> {
+    let changed =
+        conn.execute("DELETE FROM schedule_overrides WHERE workflow = ?1", params![workflow])?;
+    Ok(changed > 0)
+}
+
+pub fn pin_baseline(conn: &Connection, workflow: &str, execution_id: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO baselines (workflow, execution_id, pinned_at) VALUES (?1, ?2, ?3)",
+        params![workflow, execution_id, Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+pub fn unpin_baseline(conn: &Connection, workflow: &str) -> Result<bool> {
+    let changed = conn.execute("DELETE FROM baselines WHERE workflow = ?1", params![workflow])?;
+    Ok(changed > 0)
+}
+
+pub fn pinned_baseline(conn: &Connection, workflow: &str) -> Result<Option<String>> {
+    let id = conn
+        .query_row(
+            "SELECT execution_id FROM baselines WHERE workflow = ?1",
+            params![workflow],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(id)
+}
+
+pub fn schedule_overrides(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<ScheduleOverride>> {
+    let mut stmt = conn.prepare(
+        "SELECT workflow, every_ms, until_at, created_at FROM schedule_overrides
+         WHERE until_at > ?1",
+    )?;
+    let rows = stmt.query_map(params![now.timestamp()], |row| {
+        Ok(ScheduleOverride {
+            workflow: row.get(0)?,
+            every: row.get::<_, Option<i64>>(1)?.map(|ms| Duration::from_millis(ms as u64)),
+            until: Utc.timestamp_opt(row.get(2)?, 0).unwrap(),
+            created_at: Utc.timestamp_opt(row.get(3)?, 0).unwrap(),
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+pub fn config_snapshots(conn: &Connection) -> Result<HashMap<String, String>> {
+    let mut stmt = conn.prepare("SELECT workflow, config FROM config_snapshots")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<rusqlite::Result<HashMap<_, _>>>()?)
+}
+
+pub fn set_config_snapshot(
+    conn: &Connection,
+    workflow: &str,
+    snapshot: Option<&str>,
+) -> Result<()> {
+    match snapshot {
+        Some(config) => conn.execute(
+            "INSERT OR REPLACE INTO config_snapshots (workflow, config) VALUES (?1, ?2)",
+            params![workflow, config],
+        )?,
+        None => {
+            conn.execute("DELETE FROM config_snapshots WHERE workflow = ?1", params![workflow])?
+        }
+    };
+    Ok(())
+}
+
+pub fn add_config_change(conn: &Connection, change: &ConfigChange) -> Result<()> {
+    conn.execute(
+        "INSERT INTO config_changes (workflow, changed_at, summary) VALUES (?1, ?2, ?3)",
+        params![change.workflow, change.changed_at.timestamp(), change.summary],
+    )?;
+    Ok(())
+}
+
+pub fn config_changes(
+    conn: &Connection,
+    workflow: &str,
+    limit: usize,
+) -> Result<Vec<ConfigChange>> {
+    let mut stmt = conn.prepare(
+        "SELECT workflow, changed_at, summary FROM config_changes WHERE workflow = ?1
+         ORDER BY changed_at DESC, id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![workflow, limit as i64], |row| {
+        Ok(ConfigChange {
+            workflow: row.get(0)?,
+            changed_at: Utc.timestamp_opt(row.get(1)?, 0).unwrap(),
+            summary: row.get(2)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
+
+/// Default storage backend: a single SQLite file in the cache directory.
+pub struct SqliteStorage {
+    pool: Mutex<Vec<Connection>>,
+    available: Semaphore,
+    slow_threshold: Duration,
+}
+
+/// A connection taken from the pool; handed back on drop.
+struct PooledConn<'a> {
+    pool: &'a Mutex<Vec<Connection>>,
+    conn: Option<Connection>,
+}
+
+impl Drop for PooledConn<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.lock().unwrap().push(conn);
+        }
+    }
+}
+
+impl SqliteStorage {
+    pub fn open(slow_threshold: Duration) -> Result<Self> {
+        let mut conns = vec![open_db()?];
+        for _ in 1..POOL_SIZE {
+            conns.push(open_connection(&db_path())?);
+        }
+        Ok(Self {
+            pool: Mutex::new(conns),
+            available: Semaphore::new(POOL_SIZE),
+            slow_threshold,
+        })
+    }
+
+    /// Open the existing database without writing to it, for reading history while the runner
+    /// is not running.
+    pub fn open_read_only(slow_threshold: Duration) -> Result<Self> {
+        let path = db_path();
+        let mut conns = Vec::new();
+        for _ in 0..POOL_SIZE {
+            let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .with_context(|| format!("failed to open database: {}", path.display()))?;
+            conn.busy_timeout(BUSY_TIMEOUT)?;
+            schema_version(&conn)?;
+            conns.push(conn);
+        }
+        Ok(Self {
+            pool: Mutex::new(conns),
+            available: Semaphore::new(POOL_SIZE),
+            slow_threshold,
+        })
+    }
+
+    /// Run `f` on a pooled connection, warning when waiting for a free connection plus the
+    /// query itself takes longer than the slow threshold. The query runs via `block_in_place`
+    /// so it does not stall other tasks on this worker thread.
+    async fn with_conn<T>(&self, op: &str, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+        let started = Instant::now();
+        let _permit = self.available.acquire().await?;
+        let conn = PooledConn {
+            pool: &self.pool,
+            conn: self.pool.lock().unwrap().pop(),
+        };
+        let wait = started.elapsed();
+        let result = tokio::task::block_in_place(|| {
+            f(conn.conn.as_ref().expect("a permit guarantees a free connection"))
+        });
+        drop(conn);
+
+        let total = started.elapsed();
+        if total > self.slow_threshold {
+            warn!(
+                op = %op,
+                wait_ms = wait.as_millis() as u64,
+                query_ms = (total - wait).as_millis() as u64,
+                "slow storage call"
+            );
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    async fn insert_execution(&self, exec: &Execution) -> Result<()> {
+        self.with_conn("insert_execution", |c| insert_execution(c, exec)).await
+    }
+
+    async fn update_execution_finished(
+        &self,
+        id: &str,
+        status: ExecutionStatus,
+        finished_at: DateTime<Utc>,
+        exit_code: i32,
+    ) -> Result<()> {
+        self.with_conn("update_execution_finished", |c| {
+            update_execution_finished(c, id, status, finished_at, exit_code)
+        })
+        .await
+    }
+
+    async fn set_execution_result(&self, id: &str, result_json: &str) -> Result<()> {
+        self.with_conn("set_execution_result", |c| set_execution_result(c, id, result_json))
+            .await
+    }
+
+    async fn set_execution_severity(&self, id: &str, severity: &LogSeverity) -> Result<()> {
+        self.with_conn("set_execution_severity", |c| set_execution_severity(c, id, severity))
+            .await
+    }
+
+    async fn set_execution_binary_output(&self, id: &str) -> Result<()> {
+        self.with_conn("set_execution_binary_output", |c| set_execution_binary_output(c, id))
+            .await
+    }
+
+    async fn set_execution_deviation(&self, id: &str, deviation: &str) -> Result<()> {
+        self.with_conn("set_execution_deviation", |c| set_execution_deviation(c, id, deviation))
+            .await
+    }
+
+    async fn set_execution_steps(&self, id: &str, steps: &[StepResult]) -> Result<()> {
+        self.with_conn("set_execution_steps", |c| set_execution_steps(c, id, steps)).await
+    }
+
+    async fn set_execution_repeats(&self, id: &str, repeats: &[CollapsedRepeat]) -> Result<()> {
+        self.with_conn("set_execution_repeats", |c| set_execution_repeats(c, id, repeats)).await
+    }
+
+    async fn get_executions(&self, workflow: &str, limit: usize) -> Result<Vec<Execution>> {
+        let op = format!("get_executions workflow={} limit={}", workflow, limit);
+        self.with_conn(&op, |c| get_executions(c, workflow, limit)).await
+    }
+
+    async fn get_durations(&self, workflow: &str, limit: usize) -> Result<Vec<DurationSample>> {
+        self.with_conn("get_durations", |c| get_durations(c, workflow, limit)).await
+    }
+
+    async fn get_execution_by_id(&self, id: &str) -> Result<Option<Execution>> {
+        self.with_conn("get_execution_by_id", |c| get_execution_by_id(c, id)).await
+    }
+
+    async fn running_executions(&self) -> Result<Vec<Execution>> {
+        self.with_conn("running_executions", running_executions).await
+    }
+
+    async fn soft_delete_execution(&self, id: &str, deleted_at: DateTime<Utc>) -> Result<bool> {
+        self.with_conn("soft_delete_execution", |c| soft_delete_execution(c, id, deleted_at))
+            .await
+    }
+
+    async fn restore_execution(&self, id: &str, workflow: &str) -> Result<bool> {
+        self.with_conn("restore_execution", |c| restore_execution(c, id, workflow)).await
+    }
+
+    async fn restore_workflow_executions(&self, workflow: &str) -> Result<usize> {
+        self.with_conn("restore_workflow_executions", |c| restore_workflow_executions(c, workflow))
+            .await
+    }
+
+    async fn purge_deleted(&self, cutoff: DateTime<Utc>) -> Result<Vec<String>> {
+        self.with_conn("purge_deleted", |c| purge_deleted(c, cutoff)).await
+    }
+
+    async fn prune_executions(
+        &self,
+        keep: Option<usize>,
+        cutoff: Option<DateTime<Utc>>,
+    ) -> Result<Vec<String>> {
+        self.with_conn("prune_executions", |c| prune_executions(c, keep, cutoff)).await
+    }
+
+    async fn run_maintenance(&self) -> Result<MaintenanceReport> {
+        self.with_conn("run_maintenance", run_maintenance).await
+    }
+
+    async fn get_db_stats(&self) -> Result<DbStats> {
+        self.with_conn("get_db_stats", get_db_stats).await
+    }
+
+    async fn set_paused(&self, workflow: &str, paused: bool) -> Result<bool> {
+        self.with_conn("set_paused", |c| set_paused(c, workflow, paused)).await
+    }
+
+    async fn paused_workflows(&self) -> Result<HashSet<String>> {
+        self.with_conn("paused_workflows", paused_workflows).await
+    }
+
+    async fn set_schedule_override(&self, schedule_override: &ScheduleOverride) -> Result<()> {
+        self.with_conn("set_schedule_override", |c| set_schedule_override(c, schedule_override))
+            .await
+    }
+
+    async fn clear_schedule_override(&self, workflow: &str) -> Result<bool> {
+        self.with_conn("clear_schedule_override", |c| clear_schedule_override(c, workflow)).await
+    }
+
+    async fn schedule_overrides(&self, now: DateTime<Utc>) -> Result<Vec<ScheduleOverride>> {
+        self.with_conn("schedule_overrides", |c| schedule_overrides(c, now)).await
+    }
+
+    async fn pin_baseline(&self, workflow: &str, execution_id: &str) -> Result<()> {
+        self.with_conn("pin_baseline", |c| pin_baseline(c, workflow, execution_id)).await
+    }
+
+    async fn unpin_baseline(&self, workflow: &str) -> Result<bool> {
+        self.with_conn("unpin_baseline", |c| unpin_baseline(c, workflow)).await
+    }
+
+    async fn baseline(&self, workflow: &str) -> Result<Option<String>> {
+        self.with_conn("baseline", |c| pinned_baseline(c, workflow)).await
+    }
+
+    async fn config_snapshots(&self) -> Result<HashMap<String, String>> {
+        self.with_conn("config_snapshots", config_snapshots).await
+    }
+
+    async fn set_config_snapshot(&self, workflow: &str, snapshot: Option<&str>) -> Result<()> {
+        self.with_conn("set_config_snapshot", |c| set_config_snapshot(c, workflow, snapshot))
+            .await
+    }
+
+    async fn add_config_change(&self, change: &ConfigChange) -> Result<()> {
+        self.with_conn("add_config_change", |c| add_config_change(c, change)).await
+    }
+
+    async fn config_changes(&self, workflow: &str, limit: usize) -> Result<Vec<ConfigChange>> {
+        self.with_conn("config_changes", |c| config_changes(c, workflow, limit)).await
+    }
+
+    async fn prune_config_changes(
+        &self,
+        keep: Option<usize>,
+        cutoff: Option<DateTime<Utc>>,
+    ) -> Result<usize> {
+        self.with_conn("prune_config_changes", |c| prune_config_changes(c, keep, cutoff)).await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.with_conn("ping", |c| Ok(c.query_row("SELECT 1", [], |_| Ok(()))?)).await
     }
 }
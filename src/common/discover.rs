@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use serde_yaml::{Mapping, Value};
+use tracing::warn;
+
+use super::types::WorkflowConfig;
+
+/// Header keys that make a script a workflow, the schedules; other keys alone are taken for
+/// ordinary comments.
+const MARKERS: [&str; 5] = ["cron", "every", "at", "on_start", "watch"];
+
+/// Most of a script read looking for its header.
+const MAX_HEADER: u64 = 64 * 1024;
+
+/// What each script's header made, with the modification time and size it was read at. The
+/// config is loaded for many requests; a script is read again only once it changes, and a
+/// broken header is reported once per edit.
+static HEADERS: Mutex<Option<HashMap<PathBuf, Header>>> = Mutex::new(None);
+
+struct Header {
+    modified: SystemTime,
+    len: u64,
+    workflow: Option<WorkflowConfig>,
+}
+
+/// Workflows described by the comment headers of the scripts in `dir`, skipping `taken` scripts
+/// (those a configured workflow already runs). Scripts with a header that doesn't make a valid
+/// workflow are skipped and logged.
+pub fn scan(dir: &Path, taken: &[&str]) -> Vec<WorkflowConfig> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.') && !taken.contains(&name.as_str()))
+        .collect();
+    files.sort();
+
+    let mut headers = HEADERS.lock().unwrap_or_else(|e| e.into_inner());
+    let headers = headers.get_or_insert_with(HashMap::new);
+    headers.retain(|path, _| path.parent() != Some(dir) || path.exists());
+    let mut workflows = Vec::new();
+    for file in files {
+        let path = dir.join(&file);
+        let Ok(meta) = std::fs::metadata(&path) else {
+            continue;
+        };
+        let (modified, len) = (meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), meta.len());
+        if let Some(header) = headers.get(&path) {
+            if header.modified == modified && header.len == len {
+                workflows.extend(header.workflow.clone());
+                continue;
+            }
+        }
+        let workflow = match workflow_from_header(&path, &file) {
+            Ok(workflow) => workflow,
+            Err(e) => {
+                warn!(script = %file, error = %format!("{:#}", e), "ignoring script header");
+                None
+            }
+        };
+        workflows.extend(workflow.clone());
+        headers.insert(path, Header { modified, len, workflow });
+    }
+    workflows
+}
+
+/// The workflow in the `# key: value` lines at the top of the script (after the shebang), or
+/// `None` without any of the [`MARKERS`]. Values are read as YAML, so `# args: [a, b]` works;
+/// `name` defaults to the file name without its extension.
+fn workflow_from_header(path: &Path, file: &str) -> Result<Option<WorkflowConfig>> {
+    let lines = header_lines(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut header = Mapping::new();
+    for line in lines.iter().skip_while(|l| l.starts_with("#!")) {
+        let Some(comment) = line.strip_prefix('#') else { break };
+        let Some((key, value)) = comment.trim().split_once(':') else { continue };
+        if key.is_empty() || !key.bytes().all(|b| b.is_ascii_lowercase() || b == b'_') {
+            continue;
+        }
+        let value = value.trim();
+        // `*/5 * * * *` is not valid YAML; anything that isn't stays a string
+        let value = serde_yaml::from_str(value).unwrap_or_else(|_| Value::String(value.into()));
+        header.insert(Value::String(key.to_string()), value);
+    }
+    if !MARKERS.iter().any(|key| header.contains_key(*key)) {
+        return Ok(None);
+    }
+
+    let stem = Path::new(file).file_stem().and_then(|s| s.to_str()).unwrap_or(file);
+    if !header.contains_key("name") {
+        header.insert("name".into(), stem.into());
+    }
+    header.insert("script".into(), file.into());
+    let wf = serde_yaml::from_value(Value::Mapping(header)).context("invalid header")?;
+    Ok(Some(wf))
+}
+
+/// The lines at the top of the script up to the first that isn't a comment, reading no more
+/// than [`MAX_HEADER`] bytes; the rest of the script is never read.
+fn header_lines(path: &Path) -> std::io::Result<Vec<String>> {
+    let mut reader = BufReader::new(std::fs::File::open(path)?.take(MAX_HEADER));
+    let mut lines = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        let text = String::from_utf8_lossy(&line).trim_end_matches(['\n', '\r']).to_string();
+        let comment = text.starts_with('#');
+        lines.push(text);
+        if !comment {
+            break;
+        }
+    }
+    Ok(lines)
+}
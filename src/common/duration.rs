@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Parse a human-friendly duration such as `500ms`, `30s`, `15m`, `2h`, `7d` or `1h30m`.
+/// A bare number is interpreted as seconds.
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    if s.is_empty() {
+        bail!("empty duration");
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits_end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit_end = rest[digits_end..]
+            .find(|c: char| c.is_ascii_digit())
+            .map_or(rest.len(), |i| digits_end + i);
+        let Ok(n) = rest[..digits_end].parse::<u64>() else {
+            bail!("invalid duration: {}", s);
+        };
+        total += match &rest[digits_end..unit_end] {
+            "ms" => Duration::from_millis(n),
+            "s" => Duration::from_secs(n),
+            "m" => Duration::from_secs(n * 60),
+            "h" => Duration::from_secs(n * 3600),
+            "d" => Duration::from_secs(n * 86400),
+            "w" => Duration::from_secs(n * 7 * 86400),
+            "" => bail!("missing unit in duration: {}", s),
+            unit => bail!("invalid duration unit '{}' in: {}", unit, s),
+        };
+        rest = &rest[unit_end..];
+    }
+    Ok(total)
+}
+
+pub fn format_duration(d: &Duration) -> String {
+    let secs = d.as_secs();
+    let millis = d.subsec_millis();
+    if secs == 0 {
+        return format!("{}ms", millis);
+    }
+    let mut out = String::new();
+    let mut rest = secs;
+    for (unit, size) in [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)] {
+        if rest >= size {
+            out.push_str(&format!("{}{}", rest / size, unit));
+            rest %= size;
+        }
+    }
+    if millis > 0 {
+        out.push_str(&format!("{}ms", millis));
+    }
+    out
+}
+
+/// serde helpers for `#[serde(with = "crate::common::duration")]`.
+pub fn serialize<S: Serializer>(d: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_duration(d))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_duration(&s).map_err(serde::de::Error::custom)
+}
+
+/// serde helpers for `Option<Duration>` fields.
+pub mod option {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(d: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match d {
+            Some(d) => serializer.serialize_str(&super::format_duration(d)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        let s = Option::<String>::deserialize(deserializer)?;
+        s.map(|s| super::parse_duration(&s).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
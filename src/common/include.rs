@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use notify::RecursiveMode;
+use serde_yaml::Value;
+
+use super::config::{config_dir_path, parse};
+use super::types::IncludedConfig;
+use super::upgrade;
+
+/// The files `include` patterns match, each pattern's in name order. A pattern without
+/// wildcards names a file that has to exist; one with wildcards may match nothing.
+pub fn files(patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        let full = config_dir_path(pattern);
+        if !has_wildcards(pattern) {
+            if !full.is_file() {
+                bail!("include {}: {} not found", pattern, full.display());
+            }
+            files.push(full);
+            continue;
+        }
+        let matches = glob::glob(&full.to_string_lossy())
+            .with_context(|| format!("include {}: invalid pattern", pattern))?;
+        let mut matched: Vec<PathBuf> =
+            matches.filter_map(|m| m.ok()).filter(|p| p.is_file()).collect();
+        matched.sort();
+        files.extend(matched);
+    }
+    Ok(files)
+}
+
+/// The workflows and templates of an included file, read like the main config at its
+/// `version`: a single workflow, or `workflows` and `templates` lists.
+pub fn load(path: &Path, version: u64) -> Result<IncludedConfig> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let is_toml = path.extension().is_some_and(|e| e == "toml");
+    let context = || format!("failed to parse {}", path.display());
+    let value: Value = parse(&content, is_toml).with_context(context)?;
+    let single = value.as_mapping().is_some_and(|m| m.contains_key("name"));
+
+    if version == upgrade::CONFIG_VERSION {
+        // parsed from the text, so errors point at their line
+        if single {
+            let workflow = parse(&content, is_toml).with_context(context)?;
+            return Ok(IncludedConfig {
+                workflows: vec![workflow],
+                ..Default::default()
+            });
+        }
+        return parse(&content, is_toml).with_context(context);
+    }
+
+    // an older layout, upgraded by the same steps as a config of its own
+    let mut config = if single {
+        let mut root = serde_yaml::Mapping::new();
+        root.insert("workflows".into(), Value::Sequence(vec![value]));
+        Value::Mapping(root)
+    } else {
+        value
+    };
+    if let Some(root) = config.as_mapping_mut() {
+        root.insert("version".into(), version.into());
+    }
+    upgrade::upgrade(&mut config)
+        .with_context(|| format!("failed to upgrade {}", path.display()))?;
+    if let Some(root) = config.as_mapping_mut() {
+        root.remove("version");
+    }
+    serde_yaml::from_value(config).with_context(context)
+}
+
+/// The directories to watch for included files coming, going and changing: the part of each
+/// pattern before its first wildcard, the whole tree below it if the wildcard is in a directory
+/// name.
+pub fn watch_dirs(patterns: &[String]) -> Vec<(PathBuf, RecursiveMode)> {
+    let mut dirs: Vec<(PathBuf, RecursiveMode)> = Vec::new();
+    for pattern in patterns {
+        let full = config_dir_path(pattern);
+        let mut fixed = PathBuf::new();
+        let mut components = full.components().peekable();
+        let mut mode = RecursiveMode::NonRecursive;
+        while let Some(component) = components.next() {
+            if components.peek().is_none() {
+                break;
+            }
+            if has_wildcards(&component.as_os_str().to_string_lossy()) {
+                mode = RecursiveMode::Recursive;
+                break;
+            }
+            fixed.push(component);
+        }
+        if !dirs.iter().any(|(dir, m)| *dir == fixed && *m == mode) {
+            dirs.push((fixed, mode));
+        }
+    }
+    dirs
+}
+
+fn has_wildcards(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
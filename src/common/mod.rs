@@ -1,3 +1,12 @@
 pub mod config;
 pub mod db;
+pub mod discover;
+pub mod duration;
+pub mod include;
+pub mod paths;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod storage;
+pub mod template;
 pub mod types;
+pub mod upgrade;
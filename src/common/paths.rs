@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Overrides the config directory, as `--config-dir` does.
+pub const CONFIG_DIR_ENV: &str = "ILLEF_WORKFLOW_CONFIG_DIR";
+/// Overrides the cache directory, as `--cache-dir` does.
+pub const CACHE_DIR_ENV: &str = "ILLEF_WORKFLOW_CACHE_DIR";
+/// Overrides the Unix socket, as `--socket` does.
+pub const SOCKET_ENV: &str = "ILLEF_WORKFLOW_SOCKET";
+
+/// Where the Runner and the TUI keep their files. Both resolve them the same way, so they
+/// meet at the same socket and database without being told where.
+#[derive(Debug, Clone)]
+pub struct Paths {
+    /// `config.yaml`, `scripts/`, `plugins/` and `secrets.env`.
+    pub config_dir: PathBuf,
+    /// The database, the logs and the runner lock.
+    pub cache_dir: PathBuf,
+    /// The gRPC socket the TUI connects to.
+    pub socket: PathBuf,
+}
+
+/// Paths given on the command line; each wins over its environment variable and the XDG
+/// defaults.
+#[derive(Debug, Clone, Default)]
+pub struct PathOverrides {
+    pub config_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    pub socket: Option<PathBuf>,
+}
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+/// Resolve the paths once for the whole process. Called by the binaries before anything
+/// touches a file; later calls, and calls after [`get`], change nothing.
+pub fn init(overrides: PathOverrides) {
+    let _ = PATHS.set(Paths::resolve(overrides));
+}
+
+/// The paths from [`init`], or from the environment alone if it wasn't called.
+pub fn get() -> &'static Paths {
+    PATHS.get_or_init(|| Paths::resolve(PathOverrides::default()))
+}
+
+impl Paths {
+    fn resolve(overrides: PathOverrides) -> Paths {
+        let config_dir = overrides
+            .config_dir
+            .or_else(|| env_path(CONFIG_DIR_ENV))
+            .unwrap_or_else(|| xdg_dir("XDG_CONFIG_HOME", ".config").join("illef-workflow"));
+        let cache_dir = overrides
+            .cache_dir
+            .or_else(|| env_path(CACHE_DIR_ENV))
+            .unwrap_or_else(|| xdg_dir("XDG_CACHE_HOME", ".cache").join("illef-workflow"));
+        let socket = overrides
+            .socket
+            .or_else(|| env_path(SOCKET_ENV))
+            .unwrap_or_else(default_socket);
+        Paths {
+            config_dir,
+            cache_dir,
+            socket,
+        }
+    }
+}
+
+pub fn home() -> PathBuf {
+    PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| "/root".to_string()))
+}
+
+fn env_path(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// `$<var>`, or `~/<fallback>` when it is unset. The spec has relative values ignored.
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+    env_path(var)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home().join(fallback))
+}
+
+/// In `$XDG_RUNTIME_DIR`, which only its user can enter. Without one, a name in `/tmp` with
+/// the uid in it, so the runners of users sharing the machine don't take each other's socket.
+fn default_socket() -> PathBuf {
+    match env_path("XDG_RUNTIME_DIR").filter(|p| p.is_absolute()) {
+        Some(dir) => dir.join("illef-workflow.sock"),
+        None => {
+            // SAFETY: getuid has no preconditions and cannot fail
+            let uid = unsafe { libc::getuid() };
+            Path::new("/tmp").join(format!("illef-workflow-{}.sock", uid))
+        }
+    }
+}
@@ -0,0 +1,682 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use tokio_postgres::{Client, NoTls, Row};
+use tracing::error;
+
+use super::storage::Storage;
+use super::types::{
+    CollapsedRepeat, ConfigChange, DbStats, DurationSample, Execution, ExecutionStatus,
+    LogSeverity, MaintenanceReport, ScheduleOverride, StepResult,
+};
+
+/// One step per schema change: `MIGRATIONS[i]` takes the database from version `i` to `i + 1`,
+/// as recorded in `schema_version`. Append new steps; never change released ones.
+const MIGRATIONS: &[&str] = &[BASELINE, SCHEDULE_OVERRIDES, BASELINES, CONFIG_CHANGES, REPEATS];
+
+/// The schema as of versioning, brought up to date from whatever earlier layout it finds.
+const BASELINE: &str = "CREATE TABLE IF NOT EXISTS executions (
+        id          TEXT PRIMARY KEY,
+        workflow    TEXT NOT NULL,
+        status      TEXT NOT NULL,
+        started_at  BIGINT NOT NULL,
+        finished_at BIGINT,
+        exit_code   INTEGER,
+        log_path    TEXT NOT NULL,
+        deleted_at  BIGINT
+    );
+    ALTER TABLE executions ADD COLUMN IF NOT EXISTS args TEXT NOT NULL DEFAULT '[]';
+    ALTER TABLE executions ADD COLUMN IF NOT EXISTS result_json TEXT;
+    ALTER TABLE executions ADD COLUMN IF NOT EXISTS error_count BIGINT NOT NULL DEFAULT 0;
+    ALTER TABLE executions ADD COLUMN IF NOT EXISTS warning_count BIGINT NOT NULL DEFAULT 0;
+    ALTER TABLE executions ADD COLUMN IF NOT EXISTS first_error_line BIGINT;
+    ALTER TABLE executions ADD COLUMN IF NOT EXISTS trigger_source TEXT;
+    ALTER TABLE executions ADD COLUMN IF NOT EXISTS binary_output BOOLEAN NOT NULL DEFAULT FALSE;
+    ALTER TABLE executions ADD COLUMN IF NOT EXISTS steps_json TEXT;
+    ALTER TABLE executions ADD COLUMN IF NOT EXISTS host TEXT;
+    CREATE INDEX IF NOT EXISTS idx_executions_workflow
+        ON executions(workflow, started_at DESC);
+    CREATE TABLE IF NOT EXISTS paused_workflows (
+        workflow    TEXT PRIMARY KEY,
+        paused_at   BIGINT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS maintenance_runs (
+        ran_at      BIGINT NOT NULL,
+        size_before BIGINT NOT NULL,
+        size_after  BIGINT NOT NULL
+    );";
+
+/// Temporary schedule changes, one per workflow.
+const SCHEDULE_OVERRIDES: &str = "CREATE TABLE schedule_overrides (
+        workflow    TEXT PRIMARY KEY,
+        every_ms    BIGINT,
+        until_at    BIGINT NOT NULL,
+        created_at  BIGINT NOT NULL
+    );";
+
+/// Pinned baseline executions, one per workflow, and how each run strayed from its baseline.
+const BASELINES: &str = "CREATE TABLE baselines (
+        workflow     TEXT PRIMARY KEY,
+        execution_id TEXT NOT NULL,
+        pinned_at    BIGINT NOT NULL
+    );
+    ALTER TABLE executions ADD COLUMN baseline_deviation TEXT;";
+
+/// Each workflow's config as last loaded, and the changes noticed between loads.
+const CONFIG_CHANGES: &str = "CREATE TABLE config_snapshots (
+        workflow    TEXT PRIMARY KEY,
+        config      TEXT NOT NULL
+    );
+    CREATE TABLE config_changes (
+        id          BIGSERIAL PRIMARY KEY,
+        workflow    TEXT NOT NULL,
+        changed_at  BIGINT NOT NULL,
+        summary     TEXT NOT NULL
+    );
+    CREATE INDEX idx_config_changes_workflow ON config_changes(workflow, changed_at DESC);";
+
+/// The lines `collapse_repeats` dropped from each execution's log.
+const REPEATS: &str = "ALTER TABLE executions ADD COLUMN repeats_json TEXT;";
+
+/// Advisory lock key held while migrating.
+const MIGRATION_LOCK: i64 = 0x696c_6566_7766;
+
+/// Shared history store for runners on several hosts. Log paths stay host-local.
+pub struct PostgresStorage {
+    client: Client,
+}
+
+impl PostgresStorage {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(url, NoTls)
+            .await
+            .context("failed to connect to postgres")?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                error!(error = %e, "postgres connection error");
+            }
+        });
+
+        let mut storage = Self { client };
+        storage.init_schema().await?;
+        Ok(storage)
+    }
+
+    /// Run the migrations the database hasn't had. Runners on several hosts may share it, so
+    /// they take turns through an advisory lock, and the migrations commit with their versions.
+    async fn init_schema(&mut self) -> Result<()> {
+        self.client
+            .batch_execute("CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)")
+            .await?;
+        let tx = self.client.transaction().await?;
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&MIGRATION_LOCK]).await?;
+        let row = tx.query_one("SELECT COALESCE(MAX(version), 0) FROM schema_version", &[]).await?;
+        let version: i32 = row.get(0);
+        if version as usize > MIGRATIONS.len() {
+            bail!(
+                "the database is at schema version {}, but this illef-workflow only knows up to \
+                 {}; upgrade illef-workflow",
+                version,
+                MIGRATIONS.len()
+            );
+        }
+        for (step, sql) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            let step = step as i32 + 1;
+            tx.batch_execute(sql)
+                .await
+                .with_context(|| format!("failed to migrate the database to version {}", step))?;
+            tx.execute("INSERT INTO schema_version (version) VALUES ($1)", &[&step]).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn db_size(&self) -> Result<i64> {
+        let row = self
+            .client
+            .query_one(
+                "SELECT pg_total_relation_size('executions')
+                      + pg_total_relation_size('maintenance_runs')",
+                &[],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+}
+
+const EXECUTION_COLUMNS: &str = "id, workflow, status, started_at, finished_at, exit_code, \
+    log_path, args, result_json, error_count, warning_count, first_error_line, trigger_source, \
+    binary_output, steps_json, host, baseline_deviation, repeats_json";
+
+fn execution_from_row(row: &Row) -> Execution {
+    let status_str: String = row.get(2);
+    let started_ts: i64 = row.get(3);
+    let finished_ts: Option<i64> = row.get(4);
+    let args_json: String = row.get(7);
+    Execution {
+        id: row.get(0),
+        workflow: row.get(1),
+        status: ExecutionStatus::from_str(&status_str).unwrap_or(ExecutionStatus::Failed),
+        started_at: Utc.timestamp_opt(started_ts, 0).unwrap(),
+        finished_at: finished_ts.map(|ts| Utc.timestamp_opt(ts, 0).unwrap()),
+        exit_code: row.get(5),
+        log_path: row.get(6),
+        args: serde_json::from_str(&args_json).unwrap_or_default(),
+        result_json: row.get(8),
+        severity: LogSeverity {
+            errors: row.get::<_, i64>(9) as u32,
+            warnings: row.get::<_, i64>(10) as u32,
+            first_error_line: row.get::<_, Option<i64>>(11).map(|l| l as u32),
+        },
+        trigger: row.get::<_, Option<String>>(12).and_then(|s| s.parse().ok()),
+        binary_output: row.get(13),
+        steps: row
+            .get::<_, Option<String>>(14)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        host: row.get(15),
+        deviation: row.get(16),
+        repeats: row
+            .get::<_, Option<String>>(17)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+    }
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    async fn insert_execution(&self, exec: &Execution) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO executions (id, workflow, status, started_at, finished_at, exit_code, log_path, args,
+                                         trigger_source, host)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+                &[
+                    &exec.id,
+                    &exec.workflow,
+                    &exec.status.as_str(),
+                    &exec.started_at.timestamp(),
+                    &exec.finished_at.map(|t| t.timestamp()),
+                    &exec.exit_code,
+                    &exec.log_path,
+                    &serde_json::to_string(&exec.args)?,
+                    &exec.trigger.map(|t| t.as_str()),
+                    &exec.host,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn update_execution_finished(
+        &self,
+        id: &str,
+        status: ExecutionStatus,
+        finished_at: DateTime<Utc>,
+        exit_code: i32,
+    ) -> Result<()> {
+        self.client
+            .execute(
+                "UPDATE executions SET status = $1, finished_at = $2, exit_code = $3 WHERE id = $4",
+                &[&status.as_str(), &finished_at.timestamp(), &exit_code, &id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn set_execution_result(&self, id: &str, result_json: &str) -> Result<()> {
+        self.client
+            .execute(
+                "UPDATE executions SET result_json = $1 WHERE id = $2",
+                &[&result_json, &id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn set_execution_severity(&self, id: &str, severity: &LogSeverity) -> Result<()> {
+        self.client
+            .execute(
+                "UPDATE executions SET error_count = $1, warning_count = $2, first_error_line = $3
+                 WHERE id = $4",
+                &[
+                    &(severity.errors as i64),
+                    &(severity.warnings as i64),
+                    &severity.first_error_line.map(|l| l as i64),
+                    &id,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn set_execution_binary_output(&self, id: &str) -> Result<()> {
+        self.client
+            .execute("UPDATE executions SET binary_output = TRUE WHERE id = $1", &[&id])
+            .await?;
+        Ok(())
+    }
+
+    async fn set_execution_deviation(&self, id: &str, deviation: &str) -> Result<()> {
+        self.client
+            .execute(
+                "UPDATE executions SET baseline_deviation = $1 WHERE id = $2",
+                &[&deviation, &id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn set_execution_steps(&self, id: &str, steps: &[StepResult]) -> Result<()> {
+        self.client
+            .execute(
+                "UPDATE executions SET steps_json = $1 WHERE id = $2",
+                &[&serde_json::to_string(steps)?, &id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn set_execution_repeats(&self, id: &str, repeats: &[CollapsedRepeat]) -> Result<()> {
+        self.client
+            .execute(
+                "UPDATE executions SET repeats_json = $1 WHERE id = $2",
+                &[&serde_json::to_string(repeats)?, &id],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_executions(&self, workflow: &str, limit: usize) -> Result<Vec<Execution>> {
+        let rows = self
+            .client
+            .query(
+                &format!(
+                    "SELECT {}
+                     FROM executions
+                     WHERE workflow = $1 AND deleted_at IS NULL
+                     ORDER BY started_at DESC
+                     LIMIT $2",
+                    EXECUTION_COLUMNS
+                ),
+                &[&workflow, &(limit as i64)],
+            )
+            .await?;
+        Ok(rows.iter().map(execution_from_row).collect())
+    }
+
+    async fn get_durations(&self, workflow: &str, limit: usize) -> Result<Vec<DurationSample>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT started_at, finished_at - started_at, status
+                 FROM executions
+                 WHERE workflow = $1 AND finished_at IS NOT NULL AND deleted_at IS NULL
+                 ORDER BY started_at DESC
+                 LIMIT $2",
+                &[&workflow, &(limit as i64)],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let status_str: String = row.get(2);
+                DurationSample {
+                    started_at: Utc.timestamp_opt(row.get(0), 0).unwrap(),
+                    duration_secs: row.get(1),
+                    status: ExecutionStatus::from_str(&status_str).unwrap_or(ExecutionStatus::Failed),
+                }
+            })
+            .collect())
+    }
+
+    async fn get_execution_by_id(&self, id: &str) -> Result<Option<Execution>> {
+        let row = self
+            .client
+            .query_opt(
+                &format!(
+                    "SELECT {} FROM executions WHERE id = $1 AND deleted_at IS NULL",
+                    EXECUTION_COLUMNS
+                ),
+                &[&id],
+            )
+            .await?;
+        Ok(row.as_ref().map(execution_from_row))
+    }
+
+    async fn running_executions(&self) -> Result<Vec<Execution>> {
+        let rows = self
+            .client
+            .query(
+                &format!(
+                    "SELECT {} FROM executions WHERE status = 'running' ORDER BY started_at",
+                    EXECUTION_COLUMNS
+                ),
+                &[],
+            )
+            .await?;
+        Ok(rows.iter().map(execution_from_row).collect())
+    }
+
+    async fn soft_delete_execution(&self, id: &str, deleted_at: DateTime<Utc>) -> Result<bool> {
+        let changed = self
+            .client
+            .execute(
+                "UPDATE executions SET deleted_at = $1 WHERE id = $2 AND deleted_at IS NULL",
+                &[&deleted_at.timestamp(), &id],
+            )
+            .await?;
+        Ok(changed > 0)
+    }
+
+    async fn restore_execution(&self, id: &str, workflow: &str) -> Result<bool> {
+        let changed = self
+            .client
+            .execute(
+                "UPDATE executions SET deleted_at = NULL \
+                 WHERE id = $1 AND deleted_at IS NOT NULL AND ($2 = '' OR workflow = $2)",
+                &[&id, &workflow],
+            )
+            .await?;
+        Ok(changed > 0)
+    }
+
+    async fn restore_workflow_executions(&self, workflow: &str) -> Result<usize> {
+        let changed = self
+            .client
+            .execute(
+                "UPDATE executions SET deleted_at = NULL WHERE workflow = $1 AND deleted_at IS NOT NULL",
+                &[&workflow],
+            )
+            .await?;
+        Ok(changed as usize)
+    }
+
+    async fn purge_deleted(&self, cutoff: DateTime<Utc>) -> Result<Vec<String>> {
+        let rows = self
+            .client
+            .query(
+                "DELETE FROM executions WHERE deleted_at IS NOT NULL AND deleted_at < $1 RETURNING log_path",
+                &[&cutoff.timestamp()],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn prune_executions(
+        &self,
+        keep: Option<usize>,
+        cutoff: Option<DateTime<Utc>>,
+    ) -> Result<Vec<String>> {
+        let keep = keep.map_or(i64::MAX, |k| k as i64);
+        let cutoff = cutoff.map_or(i64::MIN, |c| c.timestamp());
+        let rows = self
+            .client
+            .query(
+                "DELETE FROM executions WHERE id IN (
+                    SELECT id FROM (
+                        SELECT id, started_at, finished_at,
+                               ROW_NUMBER() OVER (PARTITION BY workflow ORDER BY started_at DESC)
+                                   AS row_num
+                        FROM executions
+                    ) ranked
+                    WHERE finished_at IS NOT NULL AND (row_num > $1 OR started_at < $2)
+                ) AND id NOT IN (SELECT execution_id FROM baselines) RETURNING log_path",
+                &[&keep, &cutoff],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn run_maintenance(&self) -> Result<MaintenanceReport> {
+        let size_before = self.db_size().await?;
+        self.client
+            .batch_execute("VACUUM ANALYZE executions; VACUUM ANALYZE maintenance_runs;")
+            .await?;
+
+        let report = MaintenanceReport {
+            ran_at: Utc::now(),
+            size_before,
+            size_after: self.db_size().await?,
+        };
+        self.client
+            .execute(
+                "INSERT INTO maintenance_runs (ran_at, size_before, size_after) VALUES ($1, $2, $3)",
+                &[&report.ran_at.timestamp(), &report.size_before, &report.size_after],
+            )
+            .await?;
+        Ok(report)
+    }
+
+    async fn get_db_stats(&self) -> Result<DbStats> {
+        let counts = self
+            .client
+            .query_one("SELECT COUNT(*), COUNT(deleted_at) FROM executions", &[])
+            .await?;
+
+        let last_maintenance = self
+            .client
+            .query_opt(
+                "SELECT ran_at, size_before, size_after FROM maintenance_runs
+                 ORDER BY ran_at DESC LIMIT 1",
+                &[],
+            )
+            .await?
+            .map(|row| MaintenanceReport {
+                ran_at: Utc.timestamp_opt(row.get(0), 0).unwrap(),
+                size_before: row.get(1),
+                size_after: row.get(2),
+            });
+        let total_reclaimed: i64 = self
+            .client
+            .query_one(
+                "SELECT COALESCE(SUM(GREATEST(size_before - size_after, 0)), 0)::BIGINT
+                 FROM maintenance_runs",
+                &[],
+            )
+            .await?
+            .get(0);
+
+        Ok(DbStats {
+            size_bytes: self.db_size().await?,
+            // postgres manages free space itself; there is no freelist to report
+            free_bytes: 0,
+            execution_count: counts.get(0),
+            deleted_count: counts.get(1),
+            last_maintenance,
+            total_reclaimed_bytes: total_reclaimed,
+        })
+    }
+
+    async fn set_paused(&self, workflow: &str, paused: bool) -> Result<bool> {
+        let changed = if paused {
+            self.client
+                .execute(
+                    "INSERT INTO paused_workflows (workflow, paused_at) VALUES ($1, $2)
+                     ON CONFLICT (workflow) DO NOTHING",
+                    &[&workflow, &Utc::now().timestamp()],
+                )
+                .await?
+        } else {
+            self.client
+                .execute("DELETE FROM paused_workflows WHERE workflow = $1", &[&workflow])
+                .await?
+        };
+        Ok(changed > 0)
+    }
+
+    async fn paused_workflows(&self) -> Result<HashSet<String>> {
+        let rows = self.client.query("SELECT workflow FROM paused_workflows", &[]).await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn set_schedule_override(&self, o: &ScheduleOverride) -> Result<()> {
+        // overrides that ran out go whenever a new one is set
+        self.client
+            .execute(
+                "DELETE FROM schedule_overrides WHERE until_at <= $1",
+                &[&Utc::now().timestamp()],
+            )
+            .await?;
+        self.client
+            .execute(
+                "INSERT INTO schedule_overrides (workflow, every_ms, until_at, created_at)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (workflow) DO UPDATE SET every_ms = EXCLUDED.every_ms,
+                     until_at = EXCLUDED.until_at, created_at = EXCLUDED.created_at",
+                &[
+                    &o.workflow,
+                    &o.every.map(|d| d.as_millis() as i64),
+                    &o.until.timestamp(),
+                    &o.created_at.timestamp(),
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn clear_schedule_override(&self, workflow: &str) -> Result<bool> {
+        let changed = self
+            .client
+            .execute("DELETE FROM schedule_overrides WHERE workflow = $1", &[&workflow])
+            .await?;
+        Ok(changed > 0)
+    }
+
+    async fn schedule_overrides(&self, now: DateTime<Utc>) -> Result<Vec<ScheduleOverride>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT workflow, every_ms, until_at, created_at FROM schedule_overrides
+                 WHERE until_at > $1",
+                &[&now.timestamp()],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| ScheduleOverride {
+                workflow: row.get(0),
+                every: row.get::<_, Option<i64>>(1).map(|ms| Duration::from_millis(ms as u64)),
+                until: Utc.timestamp_opt(row.get(2), 0).unwrap(),
+                created_at: Utc.timestamp_opt(row.get(3), 0).unwrap(),
+            })
+            .collect())
+    }
+
+    async fn pin_baseline(&self, workflow: &str, execution_id: &str) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO baselines (workflow, execution_id, pinned_at) VALUES ($1, $2, $3)
+                 ON CONFLICT (workflow) DO UPDATE
+                 SET execution_id = EXCLUDED.execution_id, pinned_at = EXCLUDED.pinned_at",
+                &[&workflow, &execution_id, &Utc::now().timestamp()],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn unpin_baseline(&self, workflow: &str) -> Result<bool> {
+        let changed = self
+            .client
+            .execute("DELETE FROM baselines WHERE workflow = $1", &[&workflow])
+            .await?;
+        Ok(changed > 0)
+    }
+
+    async fn baseline(&self, workflow: &str) -> Result<Option<String>> {
+        let row = self
+            .client
+            .query_opt("SELECT execution_id FROM baselines WHERE workflow = $1", &[&workflow])
+            .await?;
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    async fn config_snapshots(&self) -> Result<HashMap<String, String>> {
+        let rows = self.client.query("SELECT workflow, config FROM config_snapshots", &[]).await?;
+        Ok(rows.iter().map(|row| (row.get(0), row.get(1))).collect())
+    }
+
+    async fn set_config_snapshot(&self, workflow: &str, snapshot: Option<&str>) -> Result<()> {
+        match snapshot {
+            Some(config) => {
+                self.client
+                    .execute(
+                        "INSERT INTO config_snapshots (workflow, config) VALUES ($1, $2)
+                         ON CONFLICT (workflow) DO UPDATE SET config = EXCLUDED.config",
+                        &[&workflow, &config],
+                    )
+                    .await?
+            }
+            None => {
+                self.client
+                    .execute("DELETE FROM config_snapshots WHERE workflow = $1", &[&workflow])
+                    .await?
+            }
+        };
+        Ok(())
+    }
+
+    async fn add_config_change(&self, change: &ConfigChange) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO config_changes (workflow, changed_at, summary) VALUES ($1, $2, $3)",
+                &[&change.workflow, &change.changed_at.timestamp(), &change.summary],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn config_changes(&self, workflow: &str, limit: usize) -> Result<Vec<ConfigChange>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT workflow, changed_at, summary FROM config_changes WHERE workflow = $1
+                 ORDER BY changed_at DESC, id DESC LIMIT $2",
+                &[&workflow, &(limit as i64)],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| ConfigChange {
+                workflow: row.get(0),
+                changed_at: Utc.timestamp_opt(row.get(1), 0).unwrap(),
+                summary: row.get(2),
+            })
+            .collect())
+    }
+
+    async fn prune_config_changes(
+        &self,
+        keep: Option<usize>,
+        cutoff: Option<DateTime<Utc>>,
+    ) -> Result<usize> {
+        let keep = keep.map_or(i64::MAX, |k| k as i64);
+        let cutoff = cutoff.map_or(i64::MIN, |c| c.timestamp());
+        let removed = self
+            .client
+            .execute(
+                "DELETE FROM config_changes WHERE id IN (
+                    SELECT id FROM (
+                        SELECT id, changed_at,
+                               ROW_NUMBER() OVER (
+                                   PARTITION BY workflow ORDER BY changed_at DESC, id DESC
+                               ) AS row_num
+                        FROM config_changes
+                    ) ranked
+                    WHERE row_num > $1 OR changed_at < $2
+                )",
+                &[&keep, &cutoff],
+            )
+            .await?;
+        Ok(removed as usize)
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.client.simple_query("SELECT 1").await?;
+        Ok(())
+    }
+}
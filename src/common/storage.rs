@@ -0,0 +1,167 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::db::SqliteStorage;
+use super::types::{
+    CollapsedRepeat, ConfigChange, DbStats, DurationSample, Execution, ExecutionStatus,
+    LogSeverity, MaintenanceReport, ScheduleOverride, StepResult, StorageBackend, StorageConfig,
+    WorkflowStats,
+};
+
+/// How many recent executions [`Storage::get_workflow_stats`] looks at.
+const STATS_WINDOW: usize = 1000;
+
+/// Persistent execution history. SQLite is the default; Postgres is available behind the
+/// `postgres` feature for several runners sharing one history store.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn insert_execution(&self, exec: &Execution) -> Result<()>;
+
+    async fn update_execution_finished(
+        &self,
+        id: &str,
+        status: ExecutionStatus,
+        finished_at: DateTime<Utc>,
+        exit_code: i32,
+    ) -> Result<()>;
+
+    /// Store the structured result a script wrote to `$WF_RESULT_FILE`.
+    async fn set_execution_result(&self, id: &str, result_json: &str) -> Result<()>;
+
+    /// Store the error and warning lines found in the execution's output.
+    async fn set_execution_severity(&self, id: &str, severity: &LogSeverity) -> Result<()>;
+
+    /// Note that the execution's output held binary data.
+    async fn set_execution_binary_output(&self, id: &str) -> Result<()>;
+
+    /// Note how the execution strayed from its workflow's baseline.
+    async fn set_execution_deviation(&self, id: &str, deviation: &str) -> Result<()>;
+
+    /// Store the progress of a multi-step execution, replacing what was stored before.
+    async fn set_execution_steps(&self, id: &str, steps: &[StepResult]) -> Result<()>;
+
+    /// Note the runs of lines `collapse_repeats` dropped from the execution's log.
+    async fn set_execution_repeats(&self, id: &str, repeats: &[CollapsedRepeat]) -> Result<()>;
+
+    async fn get_executions(&self, workflow: &str, limit: usize) -> Result<Vec<Execution>>;
+
+    async fn get_last_execution(&self, workflow: &str) -> Result<Option<Execution>> {
+        let mut execs = self.get_executions(workflow, 1).await?;
+        Ok(execs.pop())
+    }
+
+    async fn get_execution_by_id(&self, id: &str) -> Result<Option<Execution>>;
+
+    /// Executions still marked running, deleted or not, oldest first.
+    async fn running_executions(&self) -> Result<Vec<Execution>>;
+
+    /// Durations of the most recent finished executions, newest first.
+    async fn get_durations(&self, workflow: &str, limit: usize) -> Result<Vec<DurationSample>>;
+
+    /// Failure streaks and MTBF over the most recent finished executions.
+    async fn get_workflow_stats(&self, workflow: &str) -> Result<WorkflowStats> {
+        let samples = self.get_durations(workflow, STATS_WINDOW).await?;
+        Ok(WorkflowStats::from_samples(&samples))
+    }
+
+    async fn soft_delete_execution(&self, id: &str, deleted_at: DateTime<Utc>) -> Result<bool>;
+
+    /// Restore a soft-deleted execution, only if it belongs to `workflow` unless that's empty.
+    async fn restore_execution(&self, id: &str, workflow: &str) -> Result<bool>;
+
+    async fn restore_workflow_executions(&self, workflow: &str) -> Result<usize>;
+
+    /// Permanently remove executions soft-deleted before `cutoff`, returning their log paths.
+    async fn purge_deleted(&self, cutoff: DateTime<Utc>) -> Result<Vec<String>>;
+    /// Permanently remove finished executions beyond the newest `keep` per workflow, or started
+    /// before `cutoff`. Returns the log paths of removed rows.
+    async fn prune_executions(
+        &self,
+        keep: Option<usize>,
+        cutoff: Option<DateTime<Utc>>,
+    ) -> Result<Vec<String>>;
+
+    async fn run_maintenance(&self) -> Result<MaintenanceReport>;
+
+    async fn get_db_stats(&self) -> Result<DbStats>;
+
+    /// Returns whether the paused flag actually changed.
+    async fn set_paused(&self, workflow: &str, paused: bool) -> Result<bool>;
+
+    async fn paused_workflows(&self) -> Result<HashSet<String>>;
+
+    /// Replaces the workflow's override, if it has one.
+    async fn set_schedule_override(&self, schedule_override: &ScheduleOverride) -> Result<()>;
+
+    /// Returns whether the workflow had an override.
+    async fn clear_schedule_override(&self, workflow: &str) -> Result<bool>;
+
+    /// The overrides that haven't run out by `now`.
+    async fn schedule_overrides(&self, now: DateTime<Utc>) -> Result<Vec<ScheduleOverride>>;
+
+    /// Make `execution_id` the workflow's baseline, replacing the one before.
+    async fn pin_baseline(&self, workflow: &str, execution_id: &str) -> Result<()>;
+
+    /// Returns whether the workflow had a baseline.
+    async fn unpin_baseline(&self, workflow: &str) -> Result<bool>;
+
+    /// The id of the workflow's baseline execution, if one is pinned.
+    async fn baseline(&self, workflow: &str) -> Result<Option<String>>;
+
+    /// Each workflow's config as the Runner last loaded it, as YAML, by name.
+    async fn config_snapshots(&self) -> Result<HashMap<String, String>>;
+
+    /// Store the workflow's config as loaded now, or forget it with `None`.
+    async fn set_config_snapshot(&self, workflow: &str, snapshot: Option<&str>) -> Result<()>;
+
+    async fn add_config_change(&self, change: &ConfigChange) -> Result<()>;
+
+    /// The workflow's latest config changes, newest first.
+    async fn config_changes(&self, workflow: &str, limit: usize) -> Result<Vec<ConfigChange>>;
+
+    /// Permanently remove config changes beyond the newest `keep` per workflow, or made before
+    /// `cutoff`. Returns how many were removed.
+    async fn prune_config_changes(
+        &self,
+        keep: Option<usize>,
+        cutoff: Option<DateTime<Utc>>,
+    ) -> Result<usize>;
+
+    /// A trivial round trip, for health checks.
+    async fn ping(&self) -> Result<()>;
+}
+
+pub type Db = Arc<dyn Storage>;
+
+/// Open storage for reading only. SQLite is opened without write access; Postgres, shared by
+/// design, is opened as usual.
+pub async fn open_storage_read_only(config: &StorageConfig) -> Result<Db> {
+    match config.backend {
+        StorageBackend::Sqlite => {
+            Ok(Arc::new(SqliteStorage::open_read_only(config.slow_query_threshold)?))
+        }
+        StorageBackend::Postgres => open_storage(config).await,
+    }
+}
+
+pub async fn open_storage(config: &StorageConfig) -> Result<Db> {
+    match config.backend {
+        StorageBackend::Sqlite => Ok(Arc::new(SqliteStorage::open(config.slow_query_threshold)?)),
+        #[cfg(feature = "postgres")]
+        StorageBackend::Postgres => {
+            let url = config
+                .url
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("storage.url is required for the postgres backend"))?;
+            Ok(Arc::new(super::postgres::PostgresStorage::connect(url).await?))
+        }
+        #[cfg(not(feature = "postgres"))]
+        StorageBackend::Postgres => Err(anyhow::anyhow!(
+            "postgres storage requested but this build was compiled without the `postgres` feature"
+        )),
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result, bail};
+use serde_yaml::Value;
+
+use super::types::{WorkflowConfig, WorkflowTemplate};
+
+/// The workflows `templates` stand for, in order.
+pub fn expand(templates: &[WorkflowTemplate]) -> Result<Vec<WorkflowConfig>> {
+    let mut workflows = Vec::new();
+    for template in templates {
+        let label = match template.workflow.get("name") {
+            Some(Value::String(name)) => name.clone(),
+            _ => "template without a name".to_string(),
+        };
+        for (i, entry) in template.for_each.iter().enumerate() {
+            let vars = entry
+                .iter()
+                .map(|(key, value)| Ok((format!("{{{}}}", key), scalar(value)?)))
+                .collect::<Result<Vec<_>>>()
+                .with_context(|| format!("template {}: for_each entry {}", label, i + 1))?;
+            let value = substitute(&template.workflow, &vars);
+            let wf: WorkflowConfig = serde_yaml::from_value(value)
+                .with_context(|| format!("template {}: for_each entry {}", label, i + 1))?;
+            workflows.push(wf);
+        }
+    }
+    Ok(workflows)
+}
+
+/// Fail on two workflows with the same name, which the scheduler and triggers can't tell apart,
+/// and on an alias that is another workflow's name or alias too.
+pub fn check_names(workflows: &[WorkflowConfig]) -> Result<()> {
+    let mut seen = HashSet::new();
+    for wf in workflows {
+        if !seen.insert(wf.name.as_str()) {
+            bail!("duplicate workflow name: {}", wf.name);
+        }
+    }
+    let mut aliases = HashMap::new();
+    for wf in workflows {
+        for alias in &wf.aliases {
+            if *alias != wf.name && seen.contains(alias.as_str()) {
+                bail!("workflow {}: alias {} is the name of another workflow", wf.name, alias);
+            }
+            match aliases.insert(alias.as_str(), wf.name.as_str()) {
+                Some(other) if other != wf.name => {
+                    bail!("alias {} is used by both {} and {}", alias, other, wf.name)
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+fn scalar(value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => bail!("values must be strings, numbers or booleans"),
+    }
+}
+
+/// `value` with the placeholders replaced in every string, keys included.
+fn substitute(value: &Value, vars: &[(String, String)]) -> Value {
+    match value {
+        Value::String(s) => {
+            let mut s = s.clone();
+            for (placeholder, replacement) in vars {
+                s = s.replace(placeholder, replacement);
+            }
+            Value::String(s)
+        }
+        Value::Sequence(items) => {
+            Value::Sequence(items.iter().map(|v| substitute(v, vars)).collect())
+        }
+        Value::Mapping(map) => Value::Mapping(
+            map.iter()
+                .map(|(k, v)| (substitute(k, vars), substitute(v, vars)))
+                .collect(),
+        ),
+        Value::Tagged(tagged) => {
+            let mut tagged = tagged.as_ref().clone();
+            tagged.value = substitute(&tagged.value, vars);
+            Value::Tagged(Box::new(tagged))
+        }
+        other => other.clone(),
+    }
+}
@@ -0,0 +1,40 @@
+use anyhow::{Result, bail};
+use serde_yaml::{Mapping, Value};
+
+/// The layout of config.yaml this build writes about in its README. Files without `version`
+/// are version 1, the layout from before the key existed.
+pub const CONFIG_VERSION: u64 = 1;
+
+/// One step per layout change: `STEPS[i]` turns a version `i + 1` file into version `i + 2`,
+/// for instance by moving a renamed key. Append new steps and bump [`CONFIG_VERSION`]; never
+/// change released ones, so every old file keeps one way up.
+const STEPS: &[fn(&mut Mapping)] = &[];
+
+const _: () = assert!(STEPS.len() as u64 + 1 == CONFIG_VERSION);
+
+/// Bring a parsed config.yaml up to [`CONFIG_VERSION`] in place and return the version it was
+/// written as. A file from a newer build is refused rather than half understood.
+pub fn upgrade(config: &mut Value) -> Result<u64> {
+    let Some(root) = config.as_mapping_mut() else {
+        return Ok(CONFIG_VERSION);
+    };
+    let version = match root.get("version") {
+        None => 1,
+        Some(v) => match v.as_u64() {
+            Some(v) if v >= 1 => v,
+            _ => bail!("`version` must be a positive whole number"),
+        },
+    };
+    if version > CONFIG_VERSION {
+        bail!(
+            "it is version {}, but this illef-workflow only knows up to version {}; upgrade \
+             illef-workflow",
+            version,
+            CONFIG_VERSION
+        );
+    }
+    for step in &STEPS[version as usize - 1..] {
+        step(root);
+    }
+    Ok(version)
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, bail};
+use serde_yaml::{Mapping, Value};
+
+use crate::common::types::WorkflowConfig;
+use crate::runner::next_runs::ScheduleInfo;
+
+/// Workflows registered by `run-adhoc` instead of the config, kept in memory until the runner
+/// stops, or with `forget` until their run ends. Clones share the registry.
+#[derive(Debug, Clone, Default)]
+pub struct Adhoc(Arc<Mutex<HashMap<String, WorkflowConfig>>>);
+
+impl Adhoc {
+    pub fn get(&self, name: &str) -> Option<WorkflowConfig> {
+        self.0.lock().unwrap().get(name).cloned()
+    }
+
+    /// Every registered workflow, by name.
+    pub fn all(&self) -> Vec<WorkflowConfig> {
+        let mut all: Vec<WorkflowConfig> = self.0.lock().unwrap().values().cloned().collect();
+        all.sort_by(|a, b| a.name.cmp(&b.name));
+        all
+    }
+
+    pub fn register(&self, wf: WorkflowConfig) {
+        self.0.lock().unwrap().insert(wf.name.clone(), wf);
+    }
+
+    pub fn forget(&self, name: &str) {
+        self.0.lock().unwrap().remove(name);
+    }
+}
+
+/// A workflow running `script` (an absolute path) with `args` in `cwd`, and the config defaults
+/// for everything else.
+pub fn workflow(name: &str, script: &str, args: Vec<String>, cwd: &str) -> Result<WorkflowConfig> {
+    if name.is_empty() {
+        bail!("an ad-hoc workflow needs a name");
+    }
+    if !script.starts_with('/') {
+        bail!("script must be an absolute path, got {:?}", script);
+    }
+    let mut fields = Mapping::new();
+    fields.insert("name".into(), name.into());
+    fields.insert("script".into(), script.into());
+    fields.insert("args".into(), args.into());
+    fields.insert("confirm_trigger".into(), false.into());
+    if !cwd.is_empty() {
+        fields.insert("cwd".into(), cwd.into());
+    }
+    serde_yaml::from_value(Value::Mapping(fields)).context("invalid ad-hoc workflow")
+}
+
+/// What `ListWorkflows` reports as an ad-hoc workflow's schedule: none, it runs when asked.
+pub fn schedule() -> ScheduleInfo {
+    ScheduleInfo {
+        label: "ad hoc".to_string(),
+        description: "runs only when triggered; registered by run-adhoc".to_string(),
+        next_run: None,
+        valid: true,
+        manual: true,
+        schedule_override: None,
+    }
+}
@@ -0,0 +1,130 @@
+use tonic::service::Interceptor;
+use tonic::transport::server::UdsConnectInfo;
+use tonic::{Request, Status};
+use tracing::warn;
+
+use crate::common::config::load_config;
+use crate::common::types::{ApiToken, TokenScope};
+
+/// Permissions of the token a TCP request was made with. Requests over the Unix socket
+/// carry no grant and are trusted.
+#[derive(Debug, Clone)]
+pub struct Grant {
+    pub token_name: String,
+    scopes: Vec<TokenScope>,
+    workflows: Vec<String>,
+}
+
+impl Grant {
+    fn from_token(token: &ApiToken) -> Self {
+        Self {
+            token_name: token.name.clone(),
+            scopes: token.scopes.clone(),
+            workflows: token.workflows.clone(),
+        }
+    }
+
+    pub fn allows(&self, scope: TokenScope, workflow: Option<&str>) -> bool {
+        let scope_ok = self.scopes.contains(&TokenScope::Admin) || self.scopes.contains(&scope);
+        let workflow_ok = match workflow {
+            Some(name) => self.workflows.is_empty() || self.workflows.iter().any(|w| w == name),
+            None => true,
+        };
+        scope_ok && workflow_ok
+    }
+
+    pub fn can_see(&self, workflow: &str) -> bool {
+        self.workflows.is_empty() || self.workflows.iter().any(|w| w == workflow)
+    }
+}
+
+/// tonic interceptor for the TCP listener: resolves `authorization: Bearer <token>` against
+/// `api.tokens`.
+#[allow(clippy::result_large_err)]
+pub fn token_interceptor(mut req: Request<()>) -> Result<Request<()>, Status> {
+    let presented = req
+        .metadata()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or_else(|| Status::unauthenticated("missing bearer token"))?
+        .to_string();
+
+    let grant = lookup_token(&presented)
+        .map_err(|e| Status::internal(e.to_string()))?
+        .ok_or_else(|| Status::unauthenticated("invalid token"))?;
+
+    req.extensions_mut().insert(grant);
+    Ok(req)
+}
+
+/// tonic interceptor for the Unix socket. With a `uid` (`api.same_user_only`), it lets
+/// through only connections whose peer runs as that user.
+#[derive(Debug, Clone, Copy)]
+pub struct PeerCheck {
+    pub uid: Option<u32>,
+}
+
+impl Interceptor for PeerCheck {
+    #[allow(clippy::result_large_err)]
+    fn call(&mut self, req: Request<()>) -> Result<Request<()>, Status> {
+        let Some(uid) = self.uid else {
+            return Ok(req);
+        };
+        let cred = req.extensions().get::<UdsConnectInfo>().and_then(|info| info.peer_cred);
+        match cred {
+            Some(cred) if cred.uid() == uid => Ok(req),
+            Some(cred) => {
+                warn!(uid = cred.uid(), pid = ?cred.pid(), "rejected connection from another user");
+                Err(Status::permission_denied(format!(
+                    "the runner only accepts connections from uid {}, not {}",
+                    uid,
+                    cred.uid()
+                )))
+            }
+            None => {
+                warn!("rejected connection without peer credentials");
+                Err(Status::permission_denied("could not verify the connecting user"))
+            }
+        }
+    }
+}
+
+/// Resolve a presented token against `api.tokens`, re-reading the config so token edits
+/// apply without a restart.
+pub fn lookup_token(presented: &str) -> anyhow::Result<Option<Grant>> {
+    let config = load_config()?;
+    let grant = config
+        .api
+        .tokens
+        .iter()
+        .find(|t| constant_time_eq(t.token.as_bytes(), presented.as_bytes()))
+        .map(Grant::from_token);
+    if grant.is_none() {
+        warn!("rejected request with unknown token");
+    }
+    Ok(grant)
+}
+
+/// Check that the caller may perform `scope` (on `workflow`, if the call targets one).
+#[allow(clippy::result_large_err)]
+pub fn authorize<T>(req: &Request<T>, scope: TokenScope, workflow: Option<&str>) -> Result<(), Status> {
+    match req.extensions().get::<Grant>() {
+        Some(grant) if !grant.allows(scope, workflow) => Err(Status::permission_denied(format!(
+            "token '{}' is not allowed to do this",
+            grant.token_name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+pub fn grant<T>(req: &Request<T>) -> Option<&Grant> {
+    req.extensions().get::<Grant>()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,72 @@
+use tracing::warn;
+
+use crate::common::duration::format_duration;
+use crate::common::storage::Db;
+use crate::common::types::{BaselineConfig, Execution, ExecutionStatus};
+
+/// How a successful `run` strayed from its workflow's pinned baseline past `config`'s limits,
+/// e.g. `took 3m10s, 58% longer than the baseline's 2m`. `None` when no baseline is pinned, the
+/// run is the baseline itself or failed, or it stays within the limits.
+pub async fn compare(db: &Db, config: &BaselineConfig, run: &Execution) -> Option<String> {
+    if run.status != ExecutionStatus::Success {
+        return None;
+    }
+    let pinned = match db.baseline(&run.workflow).await {
+        Ok(pinned) => pinned?,
+        Err(e) => {
+            warn!(workflow = %run.workflow, error = %e, "failed to look up the baseline");
+            return None;
+        }
+    };
+    if pinned == run.id {
+        return None;
+    }
+    match db.get_execution_by_id(&pinned).await {
+        Ok(baseline) => deviation(config, &baseline?, run),
+        Err(e) => {
+            warn!(workflow = %run.workflow, error = %e, "failed to load the baseline");
+            None
+        }
+    }
+}
+
+/// What `run` does worse than `baseline` by more than `config` allows, parts joined by `; `.
+/// Only regressions count: a faster run, or one with fewer errors, is never flagged.
+pub fn deviation(config: &BaselineConfig, baseline: &Execution, run: &Execution) -> Option<String> {
+    let mut found = Vec::new();
+    // stored in whole seconds, so a baseline under a second gives nothing to compare with
+    let durations = (duration(baseline), duration(run));
+    if let Some((base, took)) = durations.0.filter(|d| d.as_secs() > 0).zip(durations.1) {
+        let increase = percent(base.as_secs_f64(), took.as_secs_f64());
+        if increase > config.max_duration_increase {
+            found.push(format!(
+                "took {}, {:.0}% longer than the baseline's {}",
+                format_duration(&took),
+                increase,
+                format_duration(&base)
+            ));
+        }
+    }
+    let (base, errors) = (baseline.severity.errors, run.severity.errors);
+    if percent(base as f64, errors as f64) > config.max_error_increase {
+        found.push(format!("{} error lines, up from {} in the baseline", errors, base));
+    }
+    (!found.is_empty()).then(|| found.join("; "))
+}
+
+/// In whole seconds, as storage keeps them, so a run just finished compares like one loaded.
+fn duration(execution: &Execution) -> Option<std::time::Duration> {
+    let secs = execution.finished_at?.timestamp() - execution.started_at.timestamp();
+    Some(std::time::Duration::from_secs(secs.try_into().ok()?))
+}
+
+/// How much larger `value` is than `base`, in percent; any increase on nothing is infinite.
+fn percent(base: f64, value: f64) -> f64 {
+    if value <= base {
+        0.0
+    } else if base == 0.0 {
+        f64::INFINITY
+    } else {
+        (value - base) * 100.0 / base
+    }
+}
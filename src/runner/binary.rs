@@ -0,0 +1,31 @@
+/// `output` with the bytes that would garble a text log written as `\xNN`: invalid UTF-8 and
+/// control characters other than tab, newline, carriage return, backspace and escape (colors).
+/// `None` when there are none, which is the usual case.
+pub fn escape(output: &[u8]) -> Option<Vec<u8>> {
+    if std::str::from_utf8(output).is_ok_and(|text| !text.bytes().any(is_control)) {
+        return None;
+    }
+    let mut escaped = Vec::with_capacity(output.len());
+    for chunk in output.utf8_chunks() {
+        // control characters are ASCII, so they never sit inside a multi-byte character
+        for b in chunk.valid().bytes() {
+            if is_control(b) {
+                push_escape(&mut escaped, b);
+            } else {
+                escaped.push(b);
+            }
+        }
+        for &b in chunk.invalid() {
+            push_escape(&mut escaped, b);
+        }
+    }
+    Some(escaped)
+}
+
+fn is_control(b: u8) -> bool {
+    (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x08 | 0x1b)) || b == 0x7f
+}
+
+fn push_escape(out: &mut Vec<u8>, b: u8) {
+    out.extend_from_slice(format!("\\x{:02x}", b).as_bytes());
+}
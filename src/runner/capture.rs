@@ -0,0 +1,183 @@
+use std::borrow::Cow;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+use tokio::time::Instant;
+
+use crate::common::types::{CollapsedRepeat, LogSeverity, WorkflowConfig};
+use crate::runner::output::{self, Stream};
+use crate::runner::redact::Redactor;
+use crate::runner::severity::{Detector, count_lines};
+use crate::runner::binary;
+use crate::runner::dedup::{self, Collapser, Out};
+
+/// Output written to the log at the latest this long after it arrived.
+pub const FLUSH_AFTER: Duration = Duration::from_millis(100);
+
+/// Output held in memory before it is written, at most.
+const FLUSH_SIZE: usize = 64 * 1024;
+
+/// What was noted about a script's output on its way into the log.
+#[derive(Default)]
+pub struct Captured {
+    pub severity: LogSeverity,
+    /// It held binary data.
+    pub binary: bool,
+    pub repeats: Vec<CollapsedRepeat>,
+}
+
+/// Writes script output to the log as it arrives: redacted, binary data escaped, tagged with
+/// its stream, stamped with `log_timestamps`, collapsed with `collapse_repeats`, its errors and
+/// warnings counted. Redaction and collapsing work on stretches of one stream, and of one
+/// second with timestamps; lines that may still be part of a secret are held back, and the
+/// copies of a repeated line until the run of them ends.
+pub struct Capture<'a> {
+    log_file: &'a mut File,
+    redactor: &'a Redactor,
+    detector: &'a Detector,
+    timestamps: bool,
+    /// Log line the next line written is on.
+    line: u32,
+    stretch: Option<(Stream, Option<String>)>,
+    /// Lines of the stretch not redacted yet, and how many.
+    held: Vec<u8>,
+    held_lines: usize,
+    /// With `collapse_repeats`, the run of identical lines the stretch is in.
+    collapser: Option<Collapser>,
+    /// Tagged lines not written yet, and since when there are any.
+    out: Vec<u8>,
+    since: Option<Instant>,
+    captured: Captured,
+}
+
+impl<'a> Capture<'a> {
+    /// The output starts at log line `first_line`.
+    pub fn new(
+        log_file: &'a mut File,
+        wf: &WorkflowConfig,
+        redactor: &'a Redactor,
+        detector: &'a Detector,
+        first_line: u32,
+    ) -> Self {
+        Self {
+            log_file,
+            redactor,
+            detector,
+            timestamps: wf.log_timestamps,
+            line: first_line,
+            stretch: None,
+            held: Vec::new(),
+            held_lines: 0,
+            collapser: wf.collapse_repeats.then(Collapser::default),
+            out: Vec::new(),
+            since: None,
+            captured: Captured::default(),
+        }
+    }
+
+    /// Take in a line from `stream` that arrived `at`.
+    pub async fn line(&mut self, stream: Stream, at: DateTime<Local>, line: Vec<u8>) -> Result<()> {
+        let stretch = (stream, self.timestamps.then(|| output::stamp(&at)));
+        if self.stretch.as_ref() != Some(&stretch) {
+            self.end_stretch();
+            self.stretch = Some(stretch);
+        }
+        self.held.extend_from_slice(&line);
+        self.held_lines += 1;
+        while self.held_lines >= self.redactor.span() {
+            let cut = self.redactor.cut(&self.held);
+            let text: Vec<u8> = self.held.drain(..cut).collect();
+            self.held_lines -= text.split_inclusive(|b| *b == b'\n').count();
+            self.emit(&text);
+        }
+        if self.out.len() >= FLUSH_SIZE {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// When what was taken in so far has to be written by, if anything is waiting.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.since.map(|since| since + FLUSH_AFTER)
+    }
+
+    /// Write what is waiting to the log.
+    pub async fn flush(&mut self) -> Result<()> {
+        if !self.out.is_empty() {
+            self.log_file.write_all(&self.out).await?;
+            self.log_file.flush().await?;
+            self.out.clear();
+        }
+        self.since = None;
+        Ok(())
+    }
+
+    /// Write out everything held back, once the output has ended.
+    pub async fn finish(mut self) -> Result<Captured> {
+        self.end_stretch();
+        self.flush().await?;
+        Ok(self.captured)
+    }
+
+    fn end_stretch(&mut self) {
+        if !self.held.is_empty() {
+            let text = std::mem::take(&mut self.held);
+            self.held_lines = 0;
+            self.emit(&text);
+        }
+        if let Some(out) = self.collapser.as_mut().and_then(Collapser::finish) {
+            self.put(out);
+        }
+    }
+
+    /// `text`, whole lines, redacted and escaped, on its way to the log.
+    fn emit(&mut self, text: &[u8]) {
+        let text = self.redactor.redact(Cow::Borrowed(text));
+        let text = match binary::escape(&text) {
+            Some(escaped) => {
+                self.captured.binary = true;
+                escaped.into()
+            }
+            None => text,
+        };
+        let Some(collapser) = &mut self.collapser else {
+            self.write(&text);
+            return;
+        };
+        let mut outs = Vec::new();
+        for line in text.split_inclusive(|b| *b == b'\n') {
+            outs.extend(collapser.push(line));
+        }
+        for out in outs {
+            self.put(out);
+        }
+    }
+
+    /// Write `out`, noting the run a marker stands for.
+    fn put(&mut self, out: Out) {
+        match out {
+            Out::Line(line) => self.write(&line),
+            Out::Repeated { line, dropped } => {
+                self.detector.scan_repeats(&line, dropped, &mut self.captured.severity);
+                // the copy kept was the last line written
+                let log_line = self.line - 1;
+                self.captured.repeats.push(CollapsedRepeat { log_line, times: dropped });
+                self.write(dedup::marker(dropped).as_bytes());
+            }
+        }
+    }
+
+    /// Count the errors and warnings in `text`, whole lines as they go in the log, and queue it
+    /// tagged for writing.
+    fn write(&mut self, text: &[u8]) {
+        let Some((stream, stamp)) = &self.stretch else { return };
+        self.detector.scan(text, self.line, &mut self.captured.severity);
+        let tagged = output::tag(*stream, stamp.as_deref(), text);
+        self.line += count_lines(&tagged);
+        self.out.extend_from_slice(&tagged);
+        self.since.get_or_insert_with(Instant::now);
+    }
+}
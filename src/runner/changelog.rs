@@ -0,0 +1,96 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use chrono::Utc;
+use serde_yaml::Value;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::common::storage::Db;
+use crate::common::types::{AppConfig, ConfigChange, WorkflowConfig};
+use crate::runner::config_diff;
+
+/// Record how each workflow's config differs from the last time the Runner loaded it, at
+/// startup or on a reload, and remember it as it is now. Edits made while the Runner was
+/// stopped are caught at the next start. With nothing remembered yet, as on the first start,
+/// the workflows are only remembered. Only what a change summary can show is stored; see
+/// [`snapshot`].
+pub async fn record(db: &Db, config: &AppConfig) {
+    if let Err(e) = try_record(db, config).await {
+        warn!(error = %e, "failed to record config changes");
+    }
+}
+
+async fn try_record(db: &Db, config: &AppConfig) -> Result<()> {
+    let snapshots = db.config_snapshots().await?;
+    let first = snapshots.is_empty();
+    let changed_at = Utc::now();
+    let mut recorded = 0;
+    for wf in &config.workflows {
+        let new = snapshot(wf)?;
+        let yaml = serde_yaml::to_string(&new)?;
+        let summary = match snapshots.get(&wf.name) {
+            Some(old) if *old == yaml => continue,
+            Some(old) => {
+                // snapshots stored before values were hashed are hashed alike to compare them
+                let old = hash_hidden(serde_yaml::from_str(old)?);
+                config_diff::describe_changes(&old, &new)
+            }
+            None if first => String::new(),
+            None => "added".to_string(),
+        };
+        if !summary.is_empty() {
+            let workflow = wf.name.clone();
+            db.add_config_change(&ConfigChange { workflow, changed_at, summary }).await?;
+            recorded += 1;
+        }
+        db.set_config_snapshot(&wf.name, Some(&yaml)).await?;
+    }
+    for name in snapshots.keys() {
+        if config.workflows.iter().any(|w| w.name == *name) {
+            continue;
+        }
+        let workflow = name.clone();
+        let summary = "removed".to_string();
+        db.add_config_change(&ConfigChange { workflow, changed_at, summary }).await?;
+        db.set_config_snapshot(name, None).await?;
+        recorded += 1;
+    }
+    if recorded > 0 {
+        info!(workflows = recorded, "config changes recorded");
+    }
+    Ok(())
+}
+
+/// The workflow's config as it is stored between loads. Values a change summary shows by key
+/// only, such as `env`, headers and anything else nested or long, are kept as a SHA-256 hash:
+/// enough to tell that they changed, without keeping secrets in the database.
+fn snapshot(wf: &WorkflowConfig) -> Result<Value> {
+    Ok(hash_hidden(serde_yaml::to_value(wf)?))
+}
+
+const HASH_PREFIX: &str = "sha256:";
+
+fn hash_hidden(config: Value) -> Value {
+    let Value::Mapping(config) = config else {
+        return config;
+    };
+    let hashed = config.into_iter().map(|(key, value)| {
+        let hashed = match &value {
+            Value::String(s) if s.starts_with(HASH_PREFIX) => value,
+            value if config_diff::shown(value).is_some() => value.clone(),
+            value => Value::String(hash(value)),
+        };
+        (key, hashed)
+    });
+    Value::Mapping(hashed.collect())
+}
+
+fn hash(value: &Value) -> String {
+    let yaml = serde_yaml::to_string(value).unwrap_or_default();
+    let mut hex = String::from(HASH_PREFIX);
+    for byte in Sha256::digest(yaml.as_bytes()) {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
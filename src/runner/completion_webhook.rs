@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use hmac::{Hmac, KeyInit, Mac};
+use serde_json::{Value, json};
+use sha2::Sha256;
+use tracing::{info, warn};
+
+use crate::common::types::{
+    CompletionWebhook, Execution, ExecutionStatus, SecretsConfig, WebhookEvent,
+};
+use crate::runner::{http, secrets};
+
+/// Carries the body's HMAC-SHA256 under a webhook's `secret`, as `sha256=<hex>`.
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Give up on a webhook that has not answered after this long.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// POST the finished `exec` to each of `hooks` that wants its outcome, in the background.
+pub fn fire(hooks: &[CompletionWebhook], exec: &Execution) {
+    let event = match exec.status {
+        ExecutionStatus::Success => WebhookEvent::Success,
+        ExecutionStatus::Failed | ExecutionStatus::ConfigError => WebhookEvent::Failed,
+        ExecutionStatus::Running => return,
+    };
+    let hooks: Vec<_> = hooks.iter().filter(|h| h.on.is_empty() || h.on.contains(&event)).collect();
+    if hooks.is_empty() {
+        return;
+    }
+    let current = secrets::current_secrets();
+    for hook in hooks {
+        // one that names no secret doesn't need the backend
+        let secrets = match &current {
+            Ok(secrets) => secrets.clone(),
+            Err(_) if !secrets::referenced(hook) => SecretsConfig::default(),
+            Err(_) => {
+                warn!(url = %hook.url, "completion webhook not sent: no secrets backend");
+                continue;
+            }
+        };
+        let hook = hook.clone();
+        let body = render_body(&hook, exec);
+        let workflow = exec.workflow.clone();
+        tokio::spawn(async move {
+            let sent = post(&hook.url, &hook.headers, hook.secret.as_deref(), &secrets, &body);
+            match sent.await {
+                Ok(()) => info!(workflow = %workflow, url = %hook.url, "completion webhook sent"),
+                Err(e) => {
+                    warn!(workflow = %workflow, url = %hook.url, error = %e, "completion webhook failed")
+                }
+            }
+        });
+    }
+}
+
+/// The `{{field}}` placeholders of `exec` for body templates.
+pub fn fields(exec: &Execution) -> Vec<(&'static str, String)> {
+    let finished_at = exec.finished_at.unwrap_or(exec.started_at);
+    vec![
+        ("workflow", exec.workflow.clone()),
+        ("execution_id", exec.id.clone()),
+        ("status", exec.status.as_str().to_string()),
+        ("exit_code", exec.exit_code.unwrap_or(-1).to_string()),
+        ("started_at", exec.started_at.to_rfc3339()),
+        ("finished_at", finished_at.to_rfc3339()),
+        ("duration_secs", (finished_at - exec.started_at).num_seconds().to_string()),
+        ("log_path", exec.log_path.clone()),
+        ("args", exec.args.join(" ")),
+    ]
+}
+
+/// The script's result document, or `null` without one.
+pub fn result(exec: &Execution) -> Value {
+    exec.result_json
+        .as_deref()
+        .and_then(|j| serde_json::from_str(j).ok())
+        .unwrap_or(Value::Null)
+}
+
+/// `exec` as the JSON object sent without a `body` template, also handed to plugins.
+pub fn payload(exec: &Execution) -> Value {
+    let finished_at = exec.finished_at.unwrap_or(exec.started_at);
+    json!({
+        "workflow": exec.workflow,
+        "execution_id": exec.id,
+        "status": exec.status.as_str(),
+        "exit_code": exec.exit_code,
+        "started_at": exec.started_at.to_rfc3339(),
+        "finished_at": finished_at.to_rfc3339(),
+        "duration_secs": (finished_at - exec.started_at).num_seconds(),
+        "log_path": exec.log_path,
+        "args": exec.args,
+        "result": result(exec),
+    })
+}
+
+/// The configured template with placeholders replaced, or a JSON object of all fields.
+fn render_body(hook: &CompletionWebhook, exec: &Execution) -> String {
+    match &hook.body {
+        Some(template) => render(template, &fields(exec), &result(exec)),
+        None => payload(exec).to_string(),
+    }
+}
+
+/// `template` with each `{{name}}` of `fields` replaced by its JSON-escaped value, so it can
+/// sit inside a JSON string. `{{result}}` inserts `result` as is.
+pub fn render(template: &str, fields: &[(&str, String)], result: &Value) -> String {
+    let mut body = template.to_string();
+    for (name, value) in fields {
+        let escaped = serde_json::to_string(value).unwrap_or_default();
+        body = body.replace(&format!("{{{{{}}}}}", name), &escaped[1..escaped.len() - 1]);
+    }
+    body = body.replace("{{result}}", &result.to_string());
+    render_result_fields(&body, result)
+}
+
+/// Replace `{{result.<key>}}` with that field of the result: strings JSON-escaped without
+/// quotes, other values (and missing fields, as `null`) as JSON.
+fn render_result_fields(template: &str, result: &Value) -> String {
+    const OPEN: &str = "{{result.";
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(OPEN) {
+        let Some(len) = rest[start + OPEN.len()..].find("}}") else { break };
+        let key = &rest[start + OPEN.len()..start + OPEN.len() + len];
+        out.push_str(&rest[..start]);
+        match result.get(key) {
+            Some(Value::String(s)) => {
+                let escaped = Value::String(s.clone()).to_string();
+                out.push_str(&escaped[1..escaped.len() - 1]);
+            }
+            Some(other) => out.push_str(&other.to_string()),
+            None => out.push_str("null"),
+        }
+        rest = &rest[start + OPEN.len() + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// POST `body` to `url`. Header values may be `secret://NAME` references; with `secret`, the
+/// body is signed in [`SIGNATURE_HEADER`].
+pub async fn post(
+    url: &str,
+    headers: &BTreeMap<String, String>,
+    secret: Option<&str>,
+    secrets: &SecretsConfig,
+    body: &str,
+) -> Result<()> {
+    let mut resolved = BTreeMap::new();
+    for (name, value) in headers {
+        resolved.insert(name.clone(), secrets::value(value, name, secrets).await?);
+    }
+    if let Some(secret) = secret {
+        let key = secrets::value(secret, "secret", secrets).await?;
+        resolved.insert(SIGNATURE_HEADER.to_string(), signature(&key, body));
+    }
+    let code = http::send("POST", url, &resolved, body, TIMEOUT).await?;
+    if !(200..300).contains(&code) {
+        bail!("HTTP {}", code);
+    }
+    Ok(())
+}
+
+/// `sha256=<hex>` of the HMAC-SHA256 of `body` under `key`.
+fn signature(key: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC takes any key");
+    mac.update(body.as_bytes());
+    let mut hex = String::from("sha256=");
+    for byte in mac.finalize().into_bytes() {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
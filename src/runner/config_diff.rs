@@ -0,0 +1,133 @@
+use serde_yaml::Value;
+
+use crate::common::types::{AppConfig, WorkflowConfig};
+
+/// What a reload changed in the workflow list, by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Each changed workflow with the keys that differ, e.g. `cron` or `script`.
+    pub changed: Vec<(String, Vec<String>)>,
+}
+
+impl ConfigDiff {
+    pub fn between(old: &AppConfig, new: &AppConfig) -> Self {
+        let find = |config: &AppConfig, name: &str| {
+            config.workflows.iter().find(|w| w.name == name).cloned()
+        };
+        let mut diff = ConfigDiff::default();
+        for wf in &new.workflows {
+            match find(old, &wf.name) {
+                None => diff.added.push(wf.name.clone()),
+                Some(before) => {
+                    let keys = changed_keys(&before, wf);
+                    if !keys.is_empty() {
+                        diff.changed.push((wf.name.clone(), keys));
+                    }
+                }
+            }
+        }
+        for wf in &old.workflows {
+            if find(new, &wf.name).is_none() {
+                diff.removed.push(wf.name.clone());
+            }
+        }
+        diff
+    }
+
+    /// The part of the diff about workflows `can_see` lets through.
+    pub fn visible_to(&self, can_see: impl Fn(&str) -> bool) -> Self {
+        ConfigDiff {
+            added: self.added.iter().filter(|n| can_see(n)).cloned().collect(),
+            removed: self.removed.iter().filter(|n| can_see(n)).cloned().collect(),
+            changed: self.changed.iter().filter(|(n, _)| can_see(n)).cloned().collect(),
+        }
+    }
+
+    /// One line for logs and the TUI, e.g. `added backup; changed sync (cron, script)`.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.added.is_empty() {
+            parts.push(format!("added {}", self.added.join(", ")));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("removed {}", self.removed.join(", ")));
+        }
+        if !self.changed.is_empty() {
+            let changed: Vec<String> = self
+                .changed
+                .iter()
+                .map(|(name, keys)| format!("{} ({})", name, keys.join(", ")))
+                .collect();
+            parts.push(format!("changed {}", changed.join(", ")));
+        }
+        if parts.is_empty() {
+            return "no workflow changes".to_string();
+        }
+        parts.join("; ")
+    }
+}
+
+/// The config keys whose values differ between two versions of a workflow.
+fn changed_keys(old: &WorkflowConfig, new: &WorkflowConfig) -> Vec<String> {
+    let (Ok(Value::Mapping(old)), Ok(Value::Mapping(new))) =
+        (serde_yaml::to_value(old), serde_yaml::to_value(new))
+    else {
+        return vec!["workflow".to_string()];
+    };
+    new.iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .chain(old.iter().filter(|(key, _)| !new.contains_key(*key)))
+        .filter_map(|(key, _)| key.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Longest value [`describe_changes`] shows; longer ones, and nested ones, show as their key.
+const MAX_SHOWN: usize = 40;
+
+/// What changed between two saved versions of a workflow's config, as YAML mappings: each
+/// changed key, with `old → new` for short values, e.g. `cron 0 3 * * * → 0 4 * * *, env`.
+/// Keys only one version has are left out, so fields a newer illef-workflow added don't read as
+/// edits. Empty if nothing changed.
+pub fn describe_changes(old: &Value, new: &Value) -> String {
+    let (Value::Mapping(old), Value::Mapping(new)) = (old, new) else {
+        return "workflow".to_string();
+    };
+    let mut changes = Vec::new();
+    for (key, value) in new {
+        let (Some(key), Some(before)) = (key.as_str(), old.get(key)) else {
+            continue;
+        };
+        if before == value {
+            continue;
+        }
+        match (shown(before), shown(value)) {
+            (Some(before), Some(after)) => changes.push(format!("{} {} → {}", key, before, after)),
+            _ => changes.push(key.to_string()),
+        }
+    }
+    changes.join(", ")
+}
+
+/// `value` as [`describe_changes`] shows it: a scalar, or a list of them, if short enough.
+pub fn shown(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::Null => "unset".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Sequence(items) => {
+            let items: Option<Vec<String>> = items
+                .iter()
+                .map(|item| match item {
+                    Value::Sequence(_) | Value::Mapping(_) => None,
+                    item => shown(item),
+                })
+                .collect();
+            format!("[{}]", items?.join(", "))
+        }
+        _ => return None,
+    };
+    (text.chars().count() <= MAX_SHOWN && !text.contains('\n')).then_some(text)
+}
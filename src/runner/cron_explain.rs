@@ -0,0 +1,212 @@
+//! English descriptions of cron expressions, e.g. `0 3 * * *` → "every day at 03:00".
+//!
+//! Only common shapes are described; anything else returns `None` and callers show the raw
+//! expression. Day-of-week numbers follow the `cron` crate: 1 is Sunday and 7 is Saturday.
+
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+const WEEKDAY_NAMES: [&str; 7] =
+    ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+const MONTHS: [&str; 12] =
+    ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August", "September",
+    "October", "November", "December",
+];
+
+/// Most times listed before falling back to the raw expression.
+const MAX_TIMES: usize = 6;
+
+#[derive(Debug, PartialEq)]
+enum Field {
+    Any,
+    Step(u32),
+    Values(Vec<u32>),
+}
+
+/// Describe a 5- or 6-field cron expression (and a 7-field one whose year is `*`).
+pub fn explain(expr: &str) -> Option<String> {
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    let (seconds, rest) = match fields.len() {
+        5 => ("0", &fields[..]),
+        6 => (fields[0], &fields[1..]),
+        7 if fields[6] == "*" => (fields[0], &fields[1..6]),
+        _ => return None,
+    };
+    let minute = parse(rest[0], &[], 0)?;
+    let hour = parse(rest[1], &[], 0)?;
+    let dom = parse(rest[2], &[], 0)?;
+    let month = parse(rest[3], &MONTHS, 1)?;
+    let dow = parse(rest[4], &WEEKDAYS, 1)?;
+
+    let (time, recurring) = match parse(seconds, &[], 0)? {
+        Field::Values(s) if s == [0] => time_phrase(&minute, &hour)?,
+        Field::Step(n) if minute == Field::Any && hour == Field::Any => {
+            (format!("every {} seconds", n), true)
+        }
+        _ => return None,
+    };
+    let (days, months) = match (&dom, &dow, &month) {
+        // name the dates directly: "on January 1st", "on the 1st of January and July"
+        (Field::Values(days), Field::Any, Field::Values(months)) => {
+            let days = join(days, |&d| ordinal(d));
+            let date = match months.as_slice() {
+                [m] => format!("on {} {}", month_name(*m), days),
+                _ => format!("on the {} of {}", days, join(months, |&m| month_name(m))),
+            };
+            (Some(date), None)
+        }
+        (_, _, Field::Values(m)) => {
+            let months = format!("in {}", join(m, |&m| month_name(m)));
+            (day_phrase(&dom, &dow, recurring)?, Some(months))
+        }
+        (_, _, Field::Any) => (day_phrase(&dom, &dow, recurring)?, None),
+        (_, _, Field::Step(_)) => return None,
+    };
+
+    let mut words = Vec::new();
+    if recurring {
+        words.push(time);
+        words.extend(days);
+    } else {
+        words.push(days.unwrap_or_else(|| "every day".to_string()));
+        words.push(time);
+    }
+    words.extend(months);
+    Some(words.join(" "))
+}
+
+/// `*`, `*/n`, or a comma-separated list of numbers, ranges and (if `names` is given) names.
+fn parse(field: &str, names: &[&str], first: u32) -> Option<Field> {
+    if field == "*" || field == "?" {
+        return Some(Field::Any);
+    }
+    if let Some(step) = field.strip_prefix("*/") {
+        return match step.parse().ok()? {
+            0 => None,
+            1 => Some(Field::Any),
+            n => Some(Field::Step(n)),
+        };
+    }
+    let value = |s: &str| -> Option<u32> {
+        s.parse().ok().or_else(|| {
+            let upper = s.to_ascii_uppercase();
+            names.iter().position(|n| *n == upper).map(|i| i as u32 + first)
+        })
+    };
+    let mut values = Vec::new();
+    for item in field.split(',') {
+        match item.split_once('-') {
+            Some((a, b)) => {
+                let (a, b) = (value(a)?, value(b)?);
+                if a > b {
+                    return None;
+                }
+                values.extend(a..=b);
+            }
+            None => values.push(value(item)?),
+        }
+    }
+    values.sort_unstable();
+    values.dedup();
+    Some(Field::Values(values))
+}
+
+/// The time-of-day part, and whether it repeats within a day.
+fn time_phrase(minute: &Field, hour: &Field) -> Option<(String, bool)> {
+    let phrase = match (minute, hour) {
+        (Field::Any, Field::Any) => ("every minute".to_string(), true),
+        (Field::Step(n), Field::Any) => (format!("every {} minutes", n), true),
+        (Field::Values(m), Field::Any) if m == &[0] => ("every hour".to_string(), true),
+        (Field::Values(m), Field::Any) => {
+            (format!("every hour at {}", join(m, |m| format!(":{:02}", m))), true)
+        }
+        (Field::Values(m), Field::Step(n)) if m == &[0] => (format!("every {} hours", n), true),
+        (Field::Values(m), Field::Step(n)) if m.len() == 1 => {
+            (format!("every {} hours at :{:02}", n, m[0]), true)
+        }
+        (Field::Any | Field::Step(_), Field::Values(h)) => {
+            let every = match minute {
+                Field::Step(n) => format!("every {} minutes", n),
+                _ => "every minute".to_string(),
+            };
+            (format!("{} {}", every, hour_span(h)?), true)
+        }
+        (Field::Values(m), Field::Values(h)) if m.len() * h.len() <= MAX_TIMES => {
+            let times: Vec<(u32, u32)> =
+                h.iter().flat_map(|&h| m.iter().map(move |&m| (h, m))).collect();
+            (format!("at {}", join(&times, |(h, m)| format!("{:02}:{:02}", h, m))), false)
+        }
+        (Field::Values(m), Field::Values(h)) if m.len() == 1 => {
+            (format!("every hour at :{:02} {}", m[0], hour_span(h)?), true)
+        }
+        _ => return None,
+    };
+    Some(phrase)
+}
+
+/// "from 09:00 to 17:59" for a contiguous run of hours.
+fn hour_span(hours: &[u32]) -> Option<String> {
+    let (first, last) = (*hours.first()?, *hours.last()?);
+    if last - first + 1 != hours.len() as u32 {
+        return None;
+    }
+    Some(format!("from {:02}:00 to {:02}:59", first, last))
+}
+
+/// The day part, or `None` for every day. Restricting both day fields is not described.
+fn day_phrase(dom: &Field, dow: &Field, recurring: bool) -> Option<Option<String>> {
+    let phrase = match (dom, dow) {
+        (Field::Any, Field::Any) => return Some(None),
+        (Field::Values(days), Field::Any) => {
+            let days = join(days, |&d| ordinal(d));
+            if recurring {
+                format!("on the {} of the month", days)
+            } else {
+                format!("on the {} of every month", days)
+            }
+        }
+        (Field::Any, Field::Values(days)) => {
+            let days: Vec<u32> = days.iter().copied().filter(|d| (1..=7).contains(d)).collect();
+            match days.as_slice() {
+                [2, 3, 4, 5, 6] if recurring => "on weekdays".to_string(),
+                [2, 3, 4, 5, 6] => "every weekday".to_string(),
+                [1, 7] => "on weekends".to_string(),
+                [] => return None,
+                _ if recurring => format!("on {}", join(&days, |&d| format!("{}s", weekday(d)))),
+                _ => format!("every {}", join(&days, |&d| weekday(d))),
+            }
+        }
+        _ => return None,
+    };
+    Some(Some(phrase))
+}
+
+fn weekday(day: u32) -> String {
+    WEEKDAY_NAMES[(day as usize - 1) % 7].to_string()
+}
+
+fn month_name(month: u32) -> String {
+    let name = (month as usize).checked_sub(1).and_then(|i| MONTH_NAMES.get(i));
+    name.unwrap_or(&"?").to_string()
+}
+
+fn ordinal(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// "a", "a and b", "a, b and c".
+fn join<T>(items: &[T], f: impl Fn(&T) -> String) -> String {
+    let words: Vec<String> = items.iter().map(f).collect();
+    match words.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        Some((last, _)) => last.clone(),
+        None => String::new(),
+    }
+}
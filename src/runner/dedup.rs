@@ -0,0 +1,117 @@
+/// Runs of identical consecutive lines collapsed as they come, for `collapse_repeats`: the
+/// first line of a run of three or more is kept and the rest replaced with `… repeated 8,214
+/// times`. A line repeated once is left alone; the marker would be no shorter.
+#[derive(Default)]
+pub struct Collapser {
+    /// The line the current run is of, and how many copies of it were dropped so far.
+    last: Vec<u8>,
+    dropped: u32,
+}
+
+/// What goes in the log for the lines taken in.
+#[derive(Debug, PartialEq)]
+pub enum Out {
+    Line(Vec<u8>),
+    /// The marker for `dropped` copies of `line`, which comes right after the copy kept.
+    Repeated { line: Vec<u8>, dropped: u32 },
+}
+
+impl Collapser {
+    /// Take in `line`, whole; nothing goes in the log for it while it repeats the one before.
+    pub fn push(&mut self, line: &[u8]) -> Vec<Out> {
+        if line.ends_with(b"\n") && line == self.last.as_slice() {
+            self.dropped += 1;
+            return Vec::new();
+        }
+        let mut out: Vec<Out> = self.finish().into_iter().collect();
+        self.last = line.to_vec();
+        out.push(Out::Line(line.to_vec()));
+        out
+    }
+
+    /// End the current run, as the end of a stretch of output does.
+    pub fn finish(&mut self) -> Option<Out> {
+        let line = std::mem::take(&mut self.last);
+        match std::mem::take(&mut self.dropped) {
+            0 => None,
+            1 => Some(Out::Line(line)),
+            dropped => Some(Out::Repeated { line, dropped }),
+        }
+    }
+}
+
+/// The line that stands for `dropped` copies.
+pub fn marker(dropped: u32) -> String {
+    format!("… repeated {} times\n", thousands(dropped))
+}
+
+/// `8214` as `8,214`.
+fn thousands(n: u32) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collapse(lines: &[&str]) -> Vec<Out> {
+        let mut collapser = Collapser::default();
+        let mut out: Vec<Out> = lines.iter().flat_map(|l| collapser.push(l.as_bytes())).collect();
+        out.extend(collapser.finish());
+        out
+    }
+
+    fn line(s: &str) -> Out {
+        Out::Line(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn one_copy_is_kept() {
+        assert_eq!(collapse(&["a\n", "b\n"]), [line("a\n"), line("b\n")]);
+    }
+
+    #[test]
+    fn two_copies_are_left_alone() {
+        assert_eq!(collapse(&["a\n", "a\n", "b\n"]), [line("a\n"), line("a\n"), line("b\n")]);
+    }
+
+    #[test]
+    fn three_copies_collapse() {
+        let repeated = Out::Repeated { line: b"a\n".to_vec(), dropped: 2 };
+        assert_eq!(collapse(&["a\n", "a\n", "a\n", "b\n"]), [line("a\n"), repeated, line("b\n")]);
+    }
+
+    #[test]
+    fn finish_ends_the_run() {
+        let repeated = Out::Repeated { line: b"a\n".to_vec(), dropped: 3 };
+        assert_eq!(collapse(&["a\n"; 4]), [line("a\n"), repeated]);
+    }
+
+    #[test]
+    fn unterminated_line_is_not_a_repeat() {
+        assert_eq!(collapse(&["a", "a"]), [line("a"), line("a")]);
+    }
+
+    #[test]
+    fn marker_groups_thousands() {
+        assert_eq!(marker(2), "… repeated 2 times\n");
+        assert_eq!(marker(8214), "… repeated 8,214 times\n");
+    }
+
+    #[test]
+    fn thousands_at_group_edges() {
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(1000), "1,000");
+        assert_eq!(thousands(123456), "123,456");
+        assert_eq!(thousands(u32::MAX), "4,294,967,295");
+    }
+}
@@ -0,0 +1,157 @@
+use std::cmp::Reverse;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{Local, Utc};
+use cron::Schedule;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::common::config::load_config;
+use crate::common::db::logs_dir;
+use crate::common::duration::format_duration;
+use crate::common::storage::Db;
+use crate::common::types::{AppConfig, Priority};
+use crate::runner::executor::notify;
+use crate::runner::trigger::normalize_cron;
+
+/// How far back the digest looks.
+const DIGEST_PERIOD: chrono::Duration = chrono::Duration::days(7);
+
+/// Executions per workflow read when building the digest.
+const SAMPLE_LIMIT: usize = 1000;
+
+/// How often to re-check the config while the digest is disabled or misconfigured.
+const IDLE_RECHECK: Duration = Duration::from_secs(3600);
+
+pub fn start(db: Db) -> JoinHandle<()> {
+    tokio::spawn(digest_loop(db))
+}
+
+/// Send the digest on `notifications.digest.cron`, re-reading the config before each sleep.
+async fn digest_loop(db: Db) {
+    loop {
+        let Some(cron) = load_config()
+            .ok()
+            .and_then(|c| c.notifications.digest)
+            .map(|d| d.cron)
+        else {
+            tokio::time::sleep(IDLE_RECHECK).await;
+            continue;
+        };
+        let next = Schedule::from_str(&normalize_cron(&cron))
+            .ok()
+            .and_then(|s| s.upcoming(Local).next());
+        let Some(next) = next else {
+            warn!(cron = %cron, "invalid digest cron expression, retrying in an hour");
+            tokio::time::sleep(IDLE_RECHECK).await;
+            continue;
+        };
+
+        let wait = (next - Local::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        // the digest may have been switched off while sleeping
+        let config = match load_config() {
+            Ok(config) if config.notifications.digest.is_some() => config,
+            Ok(_) => continue,
+            Err(e) => {
+                error!(error = %e, "cannot load config for digest");
+                continue;
+            }
+        };
+        match build_digest(&db, &config).await {
+            Ok(body) => {
+                let title = "Weekly workflow digest";
+                notify(&config.notifications, title, &body, Priority::Low, None);
+                info!("digest sent");
+            }
+            Err(e) => error!(error = %e, "building digest failed"),
+        }
+    }
+}
+
+/// Runs, failures, the slowest workflows and log disk usage over the last [`DIGEST_PERIOD`].
+pub async fn build_digest(db: &Db, config: &AppConfig) -> Result<String> {
+    let since = Utc::now() - DIGEST_PERIOD;
+    let mut runs = 0;
+    let mut failures: Vec<(String, usize)> = Vec::new();
+    let mut slowest: Vec<(String, i64)> = Vec::new();
+
+    for wf in &config.workflows {
+        let samples: Vec<_> = db
+            .get_durations(&wf.name, SAMPLE_LIMIT)
+            .await?
+            .into_iter()
+            .filter(|s| s.started_at >= since)
+            .collect();
+        runs += samples.len();
+
+        let failed = samples.iter().filter(|s| s.status.is_failure()).count();
+        if failed > 0 {
+            failures.push((wf.name.clone(), failed));
+        }
+        if let Some(max) = samples.iter().map(|s| s.duration_secs).max() {
+            slowest.push((wf.name.clone(), max));
+        }
+    }
+    failures.sort_by_key(|(_, n)| Reverse(*n));
+    slowest.sort_by_key(|(_, secs)| Reverse(*secs));
+
+    let failed_total: usize = failures.iter().map(|(_, n)| n).sum();
+    let mut lines = vec![format!(
+        "{} runs, {} failed since {}",
+        runs,
+        failed_total,
+        since.with_timezone(&Local).format("%Y-%m-%d")
+    )];
+    if !failures.is_empty() {
+        let list: Vec<String> = failures.iter().map(|(name, n)| format!("{} ×{}", name, n)).collect();
+        lines.push(format!("Failures: {}", list.join(", ")));
+    }
+    if !slowest.is_empty() {
+        let list: Vec<String> = slowest
+            .iter()
+            .take(3)
+            .map(|(name, secs)| match secs {
+                0 => format!("{} <1s", name),
+                _ => format!("{} {}", name, format_duration(&Duration::from_secs(*secs as u64))),
+            })
+            .collect();
+        lines.push(format!("Slowest: {}", list.join(", ")));
+    }
+
+    let logs = logs_dir();
+    let log_bytes = tokio::task::spawn_blocking(move || dir_size(&logs)).await?;
+    lines.push(format!("Logs: {}", format_bytes(log_bytes)));
+    Ok(lines.join("\n"))
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else { return 0 };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::common::config::{hostname, resolve_path};
+use crate::common::duration::format_duration;
+use crate::common::types::DistributedLockConfig;
+use crate::runner::http;
+
+/// How often a waiting run checks the lock again.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Who holds a lock. Stored as JSON in lock files and sent to lock services.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Holder {
+    host: String,
+    pid: u32,
+    workflow: String,
+    execution_id: String,
+    acquired_at: DateTime<Utc>,
+    heartbeat_at: DateTime<Utc>,
+}
+
+impl Holder {
+    fn describe(&self) -> String {
+        format!(
+            "{} on {} (pid {}) since {}",
+            self.workflow,
+            self.host,
+            self.pid,
+            self.acquired_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+        )
+    }
+}
+
+enum Backend {
+    File(PathBuf),
+    Http {
+        url: String,
+        headers: BTreeMap<String, String>,
+    },
+}
+
+enum Attempt {
+    Acquired,
+    /// The previous holder stopped refreshing the lock.
+    TookOver(Option<Holder>),
+    Held(Option<Holder>),
+}
+
+/// A held lock, refreshed in the background until [`LockGuard::release`].
+pub struct LockGuard {
+    backend: Arc<Backend>,
+    holder: Holder,
+    heartbeat: JoinHandle<()>,
+}
+
+/// Wait up to `config.wait` for the lock, writing progress to `log`.
+pub async fn acquire(
+    config: &DistributedLockConfig,
+    workflow: &str,
+    execution_id: &str,
+    log: &mut File,
+) -> Result<LockGuard> {
+    let backend = Arc::new(match (&config.path, &config.url) {
+        (Some(path), None) => Backend::File(resolve_path(path)),
+        (None, Some(url)) => Backend::Http {
+            url: url.clone(),
+            headers: config.headers.clone(),
+        },
+        _ => bail!("distributed_lock needs exactly one of `path` and `url`"),
+    });
+    let name = backend.name();
+    let mut holder = Holder {
+        host: hostname(),
+        pid: std::process::id(),
+        workflow: workflow.to_string(),
+        execution_id: execution_id.to_string(),
+        acquired_at: Utc::now(),
+        heartbeat_at: Utc::now(),
+    };
+
+    let deadline = Instant::now() + config.wait;
+    let mut waiting = false;
+    let mut last_error = None;
+    loop {
+        // what gets written on success must not already look stale after a long wait
+        holder.acquired_at = Utc::now();
+        holder.heartbeat_at = holder.acquired_at;
+        match backend.try_acquire(&holder, config.stale_after).await {
+            Ok(Attempt::Acquired) => break,
+            Ok(Attempt::TookOver(previous)) => {
+                let previous = previous.map_or("an unknown holder".to_string(), |h| h.describe());
+                log_line(log, &format!("took over stale lock {} from {}", name, previous)).await?;
+                break;
+            }
+            Ok(Attempt::Held(other)) if !waiting => {
+                let by = other.map_or(String::new(), |h| format!(", held by {}", h.describe()));
+                log_line(log, &format!("waiting for lock {}{}", name, by)).await?;
+                waiting = true;
+            }
+            Ok(Attempt::Held(_)) => {}
+            // a lock service that stays down would otherwise log every poll
+            Err(e) if last_error.as_ref() != Some(&e.to_string()) => {
+                log_line(log, &format!("lock {}: {}", name, e)).await?;
+                last_error = Some(e.to_string());
+            }
+            Err(_) => {}
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            bail!("lock {} still held after {}", name, format_duration(&config.wait));
+        }
+        tokio::time::sleep(POLL_INTERVAL.min(deadline - now)).await;
+    }
+
+    log_line(log, &format!("acquired lock {}", name)).await?;
+
+    let heartbeat = tokio::spawn({
+        let (backend, mut holder) = (Arc::clone(&backend), holder.clone());
+        let (stale_after, interval) =
+            (config.stale_after, (config.stale_after / 3).max(Duration::from_secs(1)));
+        async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                holder.heartbeat_at = Utc::now();
+                match backend.refresh(&holder, stale_after).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        let lock = backend.name();
+                        warn!(workflow = %holder.workflow, lock = %lock, "lost distributed lock");
+                        break;
+                    }
+                    Err(e) => {
+                        let lock = backend.name();
+                        warn!(lock = %lock, error = %e, "failed to refresh distributed lock");
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(LockGuard {
+        backend,
+        holder,
+        heartbeat,
+    })
+}
+
+impl LockGuard {
+    pub async fn release(self) {
+        self.heartbeat.abort();
+        if let Err(e) = self.backend.release(&self.holder).await {
+            warn!(lock = %self.backend.name(), error = %e, "failed to release distributed lock");
+        }
+    }
+}
+
+impl Drop for LockGuard {
+    // a guard dropped without release stops refreshing, so others take over once it is stale
+    fn drop(&mut self) {
+        self.heartbeat.abort();
+    }
+}
+
+impl Backend {
+    fn name(&self) -> String {
+        match self {
+            Backend::File(path) => path.display().to_string(),
+            Backend::Http { url, .. } => url.clone(),
+        }
+    }
+
+    async fn try_acquire(&self, me: &Holder, stale_after: Duration) -> Result<Attempt> {
+        match self {
+            Backend::File(path) => try_acquire_file(path, me, stale_after).await,
+            Backend::Http { url, headers } => {
+                let body = json!({
+                    "owner": me.execution_id,
+                    "holder": me,
+                    "ttl_secs": stale_after.as_secs(),
+                });
+                match http::send("POST", url, headers, &body.to_string(), HTTP_TIMEOUT).await? {
+                    200..=299 => Ok(Attempt::Acquired),
+                    409 | 423 => Ok(Attempt::Held(None)),
+                    code => bail!("lock service answered HTTP {}", code),
+                }
+            }
+        }
+    }
+
+    /// Extend the lock. `false` means someone else holds it now.
+    async fn refresh(&self, me: &Holder, stale_after: Duration) -> Result<bool> {
+        match self {
+            Backend::File(path) => {
+                let current = read_holder(path).await?;
+                if current.is_none_or(|h| h.execution_id != me.execution_id) {
+                    return Ok(false);
+                }
+                // replace in one step so readers never see a partially written file
+                let tmp = path.with_extension(format!("{}.tmp", me.execution_id));
+                tokio::fs::write(&tmp, serde_json::to_vec(me)?).await?;
+                tokio::fs::rename(&tmp, path).await?;
+                Ok(true)
+            }
+            Backend::Http { .. } => match self.try_acquire(me, stale_after).await? {
+                Attempt::Held(_) => Ok(false),
+                _ => Ok(true),
+            },
+        }
+    }
+
+    async fn release(&self, me: &Holder) -> Result<()> {
+        match self {
+            Backend::File(path) => {
+                if read_holder(path).await?.is_some_and(|h| h.execution_id == me.execution_id) {
+                    tokio::fs::remove_file(path).await?;
+                }
+                Ok(())
+            }
+            Backend::Http { url, headers } => {
+                let body = json!({ "owner": me.execution_id }).to_string();
+                match http::send("DELETE", url, headers, &body, HTTP_TIMEOUT).await? {
+                    200..=299 | 404 => Ok(()),
+                    code => bail!("lock service answered HTTP {}", code),
+                }
+            }
+        }
+    }
+}
+
+/// Create the lock file exclusively. A file left by a holder that stopped refreshing it is
+/// moved aside first; if another runner replaced it in the meantime, it is put back.
+async fn try_acquire_file(path: &PathBuf, me: &Holder, stale_after: Duration) -> Result<Attempt> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    if create_exclusive(path, me).await? {
+        return Ok(Attempt::Acquired);
+    }
+
+    let (current, changed_at) = match tokio::fs::read(path).await {
+        Ok(bytes) => (bytes, tokio::fs::metadata(path).await?.modified()?),
+        // released between our attempts
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Attempt::Held(None)),
+        Err(e) => return Err(e.into()),
+    };
+    let other: Option<Holder> = serde_json::from_slice(&current).ok();
+    // a file that doesn't parse may still be being written; its mtime says how old it is
+    let heartbeat_at = other
+        .as_ref()
+        .map_or_else(|| DateTime::<Utc>::from(changed_at), |h| h.heartbeat_at);
+    let stale = Utc::now() - heartbeat_at > chrono::Duration::from_std(stale_after)?;
+    if !stale {
+        return Ok(Attempt::Held(other));
+    }
+
+    let aside = path.with_extension(format!("{}.stale", me.execution_id));
+    match tokio::fs::rename(path, &aside).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Attempt::Held(None)),
+        Err(e) => return Err(e.into()),
+    }
+    let moved = tokio::fs::read(&aside).await?;
+    if moved != current {
+        // another runner took the lock between our read and the rename
+        let _ = tokio::fs::hard_link(&aside, path).await;
+        let _ = tokio::fs::remove_file(&aside).await;
+        return Ok(Attempt::Held(serde_json::from_slice(&moved).ok()));
+    }
+    let _ = tokio::fs::remove_file(&aside).await;
+
+    if create_exclusive(path, me).await? {
+        Ok(Attempt::TookOver(other))
+    } else {
+        Ok(Attempt::Held(None))
+    }
+}
+
+async fn create_exclusive(path: &PathBuf, me: &Holder) -> Result<bool> {
+    match OpenOptions::new().write(true).create_new(true).open(path).await {
+        Ok(mut file) => {
+            file.write_all(&serde_json::to_vec(me)?).await?;
+            file.sync_all().await?;
+            Ok(true)
+        }
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn read_holder(path: &PathBuf) -> Result<Option<Holder>> {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes).ok()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn log_line(log: &mut File, message: &str) -> Result<()> {
+    log.write_all(format!("[lock] {}\n", message).as_bytes()).await?;
+    Ok(())
+}
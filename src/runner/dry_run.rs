@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::common::config::config_name;
+use crate::common::types::{SecretsConfig, WorkflowConfig};
+use crate::runner::executor::{self, RunContext, RunRequest};
+use crate::runner::limits;
+use crate::runner::secrets;
+
+/// What a trigger would run, worked out as the executor does but without starting anything.
+#[derive(Debug, Clone, Default)]
+pub struct DryRun {
+    /// The script, or each step in order.
+    pub commands: Vec<PlannedCommand>,
+    /// Where the stdin would come from and how much, e.g. `12 bytes from /path`.
+    pub stdin: Option<String>,
+    /// What would make the run fail before its script starts; empty if nothing would.
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PlannedCommand {
+    /// The step's name; empty for a single script.
+    pub step: String,
+    pub argv: Vec<String>,
+    /// `None` = the runner's working directory.
+    pub cwd: Option<String>,
+    /// The variables set on top of the runner's environment, secrets shown as their
+    /// `secret://` reference rather than their value.
+    pub env: Vec<(String, String)>,
+}
+
+/// The commands `request` would run for `wf`. Secrets are looked up in `secrets_config`'s
+/// backend, so a missing one shows as a problem, but their values stay out of the plan.
+/// `$TMPDIR` and `$WF_RESULT_FILE` are named as a run would name them; each real run has its
+/// own.
+pub async fn plan(
+    wf: &WorkflowConfig,
+    request: &RunRequest,
+    secrets_config: &SecretsConfig,
+) -> DryRun {
+    let mut dry_run = DryRun::default();
+    if let Some(e) = executor::script_error(wf) {
+        dry_run.problems.push(format!("{}; fix `script` in {}", e, config_name()));
+    }
+    match executor::read_stdin(wf, request).await {
+        Ok(Some(input)) => {
+            let source = match (&request.stdin, wf.stdin_path()) {
+                (None, Some(path)) => path.display().to_string(),
+                _ => "the trigger".to_string(),
+            };
+            dry_run.stdin = Some(format!("{} bytes from {}", input.len(), source));
+        }
+        Ok(None) => {}
+        Err(e) => dry_run.problems.push(format!("stdin: {:#}", e)),
+    }
+    if let Some(dir) = wf.working_dir().filter(|d| !d.is_dir()) {
+        dry_run.problems.push(format!("cwd {}: not a directory", dir.display()));
+    }
+    let env = match secrets::resolve(wf, secrets_config).await {
+        Ok(env) => env,
+        Err(e) => {
+            dry_run.problems.push(format!("secrets: {:#}", e));
+            Default::default()
+        }
+    };
+
+    let execution_id = Uuid::new_v4().to_string();
+    let result_path = executor::result_path_for(&execution_id);
+    let tmp_dir = executor::tmp_dir_for(&execution_id);
+    let context = RunContext {
+        wf,
+        result_path: &result_path,
+        tmp_dir: &tmp_dir,
+        changed_path: request.changed_path.as_deref(),
+        vars: &env.vars,
+        cgroups: limits::cgroups_available(),
+    };
+    let args = request.args.clone().unwrap_or_else(|| wf.args.clone());
+    if wf.steps.is_empty() {
+        let args: Vec<&String> = args.iter().collect();
+        let script = match &wf.script_inline {
+            // written out as a run would, for its shebang to pick the interpreter
+            Some(inline) => {
+                let written = match tokio::fs::create_dir_all(&tmp_dir).await {
+                    Ok(()) => executor::write_inline_script(&tmp_dir, inline).await,
+                    Err(e) => Err(e.into()),
+                };
+                match written {
+                    Ok(path) => path,
+                    Err(e) => {
+                        dry_run.problems.push(format!("inline script: {:#}", e));
+                        tmp_dir.join("inline-script").to_string_lossy().to_string()
+                    }
+                }
+            }
+            None => wf.script.clone(),
+        };
+        let command = context.command(&script, &args, &[], &wf.limits);
+        dry_run.commands.push(planned(String::new(), &command, &wf.env));
+        let _ = tokio::fs::remove_dir_all(&tmp_dir).await;
+    } else {
+        let no_vars = Vec::new();
+        for (i, step) in wf.steps.iter().enumerate() {
+            let step_args: Vec<&String> = step.args.iter().chain(&args).collect();
+            let limits = wf.limits.merged(&step.limits);
+            let vars = env.steps.get(i).unwrap_or(&no_vars);
+            let command = context.command(&step.script, &step_args, vars, &limits);
+            let configured = wf.env.iter().chain(&step.env);
+            dry_run.commands.push(planned(step.name().to_string(), &command, configured));
+        }
+    }
+    dry_run
+}
+
+/// `command` as text, with the variables `configured` as secret references put back.
+fn planned<'a>(
+    step: String,
+    command: &Command,
+    configured: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> PlannedCommand {
+    let secret_refs: BTreeMap<&String, &String> = configured
+        .into_iter()
+        .filter(|(_, value)| value.starts_with(secrets::SCHEME))
+        .collect();
+    let command = command.as_std();
+    let text = |s: &OsStr| s.to_string_lossy().to_string();
+    let argv = std::iter::once(command.get_program()).chain(command.get_args()).map(text).collect();
+    let env = command
+        .get_envs()
+        .filter_map(|(key, value)| {
+            let key = text(key);
+            let value = match secret_refs.get(&key) {
+                Some(reference) => reference.to_string(),
+                None => text(value?),
+            };
+            Some((key, value))
+        })
+        .collect();
+    PlannedCommand {
+        step,
+        argv,
+        cwd: command.get_current_dir().map(|d| d.to_string_lossy().to_string()),
+        env,
+    }
+}
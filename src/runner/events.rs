@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+
+use crate::common::types::{Execution, ExecutionStatus, TriggerSource};
+use crate::runner::config_diff::ConfigDiff;
+
+/// Events buffered per subscriber. One that falls further behind is told to reload everything.
+const CAPACITY: usize = 256;
+
+/// Scheduler decisions kept for `ListDecisions`, across all workflows.
+const RECENT_DECISIONS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventKind {
+    Started,
+    Finished,
+    /// Paused or resumed, executions deleted or restored, or the config reloaded.
+    Changed,
+    /// The scheduler started, queued, put off or skipped a run.
+    Decision,
+    /// The runner picked up a changed config; `diff` says what changed.
+    ConfigReloaded,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Started => "started",
+            EventKind::Finished => "finished",
+            EventKind::Changed => "changed",
+            EventKind::Decision => "decision",
+            EventKind::ConfigReloaded => "config_reloaded",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecisionKind {
+    /// The run started.
+    Fired,
+    /// Another run of the workflow was going; this one starts after it.
+    Queued,
+    /// Held back for now: `jitter`, `when_idle`, or another workflow holding its `lock`.
+    Deferred,
+    /// Not run at all, e.g. paused or turned down by `should_run`.
+    Skipped,
+}
+
+impl DecisionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecisionKind::Fired => "fired",
+            DecisionKind::Queued => "queued",
+            DecisionKind::Deferred => "deferred",
+            DecisionKind::Skipped => "skipped",
+        }
+    }
+}
+
+/// What the scheduler did with one trigger of a workflow, and why.
+#[derive(Debug, Clone)]
+pub struct Decision {
+    pub workflow: String,
+    pub kind: DecisionKind,
+    pub source: TriggerSource,
+    /// Empty when there's nothing to add, e.g. for most fired runs.
+    pub reason: String,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub kind: EventKind,
+    /// Empty when any workflow may have changed.
+    pub workflow: String,
+    pub execution_id: Option<String>,
+    pub status: Option<ExecutionStatus>,
+    /// How long the execution ran, once it finished.
+    pub duration: Option<Duration>,
+    /// Set for `Decision`.
+    pub decision: Option<Decision>,
+    /// Set for `ConfigReloaded`.
+    pub diff: Option<ConfigDiff>,
+    pub at: DateTime<Utc>,
+}
+
+impl Event {
+    /// Tells a subscriber to reload everything.
+    pub fn changed_all() -> Self {
+        Self::changed("")
+    }
+
+    fn changed(workflow: &str) -> Self {
+        Self {
+            kind: EventKind::Changed,
+            workflow: workflow.to_string(),
+            execution_id: None,
+            status: None,
+            duration: None,
+            decision: None,
+            diff: None,
+            at: Utc::now(),
+        }
+    }
+
+    fn execution(kind: EventKind, exec: &Execution) -> Self {
+        Self {
+            kind,
+            workflow: exec.workflow.clone(),
+            execution_id: Some(exec.id.clone()),
+            status: Some(exec.status.clone()),
+            duration: exec.finished_at.and_then(|f| (f - exec.started_at).to_std().ok()),
+            decision: None,
+            diff: None,
+            at: Utc::now(),
+        }
+    }
+}
+
+/// Announces what happens to workflows to `WatchEvents` subscribers. Clones share the channel;
+/// events sent while nobody listens are dropped, except that the latest decisions are kept.
+#[derive(Debug, Clone)]
+pub struct Events {
+    tx: broadcast::Sender<Event>,
+    /// Oldest first.
+    decisions: Arc<Mutex<VecDeque<Decision>>>,
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self {
+            tx: broadcast::channel(CAPACITY).0,
+            decisions: Arc::default(),
+        }
+    }
+}
+
+impl Events {
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.tx.subscribe()
+    }
+
+    pub fn started(&self, exec: &Execution) {
+        self.send(Event::execution(EventKind::Started, exec));
+    }
+
+    pub fn finished(&self, exec: &Execution) {
+        self.send(Event::execution(EventKind::Finished, exec));
+    }
+
+    /// `workflow` changed other than by running; an empty name stands for all of them.
+    pub fn changed(&self, workflow: &str) {
+        self.send(Event::changed(workflow));
+    }
+
+    /// A changed config was loaded; everything may need reloading.
+    pub fn config_reloaded(&self, diff: ConfigDiff) {
+        self.send(Event {
+            kind: EventKind::ConfigReloaded,
+            diff: Some(diff),
+            ..Event::changed_all()
+        });
+    }
+
+    /// The scheduler decided `kind` for a `source` trigger of `workflow`.
+    pub fn decision(
+        &self,
+        workflow: &str,
+        kind: DecisionKind,
+        source: TriggerSource,
+        reason: impl Into<String>,
+    ) {
+        let decision = Decision {
+            workflow: workflow.to_string(),
+            kind,
+            source,
+            reason: reason.into(),
+            at: Utc::now(),
+        };
+        {
+            let mut decisions = self.decisions.lock().unwrap();
+            if decisions.len() == RECENT_DECISIONS {
+                decisions.pop_front();
+            }
+            decisions.push_back(decision.clone());
+        }
+        self.send(Event {
+            kind: EventKind::Decision,
+            workflow: workflow.to_string(),
+            execution_id: None,
+            status: None,
+            duration: None,
+            at: decision.at,
+            decision: Some(decision),
+            diff: None,
+        });
+    }
+
+    /// The kept decisions, newest first.
+    pub fn decisions(&self) -> Vec<Decision> {
+        self.decisions.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    fn send(&self, event: Event) {
+        let _ = self.tx.send(event);
+    }
+}
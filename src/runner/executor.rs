@@ -1,18 +1,40 @@
-use std::path::PathBuf;
-use std::sync::Arc;
-
-use anyhow::Result;
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use anyhow::{Context, Result, bail};
 use chrono::{Local, Utc};
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
-use tokio::process::Command;
-use tokio::sync::Mutex;
-use tracing::{error, info};
+use tokio::process::{Child, ChildStderr, ChildStdout, Command};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use crate::common::config::scripts_dir;
-use crate::common::db::{insert_execution, logs_dir, update_execution_finished};
-use crate::common::types::{Execution, ExecutionStatus, NotificationConfig};
+use crate::common::config::{config_name, hostname, scripts_dir};
+use crate::common::db::logs_dir;
+use crate::common::storage::Db;
+use crate::common::types::{
+    Execution, ExecutionStatus, Interpreter, LogSeverity, NotificationConfig, NotifyOn,
+    Priority, ResourceLimits, SecretsConfig, StepResult, StepStatus, TriggerSource, WorkflowConfig,
+};
+use crate::runner::capture::{Capture, Captured};
+use crate::runner::events::Events;
+use crate::runner::output::Interleaved;
+use crate::runner::severity::{Detector, count_lines};
+use crate::runner::redact::Redactor;
+use crate::runner::secrets::{self, ResolvedEnv};
+use crate::runner::shutdown::{self, Shutdown};
+use crate::runner::{
+    baseline, completion_webhook, distributed_lock, limits, log_compress, plugin, push, trigger,
+};
+
+/// Result files larger than this are ignored rather than stored in the database.
+const MAX_RESULT_BYTES: usize = 1024 * 1024;
+
+/// What `script_inline` is written to, in the run's tmp dir.
+const INLINE_SCRIPT: &str = "inline-script";
 
 pub fn log_path_for(workflow: &str, execution_id: &str) -> PathBuf {
     logs_dir()
@@ -20,13 +42,128 @@ pub fn log_path_for(workflow: &str, execution_id: &str) -> PathBuf {
         .join(format!("{}.log", execution_id))
 }
 
+/// The private `$TMPDIR` of an execution, created before the script starts and removed after
+/// it finishes.
+pub fn tmp_dir_for(execution_id: &str) -> PathBuf {
+    tmp_root().join(execution_id)
+}
+
+/// Where an execution's script writes `$WF_RESULT_FILE`.
+pub fn result_path_for(execution_id: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("illef-workflow-{}.json", execution_id))
+}
+
+pub fn tmp_root() -> PathBuf {
+    std::env::temp_dir().join("illef-workflow")
+}
+
+/// Sidecar file next to an execution's log where the TUI keeps bookmarked lines.
+pub fn bookmarks_path_for(log_path: &Path) -> PathBuf {
+    log_path.with_extension("bookmarks")
+}
+
+/// The lines a log starts with, so it tells what ran and how when read on its own: when, the
+/// execution, trigger and host, the schedule, the script or steps, args, working directory and
+/// the names of the variables from `env` (never their values).
+fn log_header(wf: &WorkflowConfig, execution: &Execution, env: Option<&ResolvedEnv>) -> String {
+    let mut header = format!(
+        "[{}] Starting workflow: {}\n",
+        execution.started_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+        wf.name
+    );
+    let trigger = execution.trigger.unwrap_or_default();
+    header.push_str(&format!("Execution: {}\n", execution.id));
+    header.push_str(&format!("Trigger: {}\n", trigger.as_str()));
+    if let Some(host) = &execution.host {
+        header.push_str(&format!("Host: {}\n", host));
+    }
+    // ad-hoc workflows have no schedule of their own
+    let scheduled = wf.cron.is_some()
+        || wf.every.is_some()
+        || wf.at.is_some()
+        || wf.on_start
+        || wf.watch.is_some();
+    let schedule = match trigger::triggers_for(wf) {
+        Ok(triggers) => {
+            let label = trigger::describe_all(&triggers);
+            format!("{} ({})", label, trigger::explain_all(&triggers))
+        }
+        Err(_) if !scheduled => "none, runs when triggered".to_string(),
+        Err(e) => format!("invalid, {:#}", e),
+    };
+    header.push_str(&format!("Schedule: {}\n", schedule));
+    match &wf.script_inline {
+        Some(inline) => {
+            header.push_str(&format!("Script: inline, {} lines\n", inline.lines().count()))
+        }
+        None if wf.steps.is_empty() => {
+            let path = scripts_dir().join(&wf.script);
+            header.push_str(&format!("Script: {}\n", path.display()));
+        }
+        None => {
+            let steps: Vec<String> = wf
+                .steps
+                .iter()
+                .map(|s| {
+                    let path = scripts_dir().join(&s.script);
+                    match s.name() {
+                        name if name == s.script => path.display().to_string(),
+                        name => format!("{} ({})", name, path.display()),
+                    }
+                })
+                .collect();
+            header.push_str(&format!("Steps: {}\n", steps.join(" → ")));
+        }
+    }
+    if !execution.args.is_empty() {
+        header.push_str(&format!("Args: {}\n", execution.args.join(" ")));
+    }
+    let cwd = match wf.working_dir() {
+        Some(dir) => dir.display().to_string(),
+        None => match std::env::current_dir() {
+            Ok(dir) => format!("{} (the runner's)", dir.display()),
+            Err(_) => "the runner's".to_string(),
+        },
+    };
+    header.push_str(&format!("Cwd: {}\n", cwd));
+    if let Some(env) = env {
+        let mut keys: Vec<&str> = Vec::new();
+        for (key, _) in env.vars.iter().chain(env.steps.iter().flatten()) {
+            if !keys.contains(&key.as_str()) {
+                keys.push(key);
+            }
+        }
+        if !keys.is_empty() {
+            header.push_str(&format!("Env: {} (values not shown)\n", keys.join(", ")));
+        }
+    }
+    header
+}
+
+/// Per-run parameters supplied by whoever triggered the execution.
+#[derive(Debug, Clone, Default)]
+pub struct RunRequest {
+    /// Replaces the workflow's configured `args` when set.
+    pub args: Option<Vec<String>>,
+    /// The path whose change triggered this run, for `watch` workflows.
+    pub changed_path: Option<PathBuf>,
+    /// Replaces the workflow's `stdin_file` when set.
+    pub stdin: Option<Vec<u8>>,
+    /// Recorded with the execution. The default is a scheduled run.
+    pub source: TriggerSource,
+}
+
 pub async fn execute_workflow(
-    workflow_name: String,
-    script: String,
-    message_script: Option<String>,
-    db: Arc<Mutex<rusqlite::Connection>>,
+    wf: WorkflowConfig,
+    request: RunRequest,
+    db: Db,
+    events: Events,
     notification: NotificationConfig,
+    secrets_config: SecretsConfig,
+    shutdown: Shutdown,
 ) -> Result<()> {
+    let workflow_name = wf.name.clone();
+    let args = request.args.clone().unwrap_or_else(|| wf.args.clone());
     let execution_id = Uuid::new_v4().to_string();
     let log_path = log_path_for(&workflow_name, &execution_id);
 
@@ -34,6 +171,16 @@ pub async fn execute_workflow(
         tokio::fs::create_dir_all(parent).await?;
     }
 
+    let mut steps: Vec<StepResult> = wf
+        .steps
+        .iter()
+        .map(|step| StepResult {
+            name: step.name().to_string(),
+            status: StepStatus::Pending,
+            exit_code: None,
+            log_line: None,
+        })
+        .collect();
     let execution = Execution {
         id: execution_id.clone(),
         workflow: workflow_name.clone(),
@@ -42,88 +189,610 @@ pub async fn execute_workflow(
         finished_at: None,
         exit_code: None,
         log_path: log_path.to_string_lossy().to_string(),
+        args: args.clone(),
+        result_json: None,
+        severity: LogSeverity::default(),
+        trigger: Some(request.source),
+        binary_output: false,
+        steps: steps.clone(),
+        host: Some(hostname()),
+        deviation: None,
+        repeats: Vec::new(),
     };
 
-    {
-        let conn = db.lock().await;
-        insert_execution(&conn, &execution)?;
-    }
+    db.insert_execution(&execution).await?;
+    let running = shutdown.track(&execution_id, log_path.clone());
+    events.started(&execution);
 
     info!(workflow = %workflow_name, id = %execution_id, "execution started");
 
-    let script_path = scripts_dir().join(&script);
     let mut log_file = File::create(&log_path).await?;
 
-    let header = format!(
-        "[{}] Starting workflow: {}\n",
-        Local::now().format("%Y-%m-%d %H:%M:%S"),
-        workflow_name
-    );
+    let env = secrets::resolve(&wf, &secrets_config).await;
+
+    let header = log_header(&wf, &execution, env.as_ref().ok());
     log_file.write_all(header.as_bytes()).await?;
+    if let Some(path) = &request.changed_path {
+        let line = format!("Triggered by change: {}\n", path.display());
+        log_file.write_all(line.as_bytes()).await?;
+    }
+    let stdin = read_stdin(&wf, &request).await;
+    if let Ok(Some(input)) = &stdin {
+        let source = match (&request.stdin, wf.stdin_path()) {
+            (None, Some(path)) => path.display().to_string(),
+            _ => "the trigger".to_string(),
+        };
+        let line = format!("Stdin: {} bytes from {}\n", input.len(), source);
+        log_file.write_all(line.as_bytes()).await?;
+    }
+    let cwd = wf.working_dir();
+    let result_path = result_path_for(&execution_id);
+    let tmp_dir = tmp_dir_for(&execution_id);
+    tokio::fs::create_dir_all(&tmp_dir).await?;
+    let cgroups = limits::cgroups_available();
+    let needs_cgroup = std::iter::once(wf.limits.clone())
+        .chain(wf.steps.iter().map(|step| wf.limits.merged(&step.limits)))
+        .any(|l| l.needs_cgroup());
+    if needs_cgroup && !cgroups {
+        let note = "[limits] memory_max and cpu_quota need cgroup v2 and a systemd user manager \
+                    (systemd-run --user); running without them\n";
+        log_file.write_all(note.as_bytes()).await?;
+        warn!(workflow = %workflow_name, "cgroup limits not available");
+    }
+    let context = RunContext {
+        wf: &wf,
+        result_path: &result_path,
+        tmp_dir: &tmp_dir,
+        changed_path: request.changed_path.as_deref(),
+        vars: env.as_ref().map(|e| e.vars.as_slice()).unwrap_or_default(),
+        cgroups,
+    };
+    let build_command = |script: &str,
+                         args: &[&String],
+                         vars: &[(String, String)],
+                         limits: &ResourceLimits| {
+        context.command(script, args, vars, limits)
+    };
 
-    let output = Command::new("bash")
-        .arg(&script_path)
-        .output()
-        .await;
-
-    let (status, exit_code) = match output {
-        Ok(out) => {
-            log_file.write_all(&out.stdout).await?;
-            if !out.stderr.is_empty() {
-                log_file.write_all(b"\n[stderr]\n").await?;
-                log_file.write_all(&out.stderr).await?;
-            }
+    // checked up front, so a steps run doesn't get halfway before finding one missing
+    let mut script_error =
+        script_error(&wf).map(|e| format!("{}; fix `script` in {}", e, config_name()));
+    // written before the lock is taken, so failing to leaves nothing to undo
+    let mut script = wf.script.clone();
+    if let (Some(inline), None) = (&wf.script_inline, &script_error) {
+        match write_inline_script(&tmp_dir, inline).await {
+            Ok(path) => script = path,
+            Err(e) => script_error = Some(format!("{:#}", e)),
+        }
+    }
 
-            let code = out.status.code().unwrap_or(-1);
-            let finished_line = format!(
-                "\n[{}] Finished with exit code: {}\n",
-                Local::now().format("%Y-%m-%d %H:%M:%S"),
-                code
-            );
-            log_file.write_all(finished_line.as_bytes()).await?;
+    let lock = match &wf.distributed_lock {
+        Some(_) if script_error.is_some() || stdin.is_err() || env.is_err() => Ok(None),
+        Some(config) => {
+            distributed_lock::acquire(config, &workflow_name, &execution_id, &mut log_file)
+                .await
+                .map(Some)
+        }
+        None => Ok(None),
+    };
+    let (detector, problems) = Detector::new(&wf.severity);
+    for problem in problems {
+        log_file.write_all(format!("[severity] {}\n", problem).as_bytes()).await?;
+    }
+    let secret_values = env.as_ref().map(|e| e.secrets.as_slice()).unwrap_or_default();
+    let (redactor, problems) = Redactor::new(&wf.redact, secret_values);
+    for problem in problems {
+        log_file.write_all(format!("[redact] {}\n", problem).as_bytes()).await?;
+    }
 
-            if out.status.success() {
-                (ExecutionStatus::Success, code)
-            } else {
-                (ExecutionStatus::Failed, code)
+    let (status, exit_code, captured) = match (stdin, &env, lock) {
+        _ if script_error.is_some() => {
+            let e = script_error.as_deref().unwrap_or_default();
+            let line = format!("[config] {}\n", e);
+            log_file.write_all(line.as_bytes()).await?;
+            error!(workflow = %workflow_name, error = %e, "script can't be run");
+            (ExecutionStatus::ConfigError, -1, Captured::default())
+        }
+        (Err(e), _, _) => {
+            log_file.write_all(format!("[stdin] {:#}\n", e).as_bytes()).await?;
+            error!(workflow = %workflow_name, error = %e, "could not read stdin_file");
+            (ExecutionStatus::Failed, -1, Captured::default())
+        }
+        (Ok(_), Err(e), _) => {
+            log_file.write_all(format!("[secrets] {:#}\n", e).as_bytes()).await?;
+            error!(workflow = %workflow_name, error = %e, "could not resolve secrets");
+            (ExecutionStatus::Failed, -1, Captured::default())
+        }
+        (Ok(stdin), Ok(env), Ok(lock)) if !wf.steps.is_empty() => {
+            let commands = wf
+                .steps
+                .iter()
+                .zip(&env.steps)
+                .map(|(step, vars)| {
+                    let step_args: Vec<&String> = step.args.iter().chain(&args).collect();
+                    let limits = wf.limits.merged(&step.limits);
+                    build_command(&step.script, &step_args, vars, &limits)
+                })
+                .collect();
+            let progress = StepProgress {
+                db: &db,
+                events: &events,
+                execution_id: &execution_id,
+                log_path: &log_path,
+                steps: &mut steps,
+            };
+            let log = &mut log_file;
+            let result = run_steps(&wf, commands, stdin, log, &redactor, &detector, progress).await;
+            if let Some(lock) = lock {
+                lock.release().await;
             }
+            result?
         }
-        Err(e) => {
-            let err_msg = format!("\n[error] Failed to start process: {}\n", e);
-            log_file.write_all(err_msg.as_bytes()).await?;
-            error!(workflow = %workflow_name, error = %e, "failed to start process");
-            (ExecutionStatus::Failed, -1)
+        (Ok(stdin), Ok(_), Ok(lock)) => {
+            let args: Vec<&String> = args.iter().collect();
+            let command = build_command(&script, &args, &[], &wf.limits);
+            let log = &mut log_file;
+            let result = async {
+                // error lines are numbered from here; the output starts after what's written
+                log.flush().await?;
+                let first_line = count_lines(&tokio::fs::read(&log_path).await?);
+                run_script(command, stdin, log, &wf, &redactor, &detector, first_line).await
+            }
+            .await;
+            if let Some(lock) = lock {
+                lock.release().await;
+            }
+            result?
+        }
+        (Ok(_), Ok(_), Err(e)) => {
+            log_file.write_all(format!("[lock] {}\n", e).as_bytes()).await?;
+            error!(workflow = %workflow_name, error = %e, "could not acquire distributed lock");
+            (ExecutionStatus::Failed, -1, Captured::default())
         }
     };
 
+    if shutdown.killed() && status == ExecutionStatus::Failed {
+        log_file.write_all(shutdown::interrupted_line().as_bytes()).await?;
+    }
     let finished_at = Utc::now();
+    let Captured { severity, binary: binary_output, repeats } = captured;
+
+    db.update_execution_finished(&execution_id, status.clone(), finished_at, exit_code)
+        .await?;
+    running.recorded();
 
-    {
-        let conn = db.lock().await;
-        update_execution_finished(&conn, &execution_id, status.clone(), finished_at, exit_code)?;
+    let result_json = match read_result(&result_path).await {
+        Ok(result) => result,
+        Err(e) => {
+            let line = format!("[result] ignoring $WF_RESULT_FILE: {}\n", e);
+            log_file.write_all(line.as_bytes()).await?;
+            None
+        }
+    };
+    if let Some(json) = &result_json {
+        db.set_execution_result(&execution_id, json).await?;
+    }
+    if wf.keep_tmp_on_failure && status.is_failure() {
+        log_file.write_all(format!("[tmp] kept {}\n", tmp_dir.display()).as_bytes()).await?;
+    } else if let Err(e) = tokio::fs::remove_dir_all(&tmp_dir).await {
+        warn!(path = %tmp_dir.display(), error = %e, "failed to remove execution tmp dir");
+    }
+    if severity != LogSeverity::default() {
+        db.set_execution_severity(&execution_id, &severity).await?;
     }
+    if binary_output {
+        let line = "[binary] output held bytes that aren't text, written as \\xNN escapes\n";
+        log_file.write_all(line.as_bytes()).await?;
+        db.set_execution_binary_output(&execution_id).await?;
+    }
+    if !repeats.is_empty() {
+        db.set_execution_repeats(&execution_id, &repeats).await?;
+    }
+    let mut execution = Execution {
+        status,
+        finished_at: Some(finished_at),
+        exit_code: Some(exit_code),
+        result_json,
+        severity,
+        binary_output,
+        steps,
+        repeats,
+        ..execution
+    };
+    execution.deviation = baseline::compare(&db, &wf.baseline, &execution).await;
+    if let Some(deviation) = &execution.deviation {
+        log_file.write_all(format!("[baseline] {}\n", deviation).as_bytes()).await?;
+        db.set_execution_deviation(&execution_id, deviation).await?;
+    }
+    events.finished(&execution);
 
     info!(
         workflow = %workflow_name,
         id = %execution_id,
-        status = %status.as_str(),
+        status = %execution.status.as_str(),
         exit_code = exit_code,
         "execution finished"
     );
 
-    let message_result = if status == ExecutionStatus::Success {
-        run_message_script(message_script.as_deref()).await
-    } else {
-        MessageScriptResult::NoScript
+    // a flagged run is let through where `notify` only leaves out successes
+    let regressed = wf.baseline.notify && execution.deviation.is_some();
+    let message_result = match (wf.notify, &execution.status) {
+        (NotifyOn::Never, _) => MessageScriptResult::Suppressed,
+        (NotifyOn::Failure, ExecutionStatus::Success) if !regressed => {
+            MessageScriptResult::Suppressed
+        }
+        (_, ExecutionStatus::Success) => {
+            let env = env.unwrap_or_default();
+            run_message_script(&wf, cwd.as_deref(), &result_path, &env, &redactor).await
+        }
+        (_, ExecutionStatus::ConfigError) => {
+            MessageScriptResult::Body(script_error.unwrap_or_default())
+        }
+        _ => MessageScriptResult::NoScript,
     };
     if message_result != MessageScriptResult::Suppressed {
         let body = message_result.body();
-        send_notification(&notification, &workflow_name, &status, body.as_deref()).await;
+        send_notification(&notification, &wf, &execution, body.as_deref()).await;
     }
 
+    completion_webhook::fire(&wf.webhooks, &execution);
+
+    let _ = tokio::fs::remove_file(&result_path).await;
+    if wf.compress_log {
+        log_file.flush().await?;
+        drop(log_file);
+        if let Err(e) = log_compress::compress(&log_path).await {
+            warn!(path = %log_path.display(), error = %e, "failed to compress log");
+        }
+    }
+    drop(running);
     Ok(())
 }
 
+/// What every command of a run shares: the files it gives its scripts and its environment.
+pub struct RunContext<'a> {
+    pub wf: &'a WorkflowConfig,
+    pub result_path: &'a Path,
+    pub tmp_dir: &'a Path,
+    pub changed_path: Option<&'a Path>,
+    /// The workflow's `env`, secrets resolved.
+    pub vars: &'a [(String, String)],
+    pub cgroups: bool,
+}
+
+impl RunContext<'_> {
+    /// A script with the run's environment; `vars` are a step's own additions.
+    pub fn command(
+        &self,
+        script: &str,
+        args: &[&String],
+        vars: &[(String, String)],
+        limits: &ResourceLimits,
+    ) -> Command {
+        let wf = self.wf;
+        let command = script_command(&scripts_dir().join(script), wf.interpreter.as_ref(), wf);
+        let mut command = limits::apply(command, limits, self.cgroups);
+        // a group of its own, for the runner to stop with everything it started on shutdown
+        command
+            .process_group(0)
+            .args(args)
+            .env("WF_RESULT_FILE", self.result_path)
+            .env("TMPDIR", self.tmp_dir);
+        if let Some(path) = self.changed_path {
+            command.env("WF_CHANGED_PATH", path);
+        }
+        if let Some(dir) = wf.working_dir() {
+            command.current_dir(dir);
+        }
+        command.envs(self.vars.iter().chain(vars).map(|(k, v)| (k, v)));
+        command
+    }
+}
+
+/// The JSON document a script left in `$WF_RESULT_FILE`, re-serialized compactly. `None` when
+/// the script did not write one.
+async fn read_result(path: &Path) -> Result<Option<String>> {
+    let bytes = match tokio::fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if bytes.len() > MAX_RESULT_BYTES {
+        bail!("larger than {} bytes", MAX_RESULT_BYTES);
+    }
+    if bytes.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+    let value: serde_json::Value = serde_json::from_slice(&bytes).context("invalid JSON")?;
+    Ok(Some(value.to_string()))
+}
+
+/// Where [`run_steps`] records how far it got.
+struct StepProgress<'a> {
+    db: &'a Db,
+    events: &'a Events,
+    execution_id: &'a str,
+    log_path: &'a Path,
+    steps: &'a mut Vec<StepResult>,
+}
+
+impl StepProgress<'_> {
+    async fn save(&self, workflow: &str) -> Result<()> {
+        self.db.set_execution_steps(self.execution_id, self.steps).await?;
+        self.events.changed(workflow);
+        Ok(())
+    }
+}
+
+/// Run the `commands` of the workflow's steps in order, each under a `==> step` header in the
+/// log, until one fails that may not. Only the first step gets `stdin`. The result is a failure
+/// if any step failed, with that step's exit code.
+async fn run_steps(
+    wf: &WorkflowConfig,
+    commands: Vec<Command>,
+    mut stdin: Option<Vec<u8>>,
+    log_file: &mut File,
+    redactor: &Redactor,
+    detector: &Detector,
+    progress: StepProgress<'_>,
+) -> Result<(ExecutionStatus, i32, Captured)> {
+    let mut status = ExecutionStatus::Success;
+    let mut exit_code = 0;
+    let mut captured = Captured::default();
+    let mut stopped = false;
+    let total = commands.len();
+    for (i, command) in commands.into_iter().enumerate() {
+        let step = &wf.steps[i];
+        if stopped {
+            progress.steps[i].status = StepStatus::Skipped;
+            continue;
+        }
+        log_file.flush().await?;
+        let header_line = count_lines(&tokio::fs::read(progress.log_path).await?);
+        let header = format!("==> step {}/{}: {}\n", i + 1, total, step.name());
+        log_file.write_all(header.as_bytes()).await?;
+        progress.steps[i].status = StepStatus::Running;
+        progress.steps[i].log_line = Some(header_line);
+        progress.save(&wf.name).await?;
+
+        let (step_status, code, found) = run_script(
+            command,
+            stdin.take(),
+            log_file,
+            wf,
+            redactor,
+            detector,
+            header_line + 1,
+        )
+        .await?;
+        let severity = &mut captured.severity;
+        severity.errors += found.severity.errors;
+        severity.warnings += found.severity.warnings;
+        severity.first_error_line = severity.first_error_line.or(found.severity.first_error_line);
+        captured.binary |= found.binary;
+        captured.repeats.extend(found.repeats);
+
+        progress.steps[i].exit_code = Some(code);
+        // after a failure, its exit code is the execution's
+        if status == ExecutionStatus::Success {
+            exit_code = code;
+        }
+        if step_status == ExecutionStatus::Success {
+            progress.steps[i].status = StepStatus::Success;
+        } else {
+            progress.steps[i].status = StepStatus::Failed;
+            status = ExecutionStatus::Failed;
+            stopped = !step.continue_on_failure;
+        }
+        progress.save(&wf.name).await?;
+    }
+    progress.save(&wf.name).await?;
+    Ok((status, exit_code, captured))
+}
+
+/// What the script reads on stdin: the content supplied with the trigger, else `stdin_file`.
+pub async fn read_stdin(wf: &WorkflowConfig, request: &RunRequest) -> Result<Option<Vec<u8>>> {
+    if let Some(input) = &request.stdin {
+        return Ok(Some(input.clone()));
+    }
+    let Some(path) = wf.stdin_path() else { return Ok(None) };
+    let input = tokio::fs::read(&path)
+        .await
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(Some(input))
+}
+
+/// Run the script to completion, appending its output to the log as it comes, then its exit
+/// code, and counting its error and warning lines. Stdout and stderr lines are tagged `O|` and
+/// `E|` and kept in the order they came. The output starts at log line `first_line`.
+async fn run_script(
+    command: Command,
+    stdin: Option<Vec<u8>>,
+    log_file: &mut File,
+    wf: &WorkflowConfig,
+    redactor: &Redactor,
+    detector: &Detector,
+    first_line: u32,
+) -> Result<(ExecutionStatus, i32, Captured)> {
+    let (mut child, mut lines) = match spawn_interleaved(command, stdin) {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            let err_msg = match wf.working_dir() {
+                Some(dir) => format!(
+                    "\n[error] Failed to start process: {} (cwd: {})\n",
+                    e,
+                    dir.display()
+                ),
+                None => format!("\n[error] Failed to start process: {}\n", e),
+            };
+            log_file.write_all(err_msg.as_bytes()).await?;
+            error!(workflow = %wf.name, error = %e, "failed to start process");
+            return Ok((ExecutionStatus::Failed, -1, Captured::default()));
+        }
+    };
+
+    let mut capture = Capture::new(log_file, wf, redactor, detector, first_line);
+    let read_error = loop {
+        let next = match capture.deadline() {
+            Some(deadline) => match tokio::time::timeout_at(deadline, lines.next_line()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    capture.flush().await?;
+                    continue;
+                }
+            },
+            None => lines.next_line().await,
+        };
+        match next {
+            Ok(Some((stream, at, line))) => capture.line(stream, at, line).await?,
+            Ok(None) => break None,
+            Err(e) => break Some(e),
+        }
+    };
+    let captured = capture.finish().await?;
+    if let Some(e) = &read_error {
+        // it could block writing to pipes nothing reads any more
+        let _ = child.start_kill();
+        log_file.write_all(format!("\n[error] Failed to read output: {}\n", e).as_bytes()).await?;
+        error!(workflow = %wf.name, error = %e, "failed to read script output");
+    }
+    let status = child.wait().await?;
+
+    let code = status.code().unwrap_or(-1);
+    let finished_line = format!(
+        "\n[{}] Finished with exit code: {}\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        code
+    );
+    log_file.write_all(finished_line.as_bytes()).await?;
+
+    if status.success() && read_error.is_none() {
+        Ok((ExecutionStatus::Success, code, captured))
+    } else {
+        Ok((ExecutionStatus::Failed, code, captured))
+    }
+}
+
+/// Start `command` with `input`, if any, on its stdin, and read its stdout and stderr lines in
+/// the order they come. The write runs on its own task, so a script that never reads it, or
+/// exits early, can't block the run.
+fn spawn_interleaved(
+    mut command: Command,
+    input: Option<Vec<u8>>,
+) -> std::io::Result<(Child, Interleaved<ChildStdout, ChildStderr>)> {
+    let stdin = if input.is_some() { Stdio::piped() } else { Stdio::null() };
+    command.stdin(stdin).stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = command.spawn()?;
+    if let (Some(mut pipe), Some(input)) = (child.stdin.take(), input) {
+        tokio::spawn(async move {
+            let _ = pipe.write_all(&input).await;
+        });
+    }
+    let stdout = child.stdout.take().expect("piped stdout");
+    let stderr = child.stderr.take().expect("piped stderr");
+    Ok((child, Interleaved::new(stdout, stderr)))
+}
+
+/// Build the command for a script, with the workflow's `path` entries in front of `PATH` and
+/// started through its `shell_profile`, if any.
+pub fn script_command(
+    script_path: &Path,
+    interpreter: Option<&Interpreter>,
+    wf: &WorkflowConfig,
+) -> Command {
+    let argv = script_argv(script_path, interpreter);
+    let extra = match std::env::join_paths(wf.extra_path()) {
+        Ok(extra) => extra,
+        Err(e) => {
+            warn!(workflow = %wf.name, error = %e, "ignoring `path`");
+            OsString::new()
+        }
+    };
+
+    let Some(profile) = wf.shell_profile else {
+        let mut cmd = Command::new(&argv[0]);
+        cmd.args(&argv[1..]);
+        if !extra.is_empty() {
+            let mut path = extra;
+            if let Some(inherited) = std::env::var_os("PATH").filter(|p| !p.is_empty()) {
+                path.push(":");
+                path.push(inherited);
+            }
+            // also where `interpreter` is looked up
+            cmd.env("PATH", path);
+        }
+        return cmd;
+    };
+    // The profile may reset PATH, so the entries go in front only after it ran. They arrive as
+    // $1 and the argv as the rest, which needs no quoting; $0 names the shell in its errors.
+    let mut cmd = Command::new("bash");
+    cmd.arg(profile.flags())
+        .arg(r#"[ -n "$1" ] && PATH="$1:$PATH"; shift; exec "$@""#)
+        .arg("bash")
+        .arg(extra)
+        .args(&argv);
+    cmd
+}
+
+/// The script's argv: explicit interpreter first, then the script itself if it is executable
+/// and has a shebang line, falling back to bash.
+fn script_argv(script_path: &Path, interpreter: Option<&Interpreter>) -> Vec<OsString> {
+    if let Some(argv) = interpreter.map(Interpreter::argv).filter(|argv| !argv.is_empty()) {
+        let mut argv: Vec<OsString> = argv.into_iter().map(OsString::from).collect();
+        argv.push(script_path.into());
+        return argv;
+    }
+
+    let executable = std::fs::metadata(script_path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false);
+    if executable && has_shebang(script_path) {
+        vec![script_path.into()]
+    } else {
+        vec!["bash".into(), script_path.into()]
+    }
+}
+
+/// Why `wf` can't start, if one of its scripts can't be run: its `script` or a step's.
+pub fn script_error(wf: &WorkflowConfig) -> Option<String> {
+    let step_scripts = wf.steps.iter().map(|s| s.script.as_str());
+    std::iter::once(wf.script.as_str())
+        .chain(step_scripts)
+        .filter(|script| !script.is_empty())
+        .find_map(|script| script_problem(&scripts_dir().join(script)))
+}
+
+/// Write `script_inline` into the run's tmp dir, executable so a shebang is honored, and
+/// return its path.
+pub async fn write_inline_script(tmp_dir: &Path, script: &str) -> Result<String> {
+    let path = tmp_dir.join(INLINE_SCRIPT);
+    tokio::fs::write(&path, script)
+        .await
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    tokio::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o700)).await?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Why the script at `path` can't be run, if it can't: it is missing, not a file, or not
+/// readable (everything runs it by reading it, executable or not).
+fn script_problem(path: &Path) -> Option<String> {
+    let problem = match std::fs::metadata(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => "not found".to_string(),
+        Err(e) => e.to_string(),
+        Ok(meta) if !meta.is_file() => "not a file".to_string(),
+        Ok(_) => std::fs::File::open(path).err()?.to_string(),
+    };
+    Some(format!("script {}: {}", path.display(), problem))
+}
+
+fn has_shebang(path: &Path) -> bool {
+    let mut magic = [0u8; 2];
+    std::fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .map(|_| &magic == b"#!")
+        .unwrap_or(false)
+}
+
 /// exit 0  → notification with stdout as body
 /// exit 3  → suppress notification (nothing to report)
 /// others  → notification with default body
@@ -144,19 +813,32 @@ impl MessageScriptResult {
     }
 }
 
-async fn run_message_script(message_script: Option<&str>) -> MessageScriptResult {
-    let Some(script_name) = message_script else {
+async fn run_message_script(
+    wf: &WorkflowConfig,
+    cwd: Option<&Path>,
+    result_path: &Path,
+    env: &ResolvedEnv,
+    redactor: &Redactor,
+) -> MessageScriptResult {
+    let Some(script_name) = &wf.message_script else {
         return MessageScriptResult::NoScript;
     };
     let script_path = scripts_dir().join(script_name);
-    let Ok(out) = Command::new("bash").arg(&script_path).output().await else {
+    let mut command = script_command(&script_path, None, wf);
+    command.env("WF_RESULT_FILE", result_path);
+    command.envs(env.vars.iter().map(|(k, v)| (k, v)));
+    if let Some(dir) = cwd {
+        command.current_dir(dir);
+    }
+    let Ok(out) = command.output().await else {
         return MessageScriptResult::Empty;
     };
 
     match out.status.code() {
         Some(3) => MessageScriptResult::Suppressed,
         Some(0) => {
-            let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+            let stdout = redactor.redact(Cow::Borrowed(&out.stdout));
+            let text = String::from_utf8_lossy(&stdout).trim().to_string();
             if text.is_empty() {
                 MessageScriptResult::Empty
             } else {
@@ -169,24 +851,56 @@ async fn run_message_script(message_script: Option<&str>) -> MessageScriptResult
 
 async fn send_notification(
     notification: &NotificationConfig,
-    workflow_name: &str,
-    status: &ExecutionStatus,
+    wf: &WorkflowConfig,
+    execution: &Execution,
     custom_body: Option<&str>,
 ) {
-    let (title, default_body) = match status {
+    let workflow_name = &execution.workflow;
+    let (title, default_body, priority) = match execution.status {
+        ExecutionStatus::Success if execution.deviation.is_some() => (
+            format!("{} regressed", workflow_name),
+            execution.deviation.clone().unwrap_or_default(),
+            wf.priority.success,
+        ),
         ExecutionStatus::Success => (
             format!("{} succeeded", workflow_name),
             "completed successfully".to_string(),
+            wf.priority.success,
         ),
         ExecutionStatus::Failed => (
             format!("{} failed", workflow_name),
             "".to_string(),
+            wf.priority.failure,
+        ),
+        ExecutionStatus::ConfigError => (
+            format!("{} could not start", workflow_name),
+            "its script is missing or unreadable".to_string(),
+            wf.priority.failure,
         ),
         ExecutionStatus::Running => return,
     };
 
     let body = custom_body.unwrap_or(&default_body);
+    if wf.backends.is_empty() {
+        notify(notification, &title, body, priority, Some(execution));
+    } else {
+        let mut notification = notification.clone();
+        notification.backends.extend(wf.backends.iter().cloned());
+        notify(&notification, &title, body, priority, Some(execution));
+    }
+}
 
+/// Run the notification command with `title` and `body` appended as arguments, and hand both to
+/// the push backends and notification plugins. Only those two see `priority`.
+pub fn notify(
+    notification: &NotificationConfig,
+    title: &str,
+    body: &str,
+    priority: Priority,
+    execution: Option<&Execution>,
+) {
+    push::send(&notification.backends, title, body, priority, execution);
+    plugin::notify(&notification.plugins, title, body, priority, execution);
     let parts: Vec<&str> = notification.command.split_whitespace().collect();
     if parts.is_empty() {
         return;
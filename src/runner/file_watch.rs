@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::common::config::resolve_path;
+use crate::common::types::{AppConfig, WatchConfig};
+use crate::runner::scheduler::SchedulerCommand;
+
+/// Filesystem watchers for workflows with a `watch` section, reported to the scheduler as
+/// debounced [`SchedulerCommand::FileChanged`] commands.
+pub struct FileWatches {
+    scheduler_tx: mpsc::Sender<SchedulerCommand>,
+    active: HashMap<String, ActiveWatch>,
+}
+
+struct ActiveWatch {
+    config: WatchConfig,
+    _watcher: RecommendedWatcher,
+    debouncer: JoinHandle<()>,
+}
+
+impl Drop for ActiveWatch {
+    fn drop(&mut self) {
+        self.debouncer.abort();
+    }
+}
+
+impl FileWatches {
+    pub fn new(scheduler_tx: mpsc::Sender<SchedulerCommand>) -> Self {
+        Self {
+            scheduler_tx,
+            active: HashMap::new(),
+        }
+    }
+
+    /// Start, restart or stop watchers so they match `config`. Unchanged watches are kept.
+    pub fn update(&mut self, config: &AppConfig) {
+        let wanted: HashMap<&str, &WatchConfig> = config
+            .workflows
+            .iter()
+            .filter_map(|wf| wf.watch.as_ref().map(|w| (wf.name.as_str(), w)))
+            .collect();
+
+        self.active
+            .retain(|name, active| wanted.get(name.as_str()) == Some(&&active.config));
+
+        for (name, watch) in wanted {
+            if self.active.contains_key(name) {
+                continue;
+            }
+            match self.start_watch(name, watch) {
+                Ok(active) => {
+                    info!(workflow = %name, paths = ?watch.paths, "watching for changes");
+                    self.active.insert(name.to_string(), active);
+                }
+                Err(e) => warn!(workflow = %name, error = %e, "failed to start file watch"),
+            }
+        }
+    }
+
+    fn start_watch(&self, name: &str, watch: &WatchConfig) -> Result<ActiveWatch> {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel::<PathBuf>();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let Ok(event) = res else { return };
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                for path in event.paths {
+                    let _ = event_tx.send(path);
+                }
+            }
+        })?;
+
+        let mode = if watch.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        for path in &watch.paths {
+            let resolved = resolve_path(path);
+            if let Err(e) = watcher.watch(&resolved, mode) {
+                warn!(workflow = %name, path = %resolved.display(), error = %e, "cannot watch path");
+            }
+        }
+
+        // wait for a quiet period, then report the most recently changed path
+        let debounce = watch.debounce;
+        let scheduler_tx = self.scheduler_tx.clone();
+        let workflow = name.to_string();
+        let debouncer = tokio::spawn(async move {
+            while let Some(mut path) = event_rx.recv().await {
+                loop {
+                    match tokio::time::timeout(debounce, event_rx.recv()).await {
+                        Ok(Some(next)) => path = next,
+                        Ok(None) => return,
+                        Err(_) => break,
+                    }
+                }
+                let cmd = SchedulerCommand::FileChanged(workflow.clone(), path);
+                if scheduler_tx.send(cmd).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(ActiveWatch {
+            config: watch.clone(),
+            _watcher: watcher,
+            debouncer,
+        })
+    }
+}
@@ -0,0 +1,280 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use chrono::{Datelike, Local, Timelike, Utc};
+use chrono_tz::Tz;
+use rhai::{Dynamic, Engine, Map, Scope};
+use tracing::{info, warn};
+
+use crate::common::config::{hostname, scripts_dir};
+use crate::common::storage::Db;
+use crate::common::types::{Execution, TriggerSource, WorkflowConfig};
+use crate::runner::plugin;
+
+/// How long a `should_run` script may take before the run goes ahead without its answer.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Rhai operations a `should_run` script may take, so an endless loop ends long before the
+/// timeout.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// How many past executions are read to count the current failure streak.
+const STREAK_LOOKBACK: usize = 50;
+
+/// Ask the workflow's `should_run` script, then its condition plugins, whether an automatic run
+/// may start; the first to say no skips it, and why is returned. A script or plugin that fails
+/// or times out doesn't hold up the schedule: it counts as a yes and a warning is logged.
+pub async fn skip_reason(wf: &WorkflowConfig, source: TriggerSource, db: &Db) -> Option<String> {
+    if !source.is_automatic() || (wf.should_run.is_none() && wf.conditions.is_empty()) {
+        return None;
+    }
+
+    // what the checks know about past runs: the latest one and the failure streak up to it
+    let executions = db.get_executions(&wf.name, STREAK_LOOKBACK).await.unwrap_or_else(|e| {
+        warn!(workflow = %wf.name, error = %e, "should_run: failed to read executions");
+        Vec::new()
+    });
+    let last = executions.first();
+    let streak = executions
+        .iter()
+        .take_while(|e| e.status.is_failure())
+        .count();
+
+    if let Some(hook) = &wf.should_run {
+        if let Some(reason) = run_hook(wf, hook, source, last, streak).await {
+            return Some(reason);
+        }
+    }
+    for condition in &wf.conditions {
+        match plugin::check(condition, wf, source, last, streak).await {
+            Ok((true, _)) => {}
+            Ok((false, reason)) => {
+                info!(
+                    workflow = %wf.name,
+                    trigger = source.as_str(),
+                    plugin = %condition.plugin,
+                    reason = reason.as_deref().unwrap_or(""),
+                    "condition plugin skipped run"
+                );
+                let reason = match reason {
+                    Some(reason) => format!("condition plugin {}: {}", condition.plugin, reason),
+                    None => format!("condition plugin {} said no", condition.plugin),
+                };
+                return Some(reason);
+            }
+            Err(e) => warn!(workflow = %wf.name, error = %e, "condition plugin failed"),
+        }
+    }
+    None
+}
+
+/// Evaluate the `should_run` Rhai script: `true` runs, `false` skips, a string skips with it as
+/// the reason. Anything else, an error or running out of operations counts as a yes.
+async fn run_hook(
+    wf: &WorkflowConfig,
+    hook: &str,
+    source: TriggerSource,
+    last: Option<&Execution>,
+    streak: usize,
+) -> Option<String> {
+    let script = match script_source(hook).await {
+        Ok(script) => script,
+        Err(e) => {
+            warn!(workflow = %wf.name, hook = %describe(hook), error = %e, "should_run failed");
+            return None;
+        }
+    };
+    let context = Context {
+        workflow: wf.name.clone(),
+        trigger: source.as_str(),
+        timezone: wf.timezone,
+        last: last.cloned(),
+        failure_streak: streak as i64,
+    };
+    let eval = tokio::task::spawn_blocking(move || evaluate(&script, context));
+    let answer = match tokio::time::timeout(HOOK_TIMEOUT, eval).await {
+        Ok(Ok(Ok(answer))) => answer,
+        Ok(Ok(Err(e))) => {
+            warn!(workflow = %wf.name, hook = %describe(hook), error = %e, "should_run failed");
+            return None;
+        }
+        Ok(Err(e)) => {
+            warn!(workflow = %wf.name, error = %e, "should_run panicked");
+            return None;
+        }
+        Err(_) => {
+            warn!(workflow = %wf.name, hook = %describe(hook), "should_run timed out");
+            return None;
+        }
+    };
+    let reason = answer?;
+    info!(
+        workflow = %wf.name,
+        trigger = source.as_str(),
+        reason = %reason,
+        "should_run skipped run"
+    );
+    Some(match reason.as_str() {
+        "" => format!("should_run {} said no", describe(hook)),
+        reason => format!("should_run {}: {}", describe(hook), reason),
+    })
+}
+
+/// `should_run` as the explain view names it: its file, or `inline script`.
+pub fn describe(hook: &str) -> &str {
+    if is_file(hook) { hook } else { "inline script" }
+}
+
+/// A `should_run` of one line ending in `.rhai` names a file in the scripts directory; anything
+/// else is the script itself.
+fn is_file(hook: &str) -> bool {
+    !hook.contains('\n') && hook.trim().ends_with(".rhai")
+}
+
+async fn script_source(hook: &str) -> Result<String> {
+    if !is_file(hook) {
+        return Ok(hook.to_string());
+    }
+    let path = scripts_dir().join(hook.trim());
+    tokio::fs::read_to_string(&path)
+        .await
+        .with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// What a `should_run` script is given besides `system`, read when it is evaluated.
+struct Context {
+    workflow: String,
+    trigger: &'static str,
+    timezone: Option<Tz>,
+    last: Option<Execution>,
+    failure_streak: i64,
+}
+
+/// The answer of `script`: `None` to run, or the reason to skip.
+fn evaluate(script: &str, context: Context) -> Result<Option<String>> {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.register_fn("file_exists", |path: &str| Path::new(path).exists());
+    engine.register_fn("env", |name: &str| match std::env::var(name) {
+        Ok(value) => Dynamic::from(value),
+        Err(_) => Dynamic::UNIT,
+    });
+    engine.register_fn("disk_free_mb", |path: &str| disk_free_mb(path).unwrap_or(-1));
+
+    let mut scope = Scope::new();
+    scope.push_constant("workflow", context.workflow);
+    scope.push_constant("trigger", context.trigger.to_string());
+    scope.push_constant("now", now(context.timezone.as_ref()));
+    let last = context.last.as_ref().map(last_run);
+    scope.push_constant("last", last.map_or(Dynamic::UNIT, Dynamic::from));
+    scope.push_constant("failure_streak", context.failure_streak);
+    scope.push_constant("system", system());
+
+    let answer = engine
+        .eval_with_scope::<Dynamic>(&mut scope, script)
+        .map_err(|e| anyhow!("{}", e))?;
+    if let Ok(run) = answer.as_bool() {
+        return Ok((!run).then(String::new));
+    }
+    match answer.into_string() {
+        Ok(reason) => Ok(Some(reason)),
+        Err(kind) => bail!("should return true, false or a reason, got {}", kind),
+    }
+}
+
+/// `now`: the time in the workflow's `timezone`, or the runner's local time.
+fn now(timezone: Option<&Tz>) -> Map {
+    match timezone {
+        Some(tz) => time_map(&Utc::now().with_timezone(tz)),
+        None => time_map(&Local::now()),
+    }
+}
+
+fn time_map<T: Datelike + Timelike>(at: &T) -> Map {
+    let weekday = at.weekday().to_string().to_lowercase();
+    let mut map = Map::new();
+    map.insert("year".into(), (at.year() as i64).into());
+    map.insert("month".into(), (at.month() as i64).into());
+    map.insert("day".into(), (at.day() as i64).into());
+    map.insert("hour".into(), (at.hour() as i64).into());
+    map.insert("minute".into(), (at.minute() as i64).into());
+    map.insert("weekday".into(), weekday.into());
+    map.insert("timestamp".into(), Utc::now().timestamp().into());
+    map
+}
+
+/// `last`: the latest execution, finished or not.
+fn last_run(last: &Execution) -> Map {
+    let mut map = Map::new();
+    map.insert("status".into(), last.status.as_str().to_string().into());
+    map.insert("started_at".into(), last.started_at.timestamp().into());
+    let finished_at = last.finished_at.map(|f| f.timestamp());
+    map.insert("finished_at".into(), finished_at.map_or(Dynamic::UNIT, Dynamic::from));
+    let duration = last.finished_at.map(|f| (f - last.started_at).num_seconds());
+    map.insert("duration_secs".into(), duration.map_or(Dynamic::UNIT, Dynamic::from));
+    let exit_code = last.exit_code.map(|c| c as i64);
+    map.insert("exit_code".into(), exit_code.map_or(Dynamic::UNIT, Dynamic::from));
+    map.insert("error_count".into(), (last.severity.errors as i64).into());
+    map.insert("warning_count".into(), (last.severity.warnings as i64).into());
+    map
+}
+
+/// `system`: the machine as /proc and /sys tell it. What can't be read is left out.
+fn system() -> Map {
+    let mut map = Map::new();
+    map.insert("hostname".into(), hostname().into());
+    if let Ok(loadavg) = std::fs::read_to_string("/proc/loadavg") {
+        let loads: Vec<f64> =
+            loadavg.split_whitespace().take(3).filter_map(|l| l.parse().ok()).collect();
+        for (key, load) in ["load1", "load5", "load15"].into_iter().zip(loads) {
+            map.insert(key.into(), load.into());
+        }
+    }
+    if let Ok(meminfo) = std::fs::read_to_string("/proc/meminfo") {
+        let kb = |key: &str| {
+            let line = meminfo.lines().find_map(|l| l.strip_prefix(key)?.strip_prefix(':'))?;
+            line.split_whitespace().next()?.parse::<i64>().ok()
+        };
+        if let Some(total) = kb("MemTotal") {
+            map.insert("mem_total_mb".into(), (total / 1024).into());
+        }
+        if let Some(available) = kb("MemAvailable") {
+            map.insert("mem_available_mb".into(), (available / 1024).into());
+        }
+    }
+    if let Some(uptime) = std::fs::read_to_string("/proc/uptime")
+        .ok()
+        .and_then(|u| u.split_whitespace().next()?.parse::<f64>().ok())
+    {
+        map.insert("uptime_secs".into(), (uptime as i64).into());
+    }
+    map.insert("on_battery".into(), on_battery().into());
+    map
+}
+
+/// Whether there is a mains supply and it is offline; a machine without one, such as a
+/// desktop without the sysfs entries, is not on battery.
+fn on_battery() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let read = |path: &Path| std::fs::read_to_string(path).unwrap_or_default();
+    let mains: Vec<String> = supplies
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| read(&p.join("type")).trim() == "Mains")
+        .map(|p| read(&p.join("online")).trim().to_string())
+        .collect();
+    !mains.is_empty() && mains.iter().all(|online| online == "0")
+}
+
+/// Free space for unprivileged users on the filesystem holding `path`, in MiB.
+fn disk_free_mb(path: &str) -> Option<i64> {
+    let path = std::ffi::CString::new(path).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some((stat.f_bavail as u64 * stat.f_frsize as u64 / (1024 * 1024)) as i64)
+}
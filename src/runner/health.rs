@@ -0,0 +1,66 @@
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+use crate::common::config::resolve_path;
+use crate::common::types::AppConfig;
+
+/// The scheduler loop turns at least this often, even with nothing due, to show it is alive.
+const HEARTBEAT: Duration = Duration::from_secs(10);
+
+/// What the scheduler loop reports about itself when asked.
+#[derive(Debug, Clone)]
+pub struct SchedulerHealth {
+    pub tick_at: DateTime<Utc>,
+    pub next_fire_at: Option<DateTime<Utc>>,
+    pub workflows: usize,
+    pub running: usize,
+    pub queued: usize,
+    pub config_loaded_at: DateTime<Utc>,
+    /// Why the latest reload failed, if it did; the scheduler still runs the config before it.
+    pub config_error: Option<String>,
+}
+
+/// How often the scheduler loop turns: [`HEARTBEAT`], or more often when systemd's watchdog
+/// (`WatchdogSec`) expects pings sooner.
+pub fn heartbeat_interval() -> Duration {
+    let watchdog = std::env::var("WATCHDOG_USEC")
+        .ok()
+        .and_then(|usec| usec.parse::<u64>().ok())
+        .map(|usec| Duration::from_micros(usec) / 3);
+    watchdog.map_or(HEARTBEAT, |w| w.clamp(Duration::from_millis(100), HEARTBEAT))
+}
+
+/// One turn of the scheduler loop: touch `liveness_file` and ping systemd's watchdog, if set.
+pub fn beat(config: &AppConfig) {
+    if let Some(path) = &config.liveness_file {
+        let path = resolve_path(path);
+        if let Err(e) = std::fs::write(&path, format!("{}\n", Utc::now().to_rfc3339())) {
+            warn!(path = %path.display(), error = %e, "failed to touch liveness file");
+        }
+    }
+    if std::env::var_os("WATCHDOG_USEC").is_some() {
+        notify_systemd("WATCHDOG=1");
+    }
+}
+
+/// Send `state` to systemd's notification socket (`READY=1`, `WATCHDOG=1`); nothing without
+/// one, i.e. when not started by systemd with `Type=notify` or `WatchdogSec`.
+pub fn notify_systemd(state: &str) {
+    let Some(socket) = std::env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+    let socket = socket.to_string_lossy();
+    // a leading @ names a socket in the abstract namespace
+    let addr = match socket.strip_prefix('@') {
+        Some(name) => SocketAddr::from_abstract_name(name.as_bytes()),
+        None => SocketAddr::from_pathname(socket.as_ref()),
+    };
+    let sent = addr.and_then(|addr| UnixDatagram::unbound()?.send_to_addr(state.as_bytes(), &addr));
+    if let Err(e) = sent {
+        warn!(socket = %socket, error = %e, "failed to notify systemd");
+    }
+}
@@ -0,0 +1,191 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::process::Command;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{error, info, warn};
+
+use crate::common::duration::format_duration;
+use crate::common::storage::Db;
+use crate::common::types::TriggerSource;
+use crate::runner::events::{DecisionKind, Event, EventKind, Events};
+use crate::runner::shutdown::Shutdown;
+
+/// How often a run waiting for `when_idle` looks again.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The keyboard and touchpad interrupts last counted in /proc/interrupts, and since when the
+/// count has stayed at that.
+static INPUT_INTERRUPTS: Mutex<Option<(u64, Instant)>> = Mutex::new(None);
+
+/// Hold an automatic run of workflow `name` back until the machine has been idle for `needed`,
+/// or the runner begins stopping. Pausing the workflow meanwhile ends the wait, and the reason
+/// to skip the run is returned. When no source can tell the idle time, e.g. on a headless
+/// machine, the run goes ahead and a warning is logged.
+pub async fn wait(
+    name: &str,
+    needed: Duration,
+    source: TriggerSource,
+    events: &Events,
+    db: &Db,
+    shutdown: &Shutdown,
+) -> Option<&'static str> {
+    let mut changes = events.subscribe();
+    let mut deferred = false;
+    loop {
+        if shutdown.is_stopping() {
+            return None;
+        }
+        if deferred && paused(db, name).await {
+            info!(workflow = %name, "paused while waiting for the machine to be idle");
+            return Some("paused while waiting for idle");
+        }
+        let Some(idle) = idle_time().await else {
+            warn!(workflow = %name, "idle time unknown, not waiting for when_idle");
+            return None;
+        };
+        if idle >= needed {
+            return None;
+        }
+        if !deferred {
+            let idle_secs = idle.as_secs();
+            info!(workflow = %name, idle_secs, "waiting for the machine to be idle");
+            let reason = format!(
+                "waiting for {} idle, idle for {}",
+                format_duration(&needed),
+                format_duration(&idle)
+            );
+            events.decision(name, DecisionKind::Deferred, source, reason);
+            deferred = true;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            _ = shutdown.stopping() => {}
+            _ = changed(&mut changes, name) => {}
+        }
+    }
+}
+
+async fn paused(db: &Db, name: &str) -> bool {
+    db.paused_workflows().await.map(|p| p.contains(name)).unwrap_or_else(|e| {
+        error!(error = %e, "failed to read paused workflows");
+        false
+    })
+}
+
+/// Resolves when workflow `name` may have changed, e.g. been paused.
+async fn changed(changes: &mut broadcast::Receiver<Event>, name: &str) {
+    loop {
+        match changes.recv().await {
+            Ok(event) if event.kind == EventKind::Changed => {
+                if event.workflow.is_empty() || event.workflow == name {
+                    return;
+                }
+            }
+            Ok(_) => {}
+            Err(RecvError::Lagged(_)) => return,
+            Err(RecvError::Closed) => std::future::pending().await,
+        }
+    }
+}
+
+/// How long since the last keyboard or mouse input, from the first source that knows: GNOME's
+/// idle monitor (X11 and Wayland), `xprintidle` in an X11 session, logind's idle hint, which
+/// desktops set when the screensaver starts, then the laptop keyboard and touchpad interrupts
+/// in /proc/interrupts.
+pub async fn idle_time() -> Option<Duration> {
+    if let Some(idle) = gnome_idle_time().await {
+        return Some(idle);
+    }
+    // under Wayland, X11 only sees the input of Xwayland windows
+    let display = |var| std::env::var_os(var).is_some();
+    let x11 = display("DISPLAY") && !display("WAYLAND_DISPLAY");
+    if x11 {
+        if let Some(idle) = xprintidle().await {
+            return Some(idle);
+        }
+    }
+    if let Some(idle) = logind_idle_time().await {
+        return Some(idle);
+    }
+    input_interrupts_idle_time()
+}
+
+async fn gnome_idle_time() -> Option<Duration> {
+    let stdout = output(Command::new("gdbus").args([
+        "call",
+        "--session",
+        "--dest",
+        "org.gnome.Mutter.IdleMonitor",
+        "--object-path",
+        "/org/gnome/Mutter/IdleMonitor/Core",
+        "--method",
+        "org.gnome.Mutter.IdleMonitor.GetIdletime",
+    ]))
+    .await?;
+    // e.g. `(uint64 12345,)`, in milliseconds
+    let ms = stdout.trim().strip_prefix("(uint64 ")?.strip_suffix(",)")?;
+    Some(Duration::from_millis(ms.parse().ok()?))
+}
+
+async fn xprintidle() -> Option<Duration> {
+    let ms = output(&mut Command::new("xprintidle")).await?;
+    Some(Duration::from_millis(ms.trim().parse().ok()?))
+}
+
+/// From the user's idle hint. A hint of no only counts when the user has a graphical session,
+/// whose desktop would have set it; without one it says nothing.
+async fn logind_idle_time() -> Option<Duration> {
+    let uid = unsafe { libc::getuid() }.to_string();
+    let stdout = output(Command::new("loginctl").args([
+        "show-user",
+        &uid,
+        "--property=IdleHint",
+        "--property=IdleSinceHint",
+        "--property=Display",
+    ]))
+    .await?;
+    let property = |key: &str| {
+        stdout.lines().find_map(|l| l.strip_prefix(key)?.strip_prefix('=')).unwrap_or("")
+    };
+    match property("IdleHint") {
+        "yes" => {
+            // microseconds since the epoch
+            let since: u64 = property("IdleSinceHint").parse().ok()?;
+            let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?;
+            Some(now.saturating_sub(Duration::from_micros(since)))
+        }
+        "no" if !property("Display").is_empty() => Some(Duration::ZERO),
+        _ => None,
+    }
+}
+
+/// How long the i8042 (built-in keyboard and touchpad) interrupt count has stayed the same,
+/// as far as this runner has watched it: the first look counts as input just now. `None`
+/// without such a controller.
+fn input_interrupts_idle_time() -> Option<Duration> {
+    let interrupts = std::fs::read_to_string("/proc/interrupts").ok()?;
+    let counts: Vec<u64> = interrupts
+        .lines()
+        .filter(|l| l.contains("i8042"))
+        .map(|l| l.split_whitespace().skip(1).map_while(|n| n.parse::<u64>().ok()).sum())
+        .collect();
+    if counts.is_empty() {
+        return None;
+    }
+    let count = counts.iter().sum();
+    let mut last = INPUT_INTERRUPTS.lock().unwrap();
+    match *last {
+        Some((seen, since)) if seen == count => Some(since.elapsed()),
+        _ => {
+            *last = Some((count, Instant::now()));
+            Some(Duration::ZERO)
+        }
+    }
+}
+
+/// The stdout of `command` when it runs and exits 0.
+async fn output(command: &mut Command) -> Option<String> {
+    let output = command.kill_on_drop(true).output().await.ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
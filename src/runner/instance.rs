@@ -0,0 +1,92 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, Write};
+use std::os::fd::AsRawFd;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use tracing::info;
+
+use crate::common::paths;
+
+/// How often a `--replace` start checks whether the runner it stopped has let go of the lock.
+const REPLACE_POLL: Duration = Duration::from_millis(200);
+
+/// Held for as long as the runner runs, so a second one refuses to start. The kernel releases
+/// the lock when the process exits, however it exits, so a crash leaves no stale lock behind.
+pub struct InstanceLock {
+    _file: File,
+}
+
+pub fn lock_path() -> PathBuf {
+    paths::get().cache_dir.join("runner.lock")
+}
+
+/// Take the lock, or fail naming the runner that holds it. With `replace`, that runner is sent
+/// SIGTERM instead and the lock is taken once it has shut down, allowing `wait` for that.
+pub async fn acquire(replace: bool, wait: Duration) -> Result<InstanceLock> {
+    let path = lock_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+
+    if !try_lock(&file)? {
+        let holder = holder_pid(&mut file);
+        let Some(pid) = holder.filter(|_| replace) else {
+            let who = holder.map_or("another runner".to_string(), |pid| format!("pid {}", pid));
+            bail!(
+                "a runner is already running ({}, lock {}); stop it first, or start with \
+                 --replace to take over",
+                who,
+                path.display()
+            );
+        };
+        info!(pid, "stopping the running runner to take over");
+        // SAFETY: kill only sends a signal
+        if unsafe { libc::kill(pid, libc::SIGTERM) } != 0 {
+            let e = std::io::Error::last_os_error();
+            bail!("failed to stop the running runner (pid {}): {}", pid, e);
+        }
+        let deadline = Instant::now() + wait;
+        while !try_lock(&file)? {
+            if Instant::now() > deadline {
+                bail!("the running runner (pid {}) did not stop within {:?}", pid, wait);
+            }
+            tokio::time::sleep(REPLACE_POLL).await;
+        }
+        info!(pid, "took over from the previous runner");
+    }
+
+    file.set_len(0)?;
+    file.rewind()?;
+    writeln!(file, "{}", std::process::id())?;
+    Ok(InstanceLock { _file: file })
+}
+
+/// Whether the exclusive lock was taken; false when another process holds it.
+fn try_lock(file: &File) -> Result<bool> {
+    // SAFETY: flock on a descriptor this function borrows for the call
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+    let e = std::io::Error::last_os_error();
+    if e.raw_os_error() == Some(libc::EWOULDBLOCK) {
+        return Ok(false);
+    }
+    Err(e).context("failed to lock runner.lock")
+}
+
+/// The pid the lock holder wrote into the file.
+fn holder_pid(file: &mut File) -> Option<i32> {
+    let mut contents = String::new();
+    file.rewind().ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok().filter(|pid| *pid > 0)
+}
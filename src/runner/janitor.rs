@@ -0,0 +1,202 @@
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use cron::Schedule;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::common::config::{hostname, load_config};
+use crate::common::storage::Db;
+use crate::common::types::{Execution, RetentionConfig, StorageBackend, StorageConfig};
+use crate::runner::executor::{bookmarks_path_for, tmp_dir_for, tmp_root};
+use crate::runner::log_compress;
+use crate::runner::shutdown;
+use crate::runner::trigger::normalize_cron;
+
+const JANITOR_INTERVAL: Duration = Duration::from_secs(3600);
+
+pub fn start(db: Db) -> JoinHandle<()> {
+    tokio::spawn(maintenance_loop(Arc::clone(&db)));
+    tokio::spawn(janitor_loop(db))
+}
+
+async fn janitor_loop(db: Db) {
+    let mut tick = tokio::time::interval(JANITOR_INTERVAL);
+    loop {
+        tick.tick().await;
+        // the defaults may keep less than the broken config asks for, and a purge is for good
+        let retention = match load_config() {
+            Ok(config) => config.retention,
+            Err(e) => {
+                error!(error = %format!("{:#}", e), "config can't be read; skipping retention");
+                continue;
+            }
+        };
+        if let Err(e) = purge_expired(&db, &retention).await {
+            error!(error = %e, "purging deleted executions failed");
+        }
+        if let Err(e) = apply_retention(&db, &retention).await {
+            error!(error = %e, "pruning old executions failed");
+        }
+    }
+}
+
+/// Permanently remove executions whose soft-delete grace period has passed, along with their logs.
+async fn purge_expired(
+    db: &Db,
+    retention: &RetentionConfig,
+) -> Result<()> {
+    let cutoff = Utc::now() - chrono::Duration::from_std(retention.grace_period)?;
+    let log_paths = db.purge_deleted(cutoff).await?;
+    if log_paths.is_empty() {
+        return Ok(());
+    }
+
+    remove_logs(&log_paths).await;
+    info!(count = log_paths.len(), "purged deleted executions");
+    Ok(())
+}
+
+/// Prune finished executions beyond `keep_executions` per workflow or older than
+/// `keep_logs_days`, along with their logs, and config changes by the same limits. Returns how
+/// many executions were removed.
+pub async fn apply_retention(db: &Db, retention: &RetentionConfig) -> Result<usize> {
+    if retention.keep_executions.is_none() && retention.keep_logs_days.is_none() {
+        return Ok(0);
+    }
+    let cutoff = retention
+        .keep_logs_days
+        .map(|days| Utc::now() - chrono::Duration::days(days.into()));
+    let changes = db.prune_config_changes(retention.keep_executions, cutoff).await?;
+    if changes > 0 {
+        info!(count = changes, "pruned config changes past retention");
+    }
+    let log_paths = db.prune_executions(retention.keep_executions, cutoff).await?;
+    if log_paths.is_empty() {
+        return Ok(0);
+    }
+
+    remove_logs(&log_paths).await;
+    info!(count = log_paths.len(), "pruned executions past retention");
+    Ok(log_paths.len())
+}
+
+async fn remove_logs(paths: &[String]) {
+    for path in paths {
+        if let Err(e) = tokio::fs::remove_file(path).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!(path = %path, error = %e, "failed to remove log file");
+            }
+        }
+        let path = Path::new(path);
+        let _ = tokio::fs::remove_file(log_compress::gz_path_for(path)).await;
+        let _ = tokio::fs::remove_file(bookmarks_path_for(path)).await;
+        if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
+            let _ = tokio::fs::remove_dir_all(tmp_dir_for(id)).await;
+        }
+    }
+}
+
+/// Remove the `$TMPDIR`s crashed or killed runs left behind. Called at startup, before anything
+/// runs, so every directory is a leftover; only those kept for a failed run stay.
+pub async fn sweep_tmp_dirs(db: &Db) {
+    let Ok(mut entries) = tokio::fs::read_dir(tmp_root()).await else {
+        return;
+    };
+    let mut removed = 0;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let id = entry.file_name().to_string_lossy().to_string();
+        let kept = matches!(
+            db.get_execution_by_id(&id).await,
+            Ok(Some(exec)) if exec.status.is_failure()
+        );
+        if kept {
+            continue;
+        }
+        match tokio::fs::remove_dir_all(entry.path()).await {
+            Ok(()) => removed += 1,
+            Err(e) => warn!(path = %entry.path().display(), error = %e, "failed to remove tmp dir"),
+        }
+    }
+    if removed > 0 {
+        info!(count = removed, "removed leftover execution tmp dirs");
+    }
+}
+
+/// Mark executions a crashed or killed runner left `running` as failed, with a note at the end of
+/// their logs. Called at startup, before anything runs, so none of this runner's executions is
+/// really running. In a Postgres history shared by several runners only this host's are touched.
+pub async fn recover_interrupted(db: &Db, storage: &StorageConfig) {
+    let running = match db.running_executions().await {
+        Ok(running) => running,
+        Err(e) => {
+            warn!(error = %e, "failed to look for interrupted executions");
+            return;
+        }
+    };
+    let host = hostname();
+    let mut recovered = 0;
+    for exec in running {
+        if storage.backend == StorageBackend::Postgres && exec.host.as_ref() != Some(&host) {
+            continue;
+        }
+        match recover(db, &exec).await {
+            Ok(()) => recovered += 1,
+            Err(e) => warn!(id = %exec.id, error = %e, "failed to mark execution as interrupted"),
+        }
+    }
+    if recovered > 0 {
+        warn!(count = recovered, "marked executions interrupted by a runner crash as failed");
+    }
+}
+
+async fn recover(db: &Db, exec: &Execution) -> Result<()> {
+    // the log was last written about when the runner went away
+    let log_path = Path::new(&exec.log_path);
+    let finished_at = tokio::fs::metadata(log_path)
+        .await
+        .and_then(|m| m.modified())
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(|_| Utc::now())
+        .max(exec.started_at);
+    let note = format!(
+        "\n[{}] [recovery] interrupted: the runner stopped while this ran; marked failed on \
+         restart\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+    shutdown::mark_interrupted(db, exec, log_path, finished_at, &note).await?;
+    info!(workflow = %exec.workflow, id = %exec.id, "marked interrupted execution as failed");
+    Ok(())
+}
+
+/// Run database maintenance on the `maintenance.cron` schedule, re-reading the config before each sleep.
+async fn maintenance_loop(db: Db) {
+    loop {
+        let cron = load_config().map(|c| c.maintenance).unwrap_or_default().cron;
+        let next = Schedule::from_str(&normalize_cron(&cron))
+            .ok()
+            .and_then(|s| s.upcoming(Local).next());
+        let Some(next) = next else {
+            warn!(cron = %cron, "invalid maintenance cron expression, retrying in an hour");
+            tokio::time::sleep(JANITOR_INTERVAL).await;
+            continue;
+        };
+
+        let wait = (next - Local::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        match db.run_maintenance().await {
+            Ok(report) => info!(
+                size_before = report.size_before,
+                size_after = report.size_after,
+                reclaimed = report.reclaimed_bytes(),
+                "database maintenance finished"
+            ),
+            Err(e) => error!(error = %e, "database maintenance failed"),
+        }
+    }
+}
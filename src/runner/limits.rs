@@ -0,0 +1,83 @@
+use std::io;
+use std::path::Path;
+
+use tokio::process::Command;
+
+use crate::common::types::{IoClass, ResourceLimits};
+
+/// `IOPRIO_WHO_PROCESS` of `ioprio_set(2)`; libc has the syscall number but not the constants.
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+
+/// Whether `memory_max` and `cpu_quota` can be applied: the cgroup v2 hierarchy is mounted and a
+/// systemd user manager is there for `systemd-run --user --scope` to ask for a scope.
+pub fn cgroups_available() -> bool {
+    let cgroup_v2 = Path::new("/sys/fs/cgroup/cgroup.controllers").exists();
+    let user_manager = std::env::var_os("XDG_RUNTIME_DIR")
+        .is_some_and(|dir| Path::new(&dir).join("systemd/private").exists());
+    let systemd_run = std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|d| d.join("systemd-run").is_file()));
+    cgroup_v2 && user_manager && systemd_run
+}
+
+/// Apply `limits` to a script's command, before anything but `PATH` is set on it. `nice` and
+/// `ionice` are set in the child right before exec, so they hold for everything the script
+/// starts. With `cgroups`, `memory_max` and `cpu_quota` run the command through `systemd-run
+/// --user --scope`, which execs it in place: it stays the runner's child with the same pipes.
+pub fn apply(command: Command, limits: &ResourceLimits, cgroups: bool) -> Command {
+    let mut command = if cgroups && limits.needs_cgroup() {
+        let inner = command.as_std();
+        let mut scope = Command::new("systemd-run");
+        scope.args(["--user", "--scope", "--quiet", "--collect"]);
+        if let Some(max) = limits.memory_max {
+            scope.arg(format!("--property=MemoryMax={}", max.0));
+        }
+        if let Some(quota) = limits.cpu_quota {
+            scope.arg(format!("--property=CPUQuota={}%", quota.0));
+        }
+        scope.arg("--").arg(inner.get_program()).args(inner.get_args());
+        for (key, value) in inner.get_envs() {
+            match value {
+                Some(value) => scope.env(key, value),
+                None => scope.env_remove(key),
+            };
+        }
+        if let Some(dir) = inner.get_current_dir() {
+            scope.current_dir(dir);
+        }
+        scope
+    } else {
+        command
+    };
+
+    let (nice, ionice) = (limits.nice, limits.ionice);
+    if nice.is_none() && ionice.is_none() {
+        return command;
+    }
+    let ioprio = ionice.map(|io| {
+        let class = match io.class {
+            IoClass::Realtime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        };
+        (class << IOPRIO_CLASS_SHIFT) | libc::c_int::from(io.level)
+    });
+    // SAFETY: the closure only makes the two syscalls, which are safe between fork and exec.
+    // A failure (a negative nice or `realtime` without privileges) fails the spawn.
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(nice) = nice {
+                if libc::setpriority(libc::PRIO_PROCESS, 0, nice) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            if let Some(ioprio) = ioprio {
+                if libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ioprio) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+    command
+}
@@ -0,0 +1,117 @@
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use anyhow::{Context, Result, bail};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::process::Command;
+
+/// Most of a gzipped log [`read_tail`] unpacks looking for its end.
+const MAX_UNPACKED: u64 = 256 * 1024 * 1024;
+
+/// Where `compress_log` leaves the log at `log_path`; the stored path stays the plain one.
+pub fn gz_path_for(log_path: &Path) -> PathBuf {
+    let mut path = log_path.as_os_str().to_owned();
+    path.push(".gz");
+    PathBuf::from(path)
+}
+
+/// Replace the log at `log_path` with a gzipped copy next to it, by way of `gzip`.
+pub async fn compress(log_path: &Path) -> Result<()> {
+    let output = Command::new("gzip")
+        .arg("-n")
+        .arg("-f")
+        .arg(log_path)
+        .output()
+        .await
+        .context("failed to run gzip")?;
+    if !output.status.success() {
+        bail!("gzip failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// The log at `log_path`, or its gzipped copy unpacked when only that is left. A log with
+/// neither reads as `NotFound`, like a missing file.
+pub async fn read(log_path: &Path) -> std::io::Result<Vec<u8>> {
+    match tokio::fs::read(log_path).await {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        read => return read,
+    }
+    let gz_path = gz_path_for(log_path);
+    if !tokio::fs::try_exists(&gz_path).await? {
+        return Err(std::io::ErrorKind::NotFound.into());
+    }
+    let output = Command::new("gzip").arg("-dc").arg(&gz_path).output().await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(std::io::Error::other(format!("gzip failed: {}", stderr.trim())));
+    }
+    Ok(output.stdout)
+}
+
+/// About the last `max` bytes of the log at `log_path`, or of its gzipped copy when only that is
+/// left, starting at a line. Only the end of a plain log is read; a gzipped one is unpacked as a
+/// stream, and given up on past [`MAX_UNPACKED`].
+pub async fn read_tail(log_path: &Path, max: usize) -> std::io::Result<Vec<u8>> {
+    let (mut tail, cut) = match tokio::fs::File::open(log_path).await {
+        Ok(mut file) => {
+            let len = file.metadata().await?.len();
+            let start = len.saturating_sub(max as u64);
+            file.seek(SeekFrom::Start(start)).await?;
+            let mut tail = Vec::new();
+            file.take(max as u64).read_to_end(&mut tail).await?;
+            (tail, start > 0)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => unpack_tail(log_path, max).await?,
+        Err(e) => return Err(e),
+    };
+    if cut {
+        let start = tail.iter().position(|b| *b == b'\n').map_or(tail.len(), |i| i + 1);
+        tail.drain(..start);
+    }
+    Ok(tail)
+}
+
+/// The last `max` bytes of the gzipped copy of the log at `log_path`, and whether that is less
+/// than all of it.
+async fn unpack_tail(log_path: &Path, max: usize) -> std::io::Result<(Vec<u8>, bool)> {
+    let gz_path = gz_path_for(log_path);
+    if !tokio::fs::try_exists(&gz_path).await? {
+        return Err(std::io::ErrorKind::NotFound.into());
+    }
+    let mut child = Command::new("gzip")
+        .arg("-dc")
+        .arg(&gz_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut tail = Vec::with_capacity(2 * max);
+    let mut chunk = vec![0; 64 * 1024];
+    let (mut unpacked, mut cut) = (0, false);
+    loop {
+        let n = stdout.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        unpacked += n as u64;
+        if unpacked > MAX_UNPACKED {
+            return Err(std::io::Error::other("log too large to unpack"));
+        }
+        tail.extend_from_slice(&chunk[..n]);
+        if tail.len() > 2 * max {
+            tail.drain(..tail.len() - max);
+            cut = true;
+        }
+    }
+    if !child.wait().await?.success() {
+        return Err(std::io::Error::other("gzip failed"));
+    }
+    if tail.len() > max {
+        tail.drain(..tail.len() - max);
+        cut = true;
+    }
+    Ok((tail, cut))
+}
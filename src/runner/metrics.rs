@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use axum::Router;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::runner::events::{EventKind, Events};
+use crate::runner::scheduler::QueueDepths;
+
+/// Upper bounds of the execution duration histogram, in seconds: a second to two hours.
+const BUCKETS: [f64; 10] = [1.0, 5.0, 15.0, 30.0, 60.0, 300.0, 900.0, 1800.0, 3600.0, 7200.0];
+
+/// Counters and gauges served on `GET /metrics`. Clones share them. They start from zero with
+/// every runner start, as Prometheus expects of counters.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics(Arc<Mutex<Inner>>);
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Finished executions by workflow and status.
+    executions: BTreeMap<(String, &'static str), u64>,
+    durations: BTreeMap<String, Histogram>,
+    running: BTreeMap<String, u64>,
+    /// How late the latest scheduled fire time of each workflow was picked up.
+    scheduler_lag: BTreeMap<String, Duration>,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Observations at most each of [`BUCKETS`], not cumulative.
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Metrics {
+    pub fn record_lag(&self, workflow: &str, lag: Duration) {
+        self.0.lock().unwrap().scheduler_lag.insert(workflow.to_string(), lag);
+    }
+
+    /// Count executions as `events` announces them starting and finishing.
+    pub fn start(&self, events: &Events) -> JoinHandle<()> {
+        let mut rx = events.subscribe();
+        let metrics = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = match rx.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!(missed, "metrics fell behind, some executions are not counted");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let mut inner = metrics.0.lock().unwrap();
+                match event.kind {
+                    EventKind::Started => {
+                        *inner.running.entry(event.workflow).or_default() += 1;
+                    }
+                    EventKind::Finished => {
+                        if let Some(running) = inner.running.get_mut(&event.workflow) {
+                            *running = running.saturating_sub(1);
+                        }
+                        if let Some(status) = &event.status {
+                            let key = (event.workflow.clone(), status.as_str());
+                            *inner.executions.entry(key).or_default() += 1;
+                        }
+                        if let Some(duration) = event.duration {
+                            inner.durations.entry(event.workflow).or_default().observe(duration);
+                        }
+                    }
+                    EventKind::Changed | EventKind::Decision | EventKind::ConfigReloaded => {}
+                }
+            }
+        })
+    }
+
+    /// Everything in the Prometheus text exposition format.
+    fn render(&self, queue_depths: &QueueDepths) -> String {
+        let inner = self.0.lock().unwrap();
+        let mut out = String::new();
+
+        family(&mut out, "executions_total", "counter", "Finished executions by status.");
+        for ((workflow, status), count) in &inner.executions {
+            let labels = format!("workflow=\"{}\",status=\"{}\"", escape(workflow), status);
+            sample(&mut out, "executions_total", &labels, count);
+        }
+
+        let name = "execution_duration_seconds";
+        family(&mut out, name, "histogram", "How long finished executions ran.");
+        for (workflow, histogram) in &inner.durations {
+            let workflow = format!("workflow=\"{}\"", escape(workflow));
+            let mut cumulative = 0;
+            for (le, count) in BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let labels = format!("{},le=\"{}\"", workflow, le);
+                sample(&mut out, &format!("{}_bucket", name), &labels, cumulative);
+            }
+            let labels = format!("{},le=\"+Inf\"", workflow);
+            sample(&mut out, &format!("{}_bucket", name), &labels, histogram.count);
+            sample(&mut out, &format!("{}_sum", name), &workflow, histogram.sum);
+            sample(&mut out, &format!("{}_count", name), &workflow, histogram.count);
+        }
+
+        family(&mut out, "running", "gauge", "Executions running now.");
+        for (workflow, running) in &inner.running {
+            sample(&mut out, "running", &format!("workflow=\"{}\"", escape(workflow)), running);
+        }
+
+        family(&mut out, "queued", "gauge", "Triggers waiting behind a running execution.");
+        for (workflow, depth) in queue_depths.all() {
+            sample(&mut out, "queued", &format!("workflow=\"{}\"", escape(&workflow)), depth);
+        }
+
+        let name = "scheduler_lag_seconds";
+        family(&mut out, name, "gauge", "How late the latest scheduled fire time was picked up.");
+        for (workflow, lag) in &inner.scheduler_lag {
+            let labels = format!("workflow=\"{}\"", escape(workflow));
+            sample(&mut out, name, &labels, lag.as_secs_f64());
+        }
+        out
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        if let Some(i) = BUCKETS.iter().position(|le| secs <= *le) {
+            self.buckets[i] += 1;
+        }
+        self.count += 1;
+        self.sum += secs;
+    }
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP illef_workflow_{} {}", name, help);
+    let _ = writeln!(out, "# TYPE illef_workflow_{} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &str, value: impl Display) {
+    let _ = writeln!(out, "illef_workflow_{}{{{}}} {}", name, labels, value);
+}
+
+/// A label value with backslashes, quotes and newlines escaped.
+fn escape(value: &str) -> String {
+    value.replace('\\', r"\\").replace('"', "\\\"").replace('\n', r"\n")
+}
+
+#[derive(Clone)]
+struct MetricsState {
+    metrics: Metrics,
+    queue_depths: QueueDepths,
+}
+
+/// Serve `GET /metrics` on `addr`.
+pub async fn serve(addr: &str, metrics: Metrics, queue_depths: QueueDepths) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind metrics listener on {}", addr))?;
+    info!("metrics endpoint listening on http://{}/metrics", addr);
+
+    let app = Router::new()
+        .route("/metrics", get(scrape))
+        .with_state(MetricsState { metrics, queue_depths });
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn scrape(State(state): State<MetricsState>) -> impl IntoResponse {
+    let body = state.metrics.render(&state.queue_depths);
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
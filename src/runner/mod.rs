@@ -1,47 +1,183 @@
+pub mod adhoc;
+pub mod auth;
+pub mod baseline;
+pub mod binary;
+pub mod capture;
+pub mod changelog;
+pub mod completion_webhook;
+pub mod config_diff;
+pub mod cron_explain;
+pub mod dedup;
+pub mod dry_run;
+pub mod digest;
+pub mod distributed_lock;
+pub mod events;
 pub mod executor;
+pub mod file_watch;
+pub mod gate;
+pub mod health;
+pub mod http;
+pub mod idle;
+pub mod instance;
+pub mod janitor;
+pub mod limits;
+pub mod log_compress;
+pub mod metrics;
+pub mod names;
+pub mod next_runs;
+pub mod output;
+pub mod plugin;
+pub mod push;
+pub mod redact;
+pub mod report;
+pub mod request_log;
 pub mod scheduler;
+pub mod secrets;
 pub mod server;
+pub mod severity;
+pub mod shutdown;
+pub mod trigger;
+pub mod webhook;
+pub mod workflow_health;
 
 use std::sync::Arc;
+use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::Result;
-use tokio::sync::{Mutex, mpsc};
-use tracing::info;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
 
-use crate::common::config::{load_config, watch_config};
-use crate::common::db::open_db;
-use crate::runner::scheduler::SchedulerCommand;
+use crate::common::config::{config_name, load_config, watch_config};
+use crate::common::paths;
+use crate::common::storage::open_storage;
+use crate::common::upgrade::CONFIG_VERSION;
+use crate::runner::events::Events;
+use crate::runner::metrics::Metrics;
+use crate::runner::scheduler::{QueueDepths, SchedulerCommand};
+use crate::runner::shutdown::Shutdown;
 
-pub async fn run() -> Result<()> {
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub read_only: bool,
+    /// Stop a runner that is already running and take over, instead of refusing to start.
+    pub replace: bool,
+}
+
+pub async fn run(options: RunOptions) -> Result<()> {
     let config = load_config()?;
     info!(workflows = config.workflows.len(), "config loaded");
+    if config.version < CONFIG_VERSION {
+        warn!(
+            version = config.version,
+            "{} uses an older layout and was upgraded as it was read; update it and set \
+             `version` to {} (see the README)",
+            config_name(),
+            CONFIG_VERSION
+        );
+    }
+
+    // before storage is opened, so two runners never share it; a runner we replace may take
+    // its shutdown timeout to stop
+    let wait = config.shutdown.timeout + Duration::from_secs(15);
+    let _instance = instance::acquire(options.replace, wait).await?;
+
+    let db = open_storage(&config.storage).await?;
+    changelog::record(&db, &config).await;
 
-    let db = Arc::new(Mutex::new(open_db()?));
+    // before the scheduler starts, while last executions still predate this run
+    let missed = scheduler::missed_runs(&config, &db).await;
+    scheduler::log_missed_runs(&missed);
+    janitor::sweep_tmp_dirs(&db).await;
+    // after the sweep, which keeps the $TMPDIRs of failed runs
+    janitor::recover_interrupted(&db, &config.storage).await;
 
-    let (scheduler_tx, _scheduler_handle) = scheduler::start(config.clone(), Arc::clone(&db));
+    let events = Events::default();
+    let queue_depths = QueueDepths::default();
+    let metrics = Metrics::default();
+    let _metrics_handle = metrics.start(&events);
+    let shutdown = Shutdown::default();
+    let (scheduler_tx, _scheduler_handle) = scheduler::start(
+        config.clone(),
+        Arc::clone(&db),
+        events.clone(),
+        queue_depths.clone(),
+        metrics.clone(),
+        shutdown.clone(),
+        missed.clone(),
+    );
+    let _janitor_handle = janitor::start(Arc::clone(&db));
+    let _digest_handle = digest::start(Arc::clone(&db));
+
+    let mut file_watches = file_watch::FileWatches::new(scheduler_tx.clone());
+    file_watches.update(&config);
 
     // config hot-reload
     let (config_tx, mut config_rx) = mpsc::channel::<()>(4);
-    let _watcher = watch_config(config_tx)?;
+    let discover = Arc::new(AtomicBool::new(config.discover_scripts));
+    let mut watcher = watch_config(config_tx, Arc::clone(&discover), &config)?;
     let scheduler_tx_clone = scheduler_tx.clone();
     tokio::spawn(async move {
         while config_rx.recv().await.is_some() {
             match load_config() {
                 Ok(new_config) => {
                     info!("config changed, reloading scheduler");
+                    discover.store(new_config.discover_scripts, Ordering::Relaxed);
+                    watcher.update(&new_config);
+                    file_watches.update(&new_config);
                     let _ = scheduler_tx_clone
-                        .send(SchedulerCommand::Reload(new_config))
+                        .send(SchedulerCommand::Reload(Box::new(new_config)))
                         .await;
                 }
                 Err(e) => {
                     tracing::error!("config reload failed: {}", e);
+                    let _ = scheduler_tx_clone
+                        .send(SchedulerCommand::ReloadFailed(format!("{:#}", e)))
+                        .await;
                 }
             }
         }
     });
 
+    let webhook = async {
+        match &config.api.webhook_listen {
+            Some(addr) => webhook::serve(addr, scheduler_tx.clone(), options.read_only).await,
+            None => Ok(()),
+        }
+    };
+
+    let metrics_queue_depths = queue_depths.clone();
+    let metrics_server = async {
+        match &config.api.metrics_listen {
+            Some(addr) => metrics::serve(addr, metrics, metrics_queue_depths).await,
+            None => Ok(()),
+        }
+    };
+
     // run gRPC server (blocking)
-    server::serve(Arc::clone(&db), scheduler_tx).await?;
+    let grpc = server::serve(
+        Arc::clone(&db),
+        scheduler_tx.clone(),
+        events,
+        queue_depths,
+        missed,
+        options.read_only,
+        &config.api,
+    );
+    let servers = async { tokio::try_join!(grpc, webhook, metrics_server) };
+    tokio::select! {
+        result = servers => {
+            result?;
+        }
+        signal = shutdown::wait_for_signal() => {
+            // the servers are dropped here, so no more triggers come in
+            info!(signal, "shutting down");
+            let _ = std::fs::remove_file(&paths::get().socket);
+            let timeout = load_config().map_or(config.shutdown.timeout, |c| c.shutdown.timeout);
+            shutdown.drain(&db, timeout).await;
+            info!("runner stopped");
+        }
+    }
 
     Ok(())
 }
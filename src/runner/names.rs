@@ -0,0 +1,120 @@
+use crate::common::types::WorkflowConfig;
+
+/// Outcome of looking up the workflow a trigger names.
+#[derive(Debug)]
+pub enum NameMatch<'a> {
+    Found(&'a WorkflowConfig),
+    /// Several workflows fit equally well; their names, in config order.
+    Ambiguous(Vec<&'a str>),
+    NotFound,
+}
+
+/// Find the workflow `query` means, trying in turn: an exact name, an exact alias, a prefix of
+/// a name or alias, and the letters of a name in order (`bkh` for `backup-home`). The last two
+/// ignore ASCII case. The first step with any match decides; more than one workflow there is
+/// ambiguous rather than a guess.
+pub fn resolve<'a>(workflows: &[&'a WorkflowConfig], query: &str) -> NameMatch<'a> {
+    if query.is_empty() {
+        return NameMatch::NotFound;
+    }
+    let lower = query.to_ascii_lowercase();
+    let steps: [&dyn Fn(&WorkflowConfig) -> bool; 4] = [
+        &|wf| wf.name == query,
+        &|wf| wf.aliases.iter().any(|a| a == query),
+        &|wf| {
+            std::iter::once(&wf.name)
+                .chain(&wf.aliases)
+                .any(|n| n.to_ascii_lowercase().starts_with(&lower))
+        },
+        &|wf| is_subsequence(&lower, &wf.name.to_ascii_lowercase()),
+    ];
+    for step in steps {
+        let matches: Vec<&'a WorkflowConfig> =
+            workflows.iter().copied().filter(|wf| step(wf)).collect();
+        match matches.as_slice() {
+            [] => continue,
+            [wf] => return NameMatch::Found(wf),
+            _ => return NameMatch::Ambiguous(matches.iter().map(|wf| wf.name.as_str()).collect()),
+        }
+    }
+    NameMatch::NotFound
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut rest = haystack.chars();
+    needle.chars().all(|c| rest.any(|h| h == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow(name: &str, aliases: &[&str]) -> WorkflowConfig {
+        serde_json::from_value(serde_json::json!({ "name": name, "aliases": aliases }))
+            .expect("workflow")
+    }
+
+    fn names(workflows: &[WorkflowConfig], query: &str) -> Result<String, Vec<String>> {
+        let refs: Vec<&WorkflowConfig> = workflows.iter().collect();
+        match resolve(&refs, query) {
+            NameMatch::Found(wf) => Ok(wf.name.clone()),
+            NameMatch::Ambiguous(names) => Err(names.into_iter().map(str::to_owned).collect()),
+            NameMatch::NotFound => Err(Vec::new()),
+        }
+    }
+
+    fn config() -> Vec<WorkflowConfig> {
+        vec![
+            workflow("backup-home", &["bh"]),
+            workflow("backup-photos", &[]),
+            workflow("build", &["backup"]),
+            workflow("sync", &[]),
+        ]
+    }
+
+    #[test]
+    fn exact_name_beats_prefix() {
+        let workflows = vec![workflow("sync", &[]), workflow("sync-mail", &[])];
+        assert_eq!(names(&workflows, "sync"), Ok("sync".into()));
+    }
+
+    #[test]
+    fn exact_alias_beats_prefix() {
+        assert_eq!(names(&config(), "backup"), Ok("build".into()));
+    }
+
+    #[test]
+    fn ambiguous_prefix_lists_config_order() {
+        let expected = vec!["backup-home".to_owned(), "backup-photos".into(), "build".into()];
+        assert_eq!(names(&config(), "ba"), Err(expected));
+    }
+
+    #[test]
+    fn prefix_ignores_case_and_counts_aliases() {
+        assert_eq!(names(&config(), "BACKUP-P"), Ok("backup-photos".into()));
+        assert_eq!(names(&config(), "bh"), Ok("backup-home".into()));
+    }
+
+    #[test]
+    fn subsequence_after_no_prefix() {
+        assert_eq!(names(&config(), "bkhm"), Ok("backup-home".into()));
+    }
+
+    #[test]
+    fn ambiguous_subsequence() {
+        assert_eq!(
+            names(&config(), "bkp"),
+            Err(vec!["backup-home".to_owned(), "backup-photos".into()])
+        );
+    }
+
+    #[test]
+    fn subsequence_keeps_order() {
+        assert_eq!(names(&config(), "cnys"), Err(Vec::new()));
+    }
+
+    #[test]
+    fn empty_query_matches_nothing() {
+        assert_eq!(names(&config(), ""), Err(Vec::new()));
+    }
+}
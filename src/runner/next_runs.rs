@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+use crate::common::types::{AppConfig, ScheduleOverride, WorkflowConfig};
+use crate::runner::trigger::{self, Trigger, effective_triggers, next_fire, triggers_for};
+
+/// A workflow's schedule as `ListWorkflows` reports it.
+#[derive(Debug, Clone)]
+pub struct ScheduleInfo {
+    /// See [`trigger::describe`].
+    pub label: String,
+    /// See [`trigger::explain`].
+    pub description: String,
+    pub next_run: Option<DateTime<Utc>>,
+    /// The schedule parses; when it doesn't, `description` says why.
+    pub valid: bool,
+    /// Runs only when triggered, like an ad-hoc workflow; its lack of a next run is no news.
+    pub manual: bool,
+    /// A temporary override in force; `label` and `next_run` already account for it.
+    pub schedule_override: Option<ScheduleOverride>,
+}
+
+/// Schedules parsed once per workflow instead of on every call, with their next fire time
+/// kept until it passes. Clones share the cache.
+#[derive(Debug, Clone, Default)]
+pub struct NextRuns(Arc<Mutex<HashMap<String, Entry>>>);
+
+#[derive(Debug)]
+struct Entry {
+    /// The schedule fields the entry was worked out from; a reload changing them replaces it.
+    key: String,
+    /// `None` when the schedule doesn't parse.
+    triggers: Option<Vec<Box<dyn Trigger>>>,
+    info: ScheduleInfo,
+}
+
+impl NextRuns {
+    /// Work out every workflow's schedule ahead of the first call, and forget removed ones.
+    pub fn warm(&self, config: &AppConfig) {
+        for wf in &config.workflows {
+            self.get(wf);
+        }
+        let names: Vec<&str> = config.workflows.iter().map(|wf| wf.name.as_str()).collect();
+        self.0.lock().unwrap().retain(|name, _| names.contains(&name.as_str()));
+    }
+
+    pub fn get(&self, wf: &WorkflowConfig) -> ScheduleInfo {
+        let now = Utc::now();
+        let key = schedule_key(wf);
+        let mut entries = self.0.lock().unwrap();
+        let entry = entries.entry(wf.name.clone()).or_insert_with(|| Entry::new(wf, &key, now));
+        if entry.key != key {
+            *entry = Entry::new(wf, &key, now);
+        } else if entry.info.next_run.is_some_and(|t| t <= now) {
+            entry.info.next_run = entry.triggers.as_ref().and_then(|t| next_fire(t, now));
+        }
+        entry.info.clone()
+    }
+
+    /// The first time `wf`'s schedule fires after `after`, from the parsed schedule.
+    pub fn next_after(&self, wf: &WorkflowConfig, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.get(wf);
+        let entries = self.0.lock().unwrap();
+        let triggers = entries.get(&wf.name)?.triggers.as_ref()?;
+        next_fire(triggers, after)
+    }
+
+    /// `wf`'s schedule while `schedule_override` is in force: the next run at the override's
+    /// interval, or for a snooze and once the override ends, the schedule's first after it.
+    pub fn with_override(
+        &self,
+        wf: &WorkflowConfig,
+        schedule_override: Option<&ScheduleOverride>,
+    ) -> ScheduleInfo {
+        let info = self.get(wf);
+        let Some(o) = schedule_override else {
+            return info;
+        };
+        let during = effective_triggers(wf, Some(o))
+            .ok()
+            .filter(|_| o.every.is_some())
+            .and_then(|t| next_fire(&t, Utc::now()))
+            .filter(|t| *t < o.until);
+        ScheduleInfo {
+            label: o.describe(),
+            description: format!("{}, then {}", o.describe(), info.description),
+            // a run due just as the override ends goes ahead
+            next_run: during
+                .or_else(|| self.next_after(wf, o.until - chrono::Duration::milliseconds(1))),
+            schedule_override: Some(o.clone()),
+            ..info
+        }
+    }
+}
+
+impl Entry {
+    fn new(wf: &WorkflowConfig, key: &str, now: DateTime<Utc>) -> Self {
+        let (triggers, info) = match triggers_for(wf) {
+            Ok(triggers) => {
+                let info = ScheduleInfo {
+                    label: trigger::describe_all(&triggers),
+                    description: trigger::explain_all(&triggers),
+                    next_run: next_fire(&triggers, now),
+                    valid: true,
+                    manual: false,
+                    schedule_override: None,
+                };
+                (Some(triggers), info)
+            }
+            Err(e) => {
+                let info = ScheduleInfo {
+                    label: wf.cron.clone().unwrap_or_default(),
+                    description: format!("invalid schedule: {}", e),
+                    next_run: None,
+                    valid: false,
+                    manual: false,
+                    schedule_override: None,
+                };
+                (None, info)
+            }
+        };
+        Self {
+            key: key.to_string(),
+            triggers,
+            info,
+        }
+    }
+}
+
+/// Everything [`triggers_for`] reads.
+fn schedule_key(wf: &WorkflowConfig) -> String {
+    format!(
+        "{:?}|{:?}|{:?}|{}|{:?}|{:?}",
+        wf.cron,
+        wf.every,
+        wf.at,
+        wf.on_start,
+        wf.watch.as_ref().map(|w| &w.paths),
+        wf.timezone
+    )
+}
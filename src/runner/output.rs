@@ -0,0 +1,120 @@
+use chrono::{DateTime, Local};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+/// How `log_timestamps` stamps a line: to the second, the date is in the log's header.
+const TIMESTAMP_FORMAT: &str = "%H:%M:%S";
+
+/// Which pipe a line of script output came in on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    /// What the stream's lines start with in the log.
+    pub fn tag(&self) -> &'static [u8] {
+        match self {
+            Stream::Stdout => b"O|",
+            Stream::Stderr => b"E|",
+        }
+    }
+}
+
+/// A log line without its `O|` / `E|` tag, nor the `[14:02:11] ` after it with
+/// `log_timestamps`. The runner's own lines, and those of logs written before output was
+/// tagged, come back as they are.
+pub fn untagged(line: &str) -> &str {
+    match line.strip_prefix("O|").or_else(|| line.strip_prefix("E|")) {
+        Some(text) => &text[timestamp_len(text)..],
+        None => line,
+    }
+}
+
+/// The length of the `[14:02:11] ` that `text`, a tagged line past its tag, starts with, or 0.
+pub fn timestamp_len(text: &str) -> usize {
+    let stamped = text.as_bytes().get(..11).is_some_and(|b| {
+        let digits = [1, 2, 4, 5, 7, 8].iter().all(|&i| b[i].is_ascii_digit());
+        b[0] == b'[' && b[3] == b':' && b[6] == b':' && b[9] == b']' && b[10] == b' ' && digits
+    });
+    if stamped { 11 } else { 0 }
+}
+
+/// The lines of a script's `stdout` and `stderr` in the order they arrive, each with when it
+/// did. A last line without a newline is kept as it is.
+pub struct Interleaved<O, E> {
+    stdout: BufReader<O>,
+    stderr: BufReader<E>,
+    out_line: Vec<u8>,
+    err_line: Vec<u8>,
+    out_open: bool,
+    err_open: bool,
+}
+
+impl<O: AsyncRead + Unpin, E: AsyncRead + Unpin> Interleaved<O, E> {
+    pub fn new(stdout: O, stderr: E) -> Self {
+        Self {
+            stdout: BufReader::new(stdout),
+            stderr: BufReader::new(stderr),
+            out_line: Vec::new(),
+            err_line: Vec::new(),
+            out_open: true,
+            err_open: true,
+        }
+    }
+
+    /// The next line, or `None` once both pipes have closed. It is cancel-safe: what was read
+    /// of a line is kept for the next call.
+    pub async fn next_line(
+        &mut self,
+    ) -> std::io::Result<Option<(Stream, DateTime<Local>, Vec<u8>)>> {
+        let Self { stdout, stderr, out_line, err_line, out_open, err_open } = self;
+        while *out_open || *err_open {
+            // read_until keeps what it read of a line in the buffer when the other branch
+            // wins, and carries on from there the next time round; its count is of that call's
+            // bytes, so a last line without a newline can be left in the buffer when it sees
+            // the end. When both pipes have a line waiting, which came first is unknown;
+            // stdout's goes first
+            tokio::select! {
+                biased;
+                read = stdout.read_until(b'\n', out_line), if *out_open => {
+                    *out_open = read? > 0 && out_line.ends_with(b"\n");
+                    if !out_line.is_empty() {
+                        return Ok(Some((Stream::Stdout, Local::now(), std::mem::take(out_line))));
+                    }
+                }
+                read = stderr.read_until(b'\n', err_line), if *err_open => {
+                    *err_open = read? > 0 && err_line.ends_with(b"\n");
+                    if !err_line.is_empty() {
+                        return Ok(Some((Stream::Stderr, Local::now(), std::mem::take(err_line))));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// How `log_timestamps` stamps a line that arrived `at`.
+pub fn stamp(at: &DateTime<Local>) -> String {
+    at.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Every line of `text` with `stream`'s tag in front, then `stamp` in brackets if there is
+/// one, each ending in a newline.
+pub fn tag(stream: Stream, stamp: Option<&str>, text: &[u8]) -> Vec<u8> {
+    let stamp = stamp.map(|s| format!("[{}] ", s));
+    let per_line = stream.tag().len() + stamp.as_ref().map_or(0, |s| s.len());
+    let mut tagged = Vec::with_capacity(text.len() + text.len() / 16 * per_line);
+    for line in text.split_inclusive(|b| *b == b'\n') {
+        tagged.extend_from_slice(stream.tag());
+        if let Some(stamp) = &stamp {
+            tagged.extend_from_slice(stamp.as_bytes());
+        }
+        tagged.extend_from_slice(line);
+        if !line.ends_with(b"\n") {
+            tagged.push(b'\n');
+        }
+    }
+    tagged
+}
@@ -0,0 +1,130 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+use crate::common::config::plugins_dir;
+use crate::common::types::{Execution, PluginRef, Priority, TriggerSource, WorkflowConfig};
+use crate::runner::completion_webhook;
+
+/// Version of the request documents; a plugin can refuse versions it doesn't know.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Give up on a plugin that has not answered after this long.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The executable `name` in the plugins directory.
+fn plugin_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || name.contains('/') {
+        bail!("plugin {:?}: plugins are named by their file in the plugins directory", name);
+    }
+    let path = plugins_dir().join(name);
+    let executable = std::fs::metadata(&path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false);
+    if !executable {
+        bail!("plugin {}: no executable {}", name, path.display());
+    }
+    Ok(path)
+}
+
+/// Run `plugin` as `<plugin> <kind>` with `request`, plus the protocol fields, as JSON on
+/// stdin. Returns the JSON document it printed, or `null` for no output. A nonzero exit is an
+/// error carrying the last line of stderr.
+async fn call(plugin: &PluginRef, kind: &str, mut request: Value) -> Result<Value> {
+    let path = plugin_path(&plugin.plugin)?;
+    request["protocol"] = json!(PROTOCOL_VERSION);
+    request["kind"] = json!(kind);
+    request["config"] = serde_json::to_value(&plugin.config)
+        .with_context(|| format!("plugin {}: config", plugin.plugin))?;
+
+    let mut child = Command::new(&path)
+        .arg(kind)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("plugin {}: failed to start", plugin.plugin))?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = request.to_string();
+    let output = tokio::time::timeout(TIMEOUT, async {
+        // a plugin that exits without reading its input is fine
+        let _ = stdin.write_all(input.as_bytes()).await;
+        drop(stdin);
+        child.wait_with_output().await
+    })
+    .await
+    .with_context(|| format!("plugin {}: timed out", plugin.plugin))??;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("");
+        bail!("plugin {}: {} {}", plugin.plugin, output.status, last.trim());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        return Ok(Value::Null);
+    }
+    serde_json::from_str(&stdout)
+        .with_context(|| format!("plugin {}: reply is not JSON", plugin.plugin))
+}
+
+/// Send a notification through each of `plugins` in the background. `execution` is the run it
+/// is about, if any; the digest has none.
+pub fn notify(
+    plugins: &[PluginRef],
+    title: &str,
+    body: &str,
+    priority: Priority,
+    execution: Option<&Execution>,
+) {
+    for plugin in plugins {
+        let plugin = plugin.clone();
+        let request = json!({
+            "title": title,
+            "body": body,
+            "priority": priority.as_str(),
+            "execution": execution.map(completion_webhook::payload),
+        });
+        tokio::spawn(async move {
+            match call(&plugin, "notify", request).await {
+                Ok(_) => info!(plugin = %plugin.plugin, "notification sent through plugin"),
+                Err(e) => warn!(error = %e, "notification plugin failed"),
+            }
+        });
+    }
+}
+
+/// Ask a condition plugin whether `wf` may run now. The reply is `{"run": false, "reason":
+/// "..."}` to skip the run; `run` defaults to true, so an empty reply lets it go ahead.
+pub async fn check(
+    plugin: &PluginRef,
+    wf: &WorkflowConfig,
+    source: TriggerSource,
+    last: Option<&Execution>,
+    failure_streak: usize,
+) -> Result<(bool, Option<String>)> {
+    let request = json!({
+        "workflow": wf.name,
+        "trigger": source.as_str(),
+        "last_execution": last.map(completion_webhook::payload),
+        "failure_streak": failure_streak,
+    });
+    let reply = call(plugin, "condition", request).await?;
+    let run = match reply.get("run") {
+        None | Some(Value::Null) => true,
+        Some(Value::Bool(run)) => *run,
+        Some(other) => {
+            bail!("plugin {}: `run` must be true or false, not {}", plugin.plugin, other)
+        }
+    };
+    let reason = reply.get("reason").and_then(Value::as_str).map(str::to_string);
+    Ok((run, reason))
+}
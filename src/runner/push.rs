@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use regex::Regex;
+use serde_json::{Value, json};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::common::duration::format_duration;
+use crate::common::types::{
+    Execution, ExecutionStatus, NotificationBackend, Priority, SecretsConfig,
+};
+use crate::runner::{completion_webhook, http, log_compress, secrets};
+
+/// Give up on a push service that has not answered after this long.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+const PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+
+/// A log excerpt longer than this is cut at the front; Matrix refuses events over 64 KiB.
+const MAX_EXCERPT: usize = 8 * 1024;
+
+/// How much of the end of a log is read for an excerpt; more than [`MAX_EXCERPT`], since
+/// escape sequences are taken out.
+const EXCERPT_READ: usize = 4 * MAX_EXCERPT;
+
+const DISCORD_GREEN: u32 = 0x2ecc71;
+const DISCORD_RED: u32 = 0xe74c3c;
+/// For notifications about no single run, such as the digest.
+const DISCORD_BLURPLE: u32 = 0x5865f2;
+/// Discord refuses embeds with a longer description.
+const DISCORD_DESCRIPTION_MAX: usize = 4096;
+
+/// Send a notification through each of `backends` in the background. Failures are logged and
+/// not retried. `execution` is the run it is about, if any; the digest has none.
+pub fn send(
+    backends: &[NotificationBackend],
+    title: &str,
+    body: &str,
+    priority: Priority,
+    execution: Option<&Execution>,
+) {
+    if backends.is_empty() {
+        return;
+    }
+    let current = secrets::current_secrets();
+    for backend in backends {
+        // one that names no secret doesn't need the backend
+        let secrets = match &current {
+            Ok(secrets) => secrets.clone(),
+            Err(_) if !secrets::referenced(backend) => SecretsConfig::default(),
+            Err(_) => {
+                warn!(backend = backend.name(), "push notification not sent: no secrets backend");
+                continue;
+            }
+        };
+        let backend = backend.clone();
+        let (title, body) = (title.to_string(), body.to_string());
+        let execution = execution.cloned();
+        tokio::spawn(async move {
+            let execution = execution.as_ref();
+            match push(&backend, &secrets, &title, &body, priority, execution).await {
+                Ok(()) => info!(backend = backend.name(), "notification pushed"),
+                Err(e) => warn!(backend = backend.name(), error = %e, "push notification failed"),
+            }
+        });
+    }
+}
+
+async fn push(
+    backend: &NotificationBackend,
+    secrets: &SecretsConfig,
+    title: &str,
+    body: &str,
+    priority: Priority,
+    execution: Option<&Execution>,
+) -> Result<()> {
+    // both services reject an empty message, which failed runs have by default
+    let text = if body.is_empty() { title } else { body };
+    let code = match backend {
+        NotificationBackend::Gotify { url, token } => {
+            let token = secrets::value(token, "token", secrets).await?;
+            let headers = BTreeMap::from([("X-Gotify-Key".to_string(), token)]);
+            let priority = match priority {
+                Priority::Low => 2,
+                Priority::Normal => 5,
+                Priority::High => 8,
+                Priority::Urgent => 10,
+            };
+            let message = json!({ "title": title, "message": text, "priority": priority });
+            let url = format!("{}/message", url.trim_end_matches('/'));
+            http::send("POST", &url, &headers, &message.to_string(), TIMEOUT).await?
+        }
+        NotificationBackend::Pushover { token, user, device } => {
+            let mut message = json!({
+                "token": secrets::value(token, "token", secrets).await?,
+                "user": secrets::value(user, "user", secrets).await?,
+                "title": title,
+                "message": text,
+            });
+            match priority {
+                Priority::Low => message["priority"] = json!(-1),
+                Priority::Normal => {}
+                Priority::High => message["priority"] = json!(1),
+                // emergency: repeated every minute for an hour until acknowledged
+                Priority::Urgent => {
+                    message["priority"] = json!(2);
+                    message["retry"] = json!(60);
+                    message["expire"] = json!(3600);
+                }
+            }
+            if let Some(device) = device {
+                message["device"] = json!(device);
+            }
+            http::send("POST", PUSHOVER_URL, &BTreeMap::new(), &message.to_string(), TIMEOUT)
+                .await?
+        }
+        NotificationBackend::Matrix {
+            homeserver,
+            access_token,
+            room_id,
+            log_lines,
+        } => {
+            let token = secrets::value(access_token, "access_token", secrets).await?;
+            let authorization = format!("Bearer {}", token);
+            let headers = BTreeMap::from([("Authorization".to_string(), authorization)]);
+            let excerpt = match execution {
+                Some(exec) if exec.status.is_failure() && *log_lines > 0 => {
+                    log_excerpt(&exec.log_path, *log_lines).await
+                }
+                _ => None,
+            };
+            let message = matrix_message(title, body, priority, excerpt.as_deref());
+            let url = format!(
+                "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+                homeserver.trim_end_matches('/'),
+                encode_path_segment(room_id),
+                Uuid::new_v4()
+            );
+            http::send("PUT", &url, &headers, &message.to_string(), TIMEOUT).await?
+        }
+        NotificationBackend::Discord {
+            webhook_url,
+            username,
+        } => {
+            // the URL holds the webhook's token
+            let url = secrets::value(webhook_url, "webhook_url", secrets).await?;
+            let mut message = json!({ "embeds": [discord_embed(title, body, execution)] });
+            if priority == Priority::Urgent {
+                message["content"] = json!("@here");
+                message["allowed_mentions"] = json!({ "parse": ["everyone"] });
+            }
+            if let Some(username) = username {
+                message["username"] = json!(username);
+            }
+            http::send("POST", &url, &BTreeMap::new(), &message.to_string(), TIMEOUT).await?
+        }
+        NotificationBackend::Webhook {
+            url,
+            headers,
+            body: template,
+            secret,
+        } => {
+            let message = webhook_body(template.as_deref(), title, body, priority, execution);
+            return completion_webhook::post(url, headers, secret.as_deref(), secrets, &message)
+                .await;
+        }
+    };
+    if !(200..300).contains(&code) {
+        bail!("HTTP {}", code);
+    }
+    Ok(())
+}
+
+/// The template filled in for this notification, or a JSON object of it and the execution.
+fn webhook_body(
+    template: Option<&str>,
+    title: &str,
+    body: &str,
+    priority: Priority,
+    execution: Option<&Execution>,
+) -> String {
+    let priority = priority.as_str().to_string();
+    let Some(template) = template else {
+        let execution = execution.map_or(Value::Null, completion_webhook::payload);
+        let message = json!({
+            "title": title,
+            "body": body,
+            "priority": priority,
+            "execution": execution,
+        });
+        return message.to_string();
+    };
+    let mut fields = vec![
+        ("title", title.to_string()),
+        ("body", body.to_string()),
+        ("priority", priority),
+    ];
+    let result = match execution {
+        Some(exec) => {
+            fields.extend(completion_webhook::fields(exec));
+            completion_webhook::result(exec)
+        }
+        None => Value::Null,
+    };
+    completion_webhook::render(template, &fields, &result)
+}
+
+/// An `m.text` event with the title in bold, the body, and the log excerpt as a code block.
+/// An urgent notification mentions `@room`, which notifies every member.
+fn matrix_message(
+    title: &str,
+    body: &str,
+    priority: Priority,
+    excerpt: Option<&str>,
+) -> serde_json::Value {
+    let mention = if priority == Priority::Urgent { "@room " } else { "" };
+    let mut plain = format!("{}{}", mention, title);
+    let mut html = format!("{}<strong>{}</strong>", mention, escape_html(title));
+    if !body.is_empty() {
+        plain.push_str(&format!("\n{}", body));
+        html.push_str(&format!("<br>{}", escape_html(body).replace('\n', "<br>")));
+    }
+    if let Some(excerpt) = excerpt {
+        plain.push_str(&format!("\n\n{}", excerpt));
+        html.push_str(&format!("<pre><code>{}</code></pre>", escape_html(excerpt)));
+    }
+    json!({
+        "msgtype": "m.text",
+        "body": plain,
+        "format": "org.matrix.custom.html",
+        "formatted_body": html,
+    })
+}
+
+/// An embed colored by the run's outcome, with its duration, exit code and trigger as fields.
+fn discord_embed(title: &str, body: &str, execution: Option<&Execution>) -> serde_json::Value {
+    let color = match execution.map(|e| &e.status) {
+        Some(ExecutionStatus::Success) => DISCORD_GREEN,
+        Some(status) if status.is_failure() => DISCORD_RED,
+        _ => DISCORD_BLURPLE,
+    };
+    let mut embed = json!({ "title": title, "color": color });
+    if !body.is_empty() {
+        embed["description"] = json!(truncate(body, DISCORD_DESCRIPTION_MAX));
+    }
+    let Some(exec) = execution else {
+        return embed;
+    };
+    let mut fields = Vec::new();
+    if let Some(finished) = exec.finished_at {
+        let duration = (finished - exec.started_at).to_std().unwrap_or_default();
+        let duration = format_duration(&duration);
+        fields.push(json!({ "name": "Duration", "value": duration, "inline": true }));
+        embed["timestamp"] = json!(finished.to_rfc3339());
+    }
+    if let Some(code) = exec.exit_code {
+        fields.push(json!({ "name": "Exit code", "value": code.to_string(), "inline": true }));
+    }
+    if let Some(trigger) = exec.trigger {
+        fields.push(json!({ "name": "Trigger", "value": trigger.as_str(), "inline": true }));
+    }
+    embed["fields"] = json!(fields);
+    embed
+}
+
+/// At most `max` characters of `text`, ending in an ellipsis when cut.
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max.saturating_sub(1)) {
+        Some((end, _)) if text.chars().count() > max => format!("{}…", &text[..end]),
+        _ => text.to_string(),
+    }
+}
+
+/// The last `lines` lines of the log at `path`, without terminal escape sequences. The log is
+/// already redacted; only its end is read.
+async fn log_excerpt(path: &str, lines: usize) -> Option<String> {
+    let log = match log_compress::read_tail(Path::new(path), EXCERPT_READ).await {
+        Ok(log) => log,
+        Err(e) => {
+            warn!(path, error = %e, "failed to read log for notification");
+            return None;
+        }
+    };
+    let log = String::from_utf8_lossy(&log);
+    let escapes = Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]").expect("valid regex");
+    let log = escapes.replace_all(&log, "");
+    let all: Vec<&str> = log.trim_end().lines().collect();
+    let mut excerpt = all[all.len().saturating_sub(lines)..].join("\n");
+    if excerpt.len() > MAX_EXCERPT {
+        let mut start = excerpt.len() - MAX_EXCERPT;
+        while !excerpt.is_char_boundary(start) {
+            start += 1;
+        }
+        excerpt = format!("...{}", &excerpt[start..]);
+    }
+    Some(excerpt).filter(|e| !e.is_empty())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// `text` percent-encoded for a URL path segment; room ids hold `!` and `:`, aliases `#`.
+fn encode_path_segment(text: &str) -> String {
+    let mut out = String::new();
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
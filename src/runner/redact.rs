@@ -0,0 +1,138 @@
+use std::borrow::Cow;
+
+use regex::bytes::{NoExpand, Regex};
+
+/// What replaces a redacted match in the log.
+const REDACTED: &[u8] = b"***";
+
+/// Blanks out a workflow's `redact` patterns and its secret values in script output.
+pub struct Redactor {
+    patterns: Vec<Regex>,
+    /// The most lines one match can cover.
+    span: usize,
+}
+
+impl Redactor {
+    /// `secrets` are matched literally, `patterns` as regexes. Patterns that don't compile are
+    /// left out and described in the returned messages.
+    pub fn new(patterns: &[String], secrets: &[String]) -> (Self, Vec<String>) {
+        let mut problems = Vec::new();
+        // a pattern matching a newline has to say so; `.` doesn't without `(?s)`
+        let span = patterns
+            .iter()
+            .map(|p| p.matches("\\n").count() + p.matches('\n').count())
+            .chain(secrets.iter().map(|s| s.trim_end_matches('\n').matches('\n').count()))
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let mut compiled: Vec<Regex> = patterns
+            .iter()
+            .filter_map(|p| match Regex::new(p) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    problems.push(format!("ignoring redact pattern {:?}: {}", p, e));
+                    None
+                }
+            })
+            .collect();
+        compiled.extend(
+            secrets
+                .iter()
+                .map(|s| Regex::new(&regex::escape(s)).expect("escaped pattern")),
+        );
+        (Self { patterns: compiled, span }, problems)
+    }
+
+    /// How many lines output has to be held back for a match that starts on the first of them
+    /// to be seen whole.
+    pub fn span(&self) -> usize {
+        self.span
+    }
+
+    /// Where `text`, a run of whole lines, can be cut so no match crosses the cut: after its
+    /// first line, or after the last line of a match that starts on it.
+    pub fn cut(&self, text: &[u8]) -> usize {
+        let line_end = |from: usize| {
+            text[from..].iter().position(|b| *b == b'\n').map_or(text.len(), |i| from + i + 1)
+        };
+        let mut cut = line_end(0);
+        loop {
+            let across = self
+                .patterns
+                .iter()
+                .flat_map(|re| re.find_iter(text))
+                .filter(|m| m.start() < cut && m.end() > cut)
+                .map(|m| line_end(m.end() - 1))
+                .max();
+            match across {
+                Some(end) => cut = end,
+                None => return cut,
+            }
+        }
+    }
+
+    /// `output` with every match replaced by `***`.
+    pub fn redact<'a>(&self, output: Cow<'a, [u8]>) -> Cow<'a, [u8]> {
+        let mut output = output;
+        for re in &self.patterns {
+            if re.is_match(&output) {
+                output = Cow::Owned(re.replace_all(&output, NoExpand(REDACTED)).into_owned());
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(patterns: &[&str], secrets: &[&str]) -> Redactor {
+        let own = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let (redactor, problems) = Redactor::new(&own(patterns), &own(secrets));
+        assert!(problems.is_empty(), "{:?}", problems);
+        redactor
+    }
+
+    fn redact(redactor: &Redactor, text: &str) -> String {
+        String::from_utf8(redactor.redact(Cow::Borrowed(text.as_bytes())).into_owned()).unwrap()
+    }
+
+    #[test]
+    fn secret_across_lines_is_redacted_whole() {
+        let r = redactor(&[], &["BEGIN KEY\nabc123\nEND KEY"]);
+        assert_eq!(redact(&r, "x\nBEGIN KEY\nabc123\nEND KEY\ny\n"), "x\n***\ny\n");
+    }
+
+    #[test]
+    fn span_counts_secret_lines() {
+        assert_eq!(redactor(&[], &[]).span(), 1);
+        assert_eq!(redactor(&[], &["one line\n"]).span(), 1);
+        assert_eq!(redactor(&[], &["a\nb\nc"]).span(), 3);
+    }
+
+    #[test]
+    fn span_counts_newlines_in_patterns() {
+        assert_eq!(redactor(&["token=\\S+"], &[]).span(), 1);
+        assert_eq!(redactor(&["BEGIN\\n\\S+\\nEND"], &[]).span(), 3);
+    }
+
+    #[test]
+    fn cut_after_first_line_without_a_match() {
+        let r = redactor(&[], &["a\nb"]);
+        assert_eq!(r.cut(b"x\na\n"), 2);
+        assert_eq!(r.cut(b"no newline"), 10);
+    }
+
+    #[test]
+    fn cut_moves_past_a_match_across_lines() {
+        let r = redactor(&[], &["a\nb"]);
+        assert_eq!(r.cut(b"a\nb\nc\n"), 4);
+    }
+
+    #[test]
+    fn cut_follows_overlapping_matches() {
+        let r = redactor(&["1\\n2", "2\\n3"], &[]);
+        assert_eq!(r.cut(b"1\n2\n3\n4\n"), 6);
+    }
+}
@@ -0,0 +1,255 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Local, Utc};
+
+use crate::common::duration::format_duration;
+use crate::common::storage::Db;
+use crate::common::types::{DurationSample, Execution, WorkflowConfig, WorkflowStats};
+use crate::runner::log_compress;
+
+/// Executions per workflow read when building a report.
+const EXECUTION_LIMIT: usize = 1000;
+
+/// Log lines quoted for each failure.
+const EXCERPT_LINES: usize = 15;
+
+/// Failures listed per workflow; older ones are only counted.
+const MAX_FAILURES_SHOWN: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "" | "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            _ => bail!("unknown report format: {} (expected markdown or html)", s),
+        }
+    }
+}
+
+struct Section {
+    name: String,
+    schedule: String,
+    runs: Vec<Execution>,
+    stats: WorkflowStats,
+    failures: Vec<(Execution, Vec<String>)>,
+}
+
+impl Section {
+    fn durations(&self) -> impl Iterator<Item = i64> + '_ {
+        self.runs
+            .iter()
+            .filter_map(|e| e.finished_at.map(|f| (f - e.started_at).num_seconds()))
+    }
+
+    fn avg_duration(&self) -> Option<i64> {
+        let (sum, n) = self.durations().fold((0, 0), |(s, n), d| (s + d, n + 1));
+        (n > 0).then(|| sum / n)
+    }
+
+    fn max_duration(&self) -> Option<i64> {
+        self.durations().max()
+    }
+}
+
+/// Per-workflow tables and failures with log excerpts for executions started in
+/// `[since, until)`, as one self-contained document.
+pub async fn build_report(
+    db: &Db,
+    workflows: &[&WorkflowConfig],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    format: ReportFormat,
+) -> Result<String> {
+    let mut sections = Vec::new();
+    for wf in workflows {
+        let runs: Vec<Execution> = db
+            .get_executions(&wf.name, EXECUTION_LIMIT)
+            .await?
+            .into_iter()
+            .filter(|e| e.finished_at.is_some() && e.started_at >= since && e.started_at < until)
+            .collect();
+
+        let samples: Vec<DurationSample> = runs
+            .iter()
+            .map(|e| DurationSample {
+                started_at: e.started_at,
+                duration_secs: e.finished_at.map_or(0, |f| (f - e.started_at).num_seconds()),
+                status: e.status.clone(),
+            })
+            .collect();
+
+        let mut failures = Vec::new();
+        for exec in runs.iter().filter(|e| e.status.is_failure()) {
+            if failures.len() == MAX_FAILURES_SHOWN {
+                break;
+            }
+            failures.push((exec.clone(), log_excerpt(&exec.log_path).await));
+        }
+
+        sections.push(Section {
+            name: wf.name.clone(),
+            schedule: crate::runner::trigger::describe(wf),
+            stats: WorkflowStats::from_samples(&samples),
+            runs,
+            failures,
+        });
+    }
+
+    Ok(match format {
+        ReportFormat::Markdown => render_markdown(&sections, since, until),
+        ReportFormat::Html => render_html(&sections, since, until),
+    })
+}
+
+async fn log_excerpt(path: &str) -> Vec<String> {
+    let Ok(bytes) = log_compress::read(Path::new(path)).await else {
+        return vec!["(log file missing)".to_string()];
+    };
+    let text = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(EXCERPT_LINES);
+    lines[start..].iter().map(|l| l.to_string()).collect()
+}
+
+fn local(t: DateTime<Utc>) -> String {
+    t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn secs(secs: Option<i64>) -> String {
+    match secs {
+        None => "-".to_string(),
+        Some(0) => "<1s".to_string(),
+        Some(s) => format_duration(&Duration::from_secs(s.max(0) as u64)),
+    }
+}
+
+fn failure_rate(section: &Section) -> String {
+    match section.stats.total_runs {
+        0 => "-".to_string(),
+        n => format!("{:.0}%", section.stats.failures as f64 * 100.0 / n as f64),
+    }
+}
+
+fn exit_code(exec: &Execution) -> String {
+    exec.exit_code.map_or("-".to_string(), |c| c.to_string())
+}
+
+fn render_markdown(sections: &[Section], since: DateTime<Utc>, until: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Workflow report\n\n{} – {}\n", local(since), local(until));
+
+    let _ = writeln!(out, "| Workflow | Schedule | Runs | Failed | Failure rate | Avg | Max | MTBF |");
+    let _ = writeln!(out, "|---|---|---:|---:|---:|---:|---:|---:|");
+    for s in sections {
+        let _ = writeln!(
+            out,
+            "| {} | `{}` | {} | {} | {} | {} | {} | {} |",
+            s.name,
+            s.schedule,
+            s.stats.total_runs,
+            s.stats.failures,
+            failure_rate(s),
+            secs(s.avg_duration()),
+            secs(s.max_duration()),
+            secs(s.stats.mtbf.map(|d| d.as_secs() as i64)),
+        );
+    }
+
+    for s in sections.iter().filter(|s| !s.failures.is_empty()) {
+        let _ = writeln!(out, "\n## {}\n", s.name);
+        if s.stats.failures as usize > s.failures.len() {
+            let _ = writeln!(
+                out,
+                "Showing the latest {} of {} failures.\n",
+                s.failures.len(),
+                s.stats.failures
+            );
+        }
+        for (exec, excerpt) in &s.failures {
+            let _ = writeln!(
+                out,
+                "### {} (exit {})\n\n```\n{}\n```\n",
+                local(exec.started_at),
+                exit_code(exec),
+                excerpt.join("\n")
+            );
+        }
+    }
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn render_html(sections: &[Section], since: DateTime<Utc>, until: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Workflow report</title>\n\
+         <style>body{{font-family:sans-serif;margin:2em}}table{{border-collapse:collapse}}\
+         td,th{{border:1px solid #ccc;padding:4px 8px}}td.num{{text-align:right}}\
+         .failed{{color:#b00}}pre{{background:#f4f4f4;padding:8px;overflow-x:auto}}</style>\n\
+         </head><body>\n<h1>Workflow report</h1>\n<p>{} – {}</p>",
+        local(since),
+        local(until)
+    );
+
+    out.push_str(
+        "<table>\n<tr><th>Workflow</th><th>Schedule</th><th>Runs</th><th>Failed</th>\
+         <th>Failure rate</th><th>Avg</th><th>Max</th><th>MTBF</th></tr>\n",
+    );
+    for s in sections {
+        let class = if s.stats.failures > 0 { " class=\"failed\"" } else { "" };
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td><code>{}</code></td><td class=\"num\">{}</td>\
+             <td class=\"num\"><span{}>{}</span></td><td class=\"num\">{}</td>\
+             <td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            escape_html(&s.name),
+            escape_html(&s.schedule),
+            s.stats.total_runs,
+            class,
+            s.stats.failures,
+            failure_rate(s),
+            secs(s.avg_duration()),
+            secs(s.max_duration()),
+            secs(s.stats.mtbf.map(|d| d.as_secs() as i64)),
+        );
+    }
+    out.push_str("</table>\n");
+
+    for s in sections.iter().filter(|s| !s.failures.is_empty()) {
+        let _ = writeln!(out, "<h2>{}</h2>", escape_html(&s.name));
+        if s.stats.failures as usize > s.failures.len() {
+            let _ = writeln!(
+                out,
+                "<p>Showing the latest {} of {} failures.</p>",
+                s.failures.len(),
+                s.stats.failures
+            );
+        }
+        for (exec, excerpt) in &s.failures {
+            let _ = writeln!(
+                out,
+                "<h3 class=\"failed\">{} (exit {})</h3>\n<pre>{}</pre>",
+                local(exec.started_at),
+                exit_code(exec),
+                escape_html(&excerpt.join("\n"))
+            );
+        }
+    }
+    out.push_str("</body></html>\n");
+    out
+}
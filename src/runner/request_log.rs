@@ -0,0 +1,87 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tonic::Code;
+use tonic::codegen::http;
+use tower::{Layer, Service};
+use tracing::{debug, warn};
+
+/// Logs every RPC with its duration and gRPC outcome at debug level, and warns about calls
+/// slower than `threshold`.
+#[derive(Debug, Clone)]
+pub struct RequestLogLayer {
+    threshold: Duration,
+}
+
+impl RequestLogLayer {
+    pub fn new(threshold: Duration) -> Self {
+        Self { threshold }
+    }
+}
+
+impl<S> Layer<S> for RequestLogLayer {
+    type Service = RequestLog<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestLog {
+            inner,
+            threshold: self.threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestLog<S> {
+    inner: S,
+    threshold: Duration,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for RequestLog<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: std::fmt::Display,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let method = req.uri().path().to_string();
+        let threshold = self.threshold;
+        let started = Instant::now();
+        let fut = self.inner.call(req);
+
+        Box::pin(async move {
+            let result = fut.await;
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+
+            // errors are sent as "trailers-only" responses, so a grpc-status header means failure;
+            // successful calls carry their status in the trailers
+            let outcome = match &result {
+                Ok(resp) => {
+                    let code = resp
+                        .headers()
+                        .get("grpc-status")
+                        .map(|v| Code::from_bytes(v.as_bytes()))
+                        .unwrap_or(Code::Ok);
+                    format!("{:?}", code)
+                }
+                Err(e) => format!("transport error: {}", e),
+            };
+
+            if started.elapsed() > threshold {
+                warn!(method = %method, elapsed_ms, outcome = %outcome, "slow RPC");
+            } else {
+                debug!(method = %method, elapsed_ms, outcome = %outcome, "RPC");
+            }
+            result
+        })
+    }
+}
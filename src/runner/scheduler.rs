@@ -1,25 +1,54 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
-use chrono::{Local, Utc};
-use cron::Schedule;
-use std::str::FromStr;
-use tokio::sync::{Mutex, mpsc};
+use chrono::{DateTime, Local, Utc};
+use rand::Rng;
+use tokio::sync::{Mutex, mpsc, oneshot};
 use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
-use crate::common::types::{AppConfig, WorkflowConfig};
-use crate::runner::executor::execute_workflow;
+use crate::common::duration::format_duration;
+use crate::common::storage::Db;
+use crate::common::types::{
+    AppConfig, NotificationConfig, ScheduleOverride, SecretsConfig, TriggerSource, WorkflowConfig,
+};
+use crate::runner::changelog;
+use crate::runner::config_diff::ConfigDiff;
+use crate::runner::events::{DecisionKind, Events};
+use crate::runner::executor::{RunRequest, execute_workflow};
+use crate::runner::gate;
+use crate::runner::health::{self, SchedulerHealth};
+use crate::runner::idle;
+use crate::runner::metrics::Metrics;
+use crate::runner::shutdown::Shutdown;
+use crate::runner::trigger::{
+    Trigger, effective_triggers, last_fire_between, next_fire, triggers_for,
+};
 
 #[derive(Debug)]
 pub enum SchedulerCommand {
-    Reload(AppConfig),
-    Trigger(String), // workflow name
+    Reload(Box<AppConfig>),
+    Trigger(String, RunRequest), // workflow name
+    /// Run a workflow that isn't in the config, registered by `RunAdhoc`. The sender, if any,
+    /// is told once the workflow stops running with nothing queued, whether its runs went
+    /// ahead, were skipped or failed to start.
+    RunAdhoc(Box<WorkflowConfig>, RunRequest, Option<oneshot::Sender<()>>),
+    /// A watched path of the workflow changed (already debounced).
+    FileChanged(String, PathBuf),
+    /// The changed config failed to load, so the scheduler keeps running the one before.
+    ReloadFailed(String),
+    /// A schedule override was set or cleared, so the next fire time is worked out again.
+    OverridesChanged,
+    Health(oneshot::Sender<SchedulerHealth>),
 }
 
 struct WorkflowState {
     running: bool,
-    queue: VecDeque<()>,
+    queue: VecDeque<RunRequest>,
+    /// Told when the workflow stops running, its queue empty.
+    idle: Vec<oneshot::Sender<()>>,
 }
 
 impl WorkflowState {
@@ -27,52 +56,226 @@ impl WorkflowState {
         Self {
             running: false,
             queue: VecDeque::new(),
+            idle: Vec::new(),
         }
     }
+
+    fn stopped(&mut self) {
+        self.running = false;
+        for idle in self.idle.drain(..) {
+            let _ = idle.send(());
+        }
+    }
+}
+
+/// How many triggers wait behind each workflow's running execution. Clones share the counts,
+/// so the server can report them.
+#[derive(Debug, Clone, Default)]
+pub struct QueueDepths(Arc<std::sync::Mutex<HashMap<String, usize>>>);
+
+impl QueueDepths {
+    pub fn get(&self, workflow: &str) -> usize {
+        self.0.lock().unwrap().get(workflow).copied().unwrap_or(0)
+    }
+
+    /// Every workflow with triggers waiting, sorted by name.
+    pub fn all(&self) -> Vec<(String, usize)> {
+        let depths = self.0.lock().unwrap();
+        let mut depths: Vec<_> = depths.iter().map(|(w, d)| (w.clone(), *d)).collect();
+        depths.sort();
+        depths
+    }
+
+    fn set(&self, workflow: &str, depth: usize) {
+        let mut depths = self.0.lock().unwrap();
+        if depth == 0 {
+            depths.remove(workflow);
+        } else {
+            depths.insert(workflow.to_string(), depth);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Runs {
+    workflows: HashMap<String, WorkflowState>,
+    queue_depths: QueueDepths,
+    /// One mutex per `lock` name; tokio's mutex is fair, so waiters run in trigger order.
+    locks: HashMap<String, Arc<Mutex<()>>>,
+    /// Where executions starting and finishing are announced.
+    events: Events,
+    shutdown: Shutdown,
+    /// The `notifications` and `secrets` settings of the config last loaded, for the runs
+    /// starting.
+    notifications: NotificationConfig,
+    secrets: SecretsConfig,
+}
+
+type SharedRuns = Arc<Mutex<Runs>>;
+
+type Overrides = HashMap<String, ScheduleOverride>;
+
+/// The workflow keys that make up its schedule; a reload changing one resets when the workflow
+/// last fired.
+const SCHEDULE_KEYS: &[&str] = &["cron", "every", "at", "timezone"];
+
+/// Why triggers are skipped once the runner has begun stopping.
+const SHUTTING_DOWN: &str = "the runner is shutting down";
+
+/// A scheduled fire time that passed while the runner was down.
+#[derive(Debug, Clone)]
+pub struct MissedRun {
+    pub workflow: String,
+    /// The latest fire time that was missed.
+    pub missed_at: DateTime<Utc>,
+    pub last_run_at: DateTime<Utc>,
+    /// The workflow has `catch_up` set and is not paused, so it runs on startup.
+    pub caught_up: bool,
 }
 
 pub fn start(
     initial_config: AppConfig,
-    db: Arc<Mutex<rusqlite::Connection>>,
+    db: Db,
+    events: Events,
+    queue_depths: QueueDepths,
+    metrics: Metrics,
+    shutdown: Shutdown,
+    missed: Vec<MissedRun>,
 ) -> (mpsc::Sender<SchedulerCommand>, JoinHandle<()>) {
     let (tx, rx) = mpsc::channel(32);
-    let handle = tokio::spawn(scheduler_loop(initial_config, db, rx));
+    let runs = Runs {
+        events: events.clone(),
+        queue_depths,
+        shutdown,
+        notifications: initial_config.notifications.clone(),
+        secrets: initial_config.secrets.clone(),
+        ..Default::default()
+    };
+    let handle =
+        tokio::spawn(scheduler_loop(initial_config, db, events, runs, metrics, missed, rx));
     (tx, handle)
 }
 
 async fn scheduler_loop(
     initial_config: AppConfig,
-    db: Arc<Mutex<rusqlite::Connection>>,
+    db: Db,
+    events: Events,
+    runs: Runs,
+    metrics: Metrics,
+    missed: Vec<MissedRun>,
     mut rx: mpsc::Receiver<SchedulerCommand>,
 ) {
     let mut config = initial_config;
-    let states: Arc<Mutex<HashMap<String, WorkflowState>>> =
-        Arc::new(Mutex::new(HashMap::new()));
+    let states: SharedRuns = Arc::new(Mutex::new(runs));
+    // when each workflow last fired, so a fire time is never picked up twice
+    let mut last_fired: HashMap<String, DateTime<Utc>> = HashMap::new();
 
+    let mut config_loaded_at = Utc::now();
+    let mut config_error = None;
+
+    run_startup_triggers(&config, &missed, &events, Arc::clone(&db), Arc::clone(&states)).await;
+    health::notify_systemd("READY=1");
+
+    let heartbeat = health::heartbeat_interval();
     loop {
-        let next_wake = compute_next_wake(&config);
-        let sleep_duration = match next_wake {
-            Some(dur) => dur,
-            None => std::time::Duration::from_secs(60),
+        let tick_at = Utc::now();
+        health::beat(&config);
+        // wake for the next fire time, or sooner for the heartbeat
+        let overrides = schedule_overrides(&db).await;
+        let next_fire_at = compute_next_wake(&config, &overrides);
+        let until_fire = next_fire_at.map(|t| (t - Utc::now()).to_std().unwrap_or_default());
+        let (sleep_duration, fire) = match until_fire {
+            Some(dur) if dur <= heartbeat => (dur.max(Duration::from_millis(100)), true),
+            _ => (heartbeat, false),
         };
 
         tokio::select! {
             _ = tokio::time::sleep(sleep_duration) => {
-                fire_due_workflows(&config, Arc::clone(&db), Arc::clone(&states)).await;
+                if fire {
+                    let (db, states) = (Arc::clone(&db), Arc::clone(&states));
+                    let fired = &mut last_fired;
+                    fire_due_workflows(&config, &overrides, fired, &metrics, &events, db, states)
+                        .await;
+                }
             }
             cmd = rx.recv() => {
                 match cmd {
                     Some(SchedulerCommand::Reload(new_config)) => {
-                        info!("config reloaded");
-                        config = new_config;
+                        let diff = ConfigDiff::between(&config, &new_config);
+                        info!(changes = %diff.summary(), "config reloaded");
+                        for (name, keys) in &diff.changed {
+                            // a new schedule starts afresh; otherwise when it last fired holds
+                            if keys.iter().any(|k| SCHEDULE_KEYS.contains(&k.as_str())) {
+                                last_fired.remove(name);
+                            }
+                        }
+                        for name in &diff.removed {
+                            last_fired.remove(name);
+                            forget_workflow(&states, &events, name).await;
+                        }
+                        config = *new_config;
+                        {
+                            let mut runs = states.lock().await;
+                            runs.notifications = config.notifications.clone();
+                            runs.secrets = config.secrets.clone();
+                        }
+                        changelog::record(&db, &config).await;
+                        config_loaded_at = Utc::now();
+                        config_error = None;
+                        events.config_reloaded(diff);
+                    }
+                    Some(SchedulerCommand::ReloadFailed(e)) => config_error = Some(e),
+                    Some(SchedulerCommand::OverridesChanged) => {}
+                    Some(SchedulerCommand::Health(reply)) => {
+                        let (running, queued) = {
+                            let runs = states.lock().await;
+                            let running = runs.workflows.values().filter(|s| s.running).count();
+                            let queued = runs.workflows.values().map(|s| s.queue.len()).sum();
+                            (running, queued)
+                        };
+                        let _ = reply.send(SchedulerHealth {
+                            tick_at,
+                            next_fire_at,
+                            workflows: config.workflows.len(),
+                            running,
+                            queued,
+                            config_loaded_at,
+                            config_error: config_error.clone(),
+                        });
                     }
-                    Some(SchedulerCommand::Trigger(name)) => {
+                    Some(SchedulerCommand::Trigger(name, request)) => {
                         if let Some(wf) = config.workflows.iter().find(|w| w.name == name) {
-                            trigger_workflow(wf.clone(), Arc::clone(&db), Arc::clone(&states)).await;
+                            trigger_workflow(wf.clone(), request, Arc::clone(&db), Arc::clone(&states)).await;
                         } else {
                             warn!(workflow = %name, "trigger requested for unknown workflow");
                         }
                     }
+                    Some(SchedulerCommand::RunAdhoc(wf, request, done)) => {
+                        if let Some(done) = done {
+                            let mut runs = states.lock().await;
+                            let state = runs.workflows.entry(wf.name.clone());
+                            state.or_insert_with(WorkflowState::new).idle.push(done);
+                        }
+                        trigger_workflow(*wf, request, Arc::clone(&db), Arc::clone(&states)).await;
+                    }
+                    Some(SchedulerCommand::FileChanged(name, path)) => {
+                        let Some(wf) = config.workflows.iter().find(|w| w.name == name) else {
+                            continue;
+                        };
+                        if paused_workflows(&db).await.contains(&name) {
+                            info!(workflow = %name, "skipping file-change run of paused workflow");
+                            let source = TriggerSource::Watch;
+                            events.decision(&name, DecisionKind::Skipped, source, "paused");
+                            continue;
+                        }
+                        info!(workflow = %name, path = %path.display(), "watched path changed");
+                        let request = RunRequest {
+                            changed_path: Some(path),
+                            source: TriggerSource::Watch,
+                            ..Default::default()
+                        };
+                        trigger_workflow(wf.clone(), request, Arc::clone(&db), Arc::clone(&states)).await;
+                    }
                     None => break,
                 }
             }
@@ -80,121 +283,359 @@ async fn scheduler_loop(
     }
 }
 
-fn compute_next_wake(config: &AppConfig) -> Option<std::time::Duration> {
-    let now = Local::now();
-    let mut earliest: Option<chrono::DateTime<Local>> = None;
+/// Drop what the scheduler holds for a workflow removed from the config: its queued triggers,
+/// and its state once no run of it is going.
+async fn forget_workflow(states: &SharedRuns, events: &Events, name: &str) {
+    let mut runs = states.lock().await;
+    let runs = &mut *runs;
+    let Some(state) = runs.workflows.get_mut(name) else { return };
+    for dropped in state.queue.drain(..) {
+        events.decision(name, DecisionKind::Skipped, dropped.source, "removed from the config");
+    }
+    runs.queue_depths.set(name, 0);
+    if !state.running {
+        runs.workflows.remove(name);
+    }
+}
+
+/// The earliest time any workflow fires next.
+fn compute_next_wake(config: &AppConfig, overrides: &Overrides) -> Option<DateTime<Utc>> {
+    let now = Utc::now();
+    let fires = config.workflows.iter().filter_map(|wf| {
+        let schedule_override = overrides.get(&wf.name);
+        let next = next_fire(&effective_triggers(wf, schedule_override).ok()?, now);
+        // the schedule comes back when the override runs out
+        match schedule_override {
+            Some(o) => next.map_or(Some(o.until), |next| Some(next.min(o.until))),
+            None => next,
+        }
+    });
+    fires.min()
+}
+
+async fn fire_due_workflows(
+    config: &AppConfig,
+    overrides: &Overrides,
+    last_fired: &mut HashMap<String, DateTime<Utc>>,
+    metrics: &Metrics,
+    events: &Events,
+    db: Db,
+    states: SharedRuns,
+) {
+    let now = Utc::now();
+    let paused = paused_workflows(&db).await;
 
     for wf in &config.workflows {
-        let Ok(schedule) = Schedule::from_str(&normalize_cron(&wf.cron)) else {
+        let schedule_override = overrides.get(&wf.name);
+        let triggers = match effective_triggers(wf, schedule_override) {
+            Ok(triggers) => triggers,
+            Err(e) => {
+                error!(workflow = %wf.name, error = %e, "invalid schedule");
+                continue;
+            }
+        };
+
+        let Some(fire_at) = due_at(&triggers, last_fired.get(&wf.name).copied(), now) else {
             continue;
         };
-        if let Some(next) = schedule.upcoming(Local).next() {
-            earliest = Some(match earliest {
-                Some(e) if next < e => next,
-                Some(e) => e,
-                None => next,
-            });
+        last_fired.insert(wf.name.clone(), now);
+        metrics.record_lag(&wf.name, (now - fire_at).to_std().unwrap_or_default());
+
+        if paused.contains(&wf.name) {
+            info!(workflow = %wf.name, "skipping scheduled run of paused workflow");
+            events.decision(&wf.name, DecisionKind::Skipped, TriggerSource::Schedule, "paused");
+            continue;
+        }
+        // a run due as a snooze ends goes ahead
+        if let Some(snooze) = schedule_override.filter(|o| o.every.is_none() && fire_at < o.until) {
+            info!(
+                workflow = %wf.name,
+                until = %snooze.until,
+                "skipping scheduled run of snoozed workflow"
+            );
+            events.decision(&wf.name, DecisionKind::Skipped, TriggerSource::Schedule, "snoozed");
+            continue;
+        }
+
+        let request = RunRequest {
+            source: TriggerSource::Schedule,
+            ..Default::default()
+        };
+        match wf.jitter.filter(|j| !j.is_zero()) {
+            Some(jitter) => {
+                let delay = rand::thread_rng().gen_range(Duration::ZERO..jitter);
+                info!(
+                    workflow = %wf.name,
+                    delay_secs = delay.as_secs(),
+                    "delaying scheduled run by jitter"
+                );
+                let reason = format!("jitter: starting in {}", format_duration(&delay));
+                events.decision(&wf.name, DecisionKind::Deferred, TriggerSource::Schedule, reason);
+                let (wf, db, states) = (wf.clone(), Arc::clone(&db), Arc::clone(&states));
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    trigger_workflow(wf, request, db, states).await;
+                });
+            }
+            None => {
+                trigger_workflow(wf.clone(), request, Arc::clone(&db), Arc::clone(&states)).await;
+            }
         }
     }
+}
 
-    earliest.and_then(|t| {
-        let diff = t - now;
-        if diff.num_milliseconds() > 0 {
-            Some(std::time::Duration::from_millis(diff.num_milliseconds() as u64))
-        } else {
-            Some(std::time::Duration::from_millis(100))
+/// Workflows whose latest scheduled fire time falls after their last execution, whether or
+/// not they catch up. `on_start` workflows and workflows that never ran are left out.
+pub async fn missed_runs(config: &AppConfig, db: &Db) -> Vec<MissedRun> {
+    let paused = paused_workflows(db).await;
+    let overrides = schedule_overrides(db).await;
+    let now = Utc::now();
+    let mut missed = Vec::new();
+    for wf in &config.workflows {
+        let Ok(triggers) = triggers_for(wf) else {
+            continue;
+        };
+        if triggers.iter().any(|t| t.fires_on_start()) {
+            continue;
         }
-    })
+
+        let last = match db.get_last_execution(&wf.name).await {
+            Ok(Some(exec)) => exec.started_at,
+            // never ran before: nothing was missed
+            Ok(None) => continue,
+            Err(e) => {
+                error!(workflow = %wf.name, error = %e, "failed to read last execution");
+                continue;
+            }
+        };
+
+        if let Some(missed_at) = last_fire_between(&triggers, last, now) {
+            missed.push(MissedRun {
+                workflow: wf.name.clone(),
+                missed_at,
+                last_run_at: last,
+                caught_up: wf.catch_up
+                    && !paused.contains(&wf.name)
+                    && overrides.get(&wf.name).is_none_or(|o| o.every.is_some()),
+            });
+        }
+    }
+    missed
 }
 
-async fn fire_due_workflows(
+/// Run `on_start` workflows, and `catch_up` workflows whose schedule fired while the runner
+/// was down. Each workflow runs at most once here no matter how many fire times were missed.
+async fn run_startup_triggers(
     config: &AppConfig,
-    db: Arc<Mutex<rusqlite::Connection>>,
-    states: Arc<Mutex<HashMap<String, WorkflowState>>>,
+    missed: &[MissedRun],
+    events: &Events,
+    db: Db,
+    states: SharedRuns,
 ) {
-    let now = Local::now();
-
+    let paused = paused_workflows(&db).await;
+    for run in missed.iter().filter(|m| !m.caught_up) {
+        let reason = match paused.contains(&run.workflow) {
+            true => "missed while the runner was down; paused",
+            false => "missed while the runner was down; catch_up is off",
+        };
+        events.decision(&run.workflow, DecisionKind::Skipped, TriggerSource::CatchUp, reason);
+    }
     for wf in &config.workflows {
-        let Ok(schedule) = Schedule::from_str(&normalize_cron(&wf.cron)) else {
-            error!(workflow = %wf.name, cron = %wf.cron, "invalid cron expression");
+        let Ok(triggers) = triggers_for(wf) else {
             continue;
         };
+        let on_start = triggers.iter().any(|t| t.fires_on_start());
+        if paused.contains(&wf.name) {
+            if on_start {
+                events.decision(&wf.name, DecisionKind::Skipped, TriggerSource::Startup, "paused");
+            }
+            continue;
+        }
 
-        let due = is_due(&schedule, now);
+        if on_start {
+            info!(workflow = %wf.name, "running on start");
+            let request = RunRequest {
+                source: TriggerSource::Startup,
+                ..Default::default()
+            };
+            trigger_workflow(wf.clone(), request, Arc::clone(&db), Arc::clone(&states)).await;
+            continue;
+        }
 
-        if due {
-            trigger_workflow(wf.clone(), Arc::clone(&db), Arc::clone(&states)).await;
+        if missed.iter().any(|m| m.workflow == wf.name && m.caught_up) {
+            info!(workflow = %wf.name, "catching up missed run");
+            let request = RunRequest {
+                source: TriggerSource::CatchUp,
+                ..Default::default()
+            };
+            trigger_workflow(wf.clone(), request, Arc::clone(&db), Arc::clone(&states)).await;
         }
     }
 }
 
-fn is_due(schedule: &Schedule, now: chrono::DateTime<Local>) -> bool {
-    // if the first scheduled time after window_start is <= now, it's due
-    let window_start = now - chrono::Duration::seconds(5);
-    if let Some(t) = schedule.after(&window_start).next() {
-        return t <= now;
+/// Log the missed runs so they stand out at the top of the runner's output.
+pub fn log_missed_runs(missed: &[MissedRun]) {
+    if missed.is_empty() {
+        return;
+    }
+    warn!(count = missed.len(), "schedules missed while the runner was down");
+    for run in missed {
+        warn!(
+            workflow = %run.workflow,
+            missed_at = %run.missed_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            last_run_at = %run.last_run_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            catching_up = run.caught_up,
+            "missed while down"
+        );
     }
-    false
+}
+
+/// Paused workflows only skip scheduled runs; a storage error pauses nothing rather than
+/// stopping the whole schedule.
+async fn paused_workflows(db: &Db) -> HashSet<String> {
+    db.paused_workflows().await.unwrap_or_else(|e| {
+        error!(error = %e, "failed to read paused workflows");
+        HashSet::new()
+    })
+}
+
+/// The schedule overrides in force, by workflow; a storage error overrides nothing.
+async fn schedule_overrides(db: &Db) -> Overrides {
+    let overrides = db.schedule_overrides(Utc::now()).await.unwrap_or_else(|e| {
+        error!(error = %e, "failed to read schedule overrides");
+        Vec::new()
+    });
+    overrides.into_iter().map(|o| (o.workflow.clone(), o)).collect()
+}
+
+/// The fire time that is due now, if any.
+fn due_at(
+    triggers: &[Box<dyn Trigger>],
+    last_fired: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    // a fire time within the last few seconds that hasn't been handled yet is due
+    let window_start = now - chrono::Duration::seconds(5);
+    let since = last_fired.map_or(window_start, |t| t.max(window_start));
+    next_fire(triggers, since).filter(|t| *t <= now)
 }
 
 async fn trigger_workflow(
     wf: WorkflowConfig,
-    db: Arc<Mutex<rusqlite::Connection>>,
-    states: Arc<Mutex<HashMap<String, WorkflowState>>>,
+    request: RunRequest,
+    db: Db,
+    states: SharedRuns,
 ) {
     let mut states_lock = states.lock().await;
-    let state = states_lock.entry(wf.name.clone()).or_insert_with(WorkflowState::new);
+    let runs = &mut *states_lock;
+    if runs.shutdown.is_stopping() {
+        info!(workflow = %wf.name, "runner is shutting down, not starting");
+        runs.events.decision(&wf.name, DecisionKind::Skipped, request.source, SHUTTING_DOWN);
+        if let Some(state) = runs.workflows.get_mut(&wf.name).filter(|s| !s.running) {
+            state.stopped();
+        }
+        return;
+    }
+    let state = runs.workflows.entry(wf.name.clone()).or_insert_with(WorkflowState::new);
 
     if state.running {
         info!(workflow = %wf.name, "already running, queuing");
-        state.queue.push_back(());
+        let source = request.source;
+        state.queue.push_back(request);
+        let depth = state.queue.len();
+        runs.queue_depths.set(&wf.name, depth);
+        let reason = format!("already running; {} waiting", depth);
+        runs.events.decision(&wf.name, DecisionKind::Queued, source, reason);
+        runs.events.changed(&wf.name);
         return;
     }
 
     state.running = true;
+    let group = wf
+        .lock
+        .as_ref()
+        .map(|name| (name.clone(), Arc::clone(states_lock.locks.entry(name.clone()).or_default())));
+    let events = states_lock.events.clone();
+    let shutdown = states_lock.shutdown.clone();
     drop(states_lock);
 
     let name = wf.name.clone();
-    let script = wf.script.clone();
-    let message_script = wf.message_script.clone();
     let states_clone = Arc::clone(&states);
     let db_clone = Arc::clone(&db);
 
-    // using default notification config for simplicity
     tokio::spawn(async move {
-        let notification = crate::common::types::NotificationConfig::default();
+        let mut request = request;
+        let mut from_queue = false;
 
-        if let Err(e) = execute_workflow(name.clone(), script, message_script, db_clone, notification).await {
-            error!(workflow = %name, error = %e, "execution error");
-        }
+        loop {
+            let source = request.source;
+            // asked when the run is about to start, so a queued run sees the one before it
+            let mut skip = gate::skip_reason(&wf, source, &db_clone).await;
+            // the workflow counts as running while it waits, so further triggers queue behind it
+            if let Some(needed) = wf.when_idle.filter(|_| skip.is_none() && source.is_automatic()) {
+                let waited = idle::wait(&name, needed, source, &events, &db_clone, &shutdown);
+                skip = waited.await.map(str::to_string);
+            }
+            if let Some(reason) = skip {
+                events.decision(&name, DecisionKind::Skipped, source, reason);
+            } else {
+                let guard = match &group {
+                    Some((lock, mutex)) => Some(match mutex.try_lock() {
+                        Ok(guard) => guard,
+                        Err(_) => {
+                            info!(workflow = %name, lock = %lock, "waiting for lock");
+                            let reason = format!("waiting for lock {}", lock);
+                            events.decision(&name, DecisionKind::Deferred, source, reason);
+                            mutex.lock().await
+                        }
+                    }),
+                    None => None,
+                };
+                let db = Arc::clone(&db_clone);
+                let (notification, secrets) = {
+                    let runs = states_clone.lock().await;
+                    (runs.notifications.clone(), runs.secrets.clone())
+                };
+                // the runner may have begun stopping while this waited for the gate or the lock
+                if shutdown.is_stopping() {
+                    info!(workflow = %name, "runner is shutting down, not starting");
+                    events.decision(&name, DecisionKind::Skipped, source, SHUTTING_DOWN);
+                } else {
+                    let reason = if from_queue { "after the run before it" } else { "" };
+                    events.decision(&name, DecisionKind::Fired, source, reason);
+                    let (wf, events, shutdown) = (wf.clone(), events.clone(), shutdown.clone());
+                    let run =
+                        execute_workflow(wf, request, db, events, notification, secrets, shutdown);
+                    if let Err(e) = run.await {
+                        error!(workflow = %name, error = %e, "execution error");
+                    }
+                }
+                drop(guard);
+            }
 
-        let mut states_lock = states_clone.lock().await;
-        if let Some(state) = states_lock.get_mut(&name) {
-            state.running = false;
-            if state.queue.pop_front().is_some() {
-                // queued item found; mark running and let next cycle pick it up
-                state.running = true;
-                drop(states_lock);
-                info!(workflow = %name, "queued execution will be triggered on next cycle");
+            // run queued triggers back to back; the workflow stays marked running meanwhile
+            let mut states_lock = states_clone.lock().await;
+            let runs = &mut *states_lock;
+            let Some(state) = runs.workflows.get_mut(&name) else { break };
+            if shutdown.is_stopping() && !state.queue.is_empty() {
+                info!(workflow = %name, dropped = state.queue.len(), "dropping queued triggers");
+                for dropped in state.queue.drain(..) {
+                    events.decision(&name, DecisionKind::Skipped, dropped.source, SHUTTING_DOWN);
+                }
+                runs.queue_depths.set(&name, 0);
+            }
+            match state.queue.pop_front() {
+                Some(next) => {
+                    info!(workflow = %name, "running queued execution");
+                    runs.queue_depths.set(&name, state.queue.len());
+                    request = next;
+                    from_queue = true;
+                }
+                None => {
+                    state.stopped();
+                    break;
+                }
             }
         }
     });
 }
-
-pub fn get_next_run(cron_expr: &str) -> Option<chrono::DateTime<Utc>> {
-    Schedule::from_str(&normalize_cron(cron_expr))
-        .ok()?
-        .upcoming(Local)
-        .next()
-        .map(|t| t.with_timezone(&Utc))
-}
-
-/// Normalize standard 5-field cron to the 6-field format (with seconds) required by the cron crate.
-pub fn normalize_cron(expr: &str) -> String {
-    let fields: Vec<&str> = expr.split_whitespace().collect();
-    if fields.len() == 5 {
-        format!("0 {}", expr)
-    } else {
-        expr.to_string()
-    }
-}
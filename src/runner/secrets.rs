@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::os::unix::fs::PermissionsExt;
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::common::config::{load_config, resolve_path};
+use crate::common::types::{SecretsConfig, WorkflowConfig};
+
+pub const SCHEME: &str = "secret://";
+
+/// A workflow's `env`, and those of its steps, with their secrets looked up.
+#[derive(Debug, Default)]
+pub struct ResolvedEnv {
+    pub vars: Vec<(String, String)>,
+    /// The extra variables of each step, in order.
+    pub steps: Vec<Vec<(String, String)>>,
+    /// The lines of each secret value, for the log's [`Redactor`](crate::runner::redact::Redactor).
+    pub secrets: Vec<String>,
+}
+
+/// The `secrets` settings of the config as it is now, read again for each use so edits apply
+/// without a reload. A config that can't be read is logged, and no default stands in for it.
+pub fn current_secrets() -> Result<SecretsConfig> {
+    load_config().map(|config| config.secrets).inspect_err(|e| {
+        warn!(error = %format!("{:#}", e), "config can't be read for the secrets backend");
+    })
+}
+
+/// Whether any string in `settings` is a `secret://` reference, so it needs the backend.
+pub fn referenced(settings: &impl Serialize) -> bool {
+    fn any(value: &Value) -> bool {
+        match value {
+            Value::String(s) => s.starts_with(SCHEME),
+            Value::Array(items) => items.iter().any(any),
+            Value::Object(fields) => fields.values().any(any),
+            _ => false,
+        }
+    }
+    serde_json::to_value(settings).is_ok_and(|value| any(&value))
+}
+
+/// Look up the `secret://NAME` values of the workflow's and its steps' `env` in `config`'s
+/// backend. The file backend is read once however many secrets there are. Errors name the
+/// variable and secret, never a value.
+pub async fn resolve(wf: &WorkflowConfig, config: &SecretsConfig) -> Result<ResolvedEnv> {
+    let mut lookup = Lookup {
+        config,
+        file: None,
+        secrets: Vec::new(),
+    };
+    let vars = lookup.vars(&wf.env).await?;
+    let mut steps = Vec::new();
+    for step in &wf.steps {
+        let vars = lookup.vars(&step.env).await;
+        steps.push(vars.with_context(|| format!("step {}", step.name()))?);
+    }
+    Ok(ResolvedEnv {
+        vars,
+        steps,
+        secrets: lookup.secrets,
+    })
+}
+
+/// `value` itself, or the secret it names if it is a `secret://NAME` reference. `key` names the
+/// setting in errors.
+pub async fn value(value: &str, key: &str, config: &SecretsConfig) -> Result<String> {
+    let Some(name) = value.strip_prefix(SCHEME) else {
+        return Ok(value.to_string());
+    };
+    let mut lookup = Lookup {
+        config,
+        file: None,
+        secrets: Vec::new(),
+    };
+    lookup.secret(key, name).await
+}
+
+struct Lookup<'a> {
+    config: &'a SecretsConfig,
+    /// The file backend's entries, once read.
+    file: Option<BTreeMap<String, String>>,
+    secrets: Vec<String>,
+}
+
+impl Lookup<'_> {
+    async fn vars(&mut self, env: &BTreeMap<String, String>) -> Result<Vec<(String, String)>> {
+        let mut vars = Vec::new();
+        for (key, value) in env {
+            let Some(name) = value.strip_prefix(SCHEME) else {
+                vars.push((key.clone(), value.clone()));
+                continue;
+            };
+            let secret = self.secret(key, name).await?;
+            self.secrets.extend(secret.lines().filter(|l| !l.is_empty()).map(String::from));
+            vars.push((key.clone(), secret));
+        }
+        Ok(vars)
+    }
+
+    async fn secret(&mut self, key: &str, name: &str) -> Result<String> {
+        let secret = match self.config {
+            SecretsConfig::File { path } => {
+                if self.file.is_none() {
+                    self.file = Some(read_file(path).await?);
+                }
+                let entries = self.file.as_ref().expect("just read");
+                match entries.get(name) {
+                    Some(secret) => secret.clone(),
+                    None => bail!("{}: secret {:?} is not in {}", key, name, path),
+                }
+            }
+            SecretsConfig::Pass { prefix } => {
+                let mut command = Command::new("pass");
+                command.arg("show").arg(format!("{}{}", prefix, name));
+                let out = run(command).await.with_context(|| format!("{}: pass show", key))?;
+                out.lines().next().unwrap_or_default().to_string()
+            }
+            SecretsConfig::Command { command: script } => {
+                let mut command = Command::new("sh");
+                command.arg("-c").arg(script).arg("sh").arg(name);
+                let out = run(command).await.with_context(|| format!("{}: secrets command", key))?;
+                out.strip_suffix('\n').unwrap_or(&out).to_string()
+            }
+        };
+        Ok(secret)
+    }
+}
+
+/// `NAME=value` lines; blank lines, `#` comments and a leading `export` are allowed, and a
+/// value may be quoted.
+async fn read_file(path: &str) -> Result<BTreeMap<String, String>> {
+    let resolved = resolve_path(path);
+    let meta = tokio::fs::metadata(&resolved)
+        .await
+        .with_context(|| format!("failed to read {}", resolved.display()))?;
+    let mode = meta.permissions().mode() & 0o777;
+    if mode & 0o077 != 0 {
+        bail!("{} is readable by others (mode {:o}); chmod 600 it", resolved.display(), mode);
+    }
+    let text = tokio::fs::read_to_string(&resolved)
+        .await
+        .with_context(|| format!("failed to read {}", resolved.display()))?;
+
+    let mut entries = BTreeMap::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((name, value)) = line.split_once('=') else { continue };
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+            .unwrap_or(value);
+        entries.insert(name.trim().to_string(), value.to_string());
+    }
+    Ok(entries)
+}
+
+/// The stdout of `command`, which must succeed. Its stderr goes in the error.
+async fn run(mut command: Command) -> Result<String> {
+    let out = command.output().await?;
+    if !out.status.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        match stderr.trim() {
+            "" => bail!("exited with {}", out.status),
+            message => bail!("exited with {}: {}", out.status, message),
+        }
+    }
+    String::from_utf8(out.stdout).context("output is not UTF-8")
+}
@@ -1,39 +1,269 @@
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::Path;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
-use anyhow::Result;
-use tokio::net::UnixListener;
-use tokio::sync::Mutex;
-use tokio_stream::wrappers::UnixListenerStream;
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use tokio::net::{TcpListener, UnixListener};
+use tokio::sync::oneshot;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::{BroadcastStream, TcpListenerStream, UnixListenerStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::service::interceptor::InterceptedService;
 use tonic::{Request, Response, Status, transport::Server};
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::common::config::load_config;
-use crate::common::db::{get_execution_by_id, get_executions, get_last_execution};
-use crate::common::types::ExecutionStatus;
+use crate::common::config::{config_name, load_config, scripts_dir};
+use crate::common::duration::format_duration;
+use crate::common::paths;
+use crate::common::storage::Db;
+use crate::common::types::{
+    ApiConfig, DurationSample, Execution, ExecutionStatus, Interpreter, ScheduleOverride,
+    StepResult, TokenScope, TriggerSource, WorkflowConfig,
+};
 use crate::proto::workflow_service_server::{WorkflowService, WorkflowServiceServer};
 use crate::proto::{
-    Empty, ExecutionInfo, ExecutionRequest, ListWorkflowsResponse, LogPathResponse,
-    TriggerResponse, WorkflowInfo, WorkflowRequest, WorkflowStatusResponse,
+    AdhocRequest, BaselineRequest, BaselineResponse, ConfigChangeInfo, DaemonHealthResponse,
+    DecisionsRequest, DecisionsResponse,
+    DeleteExecutionResponse, DryRunPlan, DurationPoint, DurationsRequest, ExplainScheduleRequest, ExplainScheduleResponse,
+    DurationsResponse, Empty, ExecutionInfo, ExecutionRequest, ListWorkflowsResponse, LogPathResponse, MissedRunInfo,
+    MissedRunsResponse, PauseRequest, PlannedCommand,
+    PauseResponse, ReportRequest, ReportResponse, RepeatInfo, RestoreRequest, RestoreResponse,
+    ScheduleOverrideRequest, ScheduleOverrideResponse, ScheduleTrigger, SnoozeRequest,
+    SnoozeResponse, SchedulerDecision, StatsResponse,
+    StepInfo, TriggerRequest, TriggerResponse, VacuumResponse, WorkflowEvent, WorkflowInfo, WorkflowRequest,
+    WorkflowStatsResponse, WorkflowStatusResponse,
 };
-use crate::runner::scheduler::{SchedulerCommand, get_next_run};
+use crate::runner::adhoc::{self, Adhoc};
+use crate::runner::auth::{PeerCheck, authorize, grant, token_interceptor};
+use crate::runner::dry_run::{self, DryRun};
+use crate::runner::events::{Decision, DecisionKind, Event, Events};
+use crate::runner::executor::{self, RunRequest};
+use crate::runner::gate;
+use crate::runner::janitor::apply_retention;
+use crate::runner::log_compress;
+use crate::runner::names::{self, NameMatch};
+use crate::runner::next_runs::{NextRuns, ScheduleInfo};
+use crate::runner::workflow_health::{self, RunFacts};
+use crate::runner::report::{ReportFormat, build_report};
+use crate::runner::request_log::RequestLogLayer;
+use crate::runner::scheduler::{MissedRun, QueueDepths, SchedulerCommand};
+use crate::runner::trigger::{self, triggers_for};
+
+/// How long `GetDaemonHealth` waits for the scheduler loop before calling it stuck.
+const SCHEDULER_HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Latest runs whose durations come with workflow stats.
+const RECENT_RUNS: usize = 5;
+
+/// Latest config changes that come with workflow stats.
+const CONFIG_CHANGES: usize = 10;
+/// Finished runs sent with each workflow for the history strip in the workflow list.
+const HISTORY_RUNS: usize = 10;
+
+/// Most stdin a `TriggerWorkflow` or `RunAdhoc` request may carry. The whole request is one
+/// gRPC message, which tonic caps at 4 MiB unless told otherwise.
+pub const MAX_STDIN: usize = 256 * 1024 * 1024;
 
-pub const SOCKET_PATH: &str = "/tmp/illef-workflow.sock";
+/// Largest request the Runner decodes: `MAX_STDIN` and room for the other fields.
+pub const MAX_REQUEST: usize = MAX_STDIN + 1024 * 1024;
 
 pub struct WorkflowServiceImpl {
-    db: Arc<Mutex<rusqlite::Connection>>,
+    db: Db,
     scheduler_tx: tokio::sync::mpsc::Sender<SchedulerCommand>,
+    events: Events,
+    queue_depths: QueueDepths,
+    /// Found at startup; see [`crate::runner::scheduler::missed_runs`].
+    missed: Vec<MissedRun>,
+    read_only: bool,
+    started_at: DateTime<Utc>,
+    next_runs: NextRuns,
+    adhoc: Adhoc,
 }
 
 impl WorkflowServiceImpl {
     pub fn new(
-        db: Arc<Mutex<rusqlite::Connection>>,
+        db: Db,
         scheduler_tx: tokio::sync::mpsc::Sender<SchedulerCommand>,
+        events: Events,
+        queue_depths: QueueDepths,
+        missed: Vec<MissedRun>,
+        read_only: bool,
     ) -> Self {
-        Self { db, scheduler_tx }
+        Self {
+            db,
+            scheduler_tx,
+            events,
+            queue_depths,
+            missed,
+            read_only,
+            started_at: Utc::now(),
+            next_runs: NextRuns::default(),
+            adhoc: Adhoc::default(),
+        }
+    }
+
+    /// Guard for RPCs that change state. `api.read_only` is re-read so it can be toggled
+    /// without restarting the runner.
+    #[allow(clippy::result_large_err)]
+    fn ensure_writable(&self) -> Result<(), Status> {
+        match writes_disabled(self.read_only) {
+            Some(reason) => Err(Status::permission_denied(reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// Have the scheduler take a changed override into account now rather than on its next
+    /// heartbeat. It reads overrides from the database, so a failed send only delays this.
+    async fn wake_scheduler(&self) {
+        self.scheduler_tx.send(SchedulerCommand::OverridesChanged).await.ok();
+    }
+
+    /// Drop the ad-hoc workflow `name` once the scheduler says it stopped running with nothing
+    /// queued behind it, however its runs ended. A queued run keeps it, whoever queued it.
+    fn forget_after_run(&self, name: &str, idle: oneshot::Receiver<()>) {
+        let (adhoc, events) = (self.adhoc.clone(), self.events.clone());
+        let name = name.to_string();
+        tokio::spawn(async move {
+            // a dropped sender, the scheduler stopping, means the same
+            let _ = idle.await;
+            adhoc.forget(&name);
+            events.changed(&name);
+        });
+    }
+}
+
+/// Why changes are refused, by the `--read-only` flag or by `api.read_only` in the current
+/// config; `None` if they aren't. A config that can't be read refuses them too, since it may
+/// be the one setting `read_only`.
+pub fn writes_disabled(read_only_flag: bool) -> Option<String> {
+    if read_only_flag {
+        return Some("runner is in read-only mode".to_string());
+    }
+    match load_config() {
+        Ok(config) if config.api.read_only => Some("runner is in read-only mode".to_string()),
+        Ok(_) => None,
+        Err(e) => {
+            warn!(error = %format!("{:#}", e), "refusing a change: config can't be read");
+            Some(format!("{} can't be read, so changes are refused: {:#}", config_name(), e))
+        }
+    }
+}
+
+/// [`executor::script_error`] of each of `workflows`, on a blocking thread: it stats and opens
+/// their scripts.
+async fn script_errors(workflows: Vec<WorkflowConfig>) -> Vec<Option<String>> {
+    let count = workflows.len();
+    tokio::task::spawn_blocking(move || workflows.iter().map(executor::script_error).collect())
+        .await
+        .unwrap_or_else(|_| vec![None; count])
+}
+
+/// When the scheduler last skipped an automatic run of `name`, among the kept decisions.
+fn skipped_at(decisions: &[Decision], name: &str) -> Option<DateTime<Utc>> {
+    decisions
+        .iter()
+        .find(|d| d.workflow == name && d.kind == DecisionKind::Skipped && d.source.is_automatic())
+        .map(|d| d.at)
+}
+
+fn workflow_info(
+    wf: &WorkflowConfig,
+    schedule: ScheduleInfo,
+    next_runs: &NextRuns,
+    facts: RunFacts,
+) -> WorkflowInfo {
+    let RunFacts { last, history, paused, .. } = facts;
+    let (last_run_at, last_run_status) = match last {
+        Some(exec) => (exec.started_at.timestamp(), exec.status.as_str().to_string()),
+        None => (0, String::new()),
+    };
+    let status = match last {
+        Some(exec) if exec.status == ExecutionStatus::Running => "running",
+        _ => "idle",
+    };
+    let (health, health_detail) = workflow_health::assess(wf, next_runs, &schedule, &facts);
+    let override_until = schedule.schedule_override.as_ref().map_or(0, |o| o.until.timestamp());
+    let interpreter = match &wf.interpreter {
+        Some(Interpreter::Command(cmd)) => cmd.clone(),
+        Some(Interpreter::Argv(argv)) => argv.join(" "),
+        None => String::new(),
+    };
+
+    WorkflowInfo {
+        name: wf.name.clone(),
+        cron: schedule.label,
+        script: wf.script.clone(),
+        script_inline: wf.script_inline.clone().unwrap_or_default(),
+        steps: wf.steps.iter().map(|s| s.name().to_string()).collect(),
+        status: status.to_string(),
+        next_run_at: schedule.next_run.map(|t| t.timestamp()).unwrap_or(0),
+        last_run_at,
+        last_run_status,
+        paused,
+        timezone: wf.timezone.map(|tz| tz.name().to_string()).unwrap_or_default(),
+        schedule_description: schedule.description,
+        script_path: match wf.script.as_str() {
+            "" => String::new(),
+            script => scripts_dir().join(script).to_string_lossy().to_string(),
+        },
+        message_script: wf.message_script.clone().unwrap_or_default(),
+        interpreter,
+        cwd: wf.working_dir().map(|d| d.to_string_lossy().to_string()).unwrap_or_default(),
+        args: wf.args.clone(),
+        lock: wf.lock.clone().unwrap_or_default(),
+        history: history.iter().map(duration_to_proto).collect(),
+        error_patterns: wf.severity.error.clone(),
+        aliases: wf.aliases.clone(),
+        confirm_trigger: wf.confirm_trigger,
+        queued: 0,
+        stdin_file: wf.stdin_path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
+        health: health.as_str().to_string(),
+        health_detail,
+        schedule_override: schedule.schedule_override.map(|o| o.describe()).unwrap_or_default(),
+        override_until,
+        baseline_execution_id: String::new(),
+    }
+}
+
+/// Who a trigger says it came from: the bundled clients name themselves, anything else is
+/// `api`.
+fn trigger_source(source: &str) -> TriggerSource {
+    match source.parse() {
+        Ok(source @ (TriggerSource::Tui | TriggerSource::Cli)) => source,
+        _ => TriggerSource::Api,
+    }
+}
+
+fn dry_run_to_proto(plan: DryRun) -> DryRunPlan {
+    let commands = plan
+        .commands
+        .into_iter()
+        .map(|c| PlannedCommand {
+            step: c.step,
+            argv: c.argv,
+            cwd: c.cwd.unwrap_or_default(),
+            env: c.env.into_iter().map(|(k, v)| format!("{}={}", k, v)).collect(),
+        })
+        .collect();
+    DryRunPlan {
+        commands,
+        stdin: plan.stdin.unwrap_or_default(),
+        problems: plan.problems,
+    }
+}
+
+fn duration_to_proto(sample: &DurationSample) -> DurationPoint {
+    DurationPoint {
+        started_at: sample.started_at.timestamp(),
+        duration_secs: sample.duration_secs,
+        status: sample.status.as_str().to_string(),
     }
 }
 
-fn execution_to_proto(exec: &crate::common::types::Execution) -> ExecutionInfo {
+fn execution_to_proto(exec: &Execution) -> ExecutionInfo {
     ExecutionInfo {
         id: exec.id.clone(),
         workflow: exec.workflow.clone(),
@@ -42,47 +272,109 @@ fn execution_to_proto(exec: &crate::common::types::Execution) -> ExecutionInfo {
         finished_at: exec.finished_at.map(|t| t.timestamp()).unwrap_or(0),
         exit_code: exec.exit_code.unwrap_or(-1),
         log_path: exec.log_path.clone(),
+        args: exec.args.clone(),
+        result_json: exec.result_json.clone().unwrap_or_default(),
+        error_count: exec.severity.errors,
+        warning_count: exec.severity.warnings,
+        first_error_line: exec.severity.first_error_line.unwrap_or(0),
+        trigger: exec.trigger.map(|t| t.as_str().to_string()).unwrap_or_default(),
+        binary_output: exec.binary_output,
+        steps: exec.steps.iter().map(step_to_proto).collect(),
+        baseline_deviation: exec.deviation.clone().unwrap_or_default(),
+        repeats: exec
+            .repeats
+            .iter()
+            .map(|r| RepeatInfo { log_line: r.log_line, times: r.times })
+            .collect(),
+    }
+}
+
+fn step_to_proto(step: &StepResult) -> StepInfo {
+    StepInfo {
+        name: step.name.clone(),
+        status: step.status.as_str().to_string(),
+        exit_code: step.exit_code.unwrap_or(-1),
+        log_line: step.log_line.unwrap_or(0),
+    }
+}
+
+fn event_to_proto(event: &Event) -> WorkflowEvent {
+    WorkflowEvent {
+        kind: event.kind.as_str().to_string(),
+        workflow: event.workflow.clone(),
+        execution_id: event.execution_id.clone().unwrap_or_default(),
+        status: event.status.as_ref().map(|s| s.as_str().to_string()).unwrap_or_default(),
+        at: event.at.timestamp(),
+        decision: event.decision.as_ref().map(decision_to_proto),
+        summary: event.diff.as_ref().map(|d| d.summary()).unwrap_or_default(),
+    }
+}
+
+fn decision_to_proto(decision: &Decision) -> SchedulerDecision {
+    SchedulerDecision {
+        workflow: decision.workflow.clone(),
+        kind: decision.kind.as_str().to_string(),
+        trigger: decision.source.as_str().to_string(),
+        reason: decision.reason.clone(),
+        at: decision.at.timestamp(),
     }
 }
 
 #[tonic::async_trait]
 impl WorkflowService for WorkflowServiceImpl {
+    type WatchEventsStream = Pin<Box<dyn Stream<Item = Result<WorkflowEvent, Status>> + Send>>;
+
     async fn list_workflows(
         &self,
-        _request: Request<Empty>,
+        request: Request<Empty>,
     ) -> Result<Response<ListWorkflowsResponse>, Status> {
+        authorize(&request, TokenScope::Read, None)?;
         let config = load_config().map_err(|e| Status::internal(e.to_string()))?;
-        let conn = self.db.lock().await;
+        let grant = grant(&request);
+        let paused = self.db.paused_workflows().await.map_err(|e| Status::internal(e.to_string()))?;
+        let overrides = self
+            .db
+            .schedule_overrides(Utc::now())
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let configured: Vec<WorkflowConfig> = config
+            .workflows
+            .iter()
+            .filter(|wf| grant.is_none_or(|g| g.can_see(&wf.name)))
+            .cloned()
+            .collect();
+        let adhoc = self.adhoc.all().into_iter().filter(|wf| {
+            !config.workflows.iter().any(|w| w.name == wf.name)
+                && grant.is_none_or(|g| g.can_see(&wf.name))
+        });
+        let configured_count = configured.len();
+        let visible: Vec<WorkflowConfig> = configured.into_iter().chain(adhoc).collect();
+        let script_errors = script_errors(visible.clone()).await;
+        let decisions = self.events.decisions();
 
         let mut workflows = Vec::new();
-        for wf in &config.workflows {
-            let last = get_last_execution(&conn, &wf.name)
-                .unwrap_or(None);
-            let next_run_at = get_next_run(&wf.cron)
-                .map(|t| t.timestamp())
-                .unwrap_or(0);
-
-            let (last_run_at, last_run_status) = match &last {
-                Some(exec) => (
-                    exec.started_at.timestamp(),
-                    exec.status.as_str().to_string(),
-                ),
-                None => (0, String::new()),
+        for (i, (wf, script_error)) in visible.iter().zip(script_errors).enumerate() {
+            let last = self.db.get_last_execution(&wf.name).await.unwrap_or(None);
+            let history = self.db.get_durations(&wf.name, HISTORY_RUNS).await.unwrap_or_default();
+            let schedule = match i < configured_count {
+                true => {
+                    let schedule_override = overrides.iter().find(|o| o.workflow == wf.name);
+                    self.next_runs.with_override(wf, schedule_override)
+                }
+                false => adhoc::schedule(),
             };
-
-            let status = match &last {
-                Some(exec) if exec.status == ExecutionStatus::Running => "running",
-                _ => "idle",
+            let baseline = self.db.baseline(&wf.name).await.unwrap_or(None);
+            let facts = RunFacts {
+                last: last.as_ref(),
+                history: &history,
+                skipped_at: skipped_at(&decisions, &wf.name),
+                paused: paused.contains(&wf.name),
+                script_error,
             };
-
             workflows.push(WorkflowInfo {
-                name: wf.name.clone(),
-                cron: wf.cron.clone(),
-                script: wf.script.clone(),
-                status: status.to_string(),
-                next_run_at,
-                last_run_at,
-                last_run_status,
+                queued: self.queue_depths.get(&wf.name) as u32,
+                baseline_execution_id: baseline.unwrap_or_default(),
+                ..workflow_info(wf, schedule, &self.next_runs, facts)
             });
         }
 
@@ -93,42 +385,51 @@ impl WorkflowService for WorkflowServiceImpl {
         &self,
         request: Request<WorkflowRequest>,
     ) -> Result<Response<WorkflowStatusResponse>, Status> {
+        authorize(&request, TokenScope::Read, Some(&request.get_ref().name))?;
         let name = request.into_inner().name;
         let config = load_config().map_err(|e| Status::internal(e.to_string()))?;
 
-        let wf_config = config
-            .workflows
-            .iter()
-            .find(|w| w.name == name)
-            .ok_or_else(|| Status::not_found(format!("workflow not found: {}", name)))?;
-
-        let conn = self.db.lock().await;
-        let executions = get_executions(&conn, &name, 50)
+        let overrides = self
+            .db
+            .schedule_overrides(Utc::now())
+            .await
             .map_err(|e| Status::internal(e.to_string()))?;
-
-        let last = executions.first();
-        let next_run_at = get_next_run(&wf_config.cron)
-            .map(|t| t.timestamp())
-            .unwrap_or(0);
-
-        let (last_run_at, last_run_status) = match last {
-            Some(exec) => (exec.started_at.timestamp(), exec.status.as_str().to_string()),
-            None => (0, String::new()),
+        let schedule_override = overrides.iter().find(|o| o.workflow == name);
+        let adhoc = self.adhoc.get(&name);
+        let (wf_config, schedule) = match config.workflows.iter().find(|w| w.name == name) {
+            Some(wf) => (wf, self.next_runs.with_override(wf, schedule_override)),
+            None => match &adhoc {
+                Some(wf) => (wf, adhoc::schedule()),
+                None => return Err(Status::not_found(format!("workflow not found: {}", name))),
+            },
         };
 
-        let status = match last {
-            Some(exec) if exec.status == ExecutionStatus::Running => "running",
-            _ => "idle",
-        };
+        let executions = self
+            .db
+            .get_executions(&name, 50)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
 
+        let history = self
+            .db
+            .get_durations(&name, HISTORY_RUNS)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let paused = self.db.paused_workflows().await.map_err(|e| Status::internal(e.to_string()))?;
+        let paused = paused.contains(&name);
+        let baseline = self.db.baseline(&name).await.map_err(|e| Status::internal(e.to_string()))?;
+        let script_error = script_errors(vec![wf_config.clone()]).await.pop().flatten();
+        let facts = RunFacts {
+            last: executions.first(),
+            history: &history,
+            skipped_at: skipped_at(&self.events.decisions(), &name),
+            paused,
+            script_error,
+        };
         let workflow_info = WorkflowInfo {
-            name: wf_config.name.clone(),
-            cron: wf_config.cron.clone(),
-            script: wf_config.script.clone(),
-            status: status.to_string(),
-            next_run_at,
-            last_run_at,
-            last_run_status,
+            queued: self.queue_depths.get(&name) as u32,
+            baseline_execution_id: baseline.unwrap_or_default(),
+            ..workflow_info(wf_config, schedule, &self.next_runs, facts)
         };
 
         Ok(Response::new(WorkflowStatusResponse {
@@ -141,59 +442,892 @@ impl WorkflowService for WorkflowServiceImpl {
         &self,
         request: Request<ExecutionRequest>,
     ) -> Result<Response<LogPathResponse>, Status> {
-        let execution_id = request.into_inner().execution_id;
-        let conn = self.db.lock().await;
+        let execution_id = request.get_ref().execution_id.clone();
 
-        let exec = get_execution_by_id(&conn, &execution_id)
+        let exec = self
+            .db
+            .get_execution_by_id(&execution_id)
+            .await
             .map_err(|e| Status::internal(e.to_string()))?
             .ok_or_else(|| Status::not_found(format!("execution not found: {}", execution_id)))?;
+        authorize(&request, TokenScope::Read, Some(&exec.workflow))?;
 
+        let gz_path = log_compress::gz_path_for(Path::new(&exec.log_path));
+        let compressed = !Path::new(&exec.log_path).exists() && gz_path.exists();
         Ok(Response::new(LogPathResponse {
-            log_path: exec.log_path,
+            log_path: if compressed { gz_path.display().to_string() } else { exec.log_path },
+            compressed,
         }))
     }
 
     async fn trigger_workflow(
         &self,
-        request: Request<WorkflowRequest>,
+        request: Request<TriggerRequest>,
     ) -> Result<Response<TriggerResponse>, Status> {
-        let name = request.into_inner().name;
+        // a dry run changes nothing, so a read-only runner answers it too
+        if !request.get_ref().dry_run {
+            self.ensure_writable()?;
+        }
+        let config = load_config().map_err(|e| Status::internal(e.to_string()))?;
+        // only workflows the token can see, so an ambiguous name can't reveal the others
+        let adhoc: Vec<WorkflowConfig> = self
+            .adhoc
+            .all()
+            .into_iter()
+            .filter(|a| !config.workflows.iter().any(|w| w.name == a.name))
+            .collect();
+        let visible: Vec<&WorkflowConfig> = config
+            .workflows
+            .iter()
+            .chain(&adhoc)
+            .filter(|w| grant(&request).is_none_or(|g| g.can_see(&w.name)))
+            .collect();
+        let query = &request.get_ref().name;
+        let name = match names::resolve(&visible, query) {
+            NameMatch::Found(wf) => wf.name.clone(),
+            NameMatch::Ambiguous(candidates) => {
+                return Err(Status::invalid_argument(format!(
+                    "ambiguous name {:?}: matches {}",
+                    query,
+                    candidates.join(", ")
+                )));
+            }
+            NameMatch::NotFound => {
+                return Err(Status::not_found(format!("workflow not found: {}", query)));
+            }
+        };
+        authorize(&request, TokenScope::Trigger, Some(&name))?;
+        let TriggerRequest { args, stdin, source, dry_run, .. } = request.into_inner();
+        let run = RunRequest {
+            args: (!args.is_empty()).then_some(args),
+            stdin: (!stdin.is_empty()).then_some(stdin),
+            source: trigger_source(&source),
+            ..Default::default()
+        };
+        if dry_run {
+            let Some(wf) = visible.iter().find(|w| w.name == name) else {
+                return Err(Status::not_found(format!("workflow not found: {}", name)));
+            };
+            let plan = dry_run::plan(wf, &run, &config.secrets).await;
+            info!(workflow = %name, problems = plan.problems.len(), "dry run");
+            self.events.decision(&name, DecisionKind::Skipped, run.source, "dry run");
+            let message = match plan.problems.len() {
+                0 => format!("workflow {} would run", name),
+                1 => format!("workflow {} would fail to start: 1 problem", name),
+                n => format!("workflow {} would fail to start: {} problems", name, n),
+            };
+            return Ok(Response::new(TriggerResponse {
+                queued: false,
+                message,
+                dry_run: Some(dry_run_to_proto(plan)),
+            }));
+        }
+        let command = match adhoc.into_iter().find(|w| w.name == name) {
+            Some(wf) => SchedulerCommand::RunAdhoc(Box::new(wf), run, None),
+            None => SchedulerCommand::Trigger(name.clone(), run),
+        };
+
+        self.scheduler_tx.send(command).await.map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(TriggerResponse {
+            queued: false,
+            message: format!("workflow {} triggered", name),
+            dry_run: None,
+        }))
+    }
+
+    async fn delete_execution(
+        &self,
+        request: Request<ExecutionRequest>,
+    ) -> Result<Response<DeleteExecutionResponse>, Status> {
+        self.ensure_writable()?;
+        let execution_id = request.get_ref().execution_id.clone();
+
+        let exec = self
+            .db
+            .get_execution_by_id(&execution_id)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found(format!("execution not found: {}", execution_id)))?;
+        authorize(&request, TokenScope::Admin, Some(&exec.workflow))?;
+        if exec.status == ExecutionStatus::Running {
+            return Err(Status::failed_precondition("cannot delete a running execution"));
+        }
+        let baseline = self
+            .db
+            .baseline(&exec.workflow)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        if baseline.as_deref() == Some(execution_id.as_str()) {
+            return Err(Status::failed_precondition(
+                "cannot delete the pinned baseline; unpin it first",
+            ));
+        }
+
+        let deleted = self
+            .db
+            .soft_delete_execution(&execution_id, Utc::now())
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        if deleted {
+            self.events.changed(&exec.workflow);
+        }
+
+        Ok(Response::new(DeleteExecutionResponse { deleted }))
+    }
+
+    async fn restore_executions(
+        &self,
+        request: Request<RestoreRequest>,
+    ) -> Result<Response<RestoreResponse>, Status> {
+        self.ensure_writable()?;
+        // a token limited to some workflows has to name one, which a restore by id then keeps to
+        authorize(&request, TokenScope::Admin, Some(&request.get_ref().workflow))?;
+        let req = request.into_inner();
+
+        let restored = if !req.execution_id.is_empty() {
+            let restored = self.db.restore_execution(&req.execution_id, &req.workflow).await;
+            restored.map(|ok| ok as usize)
+        } else if !req.workflow.is_empty() {
+            self.db.restore_workflow_executions(&req.workflow).await
+        } else {
+            return Err(Status::invalid_argument("workflow or execution_id is required"));
+        }
+        .map_err(|e| Status::internal(e.to_string()))?;
+        if restored > 0 {
+            // restoring by id leaves the workflow empty, which reloads them all
+            self.events.changed(&req.workflow);
+        }
+
+        Ok(Response::new(RestoreResponse {
+            restored: restored as u32,
+        }))
+    }
+
+    async fn set_paused(
+        &self,
+        request: Request<PauseRequest>,
+    ) -> Result<Response<PauseResponse>, Status> {
+        self.ensure_writable()?;
+        authorize(&request, TokenScope::Admin, Some(&request.get_ref().name))?;
+        let PauseRequest { name, paused } = request.into_inner();
 
         let config = load_config().map_err(|e| Status::internal(e.to_string()))?;
         if !config.workflows.iter().any(|w| w.name == name) {
             return Err(Status::not_found(format!("workflow not found: {}", name)));
         }
 
+        let changed = self
+            .db
+            .set_paused(&name, paused)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        if changed {
+            info!(workflow = %name, paused, "workflow pause state changed");
+            self.events.changed(&name);
+        }
+
+        Ok(Response::new(PauseResponse { changed }))
+    }
+
+    /// Run `name` at another interval, or not at all, until a given time, without touching
+    /// the config; the scheduler drops the override once that time passes.
+    async fn set_schedule_override(
+        &self,
+        request: Request<ScheduleOverrideRequest>,
+    ) -> Result<Response<ScheduleOverrideResponse>, Status> {
+        self.ensure_writable()?;
+        authorize(&request, TokenScope::Admin, Some(&request.get_ref().name))?;
+        let ScheduleOverrideRequest { name, every_secs, until, clear } = request.into_inner();
+
+        let config = load_config().map_err(|e| Status::internal(e.to_string()))?;
+        if !config.workflows.iter().any(|w| w.name == name) {
+            return Err(Status::not_found(format!("workflow not found: {}", name)));
+        }
+
+        if clear {
+            let changed = self
+                .db
+                .clear_schedule_override(&name)
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+            if changed {
+                info!(workflow = %name, "schedule override cleared");
+                self.wake_scheduler().await;
+                self.events.changed(&name);
+            }
+            return Ok(Response::new(ScheduleOverrideResponse {
+                changed,
+                schedule_override: String::new(),
+            }));
+        }
+
+        let now = Utc::now();
+        let until = Utc
+            .timestamp_opt(until, 0)
+            .single()
+            .filter(|t| *t > now)
+            .ok_or_else(|| Status::invalid_argument("until must be in the future"))?;
+        let schedule_override = ScheduleOverride {
+            workflow: name.clone(),
+            every: Some(Duration::from_secs(every_secs)).filter(|d| !d.is_zero()),
+            until,
+            created_at: now,
+        };
+        self.db
+            .set_schedule_override(&schedule_override)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let described = schedule_override.describe();
+        info!(workflow = %name, schedule_override = %described, "schedule overridden");
+        self.wake_scheduler().await;
+        self.events.changed(&name);
+
+        Ok(Response::new(ScheduleOverrideResponse {
+            changed: true,
+            schedule_override: described,
+        }))
+    }
+
+    /// Skip just the next scheduled run of `name`, or the one after a snooze in force: a snooze
+    /// override lasting until the run after it, which goes ahead.
+    async fn snooze_next_run(
+        &self,
+        request: Request<SnoozeRequest>,
+    ) -> Result<Response<SnoozeResponse>, Status> {
+        self.ensure_writable()?;
+        authorize(&request, TokenScope::Admin, Some(&request.get_ref().name))?;
+        let SnoozeRequest { name, within_secs } = request.into_inner();
+
+        let config = load_config().map_err(|e| Status::internal(e.to_string()))?;
+        let Some(wf) = config.workflows.iter().find(|w| w.name == name) else {
+            return Err(Status::not_found(format!("workflow not found: {}", name)));
+        };
+        let now = Utc::now();
+        let overrides = self
+            .db
+            .schedule_overrides(now)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let current = overrides.iter().find(|o| o.workflow == name);
+        if let Some(o) = current.filter(|o| o.every.is_some()) {
+            return Err(Status::failed_precondition(format!(
+                "{} runs {}; clear that override first",
+                name,
+                o.describe()
+            )));
+        }
+        // a snooze ends as the run at its `until` is due, so that run is the next one
+        let after = current.map_or(now, |o| o.until - chrono::Duration::milliseconds(1));
+        let Some(skipped_at) = self.next_runs.next_after(wf, after) else {
+            return Err(Status::failed_precondition(format!(
+                "{} has no scheduled run to skip",
+                name
+            )));
+        };
+        if within_secs > 0 && skipped_at > now + chrono::Duration::seconds(within_secs as i64) {
+            let at = skipped_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+            let within = format_duration(&Duration::from_secs(within_secs));
+            return Err(Status::failed_precondition(format!(
+                "the next run of {} is at {}, more than {} away",
+                name, at, within
+            )));
+        }
+
+        let snooze = ScheduleOverride {
+            workflow: name.clone(),
+            every: None,
+            until: self
+                .next_runs
+                .next_after(wf, skipped_at)
+                .unwrap_or(skipped_at + chrono::Duration::seconds(1)),
+            created_at: now,
+        };
+        self.db
+            .set_schedule_override(&snooze)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        info!(workflow = %name, skipped_at = %skipped_at, "next scheduled run snoozed");
+        self.wake_scheduler().await;
+        self.events.changed(&name);
+
+        Ok(Response::new(SnoozeResponse {
+            skipped_at: skipped_at.timestamp(),
+            schedule_override: snooze.describe(),
+        }))
+    }
+
+    async fn set_baseline(
+        &self,
+        request: Request<BaselineRequest>,
+    ) -> Result<Response<BaselineResponse>, Status> {
+        self.ensure_writable()?;
+        authorize(&request, TokenScope::Admin, Some(&request.get_ref().workflow))?;
+        let BaselineRequest { workflow, execution_id, clear } = request.into_inner();
+
+        let config = load_config().map_err(|e| Status::internal(e.to_string()))?;
+        let known = config.workflows.iter().any(|w| w.name == workflow);
+        if !known && self.adhoc.get(&workflow).is_none() {
+            return Err(Status::not_found(format!("workflow not found: {}", workflow)));
+        }
+        if clear {
+            let changed = self
+                .db
+                .unpin_baseline(&workflow)
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+            if changed {
+                info!(workflow = %workflow, "baseline unpinned");
+                self.events.changed(&workflow);
+            }
+            return Ok(Response::new(BaselineResponse { execution_id: String::new(), changed }));
+        }
+
+        let execution = if execution_id.is_empty() {
+            let executions = self
+                .db
+                .get_executions(&workflow, 50)
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+            let latest = executions.into_iter().find(|e| e.status == ExecutionStatus::Success);
+            latest.ok_or_else(|| {
+                Status::failed_precondition(format!("{} has no successful run to pin", workflow))
+            })?
+        } else {
+            let execution = self
+                .db
+                .get_execution_by_id(&execution_id)
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?
+                .filter(|e| e.workflow == workflow);
+            let Some(execution) = execution else {
+                return Err(Status::not_found(format!(
+                    "{} has no execution {}",
+                    workflow, execution_id
+                )));
+            };
+            if execution.status != ExecutionStatus::Success {
+                return Err(Status::failed_precondition(format!(
+                    "execution {} is {}; only a successful run can be the baseline",
+                    execution.id,
+                    execution.status.as_str()
+                )));
+            }
+            execution
+        };
+
+        let current = self
+            .db
+            .baseline(&workflow)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let changed = current.as_deref() != Some(execution.id.as_str());
+        if changed {
+            self.db
+                .pin_baseline(&workflow, &execution.id)
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+            info!(workflow = %workflow, id = %execution.id, "baseline pinned");
+            self.events.changed(&workflow);
+        }
+        Ok(Response::new(BaselineResponse { execution_id: execution.id, changed }))
+    }
+
+    async fn get_durations(
+        &self,
+        request: Request<DurationsRequest>,
+    ) -> Result<Response<DurationsResponse>, Status> {
+        authorize(&request, TokenScope::Read, Some(&request.get_ref().name))?;
+        let DurationsRequest { name, limit } = request.into_inner();
+        let limit = match limit {
+            0 => 100,
+            n => n.min(1000) as usize,
+        };
+
+        let samples = self
+            .db
+            .get_durations(&name, limit)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(DurationsResponse {
+            points: samples.iter().map(duration_to_proto).collect(),
+        }))
+    }
+
+    async fn get_stats(&self, request: Request<Empty>) -> Result<Response<StatsResponse>, Status> {
+        authorize(&request, TokenScope::Read, None)?;
+        let stats = self.db.get_db_stats().await.map_err(|e| Status::internal(e.to_string()))?;
+
+        let (last_maintenance_at, last_reclaimed_bytes) = match &stats.last_maintenance {
+            Some(report) => (report.ran_at.timestamp(), report.reclaimed_bytes()),
+            None => (0, 0),
+        };
+
+        Ok(Response::new(StatsResponse {
+            db_size_bytes: stats.size_bytes,
+            db_free_bytes: stats.free_bytes,
+            execution_count: stats.execution_count,
+            deleted_execution_count: stats.deleted_count,
+            last_maintenance_at,
+            last_reclaimed_bytes,
+            total_reclaimed_bytes: stats.total_reclaimed_bytes,
+        }))
+    }
+
+    async fn get_workflow_stats(
+        &self,
+        request: Request<WorkflowRequest>,
+    ) -> Result<Response<WorkflowStatsResponse>, Status> {
+        authorize(&request, TokenScope::Read, Some(&request.get_ref().name))?;
+        let name = request.into_inner().name;
+        let stats = self
+            .db
+            .get_workflow_stats(&name)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let recent = self
+            .db
+            .get_durations(&name, RECENT_RUNS)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let changes = self
+            .db
+            .config_changes(&name, CONFIG_CHANGES)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(WorkflowStatsResponse {
+            total_runs: stats.total_runs,
+            failures: stats.failures,
+            current_failure_streak: stats.current_failure_streak,
+            longest_failure_streak: stats.longest_failure_streak,
+            mtbf_secs: stats.mtbf.map_or(0, |d| d.as_secs() as i64),
+            recent: recent.iter().map(duration_to_proto).collect(),
+            config_changes: changes
+                .into_iter()
+                .map(|c| ConfigChangeInfo {
+                    changed_at: c.changed_at.timestamp(),
+                    summary: c.summary,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn get_report(
+        &self,
+        request: Request<ReportRequest>,
+    ) -> Result<Response<ReportResponse>, Status> {
+        authorize(&request, TokenScope::Read, None)?;
+        let grant = grant(&request).cloned();
+        let ReportRequest {
+            since,
+            until,
+            format,
+            workflows,
+        } = request.into_inner();
+
+        let format: ReportFormat =
+            format.parse().map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))?;
+        let since = Utc
+            .timestamp_opt(since, 0)
+            .single()
+            .ok_or_else(|| Status::invalid_argument("invalid `since` timestamp"))?;
+        let until = match until {
+            0 => Utc::now(),
+            t => Utc
+                .timestamp_opt(t, 0)
+                .single()
+                .ok_or_else(|| Status::invalid_argument("invalid `until` timestamp"))?,
+        };
+
+        let config = load_config().map_err(|e| Status::internal(e.to_string()))?;
+        for name in &workflows {
+            if !config.workflows.iter().any(|w| &w.name == name) {
+                return Err(Status::not_found(format!("workflow not found: {}", name)));
+            }
+        }
+        let selected: Vec<_> = config
+            .workflows
+            .iter()
+            .filter(|w| workflows.is_empty() || workflows.contains(&w.name))
+            .filter(|w| grant.as_ref().is_none_or(|g| g.can_see(&w.name)))
+            .collect();
+
+        let content = build_report(&self.db, &selected, since, until, format)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(ReportResponse { content }))
+    }
+
+    /// Runs missed while the runner was down, until the workflow runs again.
+    async fn get_missed_runs(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<MissedRunsResponse>, Status> {
+        authorize(&request, TokenScope::Read, None)?;
+        let grant = grant(&request);
+        let mut runs = Vec::new();
+        for missed in &self.missed {
+            if grant.is_some_and(|g| !g.can_see(&missed.workflow)) {
+                continue;
+            }
+            let last = self
+                .db
+                .get_last_execution(&missed.workflow)
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+            if last.is_some_and(|exec| exec.started_at > missed.last_run_at) {
+                continue;
+            }
+            runs.push(MissedRunInfo {
+                workflow: missed.workflow.clone(),
+                missed_at: missed.missed_at.timestamp(),
+                last_run_at: missed.last_run_at.timestamp(),
+                caught_up: missed.caught_up,
+            });
+        }
+        Ok(Response::new(MissedRunsResponse { runs }))
+    }
+
+    /// Workflows starting, finishing and changing, as they happen, for clients that would
+    /// otherwise poll.
+    async fn watch_events(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::WatchEventsStream>, Status> {
+        authorize(&request, TokenScope::Read, None)?;
+        let grant = grant(&request).cloned();
+        let stream = BroadcastStream::new(self.events.subscribe()).filter_map(move |event| {
+            let event = match event {
+                Ok(event) => event,
+                // events were dropped while this client lagged behind
+                Err(BroadcastStreamRecvError::Lagged(_)) => Event::changed_all(),
+            };
+            let can_see = |name: &str| grant.as_ref().is_none_or(|g| g.can_see(name));
+            let visible = event.workflow.is_empty() || can_see(&event.workflow);
+            let mut proto = event_to_proto(&event);
+            if let Some(diff) = &event.diff {
+                // only the workflows this caller may see
+                proto.summary = diff.visible_to(can_see).summary();
+            }
+            visible.then_some(Ok(proto))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    /// Whether the runner is up and well: its scheduler loop answers, the database answers and
+    /// the latest config change loaded.
+    async fn get_daemon_health(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<DaemonHealthResponse>, Status> {
+        authorize(&request, TokenScope::Read, None)?;
+        let now = Utc::now();
+        let mut response = DaemonHealthResponse {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: self.started_at.timestamp(),
+            uptime_secs: (now - self.started_at).num_seconds(),
+            ..Default::default()
+        };
+
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        let scheduler = async {
+            self.scheduler_tx.send(SchedulerCommand::Health(reply_tx)).await.ok()?;
+            reply_rx.await.ok()
+        };
+        if let Ok(Some(health)) = tokio::time::timeout(SCHEDULER_HEALTH_TIMEOUT, scheduler).await {
+            response.scheduler_ok = true;
+            response.scheduler_tick_at = health.tick_at.timestamp();
+            response.next_fire_at = health.next_fire_at.map_or(0, |t| t.timestamp());
+            response.workflows = health.workflows as u32;
+            response.running = health.running as u32;
+            response.queued = health.queued as u32;
+            response.config_loaded_at = health.config_loaded_at.timestamp();
+            response.config_error = health.config_error.unwrap_or_default();
+        }
+
+        match self.db.ping().await {
+            Ok(()) => response.db_ok = true,
+            Err(e) => response.db_error = format!("{:#}", e),
+        }
+        Ok(Response::new(response))
+    }
+
+    /// When the workflow runs next and everything that decides it: the schedule and its next
+    /// fire times, jitter, pausing, gating, locks and what happens to overlapping triggers.
+    async fn explain_schedule(
+        &self,
+        request: Request<ExplainScheduleRequest>,
+    ) -> Result<Response<ExplainScheduleResponse>, Status> {
+        authorize(&request, TokenScope::Read, Some(&request.get_ref().name))?;
+        let ExplainScheduleRequest { name, count } = request.into_inner();
+        let count = match count {
+            0 => 10,
+            n => n.min(100) as usize,
+        };
+        let config = load_config().map_err(|e| Status::internal(e.to_string()))?;
+        let wf = config
+            .workflows
+            .iter()
+            .find(|w| w.name == name)
+            .ok_or_else(|| Status::not_found(format!("workflow not found: {}", name)))?;
+
+        let mut response = ExplainScheduleResponse {
+            timezone: wf.timezone.map(|tz| tz.name().to_string()).unwrap_or_default(),
+            jitter_secs: wf.jitter.map_or(0, |j| j.as_secs() as i64),
+            when_idle_secs: wf.when_idle.map_or(0, |d| d.as_secs() as i64),
+            catch_up: wf.catch_up,
+            should_run: wf.should_run.as_deref().map(gate::describe).unwrap_or_default().into(),
+            conditions: wf.conditions.iter().map(|c| c.plugin.clone()).collect(),
+            // the only policy there is: see the scheduler's trigger_workflow
+            overlap: "queue".to_string(),
+            lock: wf.lock.clone().unwrap_or_default(),
+            queued: self.queue_depths.get(&name) as u32,
+            ..Default::default()
+        };
+        if let Some(lock) = &wf.distributed_lock {
+            response.distributed_lock = lock.path.clone().or(lock.url.clone()).unwrap_or_default();
+        }
+        match triggers_for(wf) {
+            Ok(triggers) => {
+                response.triggers = triggers
+                    .iter()
+                    .map(|t| ScheduleTrigger {
+                        kind: t.kind().to_string(),
+                        spec: t.describe(),
+                        description: t.explain(),
+                    })
+                    .collect();
+                response.next_fire_at = trigger::next_fires(&triggers, Utc::now(), count)
+                    .iter()
+                    .map(|t| t.timestamp())
+                    .collect();
+            }
+            Err(e) => response.schedule_error = format!("{:#}", e),
+        }
+
+        let paused = self.db.paused_workflows().await.map_err(|e| Status::internal(e.to_string()))?;
+        response.paused = paused.contains(&name);
+        let overrides = self
+            .db
+            .schedule_overrides(Utc::now())
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        if let Some(o) = overrides.iter().find(|o| o.workflow == name) {
+            response.schedule_override = o.describe();
+            let next_runs = self.next_runs.with_override(wf, Some(o));
+            response.next_fire_at = next_runs.next_run.iter().map(|t| t.timestamp()).collect();
+        }
+        let last = self
+            .db
+            .get_last_execution(&name)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        response.running = last.is_some_and(|e| e.status == ExecutionStatus::Running);
+        Ok(Response::new(response))
+    }
+
+    /// What the scheduler recently did with triggers and why, newest first: runs started,
+    /// queued, put off or skipped.
+    async fn list_decisions(
+        &self,
+        request: Request<DecisionsRequest>,
+    ) -> Result<Response<DecisionsResponse>, Status> {
+        let workflow = Some(request.get_ref().workflow.as_str()).filter(|w| !w.is_empty());
+        authorize(&request, TokenScope::Read, workflow)?;
+        let grant = grant(&request);
+        let limit = match request.get_ref().limit {
+            0 => usize::MAX,
+            n => n as usize,
+        };
+        let decisions = self
+            .events
+            .decisions()
+            .iter()
+            .filter(|d| workflow.is_none_or(|w| d.workflow == w))
+            .filter(|d| grant.is_none_or(|g| g.can_see(&d.workflow)))
+            .take(limit)
+            .map(decision_to_proto)
+            .collect();
+        Ok(Response::new(DecisionsResponse { decisions }))
+    }
+
+    /// Apply the retention policy now, then run database maintenance.
+    async fn vacuum(&self, request: Request<Empty>) -> Result<Response<VacuumResponse>, Status> {
+        self.ensure_writable()?;
+        authorize(&request, TokenScope::Admin, None)?;
+
+        let retention = load_config().map_err(|e| Status::internal(e.to_string()))?.retention;
+        let pruned = apply_retention(&self.db, &retention)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let report = self.db.run_maintenance().await.map_err(|e| Status::internal(e.to_string()))?;
+        info!(pruned, reclaimed = report.reclaimed_bytes(), "vacuum requested");
+        if pruned > 0 {
+            self.events.changed("");
+        }
+
+        Ok(Response::new(VacuumResponse {
+            pruned_executions: pruned as i64,
+            db_size_before: report.size_before,
+            db_size_after: report.size_after,
+        }))
+    }
+
+    async fn run_adhoc(
+        &self,
+        request: Request<AdhocRequest>,
+    ) -> Result<Response<TriggerResponse>, Status> {
+        self.ensure_writable()?;
+        // any script the runner can read, so it takes what changing the config would
+        authorize(&request, TokenScope::Admin, Some(&request.get_ref().name))?;
+        let AdhocRequest { name, script, args, stdin, cwd, forget, source } = request.into_inner();
+        let config = load_config().map_err(|e| Status::internal(e.to_string()))?;
+        if config.workflows.iter().any(|w| w.name == name || w.aliases.contains(&name)) {
+            return Err(Status::already_exists(format!(
+                "{} is a workflow in {}; pick another name",
+                name,
+                config_name()
+            )));
+        }
+        let wf = adhoc::workflow(&name, &script, args, &cwd)
+            .map_err(|e| Status::invalid_argument(format!("{:#}", e)))?;
+        if let Some(problem) = executor::script_error(&wf) {
+            return Err(Status::invalid_argument(problem));
+        }
+        if let Some(registered) = self.adhoc.get(&name).filter(|r| r.script != wf.script) {
+            let last = self.db.get_last_execution(&name).await.unwrap_or(None);
+            if last.is_some_and(|e| e.status == ExecutionStatus::Running) {
+                return Err(Status::failed_precondition(format!(
+                    "{} is running {}; wait for it or pick another name",
+                    name, registered.script
+                )));
+            }
+        }
+
+        self.adhoc.register(wf.clone());
+        let done = forget.then(|| {
+            let (tx, rx) = oneshot::channel();
+            self.forget_after_run(&name, rx);
+            tx
+        });
+        self.events.changed(&name);
+        let run = RunRequest {
+            stdin: (!stdin.is_empty()).then_some(stdin),
+            source: trigger_source(&source),
+            ..Default::default()
+        };
         self.scheduler_tx
-            .send(SchedulerCommand::Trigger(name.clone()))
+            .send(SchedulerCommand::RunAdhoc(Box::new(wf), run, done))
             .await
             .map_err(|e| Status::internal(e.to_string()))?;
+        info!(workflow = %name, script = %script, "ad-hoc workflow triggered");
 
         Ok(Response::new(TriggerResponse {
             queued: false,
-            message: format!("workflow {} triggered", name),
+            message: format!("ad-hoc workflow {} triggered", name),
+            dry_run: None,
         }))
     }
 }
 
 pub async fn serve(
-    db: Arc<Mutex<rusqlite::Connection>>,
+    db: Db,
     scheduler_tx: tokio::sync::mpsc::Sender<SchedulerCommand>,
+    events: Events,
+    queue_depths: QueueDepths,
+    missed: Vec<MissedRun>,
+    read_only: bool,
+    api: &ApiConfig,
 ) -> Result<()> {
-    let socket_path = std::path::Path::new(SOCKET_PATH);
+    let socket_path = &paths::get().socket;
     if socket_path.exists() {
         std::fs::remove_file(socket_path)?;
     }
+    if let Some(parent) = socket_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let listener = bind_socket(socket_path)?;
+    info!("gRPC server listening on {}", socket_path.display());
 
-    let listener = UnixListener::bind(SOCKET_PATH)?;
-    info!("gRPC server listening on {}", SOCKET_PATH);
+    if read_only {
+        info!("read-only mode: mutating RPCs are disabled");
+    }
+    let service = Arc::new(WorkflowServiceImpl::new(
+        db,
+        scheduler_tx,
+        events,
+        queue_depths,
+        missed,
+        read_only,
+    ));
 
-    let service = WorkflowServiceImpl::new(db, scheduler_tx);
+    // so the first ListWorkflows doesn't parse every schedule
+    if let Ok(config) = load_config() {
+        service.next_runs.warm(&config);
+    }
 
-    Server::builder()
-        .add_service(WorkflowServiceServer::new(service))
-        .serve_with_incoming(UnixListenerStream::new(listener))
-        .await?;
+    let request_log = RequestLogLayer::new(api.slow_call_threshold);
 
+    // SAFETY: getuid has no preconditions and cannot fail
+    let uid = api.same_user_only.then(|| unsafe { libc::getuid() });
+    if let Some(uid) = uid {
+        info!(uid, "accepting Unix socket connections from this user only");
+    }
+    let unix_server = Server::builder()
+        .layer(request_log.clone())
+        .add_service(InterceptedService::new(
+            WorkflowServiceServer::from_arc(Arc::clone(&service))
+                .max_decoding_message_size(MAX_REQUEST),
+            PeerCheck { uid },
+        ))
+        .serve_with_incoming(UnixListenerStream::new(listener));
+
+    let Some(addr) = &api.listen else {
+        unix_server.await?;
+        return Ok(());
+    };
+
+    let tcp_listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind TCP listener on {}", addr))?;
+    info!("gRPC server listening on tcp://{} (token required)", addr);
+
+    let tcp_server = Server::builder()
+        .layer(request_log)
+        .add_service(InterceptedService::new(
+            WorkflowServiceServer::from_arc(service).max_decoding_message_size(MAX_REQUEST),
+            token_interceptor,
+        ))
+        .serve_with_incoming(TcpListenerStream::new(tcp_listener));
+
+    tokio::try_join!(unix_server, tcp_server)?;
     Ok(())
 }
+
+/// Bind the socket so that only its owner may connect. It is bound in a directory only the
+/// runner's user can enter, restricted there, and then moved into place, so it is never
+/// reachable with the looser mode the umask gives it.
+fn bind_socket(path: &Path) -> Result<UnixListener> {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let private = path.with_file_name(format!(".{}.{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&private);
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private)
+        .with_context(|| format!("failed to create {}", private.display()))?;
+
+    let bound = private.join("socket");
+    let listener = UnixListener::bind(&bound)
+        .with_context(|| format!("failed to bind {}", path.display()));
+    let moved = listener.and_then(|listener| {
+        std::fs::set_permissions(&bound, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&bound, path)
+            .with_context(|| format!("failed to move the socket to {}", path.display()))?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&private);
+    moved
+}
@@ -0,0 +1,73 @@
+use regex::Regex;
+
+use crate::common::types::{LogSeverity, SeverityConfig};
+use crate::runner::output;
+
+/// Counts error and warning lines in script output with a workflow's `severity` patterns.
+pub struct Detector {
+    error: Vec<Regex>,
+    warn: Vec<Regex>,
+    /// Color codes are removed before matching, so `\x1b[31merror` still counts.
+    ansi: Regex,
+}
+
+impl Detector {
+    /// Patterns that don't compile are left out and described in the returned messages.
+    pub fn new(config: &SeverityConfig) -> (Self, Vec<String>) {
+        let mut problems = Vec::new();
+        let mut compile = |patterns: &[String]| -> Vec<Regex> {
+            patterns
+                .iter()
+                .filter_map(|p| match Regex::new(p) {
+                    Ok(re) => Some(re),
+                    Err(e) => {
+                        problems.push(format!("ignoring severity pattern {:?}: {}", p, e));
+                        None
+                    }
+                })
+                .collect()
+        };
+        let error = compile(&config.error);
+        let warn = compile(&config.warn);
+        let ansi = Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").expect("valid pattern");
+        (Self { error, warn, ansi }, problems)
+    }
+
+    /// Whether `line`, a log line with or without its stream tag, matches one of the error
+    /// patterns.
+    pub fn is_error(&self, line: &str) -> bool {
+        let line = self.ansi.replace_all(output::untagged(line), "");
+        self.error.iter().any(|re| re.is_match(&line))
+    }
+
+    /// Add the lines of `text`, which starts at line `first_line` of the log, to `severity`.
+    pub fn scan(&self, text: &[u8], first_line: u32, severity: &mut LogSeverity) {
+        for (i, line) in String::from_utf8_lossy(text).lines().enumerate() {
+            let line = self.ansi.replace_all(line, "");
+            let line = line.as_ref();
+            if self.error.iter().any(|re| re.is_match(line)) {
+                severity.errors += 1;
+                severity.first_error_line.get_or_insert(first_line + i as u32);
+            } else if self.warn.iter().any(|re| re.is_match(line)) {
+                severity.warnings += 1;
+            }
+        }
+    }
+
+    /// Add `times` more copies of `line`, dropped from the log by `collapse_repeats`.
+    pub fn scan_repeats(&self, line: &[u8], times: u32, severity: &mut LogSeverity) {
+        let line = String::from_utf8_lossy(line);
+        let line = self.ansi.replace_all(line.trim_end(), "");
+        let line = line.as_ref();
+        if self.error.iter().any(|re| re.is_match(line)) {
+            severity.errors += times;
+        } else if self.warn.iter().any(|re| re.is_match(line)) {
+            severity.warnings += times;
+        }
+    }
+}
+
+/// Lines (newlines) in `bytes`.
+pub fn count_lines(bytes: &[u8]) -> u32 {
+    bytes.iter().filter(|b| **b == b'\n').count() as u32
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use tokio::io::AsyncWriteExt;
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::common::storage::Db;
+use crate::common::types::{Execution, ExecutionStatus, StepStatus};
+
+/// How long killed scripts get to exit after SIGTERM before they get SIGKILL, and their runs
+/// to be recorded after that.
+const KILL_WAIT: Duration = Duration::from_secs(5);
+
+/// Whether the runner is stopping, and the executions still running. Clones share them.
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown(Arc<Inner>);
+
+#[derive(Debug, Default)]
+struct Inner {
+    stopping: AtomicBool,
+    /// Scripts still running when the timeout ran out were killed.
+    killed: AtomicBool,
+    /// Executions not done yet, with their log files until they are recorded as finished.
+    running: Mutex<HashMap<String, Option<PathBuf>>>,
+    /// Woken when an execution is done.
+    finished: Notify,
+    /// Woken when the runner begins stopping.
+    stop: Notify,
+}
+
+/// An execution counted as running until this is dropped.
+pub struct Running {
+    shutdown: Shutdown,
+    id: String,
+}
+
+impl Running {
+    /// The execution is recorded as finished in storage; what's left is notifying about it.
+    pub fn recorded(&self) {
+        if let Some(log_path) = self.shutdown.0.running.lock().unwrap().get_mut(&self.id) {
+            *log_path = None;
+        }
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.shutdown.0.running.lock().unwrap().remove(&self.id);
+        self.shutdown.0.finished.notify_waiters();
+    }
+}
+
+impl Shutdown {
+    /// No new executions start once this is set, including queued ones.
+    pub fn is_stopping(&self) -> bool {
+        self.0.stopping.load(Ordering::Relaxed)
+    }
+
+    /// Resolves once the runner begins stopping.
+    pub async fn stopping(&self) {
+        let stop = self.0.stop.notified();
+        if !self.is_stopping() {
+            stop.await;
+        }
+    }
+
+    /// Whether the runner killed running scripts on its way out.
+    pub fn killed(&self) -> bool {
+        self.0.killed.load(Ordering::Relaxed)
+    }
+
+    /// Count execution `id` as running until the returned guard is dropped.
+    pub fn track(&self, id: &str, log_path: PathBuf) -> Running {
+        self.0.running.lock().unwrap().insert(id.to_string(), Some(log_path));
+        Running {
+            shutdown: self.clone(),
+            id: id.to_string(),
+        }
+    }
+
+    fn running(&self) -> usize {
+        self.0.running.lock().unwrap().len()
+    }
+
+    /// Wait up to `timeout` for every running execution to be done; false if some weren't.
+    async fn wait_idle(&self, timeout: Duration) -> bool {
+        let idle = async {
+            loop {
+                let finished = self.0.finished.notified();
+                if self.running() == 0 {
+                    return;
+                }
+                finished.await;
+            }
+        };
+        tokio::time::timeout(timeout, idle).await.is_ok()
+    }
+
+    /// Stop starting executions, give the running ones `timeout` to finish, then kill their
+    /// scripts. Runs that still aren't recorded after that are marked failed here, so none
+    /// stays `running` in storage. A second signal skips the wait.
+    pub async fn drain(&self, db: &Db, timeout: Duration) {
+        self.0.stopping.store(true, Ordering::Relaxed);
+        self.0.stop.notify_waiters();
+        let running = self.running();
+        if running == 0 {
+            return;
+        }
+        info!(running, timeout_secs = timeout.as_secs(), "waiting for running executions");
+        let finished = tokio::select! {
+            finished = self.wait_idle(timeout) => finished,
+            _ = wait_for_signal() => {
+                info!("second signal, not waiting");
+                false
+            }
+        };
+        if finished {
+            return;
+        }
+
+        warn!(running = self.running(), "killing scripts still running");
+        self.0.killed.store(true, Ordering::Relaxed);
+        kill_children(libc::SIGTERM);
+        if !self.wait_idle(KILL_WAIT).await {
+            kill_children(libc::SIGKILL);
+            self.wait_idle(KILL_WAIT).await;
+        }
+
+        let left: Vec<_> = self.0.running.lock().unwrap().drain().collect();
+        for (id, log_path) in left {
+            let Some(log_path) = log_path else { continue };
+            warn!(id = %id, "marking unfinished execution as interrupted");
+            let marked = match db.get_execution_by_id(&id).await {
+                Ok(Some(exec)) => {
+                    mark_interrupted(db, &exec, &log_path, Utc::now(), &interrupted_line()).await
+                }
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = marked {
+                warn!(id = %id, error = %e, "failed to mark execution as interrupted");
+            }
+        }
+    }
+}
+
+/// The line added to the log of a run cut short by the runner stopping.
+pub fn interrupted_line() -> String {
+    format!(
+        "\n[{}] [shutdown] interrupted: the runner stopped while this ran\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    )
+}
+
+/// Record `exec`, cut short by a runner stopping, as failed at `finished_at`: a running step
+/// failed and those still pending skipped, and `note` added to the log at `log_path` if it is
+/// still there.
+pub(crate) async fn mark_interrupted(
+    db: &Db,
+    exec: &Execution,
+    log_path: &Path,
+    finished_at: DateTime<Utc>,
+    note: &str,
+) -> Result<()> {
+    db.update_execution_finished(&exec.id, ExecutionStatus::Failed, finished_at, -1).await?;
+
+    if exec.steps.iter().any(|s| matches!(s.status, StepStatus::Running | StepStatus::Pending)) {
+        let mut steps = exec.steps.clone();
+        for step in &mut steps {
+            step.status = match step.status {
+                StepStatus::Running => StepStatus::Failed,
+                StepStatus::Pending => StepStatus::Skipped,
+                other => other,
+            };
+        }
+        db.set_execution_steps(&exec.id, &steps).await?;
+    }
+
+    if let Ok(mut log) = tokio::fs::OpenOptions::new().append(true).open(log_path).await {
+        log.write_all(note.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Wait for SIGTERM (systemd stopping the service) or SIGINT (Ctrl-C). Returns its name.
+pub async fn wait_for_signal() -> &'static str {
+    let (Ok(mut term), Ok(mut int)) = (
+        signal(SignalKind::terminate()),
+        signal(SignalKind::interrupt()),
+    ) else {
+        warn!("failed to listen for signals; stop the runner with SIGKILL");
+        return std::future::pending().await;
+    };
+    tokio::select! {
+        _ = term.recv() => "SIGTERM",
+        _ = int.recv() => "SIGINT",
+    }
+}
+
+/// Send `signal` to every child process of the runner. Scripts lead process groups of their
+/// own, which get it as a whole so whatever they started stops too.
+fn kill_children(signal: libc::c_int) {
+    let me = std::process::id() as i32;
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<i32>().ok()) else {
+            continue;
+        };
+        let Ok(stat) = std::fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+            continue;
+        };
+        // "pid (comm) state ppid pgrp ...", where comm may hold spaces and parentheses
+        let Some((_, rest)) = stat.rsplit_once(')') else {
+            continue;
+        };
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let (Some(ppid), Some(pgrp)) = (fields.get(1), fields.get(2)) else {
+            continue;
+        };
+        if ppid.parse() != Ok(me) {
+            continue;
+        }
+        let target = if pgrp.parse() == Ok(pid) { -pid } else { pid };
+        // SAFETY: kill only sends a signal
+        unsafe {
+            libc::kill(target, signal);
+        }
+    }
+}
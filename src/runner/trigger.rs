@@ -0,0 +1,335 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+
+use crate::common::duration::format_duration;
+use crate::common::types::{ScheduleOverride, WorkflowConfig};
+use crate::runner::cron_explain;
+
+/// Something that decides when a workflow runs. The scheduler only asks for fire times, so a
+/// new schedule kind is a new implementation plus a line in [`triggers_for`].
+pub trait Trigger: Send + Sync + std::fmt::Debug {
+    /// The first fire time strictly after `after`, or `None` if it never fires again.
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>>;
+
+    /// The latest fire time in `(after, until]`, used to detect runs missed while down.
+    fn last_between(&self, after: DateTime<Utc>, until: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut last = None;
+        let mut cursor = after;
+        while let Some(t) = self.next_after(cursor).filter(|t| *t <= until) {
+            last = Some(t);
+            cursor = t;
+        }
+        last
+    }
+
+    /// The config key it comes from, e.g. `cron`.
+    fn kind(&self) -> &'static str;
+
+    /// Whether the workflow should run once when the runner starts.
+    fn fires_on_start(&self) -> bool {
+        false
+    }
+
+    /// Short human-readable form shown in the TUI.
+    fn describe(&self) -> String;
+
+    /// Longer English description, e.g. "every day at 03:00".
+    fn explain(&self) -> String {
+        self.describe()
+    }
+}
+
+#[derive(Debug)]
+pub struct CronTrigger {
+    expr: String,
+    schedule: Schedule,
+    timezone: Option<Tz>,
+}
+
+impl CronTrigger {
+    pub fn new(expr: &str, timezone: Option<Tz>) -> Result<Self> {
+        let schedule = Schedule::from_str(&normalize_cron(expr))
+            .with_context(|| format!("invalid cron expression: {}", expr))?;
+        Ok(Self {
+            expr: expr.to_string(),
+            schedule,
+            timezone,
+        })
+    }
+}
+
+impl Trigger for CronTrigger {
+    fn kind(&self) -> &'static str {
+        "cron"
+    }
+
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let next = match self.timezone {
+            Some(tz) => self.schedule.after(&after.with_timezone(&tz)).next().map(|t| t.to_utc()),
+            None => self.schedule.after(&after.with_timezone(&Local)).next().map(|t| t.to_utc()),
+        };
+        next
+    }
+
+    fn describe(&self) -> String {
+        match self.timezone {
+            Some(tz) => format!("{} {}", self.expr, tz.name()),
+            None => self.expr.clone(),
+        }
+    }
+
+    fn explain(&self) -> String {
+        let Some(text) = cron_explain::explain(&self.expr) else {
+            return self.describe();
+        };
+        match self.timezone {
+            Some(tz) => format!("{} ({})", text, tz.name()),
+            None => text,
+        }
+    }
+}
+
+/// Fires every `every`, aligned to multiples of the interval since the Unix epoch so the
+/// times stay stable across restarts (`every: 15m` fires at :00, :15, :30 and :45).
+#[derive(Debug)]
+pub struct IntervalTrigger {
+    every: Duration,
+}
+
+impl IntervalTrigger {
+    pub fn new(every: Duration) -> Result<Self> {
+        if every.as_millis() == 0 {
+            bail!("`every` must be greater than zero");
+        }
+        Ok(Self { every })
+    }
+
+    fn step_ms(&self) -> i64 {
+        self.every.as_millis() as i64
+    }
+}
+
+impl Trigger for IntervalTrigger {
+    fn kind(&self) -> &'static str {
+        "every"
+    }
+
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let step = self.step_ms();
+        let next = (after.timestamp_millis().div_euclid(step) + 1) * step;
+        Utc.timestamp_millis_opt(next).single()
+    }
+
+    fn last_between(&self, after: DateTime<Utc>, until: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let step = self.step_ms();
+        let last = until.timestamp_millis().div_euclid(step) * step;
+        Utc.timestamp_millis_opt(last)
+            .single()
+            .filter(|t| *t > after)
+    }
+
+    fn describe(&self) -> String {
+        format!("every {}", format_duration(&self.every))
+    }
+}
+
+/// Fires once at a fixed time.
+#[derive(Debug)]
+pub struct OnceTrigger {
+    at: DateTime<Utc>,
+}
+
+impl OnceTrigger {
+    /// Accepts RFC 3339, or `YYYY-MM-DDTHH:MM[:SS]` in `timezone` (local time when unset).
+    pub fn parse(at: &str, timezone: Option<Tz>) -> Result<Self> {
+        if let Ok(t) = DateTime::parse_from_rfc3339(at) {
+            return Ok(Self { at: t.with_timezone(&Utc) });
+        }
+        let naive = NaiveDateTime::parse_from_str(at, "%Y-%m-%dT%H:%M:%S")
+            .or_else(|_| NaiveDateTime::parse_from_str(at, "%Y-%m-%dT%H:%M"))
+            .with_context(|| format!("invalid `at` time: {}", at))?;
+        let at = match timezone {
+            Some(tz) => tz.from_local_datetime(&naive).earliest().map(|t| t.with_timezone(&Utc)),
+            None => Local.from_local_datetime(&naive).earliest().map(|t| t.with_timezone(&Utc)),
+        }
+        .with_context(|| format!("`at` time does not exist in this timezone: {}", at))?;
+        Ok(Self { at })
+    }
+}
+
+impl Trigger for OnceTrigger {
+    fn kind(&self) -> &'static str {
+        "at"
+    }
+
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        (self.at > after).then_some(self.at)
+    }
+
+    fn describe(&self) -> String {
+        format!("at {}", self.at.with_timezone(&Local).format("%Y-%m-%d %H:%M"))
+    }
+
+    fn explain(&self) -> String {
+        format!("once at {}", self.at.with_timezone(&Local).format("%Y-%m-%d %H:%M"))
+    }
+}
+
+/// Runs once whenever the runner starts.
+#[derive(Debug)]
+pub struct OnStartTrigger;
+
+impl Trigger for OnStartTrigger {
+    fn kind(&self) -> &'static str {
+        "on_start"
+    }
+
+    fn next_after(&self, _after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    fn fires_on_start(&self) -> bool {
+        true
+    }
+
+    fn describe(&self) -> String {
+        "on start".to_string()
+    }
+}
+
+/// Runs when a watched path changes. Events come from [`crate::runner::file_watch`]; it never
+/// fires on a clock.
+#[derive(Debug)]
+pub struct WatchTrigger {
+    paths: Vec<String>,
+}
+
+impl Trigger for WatchTrigger {
+    fn kind(&self) -> &'static str {
+        "watch"
+    }
+
+    fn next_after(&self, _after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    fn describe(&self) -> String {
+        format!("on change: {}", self.paths.join(" "))
+    }
+
+    fn explain(&self) -> String {
+        format!("whenever {} changes", self.paths.join(" or "))
+    }
+}
+
+/// All triggers configured for a workflow. A workflow may combine several, e.g. `on_start`
+/// with a cron schedule.
+pub fn triggers_for(wf: &WorkflowConfig) -> Result<Vec<Box<dyn Trigger>>> {
+    let mut triggers: Vec<Box<dyn Trigger>> = Vec::new();
+    if let Some(expr) = &wf.cron {
+        triggers.push(Box::new(CronTrigger::new(expr, wf.timezone)?));
+    }
+    if let Some(every) = wf.every {
+        triggers.push(Box::new(IntervalTrigger::new(every)?));
+    }
+    if let Some(at) = &wf.at {
+        triggers.push(Box::new(OnceTrigger::parse(at, wf.timezone)?));
+    }
+    if wf.on_start {
+        triggers.push(Box::new(OnStartTrigger));
+    }
+    if let Some(watch) = &wf.watch {
+        if watch.paths.is_empty() {
+            bail!("`watch.paths` must not be empty");
+        }
+        triggers.push(Box::new(WatchTrigger {
+            paths: watch.paths.clone(),
+        }));
+    }
+    if triggers.is_empty() {
+        bail!("no schedule: set one of `cron`, `every`, `at`, `on_start` or `watch`");
+    }
+    Ok(triggers)
+}
+
+/// `wf`'s triggers, or an override's interval in their place while the override lasts.
+pub fn effective_triggers(
+    wf: &WorkflowConfig,
+    schedule_override: Option<&ScheduleOverride>,
+) -> Result<Vec<Box<dyn Trigger>>> {
+    match schedule_override.and_then(|o| o.every) {
+        Some(every) => Ok(vec![Box::new(IntervalTrigger::new(every)?)]),
+        None => triggers_for(wf),
+    }
+}
+
+/// Earliest fire time after `after` across `triggers`.
+pub fn next_fire(triggers: &[Box<dyn Trigger>], after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    triggers.iter().filter_map(|t| t.next_after(after)).min()
+}
+
+/// The first `count` fire times after `after` across `triggers`.
+pub fn next_fires(
+    triggers: &[Box<dyn Trigger>],
+    after: DateTime<Utc>,
+    count: usize,
+) -> Vec<DateTime<Utc>> {
+    let mut fires = Vec::with_capacity(count);
+    let mut cursor = after;
+    while fires.len() < count {
+        let Some(next) = next_fire(triggers, cursor) else { break };
+        fires.push(next);
+        cursor = next;
+    }
+    fires
+}
+
+/// Latest fire time in `(after, until]` across `triggers`.
+pub fn last_fire_between(
+    triggers: &[Box<dyn Trigger>],
+    after: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    triggers.iter().filter_map(|t| t.last_between(after, until)).max()
+}
+
+/// Schedule label for display, e.g. `0 3 * * *` or `every 15m, on start`.
+pub fn describe(wf: &WorkflowConfig) -> String {
+    match triggers_for(wf) {
+        Ok(triggers) => describe_all(&triggers),
+        Err(_) => wf.cron.clone().unwrap_or_default(),
+    }
+}
+
+/// English description of a workflow's schedule, e.g. `every day at 03:00, on start`.
+pub fn explain(wf: &WorkflowConfig) -> String {
+    match triggers_for(wf) {
+        Ok(triggers) => explain_all(&triggers),
+        Err(e) => format!("invalid schedule: {}", e),
+    }
+}
+
+/// [`describe`] for triggers already parsed.
+pub fn describe_all(triggers: &[Box<dyn Trigger>]) -> String {
+    triggers.iter().map(|t| t.describe()).collect::<Vec<_>>().join(", ")
+}
+
+/// [`explain`] for triggers already parsed.
+pub fn explain_all(triggers: &[Box<dyn Trigger>]) -> String {
+    triggers.iter().map(|t| t.explain()).collect::<Vec<_>>().join(", ")
+}
+
+/// Normalize standard 5-field cron to the 6-field format (with seconds) required by the cron crate.
+pub fn normalize_cron(expr: &str) -> String {
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    if fields.len() == 5 {
+        format!("0 {}", expr)
+    } else {
+        expr.to_string()
+    }
+}
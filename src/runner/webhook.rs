@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tracing::info;
+
+use crate::common::config::load_config;
+use crate::common::types::{TokenScope, TriggerSource};
+use crate::runner::auth::lookup_token;
+use crate::runner::executor::RunRequest;
+use crate::runner::scheduler::SchedulerCommand;
+use crate::runner::server::writes_disabled;
+
+#[derive(Clone)]
+struct WebhookState {
+    scheduler_tx: mpsc::Sender<SchedulerCommand>,
+    read_only: bool,
+}
+
+type Reply = (StatusCode, Json<Value>);
+
+/// Serve `POST /trigger/<workflow>` on `addr` for systems that speak HTTP but not gRPC.
+pub async fn serve(
+    addr: &str,
+    scheduler_tx: mpsc::Sender<SchedulerCommand>,
+    read_only: bool,
+) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind webhook listener on {}", addr))?;
+    info!("webhook endpoint listening on http://{}/trigger/<workflow>", addr);
+
+    let app = Router::new()
+        .route("/trigger/:workflow", post(trigger))
+        .with_state(WebhookState {
+            scheduler_tx,
+            read_only,
+        });
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// The token comes from `Authorization: Bearer <token>` or, for senders that cannot set
+/// headers (GitHub webhooks), a `?token=` query parameter. A JSON body with an `args` array
+/// replaces the workflow's configured args; any other body is ignored.
+async fn trigger(
+    State(state): State<WebhookState>,
+    Path(workflow): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Reply {
+    let presented = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or(query.get("token").map(String::as_str));
+    let Some(presented) = presented else {
+        return error(StatusCode::UNAUTHORIZED, "missing token");
+    };
+
+    let grant = match lookup_token(presented) {
+        Ok(Some(grant)) => grant,
+        Ok(None) => return error(StatusCode::UNAUTHORIZED, "invalid token"),
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+    if !grant.allows(TokenScope::Trigger, Some(&workflow)) {
+        return error(
+            StatusCode::FORBIDDEN,
+            &format!("token '{}' is not allowed to do this", grant.token_name),
+        );
+    }
+    if let Some(reason) = writes_disabled(state.read_only) {
+        return error(StatusCode::FORBIDDEN, &reason);
+    }
+
+    match load_config() {
+        Ok(config) if config.workflows.iter().any(|w| w.name == workflow) => {}
+        Ok(_) => {
+            return error(StatusCode::NOT_FOUND, &format!("workflow not found: {}", workflow));
+        }
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+
+    let args = serde_json::from_slice::<Value>(&body)
+        .ok()
+        .and_then(|v| serde_json::from_value::<Vec<String>>(v.get("args")?.clone()).ok());
+    let request = RunRequest {
+        args,
+        source: TriggerSource::Webhook,
+        ..Default::default()
+    };
+
+    info!(workflow = %workflow, token = %grant.token_name, "webhook trigger");
+    if let Err(e) = state
+        .scheduler_tx
+        .send(SchedulerCommand::Trigger(workflow.clone(), request))
+        .await
+    {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string());
+    }
+
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({ "message": format!("workflow {} triggered", workflow) })),
+    )
+}
+
+fn error(status: StatusCode, message: &str) -> Reply {
+    (status, Json(json!({ "error": message })))
+}
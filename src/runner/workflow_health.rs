@@ -0,0 +1,120 @@
+use chrono::{DateTime, Duration, Local, Utc};
+
+use crate::common::types::{DurationSample, Execution, ExecutionStatus, WorkflowConfig};
+use crate::runner::next_runs::{NextRuns, ScheduleInfo};
+
+/// How late a scheduled run may start before the workflow counts as stale, on top of its
+/// `jitter`: room for a busy runner, a queue or a slow catch-up.
+const STALE_GRACE: Duration = Duration::minutes(5);
+
+/// How a workflow is doing, as the workflow list shows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkflowHealth {
+    /// The latest run succeeded and the schedule is being kept.
+    Ok,
+    /// The latest finished run failed.
+    Failing,
+    /// A scheduled run is overdue: the schedule fired since the latest run, but nothing ran.
+    Stale,
+    /// The latest run succeeded, but strayed from the pinned baseline past its limits.
+    Regressed,
+    Paused,
+    /// The schedule will never fire again, e.g. an `at` time that has passed.
+    Disabled,
+    /// The schedule doesn't parse, a script is missing, or the latest run couldn't start.
+    ConfigError,
+    NeverRun,
+}
+
+impl WorkflowHealth {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkflowHealth::Ok => "ok",
+            WorkflowHealth::Failing => "failing",
+            WorkflowHealth::Stale => "stale",
+            WorkflowHealth::Regressed => "regressed",
+            WorkflowHealth::Paused => "paused",
+            WorkflowHealth::Disabled => "disabled",
+            WorkflowHealth::ConfigError => "config_error",
+            WorkflowHealth::NeverRun => "never_run",
+        }
+    }
+}
+
+/// What the Runner knows about a workflow's runs, besides its config and schedule.
+pub struct RunFacts<'a> {
+    /// The latest execution, possibly still running.
+    pub last: Option<&'a Execution>,
+    /// Finished runs, newest first.
+    pub history: &'a [DurationSample],
+    /// When the scheduler last skipped an automatic run, e.g. because `should_run` said no.
+    pub skipped_at: Option<DateTime<Utc>>,
+    pub paused: bool,
+    /// Why its scripts can't be run, see [`crate::runner::executor::script_error`]; read by the
+    /// caller, since it touches the filesystem.
+    pub script_error: Option<String>,
+}
+
+/// `wf`'s health and why. The first state that applies wins: config error, paused, disabled,
+/// never run, failing, stale, regressed, then ok. A skipped run keeps the schedule as well as
+/// one that ran.
+pub fn assess(
+    wf: &WorkflowConfig,
+    next_runs: &NextRuns,
+    schedule: &ScheduleInfo,
+    facts: &RunFacts,
+) -> (WorkflowHealth, String) {
+    let RunFacts { last, history, skipped_at, paused, .. } = *facts;
+    let now = Utc::now();
+    let finished = history.first();
+    if !schedule.valid {
+        return (WorkflowHealth::ConfigError, schedule.description.clone());
+    }
+    if let Some(problem) = &facts.script_error {
+        return (WorkflowHealth::ConfigError, problem.clone());
+    }
+    if let Some(run) = finished.filter(|r| r.status == ExecutionStatus::ConfigError) {
+        let detail = format!("the run at {} could not start", local_time(run.started_at));
+        return (WorkflowHealth::ConfigError, detail);
+    }
+    if paused {
+        return (WorkflowHealth::Paused, "scheduled runs are skipped".to_string());
+    }
+    let fires_on_events = wf.on_start || wf.watch.is_some();
+    if schedule.next_run.is_none() && !fires_on_events && !schedule.manual {
+        return (WorkflowHealth::Disabled, "the schedule will not fire again".to_string());
+    }
+    let running = last.is_some_and(|e| e.status == ExecutionStatus::Running);
+    let Some(latest) = finished else {
+        let detail = if running { "the first run is in progress" } else { "no runs yet" };
+        return (WorkflowHealth::NeverRun, detail.to_string());
+    };
+    if latest.status.is_failure() {
+        let detail = format!("the run at {} failed", local_time(latest.started_at));
+        return (WorkflowHealth::Failing, detail);
+    }
+    // an override puts scheduled runs off or in between, so none is missed on its watch
+    let on_schedule = !schedule.manual && schedule.schedule_override.is_none();
+    if let Some(last) = last.filter(|_| !running && on_schedule) {
+        let jitter = wf.jitter.and_then(|j| Duration::from_std(j).ok()).unwrap_or_default();
+        let kept = skipped_at.map_or(last.started_at, |at| at.max(last.started_at));
+        let missed = next_runs
+            .next_after(wf, kept)
+            .filter(|due| *due + STALE_GRACE + jitter <= now);
+        if let Some(due) = missed {
+            let detail = format!("the run due at {} has not happened", local_time(due));
+            return (WorkflowHealth::Stale, detail);
+        }
+    }
+    let latest_run = last.filter(|e| e.started_at == latest.started_at);
+    if let Some(deviation) = latest_run.and_then(|e| e.deviation.as_ref()) {
+        let detail = format!("the run at {} {}", local_time(latest.started_at), deviation);
+        return (WorkflowHealth::Regressed, detail);
+    }
+    let detail = format!("the run at {} succeeded", local_time(latest.started_at));
+    (WorkflowHealth::Ok, detail)
+}
+
+fn local_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()
+}
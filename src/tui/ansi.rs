@@ -0,0 +1,144 @@
+use std::ops::Range;
+
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::runner::output;
+
+/// A log line with escape sequences removed. `styles` covers the parts of `text` that SGR
+/// sequences colored; the rest uses the default style.
+#[derive(Debug, Clone, Default)]
+pub struct LogLine {
+    pub text: String,
+    pub styles: Vec<(Range<usize>, Style)>,
+}
+
+/// Dim the `O|` tag of a stdout line and show the `E|` of a stderr line in red, and dim the
+/// timestamp after it, if any. Both come before anything the script colored.
+pub fn style_stream_tag(line: &mut LogLine) {
+    let color = match line.text.get(..2) {
+        Some("O|") => Color::DarkGray,
+        Some("E|") => Color::Red,
+        _ => return,
+    };
+    let stamp = 2..2 + output::timestamp_len(&line.text[2..]);
+    if !stamp.is_empty() {
+        line.styles.insert(0, (stamp, Style::default().fg(Color::DarkGray)));
+    }
+    line.styles.insert(0, (0..2, Style::default().fg(color)));
+}
+
+/// Parse `line`, turning SGR sequences (`ESC [ … m`) into styles and dropping every other
+/// escape sequence. With `keep_styles` false the colors are discarded too. Each line starts
+/// unstyled; tools that color output reset at the end of the line anyway.
+pub fn parse(line: &str, keep_styles: bool) -> LogLine {
+    let mut out = LogLine::default();
+    let mut style = Style::default();
+    let mut style_start = 0;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((_, ch)) = chars.next() {
+        if ch != '\x1b' {
+            out.text.push(ch);
+            continue;
+        }
+        match chars.peek().map(|&(_, c)| c) {
+            // CSI: parameters, then a final byte in @..~
+            Some('[') => {
+                chars.next();
+                let mut params = String::new();
+                let mut last = None;
+                for (_, c) in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        last = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+                if last == Some('m') && keep_styles {
+                    let next = apply_sgr(style, &params);
+                    if next != style {
+                        push_style(&mut out, style, style_start);
+                        style = next;
+                        style_start = out.text.len();
+                    }
+                }
+            }
+            // OSC: up to BEL or ESC \
+            Some(']') => {
+                chars.next();
+                while let Some((_, c)) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek().map(|&(_, c)| c) == Some('\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // two-byte sequences such as ESC ( B
+            Some(_) => {
+                chars.next();
+            }
+            None => {}
+        }
+    }
+    push_style(&mut out, style, style_start);
+    out
+}
+
+fn push_style(line: &mut LogLine, style: Style, start: usize) {
+    if style != Style::default() && start < line.text.len() {
+        line.styles.push((start..line.text.len(), style));
+    }
+}
+
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let codes: Vec<u16> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+    let mut i = 0;
+    while i < codes.len() {
+        match codes[i] {
+            0 => style = Style::default(),
+            1 => style = style.add_modifier(Modifier::BOLD),
+            2 => style = style.add_modifier(Modifier::DIM),
+            3 => style = style.add_modifier(Modifier::ITALIC),
+            4 => style = style.add_modifier(Modifier::UNDERLINED),
+            7 => style = style.add_modifier(Modifier::REVERSED),
+            9 => style = style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style = style.remove_modifier(Modifier::ITALIC),
+            24 => style = style.remove_modifier(Modifier::UNDERLINED),
+            27 => style = style.remove_modifier(Modifier::REVERSED),
+            29 => style = style.remove_modifier(Modifier::CROSSED_OUT),
+            n @ 30..=37 => style.fg = Some(Color::Indexed((n - 30) as u8)),
+            39 => style.fg = None,
+            n @ 40..=47 => style.bg = Some(Color::Indexed((n - 40) as u8)),
+            49 => style.bg = None,
+            n @ 90..=97 => style.fg = Some(Color::Indexed((n - 90 + 8) as u8)),
+            n @ 100..=107 => style.bg = Some(Color::Indexed((n - 100 + 8) as u8)),
+            n @ (38 | 48) => {
+                let color = match codes.get(i + 1) {
+                    Some(5) => {
+                        let c = codes.get(i + 2).map(|&v| Color::Indexed(v as u8));
+                        i += 2;
+                        c
+                    }
+                    Some(2) => {
+                        let rgb = codes.get(i + 2..i + 5);
+                        i += 4;
+                        rgb.map(|v| Color::Rgb(v[0] as u8, v[1] as u8, v[2] as u8))
+                    }
+                    _ => None,
+                };
+                if n == 38 {
+                    style.fg = color;
+                } else {
+                    style.bg = color;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    style
+}
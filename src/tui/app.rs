@@ -1,13 +1,85 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
-use std::path::PathBuf;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use tonic::transport::Channel;
+use chrono::{Local, TimeZone, Utc};
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
 
-use crate::proto::{ExecutionInfo, WorkflowInfo};
-use crate::proto::workflow_service_client::WorkflowServiceClient;
-use crate::tui::client;
+use crate::common::types::{SeverityConfig, TriggerSource};
+use crate::proto::{
+    BaselineResponse, DurationPoint, ExecutionInfo, ExplainScheduleResponse, MissedRunInfo,
+    SchedulerDecision, SnoozeResponse, TriggerResponse, WorkflowEvent, WorkflowInfo,
+    WorkflowStatsResponse, WorkflowStatusResponse,
+};
+use crate::tui::ViewOptions;
+use crate::tui::ansi::{self, LogLine};
+use crate::tui::bookmarks;
+use crate::tui::export::{self, ExportKind, ExportPrompt, PipeRequest};
+use crate::tui::i18n::{self, Strings, fill};
+use crate::tui::keymap::Keymap;
+use crate::tui::client::{self, Backoff, CachedClient, ConnectOptions};
+use crate::runner::log_compress;
+use crate::runner::severity::Detector;
+use crate::tui::log_tail::{self, LogChunk};
+
+/// RPCs taking longer than this are announced in the status bar.
+const SLOW_TASK: Duration = Duration::from_millis(300);
+
+/// How often the panels are refreshed while the runner is reachable but its events are not.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Refreshes while subscribed to the runner's events, in case something changed unannounced.
+const WATCHED_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Decisions the decisions screen holds; the runner keeps as many.
+const MAX_DECISIONS: usize = 500;
+
+/// Give up on an optimistic state the runner never confirmed after this long.
+const PENDING_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A state change shown locally before the runner has confirmed it.
+#[derive(Debug, Clone, Copy)]
+enum Pending {
+    /// Triggered; confirmed once the runner reports a run newer than `last_run_at`.
+    Starting { last_run_at: i64 },
+    Paused(bool),
+}
+
+impl Pending {
+    fn confirmed_by(&self, wf: &WorkflowInfo) -> bool {
+        match *self {
+            Pending::Starting { last_run_at } => {
+                wf.status == "running" || wf.last_run_at > last_run_at
+            }
+            Pending::Paused(paused) => wf.paused == paused,
+        }
+    }
+
+    fn apply(&self, wf: &mut WorkflowInfo) {
+        match *self {
+            Pending::Starting { .. } => wf.status = "starting".to_string(),
+            Pending::Paused(paused) => wf.paused = paused,
+        }
+    }
+}
+
+/// Refresh state of a panel, shown in its title and as an inline error row.
+#[derive(Debug, Clone, Default)]
+pub struct PanelStatus {
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+impl PanelStatus {
+    fn finish<T>(&mut self, result: &Result<T>) {
+        self.loading = false;
+        self.error = result.as_ref().err().map(|e| e.to_string());
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Panel {
@@ -16,141 +88,1188 @@ pub enum Panel {
     Log,
 }
 
+/// Everything the render loop hears from background work (RPCs, log tailing, the runner's
+/// events), which only talks to the App through these. Terminal input is read by the loop.
+pub enum Message {
+    Task(u64, Box<TaskOutput>),
+    Log { tail_id: u64, chunk: LogChunk },
+    Watch { watch_id: u64, update: WatchUpdate },
+}
+
+/// Progress of the subscription to the runner's events.
+pub enum WatchUpdate {
+    Subscribed,
+    Event(Box<WorkflowEvent>),
+    /// The stream closed, e.g. because the runner stopped or is too old to stream events.
+    Ended(Result<()>),
+}
+
+/// The open subscription to the runner's events. Until it is `subscribed`, the panels are
+/// polled.
+struct Watch {
+    id: u64,
+    handle: AbortHandle,
+    subscribed: bool,
+}
+
+/// Result of an RPC that ran in a background task.
+pub enum TaskOutput {
+    Workflows(Result<Vec<WorkflowInfo>>),
+    Executions {
+        workflow: String,
+        /// Open this execution's log once loaded, e.g. the newest after switching workflows.
+        open: Option<usize>,
+        result: Result<WorkflowStatusResponse>,
+    },
+    Triggered {
+        previous: WorkflowInfo,
+        result: Result<TriggerResponse>,
+    },
+    PauseSet {
+        previous: WorkflowInfo,
+        paused: bool,
+        result: Result<bool>,
+    },
+    Snoozed {
+        workflow: String,
+        result: Result<SnoozeResponse>,
+    },
+    BaselineSet {
+        workflow: String,
+        clear: bool,
+        result: Result<BaselineResponse>,
+    },
+    Deleted(Result<bool>),
+    Restored(Result<u32>),
+    Durations {
+        workflow: String,
+        result: Result<Vec<DurationPoint>>,
+    },
+    Stats {
+        workflow: String,
+        result: Result<WorkflowStatsResponse>,
+    },
+    Missed(Result<Vec<MissedRunInfo>>),
+    Decisions {
+        workflow: Option<String>,
+        result: Result<Vec<SchedulerDecision>>,
+    },
+    Explained {
+        workflow: String,
+        result: Result<ExplainScheduleResponse>,
+    },
+}
+
+/// Duration chart screen for one workflow, opened over the main layout.
+pub struct DurationsView {
+    pub workflow: String,
+    /// Oldest first, ready for plotting.
+    pub points: Vec<DurationPoint>,
+    pub status: PanelStatus,
+}
+
+/// Popup explaining when one workflow runs next, opened over the main layout.
+pub struct ExplainView {
+    pub workflow: String,
+    pub explanation: Option<ExplainScheduleResponse>,
+    pub status: PanelStatus,
+}
+
+/// The scheduler's recent decisions, about one workflow or all of them, opened over the main
+/// layout. New ones arrive with the runner's events.
+pub struct DecisionsView {
+    /// `None` shows every workflow.
+    pub workflow: Option<String>,
+    /// Newest first.
+    pub decisions: Vec<SchedulerDecision>,
+    pub scroll: usize,
+    /// Rows shown at the last draw; the page size for paging keys.
+    pub height: Cell<usize>,
+    pub status: PanelStatus,
+}
+
+/// Detail popup for one workflow, opened over the main layout.
+pub struct DetailView {
+    pub workflow: String,
+    pub stats: Option<WorkflowStatsResponse>,
+    pub status: PanelStatus,
+}
+
+/// The runner stopped answering. Refreshes pause and the workflow list is retried on a new
+/// channel with growing delays.
+pub struct Disconnected {
+    pub since: Instant,
+    pub retry_at: Instant,
+    backoff: Backoff,
+}
+
+impl Disconnected {
+    fn new() -> Self {
+        let mut backoff = Backoff::default();
+        let now = Instant::now();
+        Self {
+            since: now,
+            retry_at: now + backoff.next_delay(),
+            backoff,
+        }
+    }
+}
+
+/// `/` search in the Log panel. Matching ignores ASCII case.
+#[derive(Debug, Default)]
+pub struct LogSearch {
+    pub query: String,
+    /// The query is being typed; keys go to the input line instead of the panels.
+    pub editing: bool,
+}
+
+/// Byte ranges of `query` in `line`, ignoring ASCII case.
+pub fn find_matches(line: &str, query: &str) -> Vec<(usize, usize)> {
+    let (hay, needle) = (line.as_bytes(), query.as_bytes());
+    let mut found = Vec::new();
+    if needle.is_empty() || needle.len() > hay.len() {
+        return found;
+    }
+    let mut i = 0;
+    while i + needle.len() <= hay.len() {
+        if hay[i..i + needle.len()].eq_ignore_ascii_case(needle) {
+            found.push((i, i + needle.len()));
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+/// The first of the ascending `lines` after `from` (or the last before it), wrapping around.
+fn step(lines: &[usize], from: usize, forward: bool) -> Option<usize> {
+    let target = if forward {
+        lines.iter().find(|&&i| i > from).or(lines.first())
+    } else {
+        lines.iter().rev().find(|&&i| i < from).or(lines.last())
+    };
+    target.copied()
+}
+
+/// An RPC in flight; Esc aborts all of them.
+struct Task {
+    id: u64,
+    label: &'static str,
+    started: Instant,
+    handle: AbortHandle,
+}
+
 pub struct App {
-    pub client: WorkflowServiceClient<Channel>,
+    pub client: CachedClient,
+    view: ViewOptions,
+    pub keymap: Keymap,
+    /// The TUI's words, in the configured language.
+    pub text: &'static Strings,
+    tasks: Vec<Task>,
+    next_task_id: u64,
+    tx: mpsc::UnboundedSender<Message>,
+    pub rx: mpsc::UnboundedReceiver<Message>,
+    last_refresh: Option<Instant>,
+    pub disconnected: Option<Disconnected>,
 
     pub workflows: Vec<WorkflowInfo>,
     pub selected_workflow: usize,
+    pending: HashMap<String, Vec<(Pending, Instant)>>,
+    pub workflows_status: PanelStatus,
+    /// Schedules missed while the runner was down that have not run since.
+    pub missed: Vec<MissedRunInfo>,
 
     pub executions: Vec<ExecutionInfo>,
     pub selected_execution: usize,
+    pub executions_status: PanelStatus,
 
-    pub log_lines: Vec<String>,
+    pub log_lines: Vec<LogLine>,
     pub log_scroll: usize,
-    pub log_path: Option<PathBuf>,
-    pub log_file_pos: u64,
+    /// Keep the cursor on the last line as the log grows. Any manual scroll turns it off.
+    pub follow: bool,
+    /// Lines the Log panel showed in the last frame; the page size for paging keys.
+    pub log_height: Cell<usize>,
+    pub search: Option<LogSearch>,
+    /// Line to show once the log being opened has loaded.
+    jump_on_load: Option<usize>,
+    /// Finds error lines with the patterns of the workflow whose log is open.
+    error_detector: Option<Detector>,
+    /// Indices of the log lines that are errors, ascending.
+    pub error_lines: Vec<usize>,
+    /// Bookmarked lines of the open log, ascending; kept in a file next to the log.
+    pub bookmarks: Vec<usize>,
+    /// Where `v` started a selection; it extends to the current line.
+    pub selection_start: Option<usize>,
+    pub export: Option<ExportPrompt>,
+    /// Taken by the render loop, which leaves the alternate screen to run it.
+    pub pipe_request: Option<PipeRequest>,
+    /// A log for the render loop to open in the pager, likewise.
+    pub open_request: Option<PathBuf>,
+    log_tail: Option<(u64, AbortHandle)>,
+    next_tail_id: u64,
+    watch: Option<Watch>,
+    next_watch_id: u64,
 
     pub active_panel: Panel,
+    pub durations: Option<DurationsView>,
+    pub detail: Option<DetailView>,
+    pub decisions: Option<DecisionsView>,
+    pub explain: Option<ExplainView>,
+    /// Workflow waiting for a y/n answer before it is triggered.
+    pub confirm_trigger: Option<String>,
+    /// Scroll offset of the `?` help popup while it is open.
+    pub help_scroll: Option<u16>,
+    /// Lines the help popup showed at the last draw.
+    pub help_height: Cell<u16>,
     pub status_message: String,
     pub should_quit: bool,
 }
 
 impl App {
-    pub async fn new() -> Result<Self> {
-        let client = client::connect().await?;
+    pub fn new(options: &ConnectOptions, view: ViewOptions) -> Result<Self> {
+        let client = CachedClient::new(options)?;
+        let (keymap, problems) = Keymap::load();
+        let (tx, rx) = mpsc::unbounded_channel();
         Ok(Self {
             client,
+            view,
+            keymap,
+            text: i18n::load(),
+            tasks: Vec::new(),
+            next_task_id: 0,
+            tx,
+            rx,
+            last_refresh: None,
+            disconnected: None,
             workflows: Vec::new(),
             selected_workflow: 0,
+            pending: HashMap::new(),
+            workflows_status: PanelStatus::default(),
+            missed: Vec::new(),
             executions: Vec::new(),
             selected_execution: 0,
+            executions_status: PanelStatus::default(),
             log_lines: Vec::new(),
             log_scroll: 0,
-            log_path: None,
-            log_file_pos: 0,
+            follow: true,
+            log_height: Cell::new(0),
+            search: None,
+            jump_on_load: None,
+            error_detector: None,
+            error_lines: Vec::new(),
+            bookmarks: Vec::new(),
+            selection_start: None,
+            export: None,
+            pipe_request: None,
+            open_request: None,
+            log_tail: None,
+            next_tail_id: 0,
+            watch: None,
+            next_watch_id: 0,
             active_panel: Panel::Workflows,
-            status_message: String::new(),
+            durations: None,
+            detail: None,
+            decisions: None,
+            explain: None,
+            confirm_trigger: None,
+            help_scroll: None,
+            help_height: Cell::new(0),
+            status_message: problems.join("; "),
             should_quit: false,
         })
     }
 
-    pub async fn refresh_workflows(&mut self) -> Result<()> {
-        self.workflows = client::list_workflows(&mut self.client).await?;
-        if self.selected_workflow >= self.workflows.len() && !self.workflows.is_empty() {
-            self.selected_workflow = self.workflows.len() - 1;
+    /// Run an RPC in the background; its output comes back as [`Message::Task`] and is
+    /// handed to [`App::apply`].
+    fn spawn(&mut self, label: &'static str, fut: impl Future<Output = TaskOutput> + Send + 'static) {
+        let id = self.next_task_id;
+        self.next_task_id += 1;
+        let tx = self.tx.clone();
+        let handle = tokio::spawn(async move {
+            let _ = tx.send(Message::Task(id, Box::new(fut.await)));
+        });
+        self.tasks.push(Task {
+            id,
+            label,
+            started: Instant::now(),
+            handle: handle.abort_handle(),
+        });
+    }
+
+    /// Label of the oldest RPC that has been in flight for a noticeable time.
+    pub fn busy_with(&self) -> Option<&'static str> {
+        self.tasks
+            .first()
+            .filter(|t| t.started.elapsed() >= SLOW_TASK)
+            .map(|t| t.label)
+    }
+
+    /// Abort every in-flight RPC. Optimistic states stay until the next refresh settles them,
+    /// since a cancelled mutation may or may not have reached the runner.
+    pub fn cancel_tasks(&mut self) {
+        if self.tasks.is_empty() {
+            return;
         }
-        Ok(())
+        for task in self.tasks.drain(..) {
+            task.handle.abort();
+        }
+        self.client.cancel_inflight();
+        self.workflows_status.loading = false;
+        self.executions_status.loading = false;
+        self.status_message = self.text.cancelled.to_string();
     }
 
-    pub async fn refresh_executions(&mut self) -> Result<()> {
-        if let Some(wf) = self.workflows.get(self.selected_workflow) {
-            let name = wf.name.clone();
-            let status = client::get_workflow_status(&mut self.client, &name).await?;
-            self.executions = status.executions;
-            if self.selected_execution >= self.executions.len() && !self.executions.is_empty() {
-                self.selected_execution = self.executions.len() - 1;
+    pub fn apply(&mut self, id: u64, output: TaskOutput) {
+        let Some(pos) = self.tasks.iter().position(|t| t.id == id) else {
+            // cancelled after it had already finished
+            return;
+        };
+        self.tasks.remove(pos);
+
+        match output {
+            TaskOutput::Workflows(result) => {
+                self.workflows_status.finish(&result);
+                self.track_connection(&result);
+                let Ok(workflows) = result else { return };
+                let first_load = self.workflows.is_empty();
+                self.workflows = workflows;
+                self.reconcile_pending();
+                if self.selected_workflow >= self.workflows.len() && !self.workflows.is_empty() {
+                    self.selected_workflow = self.workflows.len() - 1;
+                }
+                if first_load && !self.workflows.is_empty() {
+                    self.load_executions(Some(0));
+                }
+            }
+            TaskOutput::Executions {
+                workflow,
+                open,
+                result,
+            } => {
+                // the user moved on to another workflow meanwhile
+                if self.selected_workflow_name() != Some(workflow.as_str()) {
+                    return;
+                }
+                self.executions_status.finish(&result);
+                let Ok(status) = result else { return };
+                self.executions = status.executions;
+                if self.selected_execution >= self.executions.len() && !self.executions.is_empty() {
+                    self.selected_execution = self.executions.len() - 1;
+                }
+                if let Some(idx) = open.filter(|_| !self.executions.is_empty()) {
+                    self.select_execution(idx.min(self.executions.len() - 1));
+                }
+            }
+            TaskOutput::Triggered { previous, result } => {
+                self.client.invalidate();
+                match result {
+                    Ok(resp) => self.status_message = resp.message,
+                    Err(e) => {
+                        self.rollback_pending(&previous.name.clone(), previous);
+                        self.status_message = fill(self.text.error, &[&e]);
+                    }
+                }
+            }
+            TaskOutput::PauseSet {
+                previous,
+                paused,
+                result,
+            } => {
+                self.client.invalidate();
+                let name = previous.name.clone();
+                self.status_message = match result {
+                    Ok(_) if paused => fill(self.text.paused_message, &[&name]),
+                    Ok(_) => fill(self.text.resumed, &[&name]),
+                    Err(e) => {
+                        self.rollback_pending(&name, previous);
+                        fill(self.text.error, &[&e])
+                    }
+                };
+            }
+            TaskOutput::Snoozed { workflow, result } => {
+                self.client.invalidate();
+                self.status_message = match result {
+                    Ok(snoozed) => {
+                        let at = Utc.timestamp_opt(snoozed.skipped_at, 0).unwrap();
+                        let at = at.with_timezone(&Local).format("%m-%d %H:%M");
+                        fill(self.text.snoozed, &[&workflow, &at])
+                    }
+                    Err(e) => fill(self.text.error, &[&e]),
+                };
+            }
+            TaskOutput::BaselineSet { workflow, clear, result } => {
+                self.client.invalidate();
+                self.status_message = match result {
+                    Ok(_) if clear => fill(self.text.baseline_unpinned, &[&workflow]),
+                    Ok(_) => fill(self.text.baseline_pinned, &[&workflow]),
+                    Err(e) => fill(self.text.error, &[&e]),
+                };
+            }
+            TaskOutput::Deleted(result) => {
+                self.client.invalidate();
+                match result {
+                    Ok(true) => {
+                        self.status_message = self.text.deleted.to_string();
+                        self.load_executions(Some(self.selected_execution));
+                    }
+                    Ok(false) => {}
+                    Err(e) => self.status_message = fill(self.text.error, &[&e]),
+                }
+            }
+            TaskOutput::Durations { workflow, result } => {
+                let Some(view) = self.durations.as_mut().filter(|v| v.workflow == workflow) else {
+                    return;
+                };
+                view.status.finish(&result);
+                if let Ok(mut points) = result {
+                    points.reverse();
+                    view.points = points;
+                }
+            }
+            TaskOutput::Stats { workflow, result } => {
+                let Some(view) = self.detail.as_mut().filter(|v| v.workflow == workflow) else {
+                    return;
+                };
+                view.status.finish(&result);
+                if let Ok(stats) = result {
+                    view.stats = Some(stats);
+                }
+            }
+            TaskOutput::Decisions { workflow, result } => {
+                let Some(view) = self.decisions.as_mut().filter(|v| v.workflow == workflow) else {
+                    return;
+                };
+                view.status.finish(&result);
+                if let Ok(decisions) = result {
+                    view.decisions = decisions;
+                    view.scroll = 0;
+                }
+            }
+            TaskOutput::Explained { workflow, result } => {
+                let Some(view) = self.explain.as_mut().filter(|v| v.workflow == workflow) else {
+                    return;
+                };
+                view.status.finish(&result);
+                if let Ok(explanation) = result {
+                    view.explanation = Some(explanation);
+                }
+            }
+            // a runner too old to report missed runs just shows no banner
+            TaskOutput::Missed(result) => self.missed = result.unwrap_or_default(),
+            TaskOutput::Restored(result) => {
+                self.client.invalidate();
+                match result {
+                    Ok(restored) => {
+                        let count = self.text.executions_count.of(restored as usize);
+                        self.status_message = fill(self.text.restored, &[&count]);
+                        self.load_executions(None);
+                    }
+                    Err(e) => self.status_message = fill(self.text.error, &[&e]),
+                }
+            }
+        }
+    }
+
+    /// Called every second. Refreshes every [`REFRESH_INTERVAL`] while the runner answers
+    /// (less often while its events arrive), and otherwise retries it when the backoff says so.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        let interval = if self.is_watching() { WATCHED_REFRESH_INTERVAL } else { REFRESH_INTERVAL };
+        if self.client.is_offline() && client::runner_available() {
+            self.reconnect();
+            self.connection_changed(self.text.runner_back);
+            return;
+        }
+        match self.disconnected.as_mut() {
+            Some(disconnected) if disconnected.retry_at > now => {}
+            Some(disconnected) => {
+                // the next attempt is due even if this one never answers
+                disconnected.retry_at = now + disconnected.backoff.next_delay();
+                self.reconnect();
+                if self.client.is_offline() {
+                    self.connection_changed(self.text.runner_stopped);
+                } else {
+                    self.refresh_workflows();
+                }
+            }
+            None if self.last_refresh.is_some_and(|t| now - t < interval) => {}
+            None => {
+                self.last_refresh = Some(now);
+                self.refresh();
+            }
+        }
+    }
+
+    fn reconnect(&mut self) {
+        self.stop_watch();
+        if let Err(e) = self.client.reconnect() {
+            self.status_message = fill(self.text.error, &[&e]);
+        }
+    }
+
+    /// Switched between the runner and the local database: reload everything from the new
+    /// source.
+    fn connection_changed(&mut self, message: &str) {
+        self.disconnected = None;
+        self.status_message = message.to_string();
+        self.client.invalidate();
+        self.last_refresh = Some(Instant::now());
+        self.refresh();
+    }
+
+    /// Follow whether the runner is reachable from the workflow list refreshes, which run
+    /// both while connected and as reconnect attempts.
+    fn track_connection<T>(&mut self, result: &Result<T>) {
+        match result {
+            Err(e) if client::is_disconnected(e) => {
+                self.disconnected.get_or_insert_with(Disconnected::new);
+            }
+            Err(_) => {}
+            Ok(_) => {
+                if self.disconnected.take().is_some() {
+                    self.status_message = self.text.reconnected.to_string();
+                    self.client.invalidate();
+                    self.load_executions(None);
+                }
+            }
+        }
+    }
+
+    /// Refresh both panels in the background, subscribing to the runner's events if not yet
+    /// subscribed. Failures keep the previous lists and show up in the panels.
+    pub fn refresh(&mut self) {
+        self.refresh_workflows();
+        self.load_executions(None);
+        if self.watch.is_none() {
+            self.subscribe();
+        }
+    }
+
+    /// Whether the runner's events are arriving, so the panels need not be polled.
+    pub fn is_watching(&self) -> bool {
+        self.watch.as_ref().is_some_and(|w| w.subscribed)
+    }
+
+    /// Open the event stream in the background; its progress comes back as [`Message::Watch`].
+    fn subscribe(&mut self) {
+        let watch_id = self.next_watch_id;
+        self.next_watch_id += 1;
+        let mut client = self.client.inner().clone();
+        let tx = self.tx.clone();
+        let handle = tokio::spawn(async move {
+            let send = |update| tx.send(Message::Watch { watch_id, update }).is_ok();
+            let result = async {
+                let mut stream = client::watch_events(&mut client).await?;
+                send(WatchUpdate::Subscribed);
+                while let Some(event) = stream.message().await? {
+                    if !send(WatchUpdate::Event(Box::new(event))) {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            .await;
+            send(WatchUpdate::Ended(result));
+        });
+        self.watch = Some(Watch {
+            id: watch_id,
+            handle: handle.abort_handle(),
+            subscribed: false,
+        });
+    }
+
+    fn stop_watch(&mut self) {
+        if let Some(watch) = self.watch.take() {
+            watch.handle.abort();
+        }
+    }
+
+    pub fn apply_watch(&mut self, watch_id: u64, update: WatchUpdate) {
+        let Some(watch) = self.watch.as_mut().filter(|w| w.id == watch_id) else { return };
+        match update {
+            WatchUpdate::Subscribed => watch.subscribed = true,
+            WatchUpdate::Event(event) => self.apply_event(*event),
+            // polling takes over, and the next refresh subscribes again
+            WatchUpdate::Ended(result) => {
+                self.watch = None;
+                if result.is_err() {
+                    self.track_connection(&result);
+                }
             }
         }
-        Ok(())
+    }
+
+    /// Reload what `event` changed. A run starting while the newest execution is followed
+    /// opens the new run's log; a config reload says what it changed in the footer.
+    fn apply_event(&mut self, event: WorkflowEvent) {
+        if event.kind == "decision" {
+            // the runner announces what the decision changed separately
+            if let (Some(view), Some(decision)) = (self.decisions.as_mut(), event.decision) {
+                if view.workflow.as_ref().is_none_or(|w| *w == decision.workflow) {
+                    view.decisions.insert(0, decision);
+                    view.decisions.truncate(MAX_DECISIONS);
+                    // what's on screen stays put unless the newest are shown
+                    if view.scroll > 0 {
+                        view.scroll += 1;
+                    }
+                }
+            }
+            return;
+        }
+        if event.kind == "config_reloaded" {
+            self.status_message = fill(self.text.config_reloaded, &[&event.summary]);
+        }
+        self.client.invalidate();
+        self.refresh_workflows();
+        if event.workflow.is_empty() || self.selected_workflow_name() == Some(&event.workflow) {
+            let open_new = event.kind == "started" && self.follow && self.selected_execution == 0;
+            self.load_executions(open_new.then_some(0));
+        }
+    }
+
+    pub fn refresh_workflows(&mut self) {
+        self.workflows_status.loading = true;
+        let client = self.client.clone();
+        self.spawn(self.text.loading_workflows, async move {
+            TaskOutput::Workflows(client.list_workflows().await)
+        });
+        let mut client = self.client.clone();
+        self.spawn(self.text.loading_missed_runs, async move {
+            TaskOutput::Missed(client::get_missed_runs(client.inner()).await)
+        });
+    }
+
+    /// Fetch the executions of the selected workflow.
+    pub fn load_executions(&mut self, open: Option<usize>) {
+        let Some(workflow) = self.selected_workflow_name().map(str::to_string) else {
+            return;
+        };
+        self.executions_status.loading = true;
+        let client = self.client.clone();
+        self.spawn(self.text.loading_executions, async move {
+            let result = client.get_workflow_status(&workflow).await;
+            TaskOutput::Executions {
+                workflow,
+                open,
+                result,
+            }
+        });
+    }
+
+    /// Open the duration chart for the selected workflow, or close it if open.
+    pub fn toggle_durations(&mut self) {
+        if self.durations.take().is_some() {
+            return;
+        }
+        self.close_popup();
+        let Some(workflow) = self.selected_workflow_name().map(str::to_string) else {
+            return;
+        };
+        self.durations = Some(DurationsView {
+            workflow: workflow.clone(),
+            points: Vec::new(),
+            status: PanelStatus {
+                loading: true,
+                error: None,
+            },
+        });
+        let mut client = self.client.inner().clone();
+        self.spawn(self.text.loading_durations, async move {
+            let result = client::get_durations(&mut client, &workflow).await;
+            TaskOutput::Durations { workflow, result }
+        });
+    }
+
+    /// Open the detail popup for the selected workflow, or close it if open.
+    pub fn toggle_detail(&mut self) {
+        if self.detail.take().is_some() {
+            return;
+        }
+        self.close_popup();
+        let Some(workflow) = self.selected_workflow_name().map(str::to_string) else {
+            return;
+        };
+        self.detail = Some(DetailView {
+            workflow: workflow.clone(),
+            stats: None,
+            status: PanelStatus {
+                loading: true,
+                error: None,
+            },
+        });
+        let mut client = self.client.inner().clone();
+        self.spawn(self.text.loading_stats, async move {
+            let result = client::get_workflow_stats(&mut client, &workflow).await;
+            TaskOutput::Stats { workflow, result }
+        });
+    }
+
+    /// Open the explanation of when the selected workflow runs next, or close it if open.
+    pub fn toggle_explain(&mut self) {
+        if self.explain.take().is_some() {
+            return;
+        }
+        self.close_popup();
+        let Some(workflow) = self.selected_workflow_name().map(str::to_string) else {
+            return;
+        };
+        self.explain = Some(ExplainView {
+            workflow: workflow.clone(),
+            explanation: None,
+            status: PanelStatus {
+                loading: true,
+                error: None,
+            },
+        });
+        let mut client = self.client.inner().clone();
+        self.spawn(self.text.explaining_schedule, async move {
+            let result = client::explain_schedule(&mut client, &workflow).await;
+            TaskOutput::Explained { workflow, result }
+        });
+    }
+
+    /// Open the scheduler's decisions about the selected workflow, or close them if open.
+    pub fn toggle_decisions(&mut self) {
+        if self.decisions.take().is_some() {
+            return;
+        }
+        self.close_popup();
+        let workflow = self.selected_workflow_name().map(str::to_string);
+        self.load_decisions(workflow);
+    }
+
+    /// Switch the open decisions between the selected workflow and all of them.
+    pub fn toggle_decisions_scope(&mut self) {
+        let Some(view) = &self.decisions else { return };
+        let workflow = match view.workflow {
+            Some(_) => None,
+            None => self.selected_workflow_name().map(str::to_string),
+        };
+        self.load_decisions(workflow);
+    }
+
+    fn load_decisions(&mut self, workflow: Option<String>) {
+        self.decisions = Some(DecisionsView {
+            workflow: workflow.clone(),
+            decisions: Vec::new(),
+            scroll: 0,
+            height: Cell::new(0),
+            status: PanelStatus {
+                loading: true,
+                error: None,
+            },
+        });
+        let mut client = self.client.inner().clone();
+        self.spawn(self.text.loading_decisions, async move {
+            let result = client::list_decisions(&mut client, workflow.as_deref()).await;
+            TaskOutput::Decisions { workflow, result }
+        });
+    }
+
+    /// Move the decisions list by `delta` rows, within bounds.
+    pub fn scroll_decisions_by(&mut self, delta: isize) {
+        let Some(view) = self.decisions.as_mut() else { return };
+        let last = view.decisions.len().saturating_sub(view.height.get().max(1));
+        view.scroll = view.scroll.saturating_add_signed(delta).min(last);
+    }
+
+    /// Open the keybinding help over whatever is shown, or close it if open.
+    pub fn toggle_help(&mut self) {
+        self.help_scroll = match self.help_scroll {
+            Some(_) => None,
+            None => Some(0),
+        };
+    }
+
+    pub fn has_popup(&self) -> bool {
+        self.durations.is_some()
+            || self.detail.is_some()
+            || self.decisions.is_some()
+            || self.explain.is_some()
+            || self.help_scroll.is_some()
+    }
+
+    pub fn close_popup(&mut self) {
+        self.durations = None;
+        self.detail = None;
+        self.decisions = None;
+        self.explain = None;
+        self.help_scroll = None;
+    }
+
+    /// Drop optimistic states the runner has caught up with (or never will), and re-apply
+    /// the rest on top of the freshly fetched list.
+    fn reconcile_pending(&mut self) {
+        for wf in &mut self.workflows {
+            let Some(pending) = self.pending.get_mut(&wf.name) else { continue };
+            pending.retain(|(p, since)| !p.confirmed_by(wf) && since.elapsed() < PENDING_TIMEOUT);
+            for (p, _) in pending.iter() {
+                p.apply(wf);
+            }
+        }
+        self.pending.retain(|_, p| !p.is_empty());
+    }
+
+    fn set_pending(&mut self, name: &str, pending: Pending) {
+        let entries = self.pending.entry(name.to_string()).or_default();
+        entries.retain(|(p, _)| std::mem::discriminant(p) != std::mem::discriminant(&pending));
+        entries.push((pending, Instant::now()));
+        if let Some(wf) = self.workflows.iter_mut().find(|w| w.name == name) {
+            pending.apply(wf);
+        }
+    }
+
+    /// Undo an optimistic state after the RPC behind it failed.
+    fn rollback_pending(&mut self, name: &str, previous: WorkflowInfo) {
+        self.pending.remove(name);
+        if let Some(wf) = self.workflows.iter_mut().find(|w| w.name == name) {
+            *wf = previous;
+        }
     }
 
     pub fn select_workflow(&mut self, idx: usize) {
         self.selected_workflow = idx;
         self.executions.clear();
+        self.stop_log_tail();
         self.log_lines.clear();
-        self.log_path = None;
-        self.log_file_pos = 0;
         self.selected_execution = 0;
     }
 
     pub fn select_execution(&mut self, idx: usize) {
         self.selected_execution = idx;
+        self.stop_log_tail();
         self.log_lines.clear();
         self.log_scroll = 0;
-        self.log_file_pos = 0;
+        self.follow = true;
+        self.jump_on_load = None;
+        self.error_lines.clear();
+        self.error_detector = self.workflows.get(self.selected_workflow).map(|wf| {
+            let config = SeverityConfig {
+                error: wf.error_patterns.clone(),
+                warn: Vec::new(),
+            };
+            Detector::new(&config).0
+        });
+
+        self.bookmarks.clear();
+        self.selection_start = None;
 
         if let Some(exec) = self.executions.get(idx) {
-            let path = PathBuf::from(&exec.log_path);
-            if path.exists() {
-                self.log_path = Some(path);
-                self.load_log_from_start();
-            } else {
-                self.log_path = None;
-                self.status_message = format!("Log file not found: {}", exec.log_path);
-            }
-        }
-    }
-
-    fn load_log_from_start(&mut self) {
-        let Some(path) = &self.log_path else { return };
-        let Ok(file) = File::open(path) else { return };
-        let reader = BufReader::new(file);
-        self.log_lines = reader.lines().map_while(Result::ok).collect();
-        self.log_file_pos = std::fs::metadata(path)
-            .map(|m| m.len())
-            .unwrap_or(0);
-        // scroll to bottom
-        self.log_scroll = self.log_lines.len().saturating_sub(1);
+            self.bookmarks = bookmarks::load(Path::new(&exec.log_path));
+            let tail_id = self.next_tail_id;
+            self.next_tail_id += 1;
+            let handle = log_tail::spawn(PathBuf::from(&exec.log_path), tail_id, self.tx.clone());
+            self.log_tail = Some((tail_id, handle));
+        }
+    }
+
+    /// Open the selected execution's log at its first error line.
+    pub fn open_first_error(&mut self) {
+        let Some(exec) = self.executions.get(self.selected_execution) else { return };
+        if exec.error_count == 0 {
+            self.status_message = self.text.no_errors_in_execution.to_string();
+            return;
+        }
+        let line = exec.first_error_line as usize;
+        self.select_execution(self.selected_execution);
+        self.follow = false;
+        self.jump_on_load = Some(line);
+        self.active_panel = Panel::Log;
+    }
+
+    fn stop_log_tail(&mut self) {
+        if let Some((_, handle)) = self.log_tail.take() {
+            handle.abort();
+        }
+    }
+
+    pub fn apply_log(&mut self, tail_id: u64, chunk: LogChunk) {
+        if self.log_tail.as_ref().map(|(id, _)| *id) != Some(tail_id) {
+            return;
+        }
+        match chunk {
+            LogChunk::Loaded(lines) => {
+                self.log_lines = self.parse_log_lines(lines);
+                self.find_error_lines(0);
+                if let Some(line) = self.jump_on_load.take() {
+                    self.log_scroll = line.min(self.log_lines.len().saturating_sub(1));
+                } else if self.follow {
+                    self.log_scroll = self.log_lines.len().saturating_sub(1);
+                }
+            }
+            LogChunk::Appended(lines) => {
+                let lines = self.parse_log_lines(lines);
+                let from = self.log_lines.len();
+                self.log_lines.extend(lines);
+                self.find_error_lines(from);
+                if self.follow {
+                    self.log_scroll = self.log_lines.len().saturating_sub(1);
+                }
+            }
+            LogChunk::Missing => {
+                if let Some(exec) = self.executions.get(self.selected_execution) {
+                    self.status_message = fill(self.text.log_not_found, &[&exec.log_path]);
+                }
+            }
+        }
+    }
+
+    /// Add the error lines from line `from` on. The runner's stored first error line counts
+    /// too, so a runner that sends no patterns still leaves one to jump to.
+    fn find_error_lines(&mut self, from: usize) {
+        if let Some(detector) = &self.error_detector {
+            let found = self.log_lines[from..].iter().enumerate();
+            let found = found.filter(|(_, line)| detector.is_error(&line.text));
+            self.error_lines.extend(found.map(|(i, _)| from + i));
+        }
+        let stored = self
+            .executions
+            .get(self.selected_execution)
+            .filter(|exec| exec.error_count > 0)
+            .map(|exec| exec.first_error_line as usize)
+            .filter(|&line| line >= from && line < self.log_lines.len());
+        if let Some(line) = stored {
+            if let Err(pos) = self.error_lines.binary_search(&line) {
+                self.error_lines.insert(pos, line);
+            }
+        }
+    }
+
+    /// Move to the next (or previous) error line, wrapping around the log.
+    pub fn jump_to_error(&mut self, forward: bool) {
+        let lines = &self.error_lines;
+        let Some(line) = step(lines, self.log_scroll, forward) else {
+            self.status_message = self.text.no_errors_in_log.to_string();
+            return;
+        };
+        let nth = lines.iter().position(|&i| i == line).unwrap_or(0) + 1;
+        self.status_message = fill(self.text.error_n_of, &[&nth, &lines.len(), &(line + 1)]);
+        self.follow = false;
+        self.log_scroll = line;
     }
 
-    pub fn poll_log_updates(&mut self) {
-        let Some(path) = &self.log_path.clone() else { return };
-        let Ok(metadata) = std::fs::metadata(path) else { return };
-        let current_len = metadata.len();
-        if current_len <= self.log_file_pos {
+    /// Bookmark the current log line, or remove its bookmark.
+    pub fn toggle_bookmark(&mut self) {
+        let Some(exec) = self.executions.get(self.selected_execution) else { return };
+        if self.log_lines.is_empty() {
             return;
         }
+        let line = self.log_scroll;
+        let message = match self.bookmarks.binary_search(&line) {
+            Ok(pos) => {
+                self.bookmarks.remove(pos);
+                fill(self.text.bookmark_removed, &[&(line + 1)])
+            }
+            Err(pos) => {
+                self.bookmarks.insert(pos, line);
+                fill(self.text.bookmarked, &[&(line + 1)])
+            }
+        };
+        self.status_message = match bookmarks::save(Path::new(&exec.log_path), &self.bookmarks) {
+            Ok(()) => message,
+            Err(e) => fill(self.text.error, &[&format!("{:#}", e)]),
+        };
+    }
 
-        let Ok(mut file) = File::open(path) else { return };
-        if file.seek(SeekFrom::Start(self.log_file_pos)).is_err() {
+    /// Move to the next (or previous) bookmark, wrapping around the log.
+    pub fn jump_to_bookmark(&mut self, forward: bool) {
+        let Some(line) = step(&self.bookmarks, self.log_scroll, forward) else {
+            self.status_message = self.text.no_bookmarks.to_string();
             return;
+        };
+        let nth = self.bookmarks.iter().position(|&i| i == line).unwrap_or(0) + 1;
+        let count = self.bookmarks.len();
+        self.status_message = fill(self.text.bookmark_n_of, &[&nth, &count, &(line + 1)]);
+        self.follow = false;
+        self.log_scroll = line;
+    }
+
+    /// Start selecting lines at the current one, or drop the selection.
+    pub fn toggle_selection(&mut self) {
+        if self.selection_start.take().is_none() && !self.log_lines.is_empty() {
+            self.selection_start = Some(self.log_scroll);
         }
+    }
 
-        let reader = BufReader::new(&file);
-        let new_lines: Vec<String> = reader.lines().map_while(Result::ok).collect();
-        self.log_file_pos = current_len;
+    /// The selected lines, between where `v` was pressed and the current line.
+    pub fn selected_lines(&self) -> Option<std::ops::RangeInclusive<usize>> {
+        let start = self.selection_start?;
+        Some(start.min(self.log_scroll)..=start.max(self.log_scroll))
+    }
 
-        let was_at_bottom = self.is_at_bottom();
-        self.log_lines.extend(new_lines);
-        if was_at_bottom {
-            self.log_scroll = self.log_lines.len().saturating_sub(1);
+    /// Queue the selected execution's log for the render loop to open in `$PAGER`. The file
+    /// has to be on this machine: a remote runner's logs are only shown in the Log panel.
+    pub fn open_log_in_pager(&mut self) {
+        let Some(exec) = self.executions.get(self.selected_execution) else { return };
+        let path = PathBuf::from(&exec.log_path);
+        if !path.exists() && !log_compress::gz_path_for(&path).exists() {
+            self.status_message = fill(self.text.log_not_found, &[&exec.log_path]);
+            return;
+        }
+        self.open_request = Some(path);
+    }
+
+    pub fn start_export(&mut self, kind: ExportKind) {
+        if self.log_lines.is_empty() {
+            self.status_message = self.text.nothing_to_export.to_string();
+            return;
         }
+        self.status_message.clear();
+        self.export = Some(ExportPrompt {
+            kind,
+            input: String::new(),
+        });
     }
 
-    fn is_at_bottom(&self) -> bool {
-        self.log_lines.is_empty() || self.log_scroll >= self.log_lines.len().saturating_sub(1)
+    /// Write the selected lines, or the whole log, to the typed path, or queue the typed
+    /// command for the render loop. Colors are not exported.
+    pub fn commit_export(&mut self) {
+        let Some(prompt) = self.export.take() else { return };
+        let target = prompt.input.trim();
+        if target.is_empty() {
+            return;
+        }
+        let range = self.selected_lines().unwrap_or(0..=self.log_lines.len().saturating_sub(1));
+        let lines = &self.log_lines[range];
+        let text: String = lines.iter().map(|l| format!("{}\n", l.text)).collect();
+        match prompt.kind {
+            ExportKind::File => {
+                self.status_message = match export::write_file(target, &text) {
+                    Ok(path) => {
+                        let count = self.text.lines.of(lines.len());
+                        fill(self.text.wrote, &[&count, &path.display()])
+                    }
+                    Err(e) => fill(self.text.error, &[&format!("{:#}", e)]),
+                };
+            }
+            ExportKind::Pipe => {
+                self.pipe_request = Some(PipeRequest {
+                    command: target.to_string(),
+                    lines: lines.len(),
+                    text,
+                });
+            }
+        }
+        self.selection_start = None;
+    }
+
+    fn parse_log_lines(&self, lines: Vec<String>) -> Vec<LogLine> {
+        let keep_styles = !self.view.strip_ansi;
+        let mut parsed: Vec<LogLine> = lines.iter().map(|l| ansi::parse(l, keep_styles)).collect();
+        if keep_styles {
+            parsed.iter_mut().for_each(ansi::style_stream_tag);
+        }
+        parsed
+    }
+
+    /// Turn follow mode on (jumping to the last line) or off.
+    pub fn toggle_follow(&mut self) {
+        self.follow = !self.follow;
+        if self.follow {
+            self.log_scroll = self.log_lines.len().saturating_sub(1);
+        }
     }
 
     pub fn scroll_log_up(&mut self) {
+        self.follow = false;
         self.log_scroll = self.log_scroll.saturating_sub(1);
     }
 
     pub fn scroll_log_down(&mut self) {
+        self.follow = false;
         if !self.log_lines.is_empty() {
             self.log_scroll = (self.log_scroll + 1).min(self.log_lines.len().saturating_sub(1));
         }
     }
 
+    /// First log line shown in a panel `height` lines tall; the view keeps the cursor
+    /// (`log_scroll`) centered where it can.
+    pub fn log_view_start(&self, height: usize) -> usize {
+        if self.log_lines.len() > height {
+            self.log_scroll
+                .saturating_sub(height / 2)
+                .min(self.log_lines.len() - height)
+        } else {
+            0
+        }
+    }
+
+    /// Scroll the view by `delta` lines, clamped to the log.
+    pub fn scroll_log_by(&mut self, delta: isize) {
+        // start from the line actually at the center, so a page moves the view by a page
+        // even when the cursor sits near either end
+        self.follow = false;
+        let height = self.log_height.get();
+        if self.log_lines.len() > height {
+            self.log_scroll = self.log_view_start(height) + height / 2;
+        }
+        let last = self.log_lines.len().saturating_sub(1);
+        self.log_scroll = self.log_scroll.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn log_page(&self) -> isize {
+        self.log_height.get().max(1) as isize
+    }
+
+    pub fn scroll_log_top(&mut self) {
+        self.follow = false;
+        self.log_scroll = 0;
+    }
+
+    pub fn scroll_log_bottom(&mut self) {
+        self.follow = false;
+        self.log_scroll = self.log_lines.len().saturating_sub(1);
+    }
+
+    pub fn start_search(&mut self) {
+        self.status_message.clear();
+        self.search = Some(LogSearch {
+            query: String::new(),
+            editing: true,
+        });
+    }
+
+    /// Stop editing and jump to the first match at or below the current line.
+    pub fn commit_search(&mut self) {
+        let Some(search) = self.search.as_mut() else { return };
+        if search.query.is_empty() {
+            self.search = None;
+            return;
+        }
+        search.editing = false;
+        if !self.match_lines().contains(&self.log_scroll) {
+            self.jump_to_match(true);
+        }
+    }
+
+    /// Line indices of the log containing the current query.
+    pub fn match_lines(&self) -> Vec<usize> {
+        let Some(search) = &self.search else { return Vec::new() };
+        self.log_lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !find_matches(&line.text, &search.query).is_empty())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Move to the next (or previous) matching line, wrapping around the log.
+    pub fn jump_to_match(&mut self, forward: bool) {
+        match step(&self.match_lines(), self.log_scroll, forward) {
+            Some(line) => {
+                self.follow = false;
+                self.log_scroll = line;
+                self.status_message.clear();
+            }
+            None => {
+                if let Some(search) = &self.search {
+                    self.status_message = fill(self.text.pattern_not_found, &[&search.query]);
+                }
+            }
+        }
+    }
+
     pub fn move_workflow_up(&mut self) {
         if self.selected_workflow > 0 {
             self.select_workflow(self.selected_workflow - 1);
@@ -175,13 +1294,138 @@ impl App {
         }
     }
 
-    pub async fn trigger_selected_workflow(&mut self) -> Result<()> {
-        if let Some(wf) = self.workflows.get(self.selected_workflow) {
+    /// Changes need the runner. Offline, say so instead of sending them.
+    fn refuse_offline(&mut self) -> bool {
+        if self.client.is_offline() {
+            self.status_message = self.text.read_only.to_string();
+        }
+        self.client.is_offline()
+    }
+
+    /// Trigger the selected workflow, after a y/n prompt unless it sets `confirm_trigger: false`.
+    pub fn trigger_selected_workflow(&mut self) {
+        if self.refuse_offline() {
+            return;
+        }
+        let Some(wf) = self.workflows.get(self.selected_workflow) else { return };
+        if wf.confirm_trigger {
+            self.confirm_trigger = Some(wf.name.clone());
+        } else {
             let name = wf.name.clone();
-            let resp = client::trigger_workflow(&mut self.client, &name).await?;
-            self.status_message = resp.message;
+            self.trigger_workflow(&name);
         }
-        Ok(())
+    }
+
+    /// Answer the trigger prompt.
+    pub fn answer_confirm_trigger(&mut self, yes: bool) {
+        if let Some(name) = self.confirm_trigger.take().filter(|_| yes) {
+            self.trigger_workflow(&name);
+        }
+    }
+
+    fn trigger_workflow(&mut self, name: &str) {
+        // by name: the list may have been refreshed while the prompt was open
+        let Some(previous) = self.workflows.iter().find(|w| w.name == name).cloned() else {
+            return;
+        };
+        let name = previous.name.clone();
+        self.set_pending(&name, Pending::Starting { last_run_at: previous.last_run_at });
+
+        let mut client = self.client.inner().clone();
+        self.spawn(self.text.triggering, async move {
+            let source = TriggerSource::Tui;
+            let result =
+                client::trigger_workflow(&mut client, &name, Vec::new(), Vec::new(), source).await;
+            TaskOutput::Triggered { previous, result }
+        });
+    }
+
+    pub fn toggle_pause_selected_workflow(&mut self) {
+        if self.refuse_offline() {
+            return;
+        }
+        let Some(previous) = self.workflows.get(self.selected_workflow).cloned() else {
+            return;
+        };
+        let name = previous.name.clone();
+        let paused = !previous.paused;
+        self.set_pending(&name, Pending::Paused(paused));
+
+        let mut client = self.client.inner().clone();
+        self.spawn(self.text.updating_pause, async move {
+            let result = client::set_paused(&mut client, &name, paused).await;
+            TaskOutput::PauseSet {
+                previous,
+                paused,
+                result,
+            }
+        });
+    }
+
+    /// Skip the next scheduled run of the selected workflow; again, the one after that.
+    pub fn snooze_selected_workflow(&mut self) {
+        if self.refuse_offline() {
+            return;
+        }
+        let Some(workflow) = self.selected_workflow_name().map(str::to_string) else {
+            return;
+        };
+        let mut client = self.client.inner().clone();
+        self.spawn(self.text.snoozing, async move {
+            let result = client::snooze_next_run(&mut client, &workflow, Duration::ZERO).await;
+            TaskOutput::Snoozed { workflow, result }
+        });
+    }
+
+    /// Pin the selected execution as its workflow's baseline, or unpin it if it is one.
+    pub fn toggle_selected_baseline(&mut self) {
+        if self.refuse_offline() {
+            return;
+        }
+        let Some(exec) = self.executions.get(self.selected_execution) else {
+            return;
+        };
+        let (workflow, id) = (exec.workflow.clone(), exec.id.clone());
+        let clear = self
+            .workflows
+            .iter()
+            .any(|w| w.name == workflow && w.baseline_execution_id == id);
+        if !clear && exec.status != "success" {
+            self.status_message = self.text.baseline_needs_success.to_string();
+            return;
+        }
+        let mut client = self.client.inner().clone();
+        self.spawn(self.text.pinning_baseline, async move {
+            let result = client::set_baseline(&mut client, &workflow, &id, clear).await;
+            TaskOutput::BaselineSet { workflow, clear, result }
+        });
+    }
+
+    pub fn delete_selected_execution(&mut self) {
+        if self.refuse_offline() {
+            return;
+        }
+        let Some(exec) = self.executions.get(self.selected_execution) else {
+            return;
+        };
+        let id = exec.id.clone();
+        let mut client = self.client.inner().clone();
+        self.spawn(self.text.deleting, async move {
+            TaskOutput::Deleted(client::delete_execution(&mut client, &id).await)
+        });
+    }
+
+    pub fn restore_selected_workflow(&mut self) {
+        if self.refuse_offline() {
+            return;
+        }
+        let Some(name) = self.selected_workflow_name().map(str::to_string) else {
+            return;
+        };
+        let mut client = self.client.inner().clone();
+        self.spawn(self.text.restoring, async move {
+            TaskOutput::Restored(client::restore_executions(&mut client, &name).await)
+        });
     }
 
     pub fn selected_workflow_name(&self) -> Option<&str> {
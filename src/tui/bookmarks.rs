@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::runner::executor::bookmarks_path_for;
+
+/// Bookmarked lines (0-based) of the log at `log_path`, ascending. A missing or unreadable
+/// sidecar file holds none.
+pub fn load(log_path: &Path) -> Vec<usize> {
+    let Ok(text) = std::fs::read_to_string(bookmarks_path_for(log_path)) else {
+        return Vec::new();
+    };
+    let mut lines: Vec<usize> = text
+        .lines()
+        .filter_map(|l| l.trim().parse::<usize>().ok())
+        .filter_map(|n| n.checked_sub(1))
+        .collect();
+    lines.sort_unstable();
+    lines.dedup();
+    lines
+}
+
+/// Write `lines` to the sidecar file as 1-based line numbers, one per line. Without
+/// bookmarks the file is removed.
+pub fn save(log_path: &Path, lines: &[usize]) -> Result<()> {
+    let path = bookmarks_path_for(log_path);
+    if lines.is_empty() {
+        return match std::fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).with_context(|| format!("failed to remove {}", path.display())),
+        };
+    }
+    let text: String = lines.iter().map(|l| format!("{}\n", l + 1)).collect();
+    std::fs::write(&path, text).with_context(|| format!("failed to write {}", path.display()))
+}
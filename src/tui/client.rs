@@ -1,32 +1,138 @@
-use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use futures::future::{BoxFuture, FutureExt, Shared};
 use hyper_util::rt::TokioIo;
-use tonic::transport::{Channel, Endpoint, Uri};
+use tokio::sync::mpsc;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Channel, Endpoint, Server, Uri};
+use tonic::{Code, Request, Status, Streaming};
 use tower::service_fn;
 
+use crate::common::config::load_config;
+use crate::common::paths;
+use crate::common::storage::open_storage_read_only;
+use crate::common::types::TriggerSource;
 use crate::proto::workflow_service_client::WorkflowServiceClient;
-use crate::proto::{Empty, ExecutionRequest, WorkflowRequest};
-use crate::runner::server::SOCKET_PATH;
+use crate::proto::workflow_service_server::WorkflowServiceServer;
+use crate::proto::{
+    AdhocRequest, BaselineRequest, BaselineResponse, DaemonHealthResponse, DecisionsRequest,
+    DurationPoint, DurationsRequest, Empty,
+    ExecutionRequest, ExplainScheduleRequest, ExplainScheduleResponse, MissedRunInfo, PauseRequest, ReportRequest, RestoreRequest,
+    ScheduleOverrideRequest, ScheduleOverrideResponse, SchedulerDecision, SnoozeRequest,
+    SnoozeResponse, TriggerRequest, WorkflowEvent, WorkflowInfo, WorkflowRequest,
+    WorkflowStatsResponse, WorkflowStatusResponse,
+};
+use crate::runner::events::Events;
+use crate::runner::scheduler::QueueDepths;
+use crate::runner::server::{MAX_REQUEST, WorkflowServiceImpl};
 
-pub async fn connect() -> Result<WorkflowServiceClient<Channel>> {
-    let channel = Endpoint::try_from("http://[::]:50051")?
-        .connect_with_connector(service_fn(|_: Uri| async {
-            let stream = tokio::net::UnixStream::connect(SOCKET_PATH).await?;
-            Ok::<_, std::io::Error>(TokioIo::new(stream))
-        }))
-        .await?;
+pub type Client = WorkflowServiceClient<InterceptedService<Channel, AuthInterceptor>>;
+
+/// Where to reach the runner: the local Unix socket by default, or a TCP listener
+/// (`http://host:50051`) with a bearer token.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectOptions {
+    pub addr: Option<String>,
+    pub token: Option<String>,
+}
 
-    Ok(WorkflowServiceClient::new(channel))
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    token: Option<MetadataValue<Ascii>>,
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut req: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(token) = &self.token {
+            req.metadata_mut().insert("authorization", token.clone());
+        }
+        Ok(req)
+    }
+}
+
+/// Build a client without waiting for the runner. The channel connects on first use, so an
+/// unreachable or hung runner shows up as a failed (cancellable) RPC rather than blocking here.
+pub fn connect(options: &ConnectOptions) -> Result<Client> {
+    let channel = match &options.addr {
+        Some(addr) => Endpoint::from_shared(addr.clone())
+            .with_context(|| format!("invalid address: {}", addr))?
+            .connect_lazy(),
+        None => Endpoint::try_from("http://[::]:50051")?.connect_with_connector_lazy(service_fn(
+            |_: Uri| async {
+                let stream = tokio::net::UnixStream::connect(&paths::get().socket).await?;
+                Ok::<_, std::io::Error>(TokioIo::new(stream))
+            },
+        )),
+    };
+
+    let token = options
+        .token
+        .as_ref()
+        .map(|t| format!("Bearer {}", t).parse())
+        .transpose()
+        .context("invalid token")?;
+
+    Ok(WorkflowServiceClient::with_interceptor(
+        channel,
+        AuthInterceptor { token },
+    ))
+}
+
+/// Whether the local runner is accepting connections.
+pub fn runner_available() -> bool {
+    std::os::unix::net::UnixStream::connect(&paths::get().socket).is_ok()
+}
+
+/// A client answered in-process from the local database and config, for when the runner is
+/// not running. It serves the runner's own RPCs in read-only mode, so changes are refused.
+pub fn connect_offline() -> Result<Client> {
+    let channel = Endpoint::try_from("http://[::]:50051")?.connect_with_connector_lazy(service_fn(
+        |_: Uri| async {
+            let config = load_config().map_err(std::io::Error::other)?;
+            let db = open_storage_read_only(&config.storage)
+                .await
+                .map_err(std::io::Error::other)?;
+            // nothing is ever scheduled: every RPC that would is refused as read-only
+            let (scheduler_tx, _) = mpsc::channel(1);
+            let service = WorkflowServiceImpl::new(
+                db,
+                scheduler_tx,
+                Events::default(),
+                QueueDepths::default(),
+                Vec::new(),
+                true,
+            );
+            let (client, server) = tokio::io::duplex(64 * 1024);
+            tokio::spawn(
+                Server::builder()
+                    .add_service(
+                        WorkflowServiceServer::new(service).max_decoding_message_size(MAX_REQUEST),
+                    )
+                    .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(server))),
+            );
+            Ok::<_, std::io::Error>(TokioIo::new(client))
+        },
+    ));
+    Ok(WorkflowServiceClient::with_interceptor(
+        channel,
+        AuthInterceptor { token: None },
+    ))
 }
 
 pub async fn list_workflows(
-    client: &mut WorkflowServiceClient<Channel>,
+    client: &mut Client,
 ) -> Result<Vec<crate::proto::WorkflowInfo>> {
     let response = client.list_workflows(Empty {}).await?;
     Ok(response.into_inner().workflows)
 }
 
 pub async fn get_workflow_status(
-    client: &mut WorkflowServiceClient<Channel>,
+    client: &mut Client,
     name: &str,
 ) -> Result<crate::proto::WorkflowStatusResponse> {
     let response = client
@@ -38,7 +144,7 @@ pub async fn get_workflow_status(
 }
 
 pub async fn get_execution_log_path(
-    client: &mut WorkflowServiceClient<Channel>,
+    client: &mut Client,
     execution_id: &str,
 ) -> Result<String> {
     let response = client
@@ -50,13 +156,377 @@ pub async fn get_execution_log_path(
 }
 
 pub async fn trigger_workflow(
-    client: &mut WorkflowServiceClient<Channel>,
+    client: &mut Client,
     name: &str,
+    args: Vec<String>,
+    stdin: Vec<u8>,
+    source: TriggerSource,
+) -> Result<crate::proto::TriggerResponse> {
+    let response = client
+        .trigger_workflow(TriggerRequest {
+            name: name.to_string(),
+            args,
+            stdin,
+            source: source.as_str().to_string(),
+            dry_run: false,
+        })
+        .await?;
+    Ok(response.into_inner())
+}
+
+/// What triggering `name` would run, without running it; the plan is in `dry_run`.
+pub async fn dry_run(
+    client: &mut Client,
+    name: &str,
+    args: Vec<String>,
+    stdin: Vec<u8>,
+    source: TriggerSource,
+) -> Result<crate::proto::TriggerResponse> {
+    let response = client
+        .trigger_workflow(TriggerRequest {
+            name: name.to_string(),
+            args,
+            stdin,
+            source: source.as_str().to_string(),
+            dry_run: true,
+        })
+        .await?;
+    Ok(response.into_inner())
+}
+
+/// Register `request`'s script as an ad-hoc workflow and run it.
+pub async fn run_adhoc(
+    client: &mut Client,
+    request: AdhocRequest,
 ) -> Result<crate::proto::TriggerResponse> {
+    let response = client.run_adhoc(request).await?;
+    Ok(response.into_inner())
+}
+
+pub async fn delete_execution(
+    client: &mut Client,
+    execution_id: &str,
+) -> Result<bool> {
+    let response = client
+        .delete_execution(ExecutionRequest {
+            execution_id: execution_id.to_string(),
+        })
+        .await?;
+    Ok(response.into_inner().deleted)
+}
+
+pub async fn restore_executions(
+    client: &mut Client,
+    workflow: &str,
+) -> Result<u32> {
+    let response = client
+        .restore_executions(RestoreRequest {
+            workflow: workflow.to_string(),
+            execution_id: String::new(),
+        })
+        .await?;
+    Ok(response.into_inner().restored)
+}
+
+pub async fn set_paused(client: &mut Client, name: &str, paused: bool) -> Result<bool> {
+    let response = client
+        .set_paused(PauseRequest {
+            name: name.to_string(),
+            paused,
+        })
+        .await?;
+    Ok(response.into_inner().changed)
+}
+
+pub async fn set_schedule_override(
+    client: &mut Client,
+    request: ScheduleOverrideRequest,
+) -> Result<ScheduleOverrideResponse> {
+    Ok(client.set_schedule_override(request).await?.into_inner())
+}
+
+/// Skip the next scheduled run of `name`, if it is due within `within` (any time when zero).
+pub async fn snooze_next_run(
+    client: &mut Client,
+    name: &str,
+    within: Duration,
+) -> Result<SnoozeResponse> {
+    let request = SnoozeRequest {
+        name: name.to_string(),
+        within_secs: within.as_secs(),
+    };
+    Ok(client.snooze_next_run(request).await?.into_inner())
+}
+
+/// Pin `execution_id` as `workflow`'s baseline, its latest successful run when empty, or unpin
+/// it with `clear`.
+pub async fn set_baseline(
+    client: &mut Client,
+    workflow: &str,
+    execution_id: &str,
+    clear: bool,
+) -> Result<BaselineResponse> {
+    let request = BaselineRequest {
+        workflow: workflow.to_string(),
+        execution_id: execution_id.to_string(),
+        clear,
+    };
+    Ok(client.set_baseline(request).await?.into_inner())
+}
+
+pub async fn get_durations(client: &mut Client, name: &str) -> Result<Vec<DurationPoint>> {
     let response = client
-        .trigger_workflow(WorkflowRequest {
+        .get_durations(DurationsRequest {
             name: name.to_string(),
+            limit: 0,
         })
         .await?;
+    Ok(response.into_inner().points)
+}
+
+pub async fn get_report(client: &mut Client, request: ReportRequest) -> Result<String> {
+    let response = client.get_report(request).await?;
+    Ok(response.into_inner().content)
+}
+
+pub async fn get_missed_runs(client: &mut Client) -> Result<Vec<MissedRunInfo>> {
+    let response = client.get_missed_runs(Empty {}).await?;
+    Ok(response.into_inner().runs)
+}
+
+pub async fn explain_schedule(client: &mut Client, name: &str) -> Result<ExplainScheduleResponse> {
+    let request = ExplainScheduleRequest {
+        name: name.to_string(),
+        count: 0,
+    };
+    Ok(client.explain_schedule(request).await?.into_inner())
+}
+
+/// The scheduler's recent decisions about `workflow`, or about all workflows, newest first.
+pub async fn list_decisions(
+    client: &mut Client,
+    workflow: Option<&str>,
+) -> Result<Vec<SchedulerDecision>> {
+    let request = DecisionsRequest {
+        workflow: workflow.unwrap_or_default().to_string(),
+        limit: 0,
+    };
+    Ok(client.list_decisions(request).await?.into_inner().decisions)
+}
+
+pub async fn get_daemon_health(client: &mut Client) -> Result<DaemonHealthResponse> {
+    let response = client.get_daemon_health(Empty {}).await?;
     Ok(response.into_inner())
 }
+
+pub async fn get_workflow_stats(client: &mut Client, name: &str) -> Result<WorkflowStatsResponse> {
+    let response = client
+        .get_workflow_stats(WorkflowRequest {
+            name: name.to_string(),
+        })
+        .await?;
+    Ok(response.into_inner())
+}
+
+/// Subscribe to the runner's workflow events; the stream stays open until the runner stops.
+pub async fn watch_events(client: &mut Client) -> Result<Streaming<WorkflowEvent>> {
+    let response = client.watch_events(Empty {}).await?;
+    Ok(response.into_inner())
+}
+
+/// Whether `err` means the runner could not be reached, as opposed to refusing a request.
+pub fn is_disconnected(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<Status>().is_some_and(|s| s.code() == Code::Unavailable)
+}
+
+/// First reconnect delay; doubled after every failed attempt up to [`MAX_BACKOFF`].
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Delays between reconnect attempts: 1s, 2s, 4s, … capped at 30s.
+#[derive(Debug, Clone, Default)]
+pub struct Backoff {
+    failures: u32,
+}
+
+impl Backoff {
+    /// Delay before the next attempt, counting one more failure.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = MIN_BACKOFF.saturating_mul(1 << self.failures.min(5)).min(MAX_BACKOFF);
+        self.failures += 1;
+        delay
+    }
+}
+
+/// How long a read waits for a refresh before falling back to the previous value.
+const STALE_BUDGET: Duration = Duration::from_millis(150);
+
+type SharedFetch<T> = Shared<BoxFuture<'static, Result<T, Arc<anyhow::Error>>>>;
+
+struct CacheEntry<T> {
+    value: Option<T>,
+    inflight: Option<SharedFetch<T>>,
+}
+
+/// Keyed cache where identical concurrent requests share one RPC. Once a value exists, a
+/// refresh that exceeds [`STALE_BUDGET`] returns the old value and finishes in the background.
+struct Cache<T> {
+    entries: Arc<Mutex<HashMap<String, CacheEntry<T>>>>,
+}
+
+impl<T> Clone for Cache<T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: Arc::clone(&self.entries),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Cache<T> {
+    fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    async fn get(&self, key: &str, fetch: impl FnOnce() -> BoxFuture<'static, Result<T>>) -> Result<T> {
+        let (fut, stale) = {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = entries.entry(key.to_string()).or_insert(CacheEntry {
+                value: None,
+                inflight: None,
+            });
+            let fut = entry
+                .inflight
+                .get_or_insert_with(|| fetch().map(|r| r.map_err(Arc::new)).boxed().shared())
+                .clone();
+            (fut, entry.value.clone())
+        };
+
+        let result = match stale {
+            Some(stale) => match tokio::time::timeout(STALE_BUDGET, fut.clone()).await {
+                Ok(result) => result,
+                Err(_) => {
+                    let cache = self.clone();
+                    let key = key.to_string();
+                    tokio::spawn(async move {
+                        let result = fut.await;
+                        cache.complete(&key, &result);
+                    });
+                    return Ok(stale);
+                }
+            },
+            None => fut.await,
+        };
+
+        self.complete(key, &result);
+        // keep the status so callers can tell a lost connection from a failed request
+        result.map_err(|e| match e.downcast_ref::<Status>() {
+            Some(status) => status.clone().into(),
+            None => anyhow::anyhow!("{:#}", e),
+        })
+    }
+
+    fn complete(&self, key: &str, result: &Result<T, Arc<anyhow::Error>>) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get_mut(key) {
+            entry.inflight = None;
+            if let Ok(value) = result {
+                entry.value = Some(value.clone());
+            }
+        }
+    }
+
+    fn invalidate(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Forget in-flight fetches so the next read starts a new RPC instead of joining one
+    /// that may be hung. Dropping the last handle to a fetch cancels it.
+    fn cancel_inflight(&self) {
+        for entry in self.entries.lock().unwrap().values_mut() {
+            entry.inflight = None;
+        }
+    }
+}
+
+/// [`Client`] with cached reads, for keeping the TUI responsive on slow connections.
+#[derive(Clone)]
+pub struct CachedClient {
+    inner: Client,
+    options: ConnectOptions,
+    /// Reading the local database because the runner is not running.
+    offline: bool,
+    workflows: Cache<Vec<WorkflowInfo>>,
+    statuses: Cache<WorkflowStatusResponse>,
+}
+
+impl CachedClient {
+    /// Connect to the runner, or read the local database if the local runner is not running.
+    pub fn new(options: &ConnectOptions) -> Result<Self> {
+        let mut client = Self {
+            inner: connect(options)?,
+            options: options.clone(),
+            offline: false,
+            workflows: Cache::new(),
+            statuses: Cache::new(),
+        };
+        if client.should_be_offline() {
+            client.inner = connect_offline()?;
+            client.offline = true;
+        }
+        Ok(client)
+    }
+
+    /// Replace the channel with a new one, e.g. after the runner restarted, falling back to
+    /// the local database while the local runner is down. Cached values are kept so the
+    /// panels still have something to show until the next refresh.
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.offline = self.should_be_offline();
+        self.inner = if self.offline { connect_offline()? } else { connect(&self.options)? };
+        self.cancel_inflight();
+        Ok(())
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Only a local runner's database can stand in for it.
+    fn should_be_offline(&self) -> bool {
+        self.options.addr.is_none() && !runner_available()
+    }
+
+    /// The uncached client, for RPCs that change state.
+    pub fn inner(&mut self) -> &mut Client {
+        &mut self.inner
+    }
+
+    /// Drop cached values, e.g. after a mutation made them wrong.
+    pub fn invalidate(&self) {
+        self.workflows.invalidate();
+        self.statuses.invalidate();
+    }
+
+    pub fn cancel_inflight(&self) {
+        self.workflows.cancel_inflight();
+        self.statuses.cancel_inflight();
+    }
+
+    pub async fn list_workflows(&self) -> Result<Vec<WorkflowInfo>> {
+        let mut client = self.inner.clone();
+        self.workflows
+            .get("", move || async move { list_workflows(&mut client).await }.boxed())
+            .await
+    }
+
+    pub async fn get_workflow_status(&self, name: &str) -> Result<WorkflowStatusResponse> {
+        let mut client = self.inner.clone();
+        let owned = name.to_string();
+        self.statuses
+            .get(name, move || {
+                async move { get_workflow_status(&mut client, &owned).await }.boxed()
+            })
+            .await
+    }
+}
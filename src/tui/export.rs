@@ -0,0 +1,94 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+use anyhow::{Context, Result};
+
+use crate::common::paths;
+
+/// Where `>` and `|` in the Log panel send the log (or the selected lines).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportKind {
+    /// Write a file.
+    File,
+    /// Feed a shell command on its stdin.
+    Pipe,
+}
+
+impl ExportKind {
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            ExportKind::File => ">",
+            ExportKind::Pipe => "|",
+        }
+    }
+}
+
+/// The input line for the path or command being typed.
+#[derive(Debug)]
+pub struct ExportPrompt {
+    pub kind: ExportKind,
+    pub input: String,
+}
+
+/// A command to run with the terminal handed back to it, queued for the render loop.
+#[derive(Debug)]
+pub struct PipeRequest {
+    pub command: String,
+    pub text: String,
+    pub lines: usize,
+}
+
+/// Write `text` to `path`, with a leading `~` expanded and relative paths taken from the
+/// current directory. Returns the path written.
+pub fn write_file(path: &str, text: &str) -> Result<PathBuf> {
+    let path = match path.strip_prefix("~") {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            PathBuf::from(format!("{}{}", paths::home().display(), rest))
+        }
+        _ => PathBuf::from(path),
+    };
+    std::fs::write(&path, text).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// What `o` opens a log with: `$PAGER`, else `$EDITOR`, else `less -R`, which shows the
+/// log's colors.
+pub fn viewer() -> String {
+    ["PAGER", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| "less -R".to_string())
+}
+
+/// Run `viewer` on the file at `path` with `sh -c`, so it may carry arguments of its own, with
+/// the terminal handed to it.
+pub fn open_file(viewer: &str, path: &Path) -> Result<ExitStatus> {
+    Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", viewer))
+        .arg("sh")
+        .arg(path)
+        .status()
+        .context("failed to run sh")
+}
+
+/// Run `command` with `sh -c`, `text` on its stdin and the terminal as its stdout and stderr.
+pub fn run_pipe(command: &str, text: &str) -> Result<ExitStatus> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("failed to run sh")?;
+    // from another thread, so a command that reads slowly (a pager) or not at all can't block
+    // the wait; a command that exits early just closes the pipe
+    if let Some(mut stdin) = child.stdin.take() {
+        let text = text.to_string();
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(text.as_bytes());
+        });
+    }
+    Ok(child.wait()?)
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use chrono::{Local, TimeZone, Utc};
+
+use crate::common::duration::format_duration;
+use crate::tui::client::{self, ConnectOptions};
+
+/// A scheduler loop that hasn't turned for this long is stuck; it turns every ten seconds.
+const STALE_TICK_SECS: i64 = 60;
+
+/// Print the runner's health. Returns the exit code: 1 when its scheduler loop or database
+/// doesn't answer, else 0. A failed config reload is shown but leaves the runner healthy, as it
+/// keeps running the config before.
+pub async fn run(connect: &ConnectOptions) -> Result<i32> {
+    let mut client = client::connect(connect)?;
+    let health = client::get_daemon_health(&mut client).await?;
+    let now = Utc::now().timestamp();
+
+    let uptime = format_duration(&std::time::Duration::from_secs(health.uptime_secs.max(0) as u64));
+    println!("version    {}", health.version);
+    println!("uptime     {} (since {})", uptime, local(health.started_at));
+
+    let tick_age = now - health.scheduler_tick_at;
+    let scheduler_ok = health.scheduler_ok && tick_age < STALE_TICK_SECS;
+    if !health.scheduler_ok {
+        println!("scheduler  not responding");
+    } else {
+        let next = match health.next_fire_at {
+            0 => "nothing scheduled".to_string(),
+            at => format!("next run {}", local(at)),
+        };
+        println!(
+            "scheduler  {}, last turn {}s ago, {} workflows, {} running, {} queued, {}",
+            if scheduler_ok { "ok" } else { "stuck" },
+            tick_age.max(0),
+            health.workflows,
+            health.running,
+            health.queued,
+            next
+        );
+    }
+
+    if health.db_ok {
+        println!("database   ok");
+    } else {
+        println!("database   error: {}", health.db_error);
+    }
+
+    if health.scheduler_ok {
+        println!("config     loaded {}", local(health.config_loaded_at));
+    }
+    if !health.config_error.is_empty() {
+        println!("config     reload failed: {}", health.config_error);
+    }
+
+    Ok(if scheduler_ok && health.db_ok { 0 } else { 1 })
+}
+
+fn local(timestamp: i64) -> String {
+    match Local.timestamp_opt(timestamp, 0).single() {
+        Some(t) => t.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => "-".to_string(),
+    }
+}
@@ -0,0 +1,812 @@
+use std::fmt::Display;
+
+use crate::common::config::load_config;
+use crate::common::types::Language;
+
+/// The TUI's words in one language. Every bundle fills in every field, so a string added here
+/// doesn't build until each language has it. `{}` in a field is filled in by [`fill`], in
+/// order, or `{0}`, `{1}` by position where a language puts them the other way round.
+#[derive(Debug)]
+pub struct Strings {
+    // panels
+    pub workflows: &'static str,
+    pub executions: &'static str,
+    pub executions_of: &'static str,
+    pub log: &'static str,
+    pub log_of: &'static str,
+    pub log_selected: &'static str,
+    pub follow: &'static str,
+    pub refreshing: &'static str,
+    pub refresh_failed: &'static str,
+
+    // workflow and execution lists
+    pub starting: &'static str,
+    pub paused: &'static str,
+    pub queued: &'static str,
+    pub now: &'static str,
+    pub in_hours: &'static str,
+    pub in_minutes: &'static str,
+    pub in_seconds: &'static str,
+    pub unknown_time: &'static str,
+    pub binary: &'static str,
+    pub baseline: &'static str,
+    pub regressed: &'static str,
+    pub errors: Plural,
+    pub warnings: Plural,
+    pub lines: Plural,
+    pub collapsed: Plural,
+    pub bookmarks: Plural,
+    pub executions_count: Plural,
+    pub trigger_on_start: &'static str,
+    pub trigger_catch_up: &'static str,
+    pub trigger_file_change: &'static str,
+    pub trigger_manual: &'static str,
+    pub trigger_manual_cli: &'static str,
+    pub trigger_webhook: &'static str,
+    pub trigger_api: &'static str,
+    pub status_success: &'static str,
+    pub status_failed: &'static str,
+    pub status_running: &'static str,
+    pub status_config_error: &'static str,
+    pub health_ok: &'static str,
+    pub health_failing: &'static str,
+    pub health_stale: &'static str,
+    pub health_regressed: &'static str,
+    pub health_paused: &'static str,
+    pub health_disabled: &'static str,
+    pub health_config_error: &'static str,
+    pub health_never_run: &'static str,
+
+    // durations chart
+    pub durations_of: &'static str,
+    pub no_finished_runs: &'static str,
+    pub median: &'static str,
+    pub duration: &'static str,
+    pub failed: &'static str,
+    pub durations_summary: &'static str,
+
+    // scheduler decisions
+    pub decisions: &'static str,
+    pub decisions_of: &'static str,
+    pub all_workflows: &'static str,
+    pub selected_workflow: &'static str,
+    pub decisions_footer: &'static str,
+    pub no_decisions: &'static str,
+
+    // next run explanation
+    pub next_run_of: &'static str,
+    pub explain_close: &'static str,
+    pub invalid_schedule: &'static str,
+    pub explain_paused: &'static str,
+    pub next_run_at: &'static str,
+    pub up_to_later: &'static str,
+    pub if_checks_allow: &'static str,
+    pub after_current_run: &'static str,
+    pub one_off_passed: &'static str,
+    pub no_scheduled_time: &'static str,
+    pub schedule: &'static str,
+    pub timezone: &'static str,
+    pub runner_local_time: &'static str,
+    pub upcoming: &'static str,
+    pub jitter: &'static str,
+    pub no_jitter: &'static str,
+    pub jitter_up_to: &'static str,
+    pub when_idle: &'static str,
+    pub when_idle_waits: &'static str,
+    pub paused_label: &'static str,
+    pub paused_yes: &'static str,
+    pub paused_no: &'static str,
+    pub schedule_override: &'static str,
+    pub override_in_place: &'static str,
+    pub checks: &'static str,
+    pub no_checks: &'static str,
+    pub should_run_exit: &'static str,
+    pub conditions: &'static str,
+    pub conditions_agree: &'static str,
+    pub overlap: &'static str,
+    pub overlap_queue: &'static str,
+    pub running_queued: &'static str,
+    pub lock: &'static str,
+    pub lock_waits: &'static str,
+    pub distributed_lock: &'static str,
+    pub distributed_lock_waits: &'static str,
+    pub missed_runs: &'static str,
+    pub catch_up_once: &'static str,
+    pub catch_up_reported: &'static str,
+
+    // trigger confirmation
+    pub confirm: &'static str,
+    pub confirm_before: &'static str,
+    pub confirm_after: &'static str,
+    pub confirm_keys: &'static str,
+
+    // keybinding help
+    pub help: &'static str,
+    pub help_footer: &'static str,
+    pub help_everywhere: &'static str,
+    pub help_lists: &'static str,
+    pub help_log: &'static str,
+    pub help_decisions: &'static str,
+    pub help_toggle_help: &'static str,
+    pub help_next_panel: &'static str,
+    pub help_workflows: &'static str,
+    pub help_select: &'static str,
+    pub help_enter: &'static str,
+    pub help_run: &'static str,
+    pub help_pause: &'static str,
+    pub help_details: &'static str,
+    pub help_durations: &'static str,
+    pub help_decisions_popup: &'static str,
+    pub help_explain: &'static str,
+    pub help_restore: &'static str,
+    pub help_cancel: &'static str,
+    pub help_quit: &'static str,
+    pub help_switch_list: &'static str,
+    pub help_snooze: &'static str,
+    pub help_first_error: &'static str,
+    pub help_delete: &'static str,
+    pub help_baseline: &'static str,
+    pub help_scroll_line: &'static str,
+    pub help_scroll_page: &'static str,
+    pub help_scroll_half: &'static str,
+    pub help_top_bottom: &'static str,
+    pub help_follow: &'static str,
+    pub help_search: &'static str,
+    pub help_matches: &'static str,
+    pub help_errors: &'static str,
+    pub help_bookmark: &'static str,
+    pub help_bookmarks: &'static str,
+    pub help_selection: &'static str,
+    pub help_save: &'static str,
+    pub help_pipe: &'static str,
+    pub help_open_log: &'static str,
+    pub help_decisions_scope: &'static str,
+    pub help_newest_oldest: &'static str,
+
+    // workflow details
+    pub aliases: &'static str,
+    pub temporary_override: &'static str,
+    pub script: &'static str,
+    pub inline_script: &'static str,
+    pub steps: &'static str,
+    pub message_script: &'static str,
+    pub interpreter: &'static str,
+    pub working_directory: &'static str,
+    pub arguments: &'static str,
+    pub stdin: &'static str,
+    pub baseline_label: &'static str,
+    pub next_run: &'static str,
+    pub last_run: &'static str,
+    pub health: &'static str,
+    pub success_rate: &'static str,
+    pub success_of: &'static str,
+    pub recent_durations: &'static str,
+    pub current_failure_streak: &'static str,
+    pub longest_failure_streak: &'static str,
+    pub mean_time_between_failures: &'static str,
+    pub config_changes: &'static str,
+    pub detail_close: &'static str,
+
+    // banners
+    pub disconnected: &'static str,
+    pub retrying_in: &'static str,
+    pub reconnecting: &'static str,
+    pub offline: &'static str,
+    pub missed: &'static str,
+    pub catching_up: &'static str,
+
+    // status bar
+    pub export_selection: &'static str,
+    pub export_log: &'static str,
+    pub export_file: &'static str,
+    pub export_command: &'static str,
+    pub export_hint: &'static str,
+    pub busy: &'static str,
+    pub search_hint: &'static str,
+    pub log_hint: &'static str,
+    pub lists_hint: &'static str,
+    pub error: &'static str,
+    pub cancelled: &'static str,
+    pub paused_message: &'static str,
+    pub resumed: &'static str,
+    pub snoozed: &'static str,
+    pub baseline_unpinned: &'static str,
+    pub baseline_pinned: &'static str,
+    pub baseline_needs_success: &'static str,
+    pub deleted: &'static str,
+    pub restored: &'static str,
+    pub runner_back: &'static str,
+    pub runner_stopped: &'static str,
+    pub reconnected: &'static str,
+    pub config_reloaded: &'static str,
+    pub no_errors_in_execution: &'static str,
+    pub log_not_found: &'static str,
+    pub no_errors_in_log: &'static str,
+    pub error_n_of: &'static str,
+    pub bookmark_removed: &'static str,
+    pub bookmarked: &'static str,
+    pub no_bookmarks: &'static str,
+    pub bookmark_n_of: &'static str,
+    pub nothing_to_export: &'static str,
+    pub wrote: &'static str,
+    pub piped: &'static str,
+    pub pipe_exited: &'static str,
+    pub press_enter: &'static str,
+    pub pattern_not_found: &'static str,
+    pub read_only: &'static str,
+
+    // the terminal's title, with `tui.title`
+    pub terminal_title: &'static str,
+
+    // what the status bar says while a request takes a while
+    pub loading_workflows: &'static str,
+    pub loading_missed_runs: &'static str,
+    pub loading_executions: &'static str,
+    pub loading_durations: &'static str,
+    pub loading_stats: &'static str,
+    pub explaining_schedule: &'static str,
+    pub loading_decisions: &'static str,
+    pub triggering: &'static str,
+    pub updating_pause: &'static str,
+    pub snoozing: &'static str,
+    pub pinning_baseline: &'static str,
+    pub deleting: &'static str,
+    pub restoring: &'static str,
+}
+
+/// A count with its noun, e.g. `1 error`, `3 errors`.
+#[derive(Debug)]
+pub struct Plural {
+    pub one: &'static str,
+    pub other: &'static str,
+}
+
+impl Plural {
+    pub fn of(&self, count: usize) -> String {
+        fill(if count == 1 { self.one } else { self.other }, &[&count])
+    }
+}
+
+impl Strings {
+    /// How a workflow's `health` state reads; one the bundle doesn't know reads as it is.
+    pub fn health_state<'a>(&self, health: &'a str) -> &'a str {
+        match health {
+            "ok" => self.health_ok,
+            "failing" => self.health_failing,
+            "stale" => self.health_stale,
+            "regressed" => self.health_regressed,
+            "paused" => self.health_paused,
+            "disabled" => self.health_disabled,
+            "config_error" => self.health_config_error,
+            "never_run" => self.health_never_run,
+            other => other,
+        }
+    }
+
+    /// How an execution's status reads; one the bundle doesn't know reads as it is.
+    pub fn run_status<'a>(&self, status: &'a str) -> &'a str {
+        match status {
+            "success" => self.status_success,
+            "failed" => self.status_failed,
+            "running" => self.status_running,
+            "config_error" => self.status_config_error,
+            other => other,
+        }
+    }
+}
+
+/// The bundle for the local config.yaml's `tui.language`, or English without a config (e.g. on
+/// a machine that only connects to a remote runner).
+pub fn load() -> &'static Strings {
+    match load_config() {
+        Ok(config) => strings(config.tui.language),
+        Err(_) => &EN,
+    }
+}
+
+pub fn strings(language: Language) -> &'static Strings {
+    match language {
+        Language::En => &EN,
+        Language::Ko => &KO,
+    }
+}
+
+/// `template` with each `{}` replaced by the next of `args`, and each `{N}` by the Nth.
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('}').map(|i| open + i) else {
+            rest = &rest[open..];
+            break;
+        };
+        let index = match &rest[open + 1..close] {
+            "" => {
+                next += 1;
+                next - 1
+            }
+            n => n.parse().unwrap_or(usize::MAX),
+        };
+        match args.get(index) {
+            Some(arg) => filled.push_str(&arg.to_string()),
+            None => filled.push_str(&rest[open..=close]),
+        }
+        rest = &rest[close + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+pub static EN: Strings = Strings {
+    workflows: " Workflows ",
+    executions: " Executions ",
+    executions_of: " {} - Executions ",
+    log: " Log ",
+    log_of: " Log - {} ",
+    log_selected: "- {} selected ",
+    follow: "FOLLOW ",
+    refreshing: "⟳ refreshing… ",
+    refresh_failed: "⚠ refresh failed: {}",
+
+    starting: "starting…",
+    paused: "paused",
+    queued: "+{} queued",
+    now: "now",
+    in_hours: "in {}h {}m",
+    in_minutes: "in {}m {}s",
+    in_seconds: "in {}s",
+    unknown_time: "unknown",
+    binary: "binary",
+    baseline: "baseline",
+    regressed: "regressed",
+    errors: Plural { one: "1 error", other: "{} errors" },
+    warnings: Plural { one: "1 warning", other: "{} warnings" },
+    lines: Plural { one: "1 line", other: "{} lines" },
+    collapsed: Plural { one: "1 line collapsed", other: "{} lines collapsed" },
+    bookmarks: Plural { one: "1 bookmark", other: "{} bookmarks" },
+    executions_count: Plural { one: "1 execution", other: "{} executions" },
+    trigger_on_start: "on start",
+    trigger_catch_up: "catch-up",
+    trigger_file_change: "file change",
+    trigger_manual: "manual",
+    trigger_manual_cli: "manual (cli)",
+    trigger_webhook: "webhook",
+    trigger_api: "api",
+    status_success: "success",
+    status_failed: "failed",
+    status_running: "running",
+    status_config_error: "config error",
+    health_ok: "ok",
+    health_failing: "failing",
+    health_stale: "stale",
+    health_regressed: "regressed",
+    health_paused: "paused",
+    health_disabled: "disabled",
+    health_config_error: "config error",
+    health_never_run: "never run",
+
+    durations_of: " {} - Durations ",
+    no_finished_runs: "No finished executions yet",
+    median: "median",
+    duration: "duration",
+    failed: "failed",
+    durations_summary: "last {} runs   min {}   median {}   max {}   [c/Esc] close",
+
+    decisions: " Scheduler decisions ",
+    decisions_of: " {} - Scheduler decisions ",
+    all_workflows: "all workflows",
+    selected_workflow: "selected workflow",
+    decisions_footer: " [a] {}  [↑↓ PgUp/PgDn] scroll  [s/Esc] close ",
+    no_decisions: "Nothing decided since the runner started",
+
+    next_run_of: " {} - Next run ",
+    explain_close: " [x/Esc] close ",
+    invalid_schedule: "Invalid schedule: {}",
+    explain_paused: "Paused: scheduled runs are skipped until it is resumed",
+    next_run_at: "Next run {}",
+    up_to_later: ", up to {} later",
+    if_checks_allow: ", if its checks allow it",
+    after_current_run: ", after the run going now",
+    one_off_passed: "No upcoming run: its one-off time has passed",
+    no_scheduled_time: "No scheduled time: it runs on start or when watched paths change",
+    schedule: "Schedule",
+    timezone: "Timezone",
+    runner_local_time: "the runner's local time",
+    upcoming: "Upcoming",
+    jitter: "Jitter",
+    no_jitter: "none",
+    jitter_up_to: "each scheduled run starts up to {} later",
+    when_idle: "When idle",
+    when_idle_waits: "automatic runs wait until there has been no input for {}",
+    paused_label: "Paused",
+    paused_yes: "yes: scheduled and watch runs are skipped",
+    paused_no: "no",
+    schedule_override: "Override",
+    override_in_place: "{}, in place of the schedule above",
+    checks: "Checks",
+    no_checks: "none: every automatic run starts",
+    should_run_exit: "{} must return true before each automatic run",
+    conditions: "Conditions",
+    conditions_agree: "{} must agree after that",
+    overlap: "Overlap",
+    overlap_queue: "a trigger during a run waits and runs after it",
+    running_queued: "; running now, {} queued",
+    lock: "Lock",
+    lock_waits: "waits while another workflow holds {}",
+    distributed_lock: "Distributed lock",
+    distributed_lock_waits: "waits for {}, shared with other machines",
+    missed_runs: "Missed runs",
+    catch_up_once: "a time missed while the runner was down runs once on start",
+    catch_up_reported: "times missed while the runner was down are only reported",
+
+    confirm: " Confirm ",
+    confirm_before: "Run ",
+    confirm_after: " now?  ",
+    confirm_keys: "[y]es  [n]o",
+
+    help: " Keybindings ",
+    help_footer: " [?/Esc] close  [↑↓ PgUp/PgDn] scroll ",
+    help_everywhere: "Everywhere",
+    help_lists: "Workflows / Executions",
+    help_log: "Log",
+    help_decisions: "Scheduler decisions",
+    help_toggle_help: "Show or close this help",
+    help_next_panel: "Cycle to next panel",
+    help_workflows: "Jump to Workflows panel",
+    help_select: "Select previous / next item",
+    help_enter: "Confirm selection and move to next panel",
+    help_run: "Trigger selected workflow, after a y/n prompt",
+    help_pause: "Pause / resume scheduled runs of selected workflow",
+    help_details: "Details of selected workflow",
+    help_durations: "Chart recent execution durations",
+    help_decisions_popup: "Scheduler decisions: why a run started, waited or was skipped",
+    help_explain: "Explain when selected workflow runs next",
+    help_restore: "Restore deleted executions of selected workflow",
+    help_cancel: "Close popup, clear search or selection, cancel requests",
+    help_quit: "Quit",
+    help_switch_list: "Move between Workflows and Executions",
+    help_snooze: "Skip the next scheduled run of selected workflow",
+    help_first_error: "Open the selected execution's log at its first error",
+    help_delete: "Delete selected execution (restorable)",
+    help_baseline: "Pin / unpin selected execution as the baseline",
+    help_scroll_line: "Scroll by a line",
+    help_scroll_page: "Scroll by a page",
+    help_scroll_half: "Scroll by half a page",
+    help_top_bottom: "Jump to the top / bottom",
+    help_follow: "Toggle follow mode",
+    help_search: "Search; Enter confirms",
+    help_matches: "Next / previous search match",
+    help_errors: "Next / previous error line",
+    help_bookmark: "Bookmark the current line, or remove its bookmark",
+    help_bookmarks: "Next / previous bookmark",
+    help_selection: "Start or drop a selection of lines",
+    help_save: "Save the selection, or the whole log, to a file",
+    help_pipe: "Pipe the selection, or the whole log, through a command",
+    help_open_log: "Open the selected execution's log in $PAGER (or $EDITOR)",
+    help_decisions_scope: "Switch between the selected workflow and all of them",
+    help_newest_oldest: "Jump to the newest / oldest",
+
+    aliases: "Aliases",
+    temporary_override: "(temporary override)",
+    script: "Script",
+    inline_script: "inline, {}: {}",
+    steps: "Steps",
+    message_script: "Message script",
+    interpreter: "Interpreter",
+    working_directory: "Working directory",
+    arguments: "Arguments",
+    stdin: "Stdin",
+    baseline_label: "Baseline",
+    next_run: "Next run",
+    last_run: "Last run",
+    health: "Health",
+    success_rate: "Success rate",
+    success_of: "{}% ({} of {} runs)",
+    recent_durations: "Recent durations",
+    current_failure_streak: "Current failure streak",
+    longest_failure_streak: "Longest failure streak",
+    mean_time_between_failures: "Mean time between fails",
+    config_changes: "Config changes",
+    detail_close: "[i/Esc] close",
+
+    disconnected: " ⚠ Disconnected from the runner for {}, {}",
+    retrying_in: "retrying in {}",
+    reconnecting: "reconnecting…",
+    offline: " Runner not running: showing history from the database, read-only",
+    missed: " Missed while the runner was down: {}",
+    catching_up: ", catching up",
+
+    export_selection: "selected lines",
+    export_log: "log",
+    export_file: "file",
+    export_command: "command",
+    export_hint: "({} to {}; [Enter] run  [{}] cancel)",
+    busy: "{}…  [{}] cancel",
+    search_hint: "[n] next match  [N] previous match  [{}] new search  [{}] clear search",
+    log_hint: "[{}{}] scroll  [{}/{}] page  [f] follow  [{}] search  [e/E] errors  [m] mark  \
+               [v] select  [>/|] save/pipe  [o] pager  [{}] next panel  [?] help  [{}] quit",
+    lists_hint: "[←→] switch panel  [w] workflows  [{}{}] select  [{}] run now  [{}] pause  \
+                 [i] info  [c] durations  [x] next run  [s] decisions  [?] help  [{}] quit",
+    error: "Error: {}",
+    cancelled: "Cancelled",
+    paused_message: "{} paused; scheduled runs are skipped",
+    resumed: "{} resumed",
+    snoozed: "{}: skipping the run at {}",
+    baseline_unpinned: "{}: baseline unpinned",
+    baseline_pinned: "{}: later runs are compared with this one",
+    baseline_needs_success: "Only a successful run can be the baseline",
+    deleted: "Execution deleted ([u] to restore)",
+    restored: "Restored {}",
+    runner_back: "The runner is back; showing live data",
+    runner_stopped: "The runner stopped; showing history read-only",
+    reconnected: "Reconnected to the runner",
+    config_reloaded: "Config reloaded: {}",
+    no_errors_in_execution: "No errors in this execution",
+    log_not_found: "Log file not found: {}",
+    no_errors_in_log: "No errors in this log",
+    error_n_of: "Error {} of {} (line {})",
+    bookmark_removed: "Removed bookmark at line {}",
+    bookmarked: "Bookmarked line {}",
+    no_bookmarks: "No bookmarks in this log ([m] to add one)",
+    bookmark_n_of: "Bookmark {} of {} (line {})",
+    nothing_to_export: "No log to export",
+    wrote: "Wrote {} to {}",
+    piped: "Piped {} to {}",
+    pipe_exited: "{} exited with {}",
+    press_enter: "{}. Press Enter to return.",
+    pattern_not_found: "Pattern not found: {}",
+    read_only: "Read-only: the runner is not running",
+    terminal_title: "wf: {} running, {} failed",
+
+    loading_workflows: "loading workflows",
+    loading_missed_runs: "loading missed runs",
+    loading_executions: "loading executions",
+    loading_durations: "loading durations",
+    loading_stats: "loading stats",
+    explaining_schedule: "explaining schedule",
+    loading_decisions: "loading decisions",
+    triggering: "triggering",
+    updating_pause: "updating pause",
+    snoozing: "snoozing",
+    pinning_baseline: "pinning baseline",
+    deleting: "deleting",
+    restoring: "restoring",
+};
+
+pub static KO: Strings = Strings {
+    workflows: " 워크플로 ",
+    executions: " 실행 기록 ",
+    executions_of: " {} - 실행 기록 ",
+    log: " 로그 ",
+    log_of: " 로그 - {} ",
+    log_selected: "- {} 선택됨 ",
+    follow: "따라가기 ",
+    refreshing: "⟳ 새로 고치는 중… ",
+    refresh_failed: "⚠ 새로 고치지 못함: {}",
+
+    starting: "시작하는 중…",
+    paused: "일시 정지",
+    queued: "+{}개 대기",
+    now: "지금",
+    in_hours: "{}시간 {}분 후",
+    in_minutes: "{}분 {}초 후",
+    in_seconds: "{}초 후",
+    unknown_time: "알 수 없음",
+    binary: "바이너리",
+    baseline: "기준",
+    regressed: "성능 저하",
+    errors: Plural { one: "오류 1개", other: "오류 {}개" },
+    warnings: Plural { one: "경고 1개", other: "경고 {}개" },
+    lines: Plural { one: "1줄", other: "{}줄" },
+    collapsed: Plural { one: "1줄 접힘", other: "{}줄 접힘" },
+    bookmarks: Plural { one: "북마크 1개", other: "북마크 {}개" },
+    executions_count: Plural { one: "실행 1건", other: "실행 {}건" },
+    trigger_on_start: "시작 시",
+    trigger_catch_up: "놓친 실행",
+    trigger_file_change: "파일 변경",
+    trigger_manual: "수동",
+    trigger_manual_cli: "수동 (cli)",
+    trigger_webhook: "웹훅",
+    trigger_api: "api",
+    status_success: "성공",
+    status_failed: "실패",
+    status_running: "실행 중",
+    status_config_error: "설정 오류",
+    health_ok: "정상",
+    health_failing: "실패 중",
+    health_stale: "지연",
+    health_regressed: "성능 저하",
+    health_paused: "일시 정지",
+    health_disabled: "비활성",
+    health_config_error: "설정 오류",
+    health_never_run: "실행 전",
+
+    durations_of: " {} - 실행 시간 ",
+    no_finished_runs: "아직 끝난 실행이 없습니다",
+    median: "중앙값",
+    duration: "실행 시간",
+    failed: "실패",
+    durations_summary: "최근 {}회   최소 {}   중앙값 {}   최대 {}   [c/Esc] 닫기",
+
+    decisions: " 스케줄러 결정 ",
+    decisions_of: " {} - 스케줄러 결정 ",
+    all_workflows: "모든 워크플로",
+    selected_workflow: "선택한 워크플로",
+    decisions_footer: " [a] {}  [↑↓ PgUp/PgDn] 스크롤  [s/Esc] 닫기 ",
+    no_decisions: "러너가 시작된 뒤로 내린 결정이 없습니다",
+
+    next_run_of: " {} - 다음 실행 ",
+    explain_close: " [x/Esc] 닫기 ",
+    invalid_schedule: "잘못된 일정: {}",
+    explain_paused: "일시 정지: 다시 시작할 때까지 예약 실행을 건너뜁니다",
+    next_run_at: "다음 실행 {}",
+    up_to_later: ", 최대 {} 늦게",
+    if_checks_allow: ", 검사를 통과하면",
+    after_current_run: ", 지금 실행이 끝난 뒤",
+    one_off_passed: "예정된 실행 없음: 한 번만 실행할 시각이 지났습니다",
+    no_scheduled_time: "예약 시각 없음: 시작할 때나 감시하는 경로가 바뀔 때 실행됩니다",
+    schedule: "일정",
+    timezone: "시간대",
+    runner_local_time: "러너의 현지 시각",
+    upcoming: "예정",
+    jitter: "지터",
+    no_jitter: "없음",
+    jitter_up_to: "예약 실행마다 최대 {} 늦게 시작합니다",
+    when_idle: "유휴 시",
+    when_idle_waits: "입력이 {} 동안 없을 때까지 자동 실행이 기다립니다",
+    paused_label: "일시 정지",
+    paused_yes: "예: 예약 실행과 감시 실행을 건너뜁니다",
+    paused_no: "아니요",
+    schedule_override: "임시 일정",
+    override_in_place: "{}, 위 일정 대신",
+    checks: "검사",
+    no_checks: "없음: 모든 자동 실행이 시작됩니다",
+    should_run_exit: "자동 실행마다 먼저 {}이(가) true를 돌려줘야 합니다",
+    conditions: "조건",
+    conditions_agree: "그다음 {}이(가) 허락해야 합니다",
+    overlap: "겹칠 때",
+    overlap_queue: "실행 중에 들어온 트리거는 기다렸다가 끝난 뒤 실행됩니다",
+    running_queued: "; 지금 실행 중, {}개 대기",
+    lock: "잠금",
+    lock_waits: "다른 워크플로가 {}을(를) 잡고 있으면 기다립니다",
+    distributed_lock: "분산 잠금",
+    distributed_lock_waits: "다른 머신과 함께 쓰는 {}을(를) 기다립니다",
+    missed_runs: "놓친 실행",
+    catch_up_once: "러너가 꺼져 있는 동안 놓친 시각은 시작할 때 한 번 실행됩니다",
+    catch_up_reported: "러너가 꺼져 있는 동안 놓친 시각은 알리기만 합니다",
+
+    confirm: " 확인 ",
+    confirm_before: "",
+    confirm_after: "을(를) 지금 실행할까요?  ",
+    confirm_keys: "[y] 예  [n] 아니요",
+
+    help: " 단축키 ",
+    help_footer: " [?/Esc] 닫기  [↑↓ PgUp/PgDn] 스크롤 ",
+    help_everywhere: "어디서나",
+    help_lists: "워크플로 / 실행 기록",
+    help_log: "로그",
+    help_decisions: "스케줄러 결정",
+    help_toggle_help: "이 도움말 열기 / 닫기",
+    help_next_panel: "다음 패널로 이동",
+    help_workflows: "워크플로 패널로 이동",
+    help_select: "이전 / 다음 항목 선택",
+    help_enter: "선택하고 다음 패널로 이동",
+    help_run: "선택한 워크플로를 y/n 확인 뒤 실행",
+    help_pause: "선택한 워크플로의 예약 실행 일시 정지 / 다시 시작",
+    help_details: "선택한 워크플로의 자세한 정보",
+    help_durations: "최근 실행 시간 차트",
+    help_decisions_popup: "스케줄러 결정: 실행이 시작되거나 기다리거나 건너뛴 이유",
+    help_explain: "선택한 워크플로가 다음에 언제 실행되는지 설명",
+    help_restore: "선택한 워크플로의 삭제한 실행 기록 복원",
+    help_cancel: "팝업 닫기, 검색이나 선택 지우기, 요청 취소",
+    help_quit: "종료",
+    help_switch_list: "워크플로와 실행 기록 사이 이동",
+    help_snooze: "선택한 워크플로의 다음 예약 실행 건너뛰기",
+    help_first_error: "선택한 실행의 로그를 첫 오류에서 열기",
+    help_delete: "선택한 실행 기록 삭제 (복원 가능)",
+    help_baseline: "선택한 실행을 기준으로 고정 / 해제",
+    help_scroll_line: "한 줄씩 스크롤",
+    help_scroll_page: "한 쪽씩 스크롤",
+    help_scroll_half: "반 쪽씩 스크롤",
+    help_top_bottom: "맨 위 / 맨 아래로 이동",
+    help_follow: "따라가기 켜기 / 끄기",
+    help_search: "검색; Enter로 확정",
+    help_matches: "다음 / 이전 검색 결과",
+    help_errors: "다음 / 이전 오류 줄",
+    help_bookmark: "지금 줄에 북마크 추가 / 제거",
+    help_bookmarks: "다음 / 이전 북마크",
+    help_selection: "줄 선택 시작 / 취소",
+    help_save: "선택한 줄이나 로그 전체를 파일로 저장",
+    help_pipe: "선택한 줄이나 로그 전체를 명령으로 보내기",
+    help_open_log: "선택한 실행의 로그를 $PAGER(또는 $EDITOR)로 열기",
+    help_decisions_scope: "선택한 워크플로와 모든 워크플로 사이 전환",
+    help_newest_oldest: "가장 최근 / 가장 오래된 결정으로 이동",
+
+    aliases: "별칭",
+    temporary_override: "(임시 일정)",
+    script: "스크립트",
+    inline_script: "인라인, {}: {}",
+    steps: "단계",
+    message_script: "메시지 스크립트",
+    interpreter: "인터프리터",
+    working_directory: "작업 디렉터리",
+    arguments: "인자",
+    stdin: "표준 입력",
+    baseline_label: "기준",
+    next_run: "다음 실행",
+    last_run: "마지막 실행",
+    health: "상태",
+    success_rate: "성공률",
+    success_of: "{0}% ({2}회 중 {1}회)",
+    recent_durations: "최근 실행 시간",
+    current_failure_streak: "현재 연속 실패",
+    longest_failure_streak: "최장 연속 실패",
+    mean_time_between_failures: "평균 실패 간격",
+    config_changes: "설정 변경",
+    detail_close: "[i/Esc] 닫기",
+
+    disconnected: " ⚠ 러너와 {} 동안 연결이 끊겼습니다, {}",
+    retrying_in: "{} 후 다시 시도",
+    reconnecting: "다시 연결하는 중…",
+    offline: " 러너가 실행 중이 아닙니다: 데이터베이스의 기록을 읽기 전용으로 보여 줍니다",
+    missed: " 러너가 꺼져 있는 동안 놓친 실행: {}",
+    catching_up: ", 따라잡는 중",
+
+    export_selection: "선택한 줄",
+    export_log: "로그",
+    export_file: "파일",
+    export_command: "명령",
+    export_hint: "({} → {}; [Enter] 실행  [{}] 취소)",
+    busy: "{}…  [{}] 취소",
+    search_hint: "[n] 다음 결과  [N] 이전 결과  [{}] 새 검색  [{}] 검색 지우기",
+    log_hint: "[{}{}] 스크롤  [{}/{}] 쪽  [f] 따라가기  [{}] 검색  [e/E] 오류  [m] 북마크  \
+               [v] 선택  [>/|] 저장/보내기  [o] 페이저  [{}] 다음 패널  [?] 도움말  [{}] 종료",
+    lists_hint: "[←→] 패널 전환  [w] 워크플로  [{}{}] 선택  [{}] 지금 실행  [{}] 일시 정지  \
+                 [i] 정보  [c] 실행 시간  [x] 다음 실행  [s] 결정  [?] 도움말  [{}] 종료",
+    error: "오류: {}",
+    cancelled: "취소했습니다",
+    paused_message: "{}을(를) 일시 정지했습니다; 예약 실행을 건너뜁니다",
+    resumed: "{}을(를) 다시 시작했습니다",
+    snoozed: "{}: {} 실행을 건너뜁니다",
+    baseline_unpinned: "{}: 기준을 해제했습니다",
+    baseline_pinned: "{}: 이후 실행을 이 실행과 비교합니다",
+    baseline_needs_success: "성공한 실행만 기준이 될 수 있습니다",
+    deleted: "실행 기록을 삭제했습니다 ([u]로 복원)",
+    restored: "{}을(를) 복원했습니다",
+    runner_back: "러너가 돌아왔습니다; 실시간 데이터를 보여 줍니다",
+    runner_stopped: "러너가 멈췄습니다; 기록을 읽기 전용으로 보여 줍니다",
+    reconnected: "러너에 다시 연결했습니다",
+    config_reloaded: "설정을 다시 읽었습니다: {}",
+    no_errors_in_execution: "이 실행에는 오류가 없습니다",
+    log_not_found: "로그 파일이 없습니다: {}",
+    no_errors_in_log: "이 로그에는 오류가 없습니다",
+    error_n_of: "오류 {1}개 중 {0}번째 ({2}번째 줄)",
+    bookmark_removed: "{}번째 줄의 북마크를 제거했습니다",
+    bookmarked: "{}번째 줄에 북마크를 추가했습니다",
+    no_bookmarks: "이 로그에는 북마크가 없습니다 ([m]으로 추가)",
+    bookmark_n_of: "북마크 {1}개 중 {0}번째 ({2}번째 줄)",
+    nothing_to_export: "내보낼 로그가 없습니다",
+    wrote: "{0}을(를) {1}에 저장했습니다",
+    piped: "{0}을(를) {1}(으)로 보냈습니다",
+    pipe_exited: "{}이(가) {}(으)로 끝났습니다",
+    press_enter: "{}. 돌아가려면 Enter를 누르세요.",
+    pattern_not_found: "찾을 수 없습니다: {}",
+    read_only: "읽기 전용: 러너가 실행 중이 아닙니다",
+    terminal_title: "wf: 실행 중 {}, 실패 {}",
+
+    loading_workflows: "워크플로를 불러오는 중",
+    loading_missed_runs: "놓친 실행을 불러오는 중",
+    loading_executions: "실행 기록을 불러오는 중",
+    loading_durations: "실행 시간을 불러오는 중",
+    loading_stats: "통계를 불러오는 중",
+    explaining_schedule: "일정을 확인하는 중",
+    loading_decisions: "결정을 불러오는 중",
+    triggering: "실행 요청 중",
+    updating_pause: "일시 정지를 바꾸는 중",
+    snoozing: "건너뛰는 중",
+    pinning_baseline: "기준을 고정하는 중",
+    deleting: "삭제하는 중",
+    restoring: "복원하는 중",
+};
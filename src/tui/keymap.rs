@@ -0,0 +1,260 @@
+use std::str::FromStr;
+
+use anyhow::{Result, bail};
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::common::config::load_config;
+use crate::common::types::{KeyPreset, KeysConfig};
+
+/// What the rebindable keys do. Every other key is fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Run,
+    Pause,
+    /// Close the popup, clear the search or selection, or cancel in-flight requests.
+    Cancel,
+    Search,
+    NextPanel,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+}
+
+const ACTIONS: [(Action, &str); 10] = [
+    (Action::Quit, "quit"),
+    (Action::Run, "run"),
+    (Action::Pause, "pause"),
+    (Action::Cancel, "cancel"),
+    (Action::Search, "search"),
+    (Action::NextPanel, "next_panel"),
+    (Action::Up, "up"),
+    (Action::Down, "down"),
+    (Action::PageUp, "page_up"),
+    (Action::PageDown, "page_down"),
+];
+
+/// A key with its modifiers, written like `q`, `esc`, `ctrl-s` or `alt-v` in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn matches(&self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        // the character already says whether shift was held
+        let modifiers = match code {
+            KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        self.code == code && self.modifiers == modifiers
+    }
+
+    /// Short label for help texts: `q`, `^s`, `M-v`, `Esc`.
+    pub fn label(&self) -> String {
+        let code = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "S-Tab".to_string(),
+            KeyCode::Backspace => "Bksp".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::Insert => "Ins".to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            other => format!("{:?}", other),
+        };
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push('^');
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("M-");
+        }
+        label + code.as_str()
+    }
+}
+
+impl FromStr for Key {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        // a lone "-" is the minus key, not a modifier separator
+        while rest.len() > 1 {
+            let lower = rest.to_ascii_lowercase();
+            if lower.starts_with("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = &rest[5..];
+            } else if lower.starts_with("alt-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = &rest[4..];
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            // ctrl-S reads as ctrl-s: terminals don't report shift with control
+            (Some(c), None) if modifiers.is_empty() => KeyCode::Char(c),
+            (Some(c), None) => KeyCode::Char(c.to_ascii_lowercase()),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" | "shift-tab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pgup" | "pageup" => KeyCode::PageUp,
+                "pgdn" | "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "del" | "delete" => KeyCode::Delete,
+                "ins" | "insert" => KeyCode::Insert,
+                f if f.len() > 1 && f.starts_with('f') => match f[1..].parse() {
+                    Ok(n @ 1..=12) => KeyCode::F(n),
+                    _ => bail!("unknown key {:?}", s),
+                },
+                _ => bail!("unknown key {:?}", s),
+            },
+        };
+        Ok(Key { code, modifiers })
+    }
+}
+
+/// Keys for each [`Action`], from the configured preset and bindings.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&KeysConfig::default()).0
+    }
+}
+
+impl Keymap {
+    /// The keymap of the local config.yaml, or the default one without a config (e.g. on a
+    /// machine that only connects to a remote runner). Problems are described in the messages.
+    pub fn load() -> (Self, Vec<String>) {
+        match load_config() {
+            Ok(config) => Self::new(&config.keys),
+            Err(_) => (Self::default(), Vec::new()),
+        }
+    }
+
+    /// Keys that don't parse and unknown actions are skipped and described in the returned
+    /// messages; an action left without keys keeps its preset ones.
+    pub fn new(config: &KeysConfig) -> (Self, Vec<String>) {
+        let mut problems = Vec::new();
+        let mut bindings: Vec<(Action, Vec<Key>)> = ACTIONS
+            .iter()
+            .map(|&(action, _)| {
+                let keys = preset_keys(config.preset, action).iter();
+                (action, keys.map(|k| k.parse().expect("valid preset key")).collect())
+            })
+            .collect();
+
+        for (name, keys) in &config.bindings {
+            let Some(&(action, _)) = ACTIONS.iter().find(|(_, n)| n == name) else {
+                problems.push(format!("keys: unknown action {:?}", name));
+                continue;
+            };
+            let parsed: Vec<Key> = keys
+                .iter()
+                .filter_map(|k| match k.parse() {
+                    Ok(key) => Some(key),
+                    Err(e) => {
+                        problems.push(format!("keys.{}: {}", name, e));
+                        None
+                    }
+                })
+                .collect();
+            if let Some(entry) = bindings.iter_mut().find(|(a, _)| *a == action) {
+                if !parsed.is_empty() {
+                    entry.1 = parsed;
+                }
+            }
+        }
+        (Self { bindings }, problems)
+    }
+
+    pub fn action(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|k| k.matches(code, modifiers)))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn is(&self, action: Action, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        self.keys(action).iter().any(|k| k.matches(code, modifiers))
+    }
+
+    /// Label of the first key bound to `action`, for help texts.
+    pub fn label(&self, action: Action) -> String {
+        self.keys(action).first().map_or_else(|| "?".to_string(), Key::label)
+    }
+
+    /// Labels of all keys bound to `action`, e.g. `^p/↑`.
+    pub fn labels(&self, action: Action) -> String {
+        let labels: Vec<String> = self.keys(action).iter().map(Key::label).collect();
+        labels.join("/")
+    }
+
+    fn keys(&self, action: Action) -> &[Key] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or(&[], |(_, keys)| keys.as_slice())
+    }
+}
+
+fn preset_keys(preset: KeyPreset, action: Action) -> &'static [&'static str] {
+    match (preset, action) {
+        (_, Action::Run) => &["r"],
+        (_, Action::Pause) => &["p"],
+        (_, Action::Quit) => &["q"],
+
+        (KeyPreset::Default, Action::Cancel) => &["esc"],
+        (KeyPreset::Default, Action::Search) => &["/"],
+        (KeyPreset::Default, Action::NextPanel) => &["tab"],
+        (KeyPreset::Default, Action::Up) => &["up", "k"],
+        (KeyPreset::Default, Action::Down) => &["down", "j"],
+        (KeyPreset::Default, Action::PageUp) => &["pgup"],
+        (KeyPreset::Default, Action::PageDown) => &["pgdn"],
+
+        (KeyPreset::Vim, Action::Cancel) => &["esc", "ctrl-c"],
+        (KeyPreset::Vim, Action::Search) => &["/"],
+        (KeyPreset::Vim, Action::NextPanel) => &["tab", "ctrl-w"],
+        (KeyPreset::Vim, Action::Up) => &["k", "up"],
+        (KeyPreset::Vim, Action::Down) => &["j", "down"],
+        (KeyPreset::Vim, Action::PageUp) => &["ctrl-b", "pgup"],
+        (KeyPreset::Vim, Action::PageDown) => &["ctrl-f", "pgdn"],
+
+        (KeyPreset::Emacs, Action::Cancel) => &["ctrl-g", "esc"],
+        (KeyPreset::Emacs, Action::Search) => &["ctrl-s", "/"],
+        (KeyPreset::Emacs, Action::NextPanel) => &["ctrl-o", "tab"],
+        (KeyPreset::Emacs, Action::Up) => &["ctrl-p", "up"],
+        (KeyPreset::Emacs, Action::Down) => &["ctrl-n", "down"],
+        (KeyPreset::Emacs, Action::PageUp) => &["alt-v", "pgup"],
+        (KeyPreset::Emacs, Action::PageDown) => &["ctrl-v", "pgdn"],
+    }
+}
@@ -0,0 +1,82 @@
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+
+use crate::runner::log_compress;
+use crate::tui::app::Message;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Update from a log tailer. `tail_id` identifies the tailer so the App can ignore output of
+/// one it already replaced.
+#[derive(Debug)]
+pub enum LogChunk {
+    /// The whole file as it was when tailing started.
+    Loaded(Vec<String>),
+    /// Complete lines written since the last chunk.
+    Appended(Vec<String>),
+    Missing,
+}
+
+/// Read `path` and keep following it in the background until aborted. A log gzipped after its
+/// run is loaded once, unpacked; nothing is written to it any more.
+pub fn spawn(path: PathBuf, tail_id: u64, tx: mpsc::UnboundedSender<Message>) -> AbortHandle {
+    let handle = tokio::spawn(async move {
+        let send = |chunk| tx.send(Message::Log { tail_id, chunk }).is_ok();
+
+        let Ok(mut file) = tokio::fs::File::open(&path).await else {
+            match log_compress::read(&path).await {
+                Ok(mut log) => send(LogChunk::Loaded(take_complete_lines(&mut log))),
+                Err(_) => send(LogChunk::Missing),
+            };
+            return;
+        };
+        let mut pos = 0u64;
+        // bytes after the last newline; kept until the line is complete
+        let mut partial = Vec::new();
+        let mut first = true;
+
+        loop {
+            let len = file.metadata().await.map(|m| m.len()).unwrap_or(pos);
+            if len > pos || first {
+                let mut buf = Vec::new();
+                if file.seek(SeekFrom::Start(pos)).await.is_err()
+                    || file.read_to_end(&mut buf).await.is_err()
+                {
+                    return;
+                }
+                pos += buf.len() as u64;
+                partial.extend_from_slice(&buf);
+
+                let lines = take_complete_lines(&mut partial);
+                let chunk = if first {
+                    LogChunk::Loaded(lines)
+                } else {
+                    LogChunk::Appended(lines)
+                };
+                first = false;
+                if !send(chunk) {
+                    return;
+                }
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+    handle.abort_handle()
+}
+
+fn take_complete_lines(buf: &mut Vec<u8>) -> Vec<String> {
+    let Some(last_newline) = buf.iter().rposition(|b| *b == b'\n') else {
+        return Vec::new();
+    };
+    let rest = buf.split_off(last_newline + 1);
+    let complete = std::mem::replace(buf, rest);
+    String::from_utf8_lossy(&complete)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
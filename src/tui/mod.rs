@@ -1,28 +1,58 @@
+pub mod ansi;
 pub mod app;
+pub mod bookmarks;
 pub mod client;
+pub mod export;
+pub mod health;
+pub mod i18n;
+pub mod keymap;
+pub mod log_tail;
+pub mod report;
+pub mod schedule_override;
+pub mod status;
+pub mod title;
 pub mod ui;
 
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crossterm::{
-    event::{self, Event, KeyCode, KeyModifiers},
+    cursor,
+    event::{Event, EventStream, KeyCode, KeyModifiers},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use futures::StreamExt;
 use ratatui::{Terminal, backend::CrosstermBackend};
 use tokio::time::interval;
+use uuid::Uuid;
 
-use crate::tui::app::{App, Panel};
+use crate::runner::log_compress;
+use crate::tui::app::{App, Message, Panel};
+use crate::tui::client::ConnectOptions;
+use crate::tui::export::{ExportKind, PipeRequest};
+use crate::tui::i18n::{Strings, fill};
+use crate::tui::keymap::Action;
+use crate::tui::title::Title;
 
-pub async fn run() -> Result<()> {
+/// Display settings from the command line.
+#[derive(Debug, Clone, Default)]
+pub struct ViewOptions {
+    /// Drop ANSI colors from logs instead of rendering them.
+    pub strip_ansi: bool,
+}
+
+pub async fn run(options: ConnectOptions, view: ViewOptions) -> Result<()> {
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let result = run_app(&mut terminal).await;
+    let result = run_app(&mut terminal, &options, view).await;
 
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
@@ -31,40 +61,51 @@ pub async fn run() -> Result<()> {
     result
 }
 
-async fn run_app(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>) -> Result<()> {
-    let mut app = App::new().await?;
-
-    app.refresh_workflows().await?;
-    if !app.workflows.is_empty() {
-        app.refresh_executions().await?;
-        if !app.executions.is_empty() {
-            app.select_execution(0);
-        }
-    }
+async fn run_app(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    options: &ConnectOptions,
+    view: ViewOptions,
+) -> Result<()> {
+    let mut app = App::new(options, view)?;
+    let mut title = Title::load();
+    title.save(terminal.backend_mut())?;
 
-    let mut refresh_interval = interval(Duration::from_secs(5));
-    let mut log_poll_interval = interval(Duration::from_millis(500));
+    // polled by the select below, so input needs no thread of its own
+    let mut input = EventStream::new();
+    let mut tick = interval(Duration::from_secs(1));
 
     loop {
         terminal.draw(|f| ui::draw(f, &app))?;
+        title.update(terminal.backend_mut(), &app.workflows, app.text)?;
 
         tokio::select! {
-            _ = refresh_interval.tick() => {
-                let _ = app.refresh_workflows().await;
-                let _ = app.refresh_executions().await;
-            }
-            _ = log_poll_interval.tick() => {
-                app.poll_log_updates();
-            }
-            _ = tokio::task::spawn_blocking(|| {
-                event::poll(Duration::from_millis(100))
-            }) => {
-                if event::poll(Duration::from_millis(0))? {
-                    if let Event::Key(key) = event::read()? {
-                        handle_key(&mut app, key.code, key.modifiers).await?;
-                    }
-                }
+            _ = tick.tick() => {
+                app.tick();
             }
+            event = input.next() => match event {
+                Some(Ok(Event::Key(key))) => handle_key(&mut app, key.code, key.modifiers),
+                // anything else (e.g. a resize) only needs the redraw
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+                None => break,
+            },
+            Some(message) = app.rx.recv() => match message {
+                Message::Task(id, output) => app.apply(id, *output),
+                Message::Log { tail_id, chunk } => app.apply_log(tail_id, chunk),
+                Message::Watch { watch_id, update } => app.apply_watch(watch_id, update),
+            },
+        }
+
+        if let Some(pipe) = app.pipe_request.take() {
+            // the stream would otherwise read keys meant for the command
+            drop(input);
+            app.status_message = run_pipe(terminal, &pipe, app.text)?;
+            input = EventStream::new();
+        }
+        if let Some(path) = app.open_request.take() {
+            drop(input);
+            app.status_message = open_log(terminal, &path, app.text).await?;
+            input = EventStream::new();
         }
 
         if app.should_quit {
@@ -72,31 +113,263 @@ async fn run_app(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>) ->
         }
     }
 
-    Ok(())
+    title.restore(terminal.backend_mut())
+}
+
+/// Run a `|` command on the normal screen, so its output (or a pager) is visible, and come
+/// back once Enter is pressed. Returns the outcome for the status bar.
+fn run_pipe(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    pipe: &PipeRequest,
+    text: &Strings,
+) -> Result<String> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, cursor::Show)?;
+    println!("$ {}", pipe.command);
+    let outcome = match export::run_pipe(&pipe.command, &pipe.text) {
+        Ok(status) if status.success() => {
+            fill(text.piped, &[&text.lines.of(pipe.lines), &pipe.command])
+        }
+        Ok(status) => fill(text.pipe_exited, &[&pipe.command, &status]),
+        Err(e) => fill(text.error, &[&format!("{:#}", e)]),
+    };
+    println!("\n{}", fill(text.press_enter, &[&outcome]));
+    let _ = std::io::stdin().read_line(&mut String::new());
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    terminal.clear()?;
+    Ok(outcome)
+}
+
+/// Open the log at `path` in the pager on the normal screen, and come back once it exits. A
+/// gzipped log is unpacked to a temporary file first, which is removed afterwards. Returns the
+/// outcome for the status bar.
+async fn open_log(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    path: &Path,
+    text: &Strings,
+) -> Result<String> {
+    let viewer = export::viewer();
+    let unpacked = match path.exists() {
+        true => None,
+        false => match unpack(path).await {
+            Ok(dir) => Some(dir),
+            Err(e) => return Ok(fill(text.error, &[&format!("{:#}", e)])),
+        },
+    };
+    let file = match &unpacked {
+        Some(dir) => dir.join(path.file_name().unwrap_or_default()),
+        None => path.to_path_buf(),
+    };
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, cursor::Show)?;
+    let result = export::open_file(&viewer, &file);
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    terminal.clear()?;
+    if let Some(dir) = unpacked {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+    Ok(match result {
+        Ok(status) if status.success() => String::new(),
+        Ok(status) => fill(text.pipe_exited, &[&viewer, &status]),
+        Err(e) => fill(text.error, &[&format!("{:#}", e)]),
+    })
+}
+
+/// The gzipped log of `path` written out under a new directory of the temp dir that only the
+/// user can enter. Both are created afresh, never reused: another user can't have opened or
+/// linked them beforehand. Returns the directory, which holds the log under its own name.
+async fn unpack(path: &Path) -> Result<PathBuf> {
+    let log = log_compress::read(path).await?;
+    let dir = std::env::temp_dir().join(format!("illef-workflow-{}", Uuid::new_v4()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&dir)
+        .with_context(|| format!("failed to create {}", dir.display()))?;
+    let unpacked = dir.join(path.file_name().unwrap_or_default());
+    let written = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&unpacked)
+        .and_then(|mut file| file.write_all(&log))
+        .with_context(|| format!("failed to write {}", unpacked.display()));
+    if let Err(e) = written {
+        let _ = std::fs::remove_dir_all(&dir);
+        return Err(e);
+    }
+    Ok(dir)
+}
+
+fn handle_key(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
+    if app.search.as_ref().is_some_and(|s| s.editing) {
+        handle_search_key(app, key, modifiers);
+        return;
+    }
+    if app.export.is_some() {
+        handle_export_key(app, key, modifiers);
+        return;
+    }
+    if app.confirm_trigger.is_some() {
+        handle_confirm_key(app, key, modifiers);
+        return;
+    }
+    if app.help_scroll.is_some() {
+        handle_help_key(app, key, modifiers);
+        return;
+    }
+    if app.decisions.is_some() {
+        handle_decisions_key(app, key, modifiers);
+        return;
+    }
+    handle_panel_key(app, key, modifiers);
 }
 
-async fn handle_key(app: &mut App, key: KeyCode, _modifiers: KeyModifiers) -> Result<()> {
+/// Keys while the trigger prompt is open: y or Enter runs, n or Esc doesn't, the rest is ignored.
+fn handle_confirm_key(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
     match key {
-        KeyCode::Char('q') => {
+        KeyCode::Char('y' | 'Y') | KeyCode::Enter => app.answer_confirm_trigger(true),
+        KeyCode::Char('n' | 'N') | KeyCode::Esc => app.answer_confirm_trigger(false),
+        _ if app.keymap.is(Action::Cancel, key, modifiers) => app.answer_confirm_trigger(false),
+        _ => {}
+    }
+}
+
+/// Keys while the `?` help popup is open: it scrolls, and everything else is ignored.
+fn handle_help_key(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
+    let Some(scroll) = app.help_scroll else { return };
+    let height = app.help_height.get().max(1);
+    let last = (ui::help_lines(&app.keymap, app.text).len() as u16).saturating_sub(height);
+    let scroll = match app.keymap.action(key, modifiers) {
+        Some(Action::Quit) => {
             app.should_quit = true;
+            return;
         }
-        KeyCode::Char('w') => {
-            app.active_panel = Panel::Workflows;
+        Some(Action::Up) => scroll.saturating_sub(1),
+        Some(Action::Down) => scroll + 1,
+        Some(Action::PageUp) => scroll.saturating_sub(height),
+        Some(Action::PageDown) => scroll + height,
+        Some(Action::Cancel) => {
+            app.help_scroll = None;
+            return;
+        }
+        _ if matches!(key, KeyCode::Char('?') | KeyCode::Esc) => {
+            app.help_scroll = None;
+            return;
+        }
+        _ => match key {
+            KeyCode::Char('g') | KeyCode::Home => 0,
+            KeyCode::Char('G') | KeyCode::End => last,
+            _ => return,
+        },
+    };
+    app.help_scroll = Some(scroll.min(last));
+}
+
+/// Keys while the decisions screen is open: it scrolls, `a` switches between the selected
+/// workflow and all of them, and everything else but the help is ignored.
+fn handle_decisions_key(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
+    let page = app.decisions.as_ref().map_or(1, |v| v.height.get().max(1)) as isize;
+    match app.keymap.action(key, modifiers) {
+        Some(Action::Quit) => app.should_quit = true,
+        Some(Action::Up) => app.scroll_decisions_by(-1),
+        Some(Action::Down) => app.scroll_decisions_by(1),
+        Some(Action::PageUp) => app.scroll_decisions_by(-page),
+        Some(Action::PageDown) => app.scroll_decisions_by(page),
+        Some(Action::Cancel) => app.decisions = None,
+        _ => match key {
+            KeyCode::Char('s') | KeyCode::Esc => app.decisions = None,
+            KeyCode::Char('a') => app.toggle_decisions_scope(),
+            KeyCode::Char('?') => app.toggle_help(),
+            KeyCode::Char('g') | KeyCode::Home => app.scroll_decisions_by(isize::MIN),
+            KeyCode::Char('G') | KeyCode::End => app.scroll_decisions_by(isize::MAX),
+            _ => {}
+        },
+    }
+}
+
+/// Whether `key` closes an input line: Esc, or a `cancel` key that can't be typed.
+fn cancels_input(app: &App, key: KeyCode, modifiers: KeyModifiers) -> bool {
+    let typed = matches!(key, KeyCode::Char(_)) && (modifiers - KeyModifiers::SHIFT).is_empty();
+    key == KeyCode::Esc || (!typed && app.keymap.is(Action::Cancel, key, modifiers))
+}
+
+/// Keys while the `/` input line is open.
+fn handle_search_key(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
+    if cancels_input(app, key, modifiers) {
+        app.search = None;
+        return;
+    }
+    let Some(search) = app.search.as_mut() else { return };
+    match key {
+        KeyCode::Enter => app.commit_search(),
+        KeyCode::Backspace => {
+            search.query.pop();
+        }
+        KeyCode::Char(c) => search.query.push(c),
+        _ => {}
+    }
+}
+
+/// Keys while the `>` / `|` input line is open.
+fn handle_export_key(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
+    if cancels_input(app, key, modifiers) {
+        app.export = None;
+        return;
+    }
+    let Some(prompt) = app.export.as_mut() else { return };
+    match key {
+        KeyCode::Enter => app.commit_export(),
+        KeyCode::Backspace => {
+            prompt.input.pop();
         }
-        KeyCode::Tab => {
+        KeyCode::Char(c) => prompt.input.push(c),
+        _ => {}
+    }
+}
+
+/// Page and jump keys of the Log panel. Returns whether `key` was one of them.
+fn handle_log_paging(app: &mut App, key: KeyCode, modifiers: KeyModifiers) -> bool {
+    let page = app.log_page();
+    match key {
+        KeyCode::Char('u') if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.scroll_log_by(-page / 2)
+        }
+        KeyCode::Char('d') if modifiers.contains(KeyModifiers::CONTROL) => {
+            app.scroll_log_by(page / 2)
+        }
+        KeyCode::Char('g') | KeyCode::Home => app.scroll_log_top(),
+        KeyCode::Char('G') | KeyCode::End => app.scroll_log_bottom(),
+        _ => return false,
+    }
+    true
+}
+
+/// Run a rebindable action. Returns whether it applies in the current panel.
+fn handle_action(app: &mut App, action: Action) -> bool {
+    let in_log = app.active_panel == Panel::Log;
+    match action {
+        Action::Quit => app.should_quit = true,
+        Action::Cancel if app.has_popup() => app.close_popup(),
+        Action::Cancel if app.search.is_some() => app.search = None,
+        Action::Cancel if app.selection_start.is_some() => app.selection_start = None,
+        Action::Cancel => app.cancel_tasks(),
+        Action::Search if in_log => app.start_search(),
+        Action::PageUp if in_log => app.scroll_log_by(-app.log_page()),
+        Action::PageDown if in_log => app.scroll_log_by(app.log_page()),
+        Action::Search | Action::PageUp | Action::PageDown => return false,
+        Action::NextPanel => {
             app.active_panel = match app.active_panel {
                 Panel::Workflows => Panel::Executions,
                 Panel::Executions => Panel::Log,
                 Panel::Log => Panel::Workflows,
             };
         }
-        KeyCode::Up | KeyCode::Char('k') => match app.active_panel {
+        Action::Up => match app.active_panel {
             Panel::Workflows => {
                 app.move_workflow_up();
-                let _ = app.refresh_executions().await;
-                if !app.executions.is_empty() {
-                    app.select_execution(0);
-                }
+                app.load_executions(Some(0));
             }
             Panel::Executions => {
                 app.move_execution_up();
@@ -105,13 +378,10 @@ async fn handle_key(app: &mut App, key: KeyCode, _modifiers: KeyModifiers) -> Re
                 app.scroll_log_up();
             }
         },
-        KeyCode::Down | KeyCode::Char('j') => match app.active_panel {
+        Action::Down => match app.active_panel {
             Panel::Workflows => {
                 app.move_workflow_down();
-                let _ = app.refresh_executions().await;
-                if !app.executions.is_empty() {
-                    app.select_execution(0);
-                }
+                app.load_executions(Some(0));
             }
             Panel::Executions => {
                 app.move_execution_down();
@@ -120,22 +390,89 @@ async fn handle_key(app: &mut App, key: KeyCode, _modifiers: KeyModifiers) -> Re
                 app.scroll_log_down();
             }
         },
-        KeyCode::Right => match app.active_panel {
-            Panel::Workflows => {
-                let _ = app.refresh_executions().await;
-                app.active_panel = Panel::Executions;
-            }
-            _ => {}
-        },
-        KeyCode::Left => match app.active_panel {
-            Panel::Executions => {
-                app.active_panel = Panel::Workflows;
-            }
-            _ => {}
-        },
+        Action::Run => {
+            app.status_message = String::new();
+            app.trigger_selected_workflow();
+        }
+        Action::Pause => app.toggle_pause_selected_workflow(),
+    }
+    true
+}
+
+fn handle_panel_key(app: &mut App, key: KeyCode, modifiers: KeyModifiers) {
+    if let Some(action) = app.keymap.action(key, modifiers) {
+        if handle_action(app, action) {
+            return;
+        }
+    }
+    if app.active_panel == Panel::Log && handle_log_paging(app, key, modifiers) {
+        return;
+    }
+    match key {
+        KeyCode::Char('f') if app.active_panel == Panel::Log => {
+            app.toggle_follow();
+        }
+        KeyCode::Char('e') if app.active_panel == Panel::Log => {
+            app.jump_to_error(true);
+        }
+        KeyCode::Char('E') if app.active_panel == Panel::Log => {
+            app.jump_to_error(false);
+        }
+        KeyCode::Char('m') if app.active_panel == Panel::Log => {
+            app.toggle_bookmark();
+        }
+        KeyCode::Char('b') if app.active_panel == Panel::Log => {
+            app.jump_to_bookmark(true);
+        }
+        KeyCode::Char('B') if app.active_panel == Panel::Log => {
+            app.jump_to_bookmark(false);
+        }
+        KeyCode::Char('v') if app.active_panel == Panel::Log => {
+            app.toggle_selection();
+        }
+        KeyCode::Char('>') if app.active_panel == Panel::Log => {
+            app.start_export(ExportKind::File);
+        }
+        KeyCode::Char('|') if app.active_panel == Panel::Log => {
+            app.start_export(ExportKind::Pipe);
+        }
+        KeyCode::Char('o') if app.active_panel != Panel::Workflows => {
+            app.open_log_in_pager();
+        }
+        KeyCode::Char('n') if app.search.is_some() => {
+            app.jump_to_match(true);
+        }
+        KeyCode::Char('N') if app.search.is_some() => {
+            app.jump_to_match(false);
+        }
+        KeyCode::Char('c') => {
+            app.toggle_durations();
+        }
+        KeyCode::Char('i') => {
+            app.toggle_detail();
+        }
+        KeyCode::Char('s') => {
+            app.toggle_decisions();
+        }
+        KeyCode::Char('x') => {
+            app.toggle_explain();
+        }
+        KeyCode::Char('?') => {
+            app.toggle_help();
+        }
+        KeyCode::Char('w') => {
+            app.active_panel = Panel::Workflows;
+        }
+        KeyCode::Right if app.active_panel == Panel::Workflows => {
+            app.load_executions(None);
+            app.active_panel = Panel::Executions;
+        }
+        KeyCode::Left if app.active_panel == Panel::Executions => {
+            app.active_panel = Panel::Workflows;
+        }
         KeyCode::Enter => match app.active_panel {
             Panel::Workflows => {
-                let _ = app.refresh_executions().await;
+                app.load_executions(None);
                 app.active_panel = Panel::Executions;
             }
             Panel::Executions => {
@@ -145,13 +482,21 @@ async fn handle_key(app: &mut App, key: KeyCode, _modifiers: KeyModifiers) -> Re
             }
             Panel::Log => {}
         },
-        KeyCode::Char('r') => {
-            app.status_message = String::new();
-            if let Err(e) = app.trigger_selected_workflow().await {
-                app.status_message = format!("Error: {}", e);
-            }
+        KeyCode::Char('e') if app.active_panel == Panel::Executions => {
+            app.open_first_error();
+        }
+        KeyCode::Char('d') if app.active_panel == Panel::Executions => {
+            app.delete_selected_execution();
+        }
+        KeyCode::Char('b') if app.active_panel == Panel::Executions => {
+            app.toggle_selected_baseline();
+        }
+        KeyCode::Char('u') => {
+            app.restore_selected_workflow();
+        }
+        KeyCode::Char('z') if app.active_panel == Panel::Workflows => {
+            app.snooze_selected_workflow();
         }
         _ => {}
     }
-    Ok(())
 }
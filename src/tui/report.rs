@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+
+use crate::common::duration::parse_duration;
+use crate::proto::ReportRequest;
+use crate::tui::client::{self, ConnectOptions};
+
+/// Options of the `report` subcommand.
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    pub since: String,
+    pub until: Option<String>,
+    pub format: String,
+    pub workflows: Vec<String>,
+    pub output: Option<PathBuf>,
+}
+
+/// Fetch a report from the runner and write it to `output` or stdout.
+pub async fn run(connect: &ConnectOptions, options: ReportOptions) -> Result<()> {
+    let since = parse_time(&options.since)?;
+    let until = options.until.as_deref().map(parse_time).transpose()?;
+
+    let mut client = client::connect(connect)?;
+    let content = client::get_report(
+        &mut client,
+        ReportRequest {
+            since: since.timestamp(),
+            until: until.map_or(0, |t| t.timestamp()),
+            format: options.format,
+            workflows: options.workflows,
+        },
+    )
+    .await?;
+
+    match options.output {
+        Some(path) => std::fs::write(&path, content)
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+/// `7d` / `12h` (that long ago), `YYYY-MM-DD` (local midnight) or RFC 3339.
+fn parse_time(s: &str) -> Result<DateTime<Utc>> {
+    if let Ok(ago) = parse_duration(s) {
+        return Ok(Utc::now() - chrono::Duration::from_std(ago)?);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+        return Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+            .with_context(|| format!("midnight does not exist on {}", s));
+    }
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .with_context(|| format!("invalid time: {} (expected e.g. 7d, 2024-05-01 or RFC 3339)", s))
+}
@@ -0,0 +1,72 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::common::duration::parse_duration;
+use crate::proto::ScheduleOverrideRequest;
+use crate::tui::client::{self, ConnectOptions};
+
+/// Options of the `override` subcommand.
+#[derive(Debug, Clone)]
+pub struct OverrideOptions {
+    pub workflow: String,
+    pub every: Option<String>,
+    pub snooze: bool,
+    pub duration: Option<String>,
+    pub until: Option<String>,
+    pub clear: bool,
+}
+
+/// Set or clear `workflow`'s schedule override and print what is in force now.
+pub async fn run(connect: &ConnectOptions, options: OverrideOptions) -> Result<()> {
+    let mut request = ScheduleOverrideRequest {
+        name: options.workflow.clone(),
+        clear: options.clear,
+        ..Default::default()
+    };
+    if !options.clear {
+        if options.every.is_some() == options.snooze {
+            bail!("pass one of --every and --snooze, or --clear");
+        }
+        if let Some(every) = &options.every {
+            request.every_secs = parse_duration(every)?.as_secs();
+            if request.every_secs == 0 {
+                bail!("--every must be at least a second");
+            }
+        }
+        let until = match (&options.duration, &options.until) {
+            (Some(duration), None) => {
+                Utc::now() + chrono::Duration::from_std(parse_duration(duration)?)?
+            }
+            (None, Some(until)) => parse_until(until)?,
+            _ => bail!("pass one of --for and --until"),
+        };
+        request.until = until.timestamp();
+    }
+
+    let mut client = client::connect(connect)?;
+    let response = client::set_schedule_override(&mut client, request).await?;
+    match (options.clear, response.changed) {
+        (true, true) => println!("{}: override cleared, back on its schedule", options.workflow),
+        (true, false) => println!("{}: no override to clear", options.workflow),
+        _ => println!("{}: {}", options.workflow, response.schedule_override),
+    }
+    Ok(())
+}
+
+/// `YYYY-MM-DD` (local midnight), `YYYY-MM-DD HH:MM` (local time) or RFC 3339.
+fn parse_until(s: &str) -> Result<DateTime<Utc>> {
+    let local = if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        date.and_hms_opt(0, 0, 0).unwrap()
+    } else if let Ok(time) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M") {
+        time
+    } else {
+        return DateTime::parse_from_rfc3339(s).map(|t| t.with_timezone(&Utc)).with_context(|| {
+            format!("invalid time: {} (expected e.g. 2024-05-06, 2024-05-06 09:00 or RFC 3339)", s)
+        });
+    };
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .with_context(|| format!("{} does not exist in local time", s))
+}
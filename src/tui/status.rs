@@ -0,0 +1,64 @@
+use anyhow::{Result, bail};
+use chrono::Utc;
+
+use crate::common::duration::format_duration;
+use crate::proto::WorkflowInfo;
+use crate::tui::client::{self, ConnectOptions};
+
+/// Options of the `status` subcommand.
+#[derive(Debug, Clone)]
+pub struct StatusOptions {
+    /// Print only this workflow and exit nonzero if its latest execution failed.
+    pub exit_code_from: Option<String>,
+}
+
+/// Print one line per workflow: name, latest status and how long ago it started. Returns the
+/// exit code: 1 when the `exit_code_from` workflow's latest execution failed, else 0.
+pub async fn run(connect: &ConnectOptions, options: StatusOptions) -> Result<i32> {
+    let mut client = client::connect(connect)?;
+    let mut workflows = client::list_workflows(&mut client).await?;
+
+    let mut code = 0;
+    if let Some(name) = &options.exit_code_from {
+        workflows.retain(|w| &w.name == name);
+        let Some(wf) = workflows.first() else {
+            bail!("workflow not found: {}", name);
+        };
+        if matches!(wf.last_run_status.as_str(), "failed" | "config_error") {
+            code = 1;
+        }
+    }
+
+    let width = workflows.iter().map(|w| w.name.len()).max().unwrap_or(0);
+    for wf in &workflows {
+        println!("{:<width$}  {}", wf.name, summary(wf), width = width);
+    }
+    Ok(code)
+}
+
+/// `failed 3m ago`, `running 12s ago, 2 queued (paused)`, `success 2d ago, stale`, `never run`,
+/// `success 5m ago, regressed`, `success 1h ago (snoozed until 10-19 09:00)`.
+fn summary(wf: &WorkflowInfo) -> String {
+    let mut text = if wf.last_run_at > 0 {
+        let secs = (Utc::now().timestamp() - wf.last_run_at).max(0) as u64;
+        let ago = format_duration(&std::time::Duration::from_secs(secs));
+        format!("{} {} ago", wf.last_run_status, ago)
+    } else {
+        "never run".to_string()
+    };
+    // states the latest status doesn't already tell
+    let health = wf.health.as_str();
+    let untold = matches!(health, "stale" | "regressed" | "disabled" | "config_error");
+    if untold && health != wf.last_run_status {
+        text.push_str(&format!(", {}", health));
+    }
+    if wf.queued > 0 {
+        text.push_str(&format!(", {} queued", wf.queued));
+    }
+    if wf.paused {
+        text.push_str(" (paused)");
+    } else if !wf.schedule_override.is_empty() {
+        text.push_str(&format!(" ({})", wf.schedule_override));
+    }
+    text
+}
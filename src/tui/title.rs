@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use anyhow::Result;
+use crossterm::{execute, terminal::SetTitle};
+
+use crate::common::config::load_config;
+use crate::common::types::{ExecutionStatus, TuiConfig};
+use crate::proto::WorkflowInfo;
+use crate::tui::i18n::{Strings, fill};
+
+/// Saves the terminal's title on a stack of its own, and puts it back; terminals without the
+/// stack ignore both.
+const PUSH_TITLE: &[u8] = b"\x1b[22;0t";
+const POP_TITLE: &[u8] = b"\x1b[23;0t";
+
+/// The terminal title and bell the TUI keeps up for `tui.title` and `tui.bell_on_failure`, so
+/// a TUI left in a background tmux pane still says how things are going.
+pub struct Title {
+    config: TuiConfig,
+    shown: Option<String>,
+    /// Each workflow's latest run and its status as last seen, to tell a run that just failed
+    /// from one seen before. `None` until the first list arrives, whose failures are old news.
+    last_runs: Option<HashMap<String, (i64, String)>>,
+}
+
+impl Title {
+    /// The settings of the local config.yaml's `tui`, all off without a config.
+    pub fn load() -> Self {
+        Self {
+            config: load_config().map(|c| c.tui).unwrap_or_default(),
+            shown: None,
+            last_runs: None,
+        }
+    }
+
+    /// Keep the terminal's title, to put back on [`Title::restore`].
+    pub fn save(&self, out: &mut impl Write) -> Result<()> {
+        if self.config.title {
+            out.write_all(PUSH_TITLE)?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Show what `workflows` are doing in the title if it changed, and ring the bell for each
+    /// run that failed since the last list.
+    pub fn update(
+        &mut self,
+        out: &mut impl Write,
+        workflows: &[WorkflowInfo],
+        text: &Strings,
+    ) -> Result<()> {
+        if self.config.title {
+            let title = summary(workflows, text);
+            if self.shown.as_ref() != Some(&title) {
+                execute!(out, SetTitle(&title))?;
+                self.shown = Some(title);
+            }
+        }
+        if self.config.bell_on_failure && !workflows.is_empty() {
+            let last_runs = workflows
+                .iter()
+                .map(|w| (w.name.clone(), (w.last_run_at, w.last_run_status.clone())))
+                .collect();
+            if let Some(seen) = self.last_runs.replace(last_runs) {
+                let failed = workflows.iter().filter(|w| failure(w)).any(|w| {
+                    seen.get(&w.name).is_none_or(|(at, status)| {
+                        *at != w.last_run_at || *status != w.last_run_status
+                    })
+                });
+                if failed {
+                    out.write_all(b"\x07")?;
+                    out.flush()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Put back the title from before the TUI started.
+    pub fn restore(&self, out: &mut impl Write) -> Result<()> {
+        if self.config.title {
+            out.write_all(POP_TITLE)?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// e.g. `wf: 1 running, 0 failed`, counting workflows whose latest finished run failed.
+pub fn summary(workflows: &[WorkflowInfo], text: &Strings) -> String {
+    let running = workflows.iter().filter(|w| w.status == "running").count();
+    let failed = workflows.iter().filter(|w| failure(w)).count();
+    fill(text.terminal_title, &[&running, &failed])
+}
+
+fn failure(wf: &WorkflowInfo) -> bool {
+    wf.last_run_status.parse::<ExecutionStatus>().is_ok_and(|s| s.is_failure())
+}
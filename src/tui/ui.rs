@@ -1,17 +1,44 @@
+use std::time::Instant;
+
 use chrono::{Local, TimeZone, Utc};
 use chrono::DateTime;
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols::Marker,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{
+        Axis, Block, Borders, Chart, Clear, Dataset, GraphType, List, ListItem, ListState,
+        Paragraph, Wrap,
+    },
 };
 
-use crate::tui::app::{App, Panel};
+use crate::common::duration::format_duration;
+use crate::proto::DurationPoint;
+use crate::tui::ansi::LogLine;
+use crate::tui::app::{
+    App, DecisionsView, DetailView, DurationsView, ExplainView, Panel, PanelStatus, find_matches,
+};
+use crate::tui::export::ExportKind;
+use crate::tui::i18n::{Strings, fill};
+use crate::tui::keymap::{Action, Keymap};
 
 pub fn draw(frame: &mut Frame, app: &App) {
-    let area = frame.area();
+    let mut area = frame.area();
+    let banners: Vec<Line> =
+        [connection_banner(app), offline_banner(app), missed_banner(app)]
+            .into_iter()
+            .flatten()
+            .collect();
+    if !banners.is_empty() {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(banners.len() as u16), Constraint::Min(0)])
+            .split(area);
+        frame.render_widget(Paragraph::new(banners), rows[0]);
+        area = rows[1];
+    }
 
     // layout: top (lists) | bottom (log) | status bar
     let vertical = Layout::default()
@@ -33,9 +60,29 @@ pub fn draw(frame: &mut Frame, app: &App) {
     draw_executions(frame, app, top_chunks[1]);
     draw_log(frame, app, vertical[1]);
     draw_status_bar(frame, app, vertical[2]);
+
+    if let Some(view) = &app.durations {
+        draw_durations(frame, app.text, view, centered(area, 90, 80));
+    }
+    if let Some(view) = &app.detail {
+        draw_detail(frame, app, view, centered(area, 70, 80));
+    }
+    if let Some(view) = &app.decisions {
+        draw_decisions(frame, app.text, view, centered(area, 90, 80));
+    }
+    if let Some(view) = &app.explain {
+        draw_explain(frame, app.text, view, centered(area, 70, 80));
+    }
+    if let Some(name) = &app.confirm_trigger {
+        draw_confirm(frame, app.text, name, area);
+    }
+    if let Some(scroll) = app.help_scroll {
+        draw_help(frame, app, scroll, centered(area, 70, 80));
+    }
 }
 
 fn draw_workflows(frame: &mut Frame, app: &App, area: Rect) {
+    let text = app.text;
     let is_active = app.active_panel == Panel::Workflows;
     let border_style = if is_active {
         Style::default().fg(Color::Yellow)
@@ -48,21 +95,37 @@ fn draw_workflows(frame: &mut Frame, app: &App, area: Rect) {
         .iter()
         .map(|wf| {
             let (status_icon, icon_style) = match wf.status.as_str() {
+                _ if wf.queued > 0 => ("◉", Style::default().fg(Color::Magenta)),
                 "running" => ("●", Style::default().fg(Color::Green)),
-                _ => ("○", Style::default().fg(Color::DarkGray)),
+                "starting" => ("◌", Style::default().fg(Color::Yellow)),
+                _ => health_icon(&wf.health),
             };
 
-            let countdown = if wf.next_run_at > 0 {
+            let countdown = if wf.status == "starting" {
+                text.starting.to_string()
+            } else if wf.paused {
+                text.paused.to_string()
+            } else if wf.next_run_at > 0 {
                 let next: DateTime<Utc> = Utc.timestamp_opt(wf.next_run_at, 0).unwrap();
-                format_countdown(next)
+                if wf.timezone.is_empty() {
+                    format_countdown(text, next)
+                } else {
+                    // schedules in another zone: also show when that is here
+                    let local = next.with_timezone(&Local);
+                    format!("{} ({})", format_countdown(text, next), local.format("%H:%M"))
+                }
             } else {
                 "-".to_string()
             };
 
-            let line1 = Line::from(vec![
+            let mut line1 = Line::from(vec![
                 Span::styled(format!("{} ", status_icon), icon_style),
                 Span::styled(&wf.name, Style::default().add_modifier(Modifier::BOLD)),
             ]);
+            if wf.queued > 0 {
+                let queued = fill(text.queued, &[&wf.queued]);
+                line1.push_span(Span::styled(format!("  {}", queued), icon_style));
+            }
             let line2 = Line::from(vec![
                 Span::raw("  "),
                 Span::styled(&wf.cron, Style::default().fg(Color::DarkGray)),
@@ -70,21 +133,26 @@ fn draw_workflows(frame: &mut Frame, app: &App, area: Rect) {
                 Span::styled(countdown, Style::default().fg(Color::Cyan)),
             ]);
 
-            ListItem::new(vec![line1, line2])
+            let mut lines = vec![line1, line2];
+            if !wf.history.is_empty() {
+                lines.push(history_line(&wf.history));
+            }
+            ListItem::new(lines)
         })
         .collect();
+    let (items, offset) = with_error_row(text, items, &app.workflows_status);
 
     let mut state = ListState::default();
     state.select(if app.workflows.is_empty() {
         None
     } else {
-        Some(app.selected_workflow)
+        Some(app.selected_workflow + offset)
     });
 
     let list = List::new(items)
         .block(
             Block::default()
-                .title(" Workflows ")
+                .title(panel_title(text, text.workflows, &app.workflows_status))
                 .borders(Borders::ALL)
                 .border_style(border_style),
         )
@@ -98,7 +166,52 @@ fn draw_workflows(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_stateful_widget(list, area, &mut state);
 }
 
+/// The icon for a `WorkflowInfo.health` state.
+fn health_icon(health: &str) -> (&'static str, Style) {
+    let (icon, color) = match health {
+        "ok" => ("✓", Color::Green),
+        "failing" => ("✗", Color::Red),
+        "stale" => ("◷", Color::Yellow),
+        "regressed" => ("▲", Color::Yellow),
+        "paused" => ("⏸", Color::DarkGray),
+        "disabled" => ("⊘", Color::DarkGray),
+        "config_error" => ("⚠", Color::Red),
+        _ => ("○", Color::DarkGray),
+    };
+    (icon, Style::default().fg(color))
+}
+
+/// Recent outcomes oldest to newest, e.g. `✓✓✗✓✓ 80% ~12s`: success rate and mean duration
+/// over the same runs.
+fn history_line(history: &[DurationPoint]) -> Line<'static> {
+    let mut spans = vec![Span::raw("  ")];
+    for point in history.iter().rev() {
+        let (mark, color) = match point.status.as_str() {
+            "success" => ("✓", Color::Green),
+            _ => ("✗", Color::Red),
+        };
+        spans.push(Span::styled(mark, Style::default().fg(color)));
+    }
+    let runs = history.len() as i64;
+    let succeeded = history.iter().filter(|p| p.status == "success").count() as i64;
+    let rate = succeeded * 100 / runs;
+    let mean = history.iter().map(|p| p.duration_secs).sum::<i64>() / runs;
+    let rate_color = match rate {
+        100 => Color::Green,
+        50.. => Color::Yellow,
+        _ => Color::Red,
+    };
+    spans.push(Span::raw(" "));
+    spans.push(Span::styled(format!("{}%", rate), Style::default().fg(rate_color)));
+    spans.push(Span::styled(
+        format!(" ~{}", secs_label(mean)),
+        Style::default().fg(Color::DarkGray),
+    ));
+    Line::from(spans)
+}
+
 fn draw_executions(frame: &mut Frame, app: &App, area: Rect) {
+    let text = app.text;
     let is_active = app.active_panel == Panel::Executions;
     let border_style = if is_active {
         Style::default().fg(Color::Yellow)
@@ -106,6 +219,11 @@ fn draw_executions(frame: &mut Frame, app: &App, area: Rect) {
         Style::default()
     };
 
+    let baseline = app
+        .workflows
+        .get(app.selected_workflow)
+        .map(|w| w.baseline_execution_id.as_str())
+        .filter(|id| !id.is_empty());
     let items: Vec<ListItem> = app
         .executions
         .iter()
@@ -113,6 +231,7 @@ fn draw_executions(frame: &mut Frame, app: &App, area: Rect) {
             let (icon, color) = match exec.status.as_str() {
                 "success" => ("✓", Color::Green),
                 "failed" => ("✗", Color::Red),
+                "config_error" => ("⚠", Color::Red),
                 "running" => ("●", Color::Yellow),
                 _ => ("?", Color::DarkGray),
             };
@@ -121,28 +240,83 @@ fn draw_executions(frame: &mut Frame, app: &App, area: Rect) {
                 let dt = Utc.timestamp_opt(exec.started_at, 0).unwrap().with_timezone(&Local);
                 dt.format("%m-%d %H:%M").to_string()
             } else {
-                "unknown".to_string()
+                text.unknown_time.to_string()
             };
 
-            let line = Line::from(vec![
+            let mut spans = vec![
                 Span::styled(format!("{} ", icon), Style::default().fg(color)),
                 Span::raw(time),
-            ]);
+            ];
+            if !exec.steps.is_empty() {
+                spans.push(Span::raw("  "));
+            }
+            for step in &exec.steps {
+                let (icon, color) = match step.status.as_str() {
+                    "success" => ("✓", Color::Green),
+                    "failed" => ("✗", Color::Red),
+                    "running" => ("●", Color::Yellow),
+                    "skipped" => ("-", Color::DarkGray),
+                    _ => ("·", Color::DarkGray),
+                };
+                spans.push(Span::styled(icon, Style::default().fg(color)));
+            }
+            if let Some(label) = trigger_label(text, &exec.trigger) {
+                spans.push(Span::styled(format!("  {}", label), Style::default().fg(Color::Blue)));
+            }
+            if exec.error_count > 0 {
+                spans.push(Span::styled(
+                    format!("  {}", text.errors.of(exec.error_count as usize)),
+                    Style::default().fg(Color::Red),
+                ));
+            }
+            if exec.warning_count > 0 {
+                spans.push(Span::styled(
+                    format!("  {}", text.warnings.of(exec.warning_count as usize)),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            let collapsed: u32 = exec.repeats.iter().map(|r| r.times).sum();
+            if collapsed > 0 {
+                spans.push(Span::styled(
+                    format!("  {}", text.collapsed.of(collapsed as usize)),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if exec.binary_output {
+                let binary = format!("  {}", text.binary);
+                spans.push(Span::styled(binary, Style::default().fg(Color::Magenta)));
+            }
+            if baseline == Some(exec.id.as_str()) {
+                let baseline = format!("  {}", text.baseline);
+                spans.push(Span::styled(baseline, Style::default().fg(Color::Cyan)));
+            } else if !exec.baseline_deviation.is_empty() {
+                let regressed = format!("  ▲ {}", text.regressed);
+                spans.push(Span::styled(regressed, Style::default().fg(Color::Yellow)));
+            }
+            if !exec.args.is_empty() {
+                spans.push(Span::styled(
+                    format!("  {}", exec.args.join(" ")),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            let line = Line::from(spans);
             ListItem::new(line)
         })
         .collect();
+    let (items, offset) = with_error_row(text, items, &app.executions_status);
 
     let mut state = ListState::default();
     state.select(if app.executions.is_empty() {
         None
     } else {
-        Some(app.selected_execution)
+        Some(app.selected_execution + offset)
     });
 
     let title = app
         .selected_workflow_name()
-        .map(|n| format!(" {} - Executions ", n))
-        .unwrap_or_else(|| " Executions ".to_string());
+        .map(|n| fill(text.executions_of, &[&n]))
+        .unwrap_or_else(|| text.executions.to_string());
+    let title = panel_title(text, &title, &app.executions_status);
 
     let list = List::new(items)
         .block(
@@ -161,7 +335,37 @@ fn draw_executions(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_stateful_widget(list, area, &mut state);
 }
 
+/// Panel title with a marker while a refresh is in flight.
+fn panel_title<'a>(text: &Strings, title: &str, status: &PanelStatus) -> Line<'a> {
+    let mut spans = vec![Span::raw(title.to_string())];
+    if status.loading {
+        spans.push(Span::styled(text.refreshing, Style::default().fg(Color::DarkGray)));
+    }
+    Line::from(spans)
+}
+
+/// Prepend an error row when the last refresh failed. Returns the items and the offset to
+/// apply to the selected index.
+fn with_error_row<'a>(
+    text: &Strings,
+    mut items: Vec<ListItem<'a>>,
+    status: &PanelStatus,
+) -> (Vec<ListItem<'a>>, usize) {
+    let Some(error) = &status.error else {
+        return (items, 0);
+    };
+    items.insert(
+        0,
+        ListItem::new(Line::from(Span::styled(
+            fill(text.refresh_failed, &[error]),
+            Style::default().fg(Color::Red),
+        ))),
+    );
+    (items, 1)
+}
+
 fn draw_log(frame: &mut Frame, app: &App, area: Rect) {
+    let text = app.text;
     let is_active = app.active_panel == Panel::Log;
     let border_style = if is_active {
         Style::default().fg(Color::Yellow)
@@ -170,33 +374,63 @@ fn draw_log(frame: &mut Frame, app: &App, area: Rect) {
     };
 
     let visible_height = area.height.saturating_sub(2) as usize;
-    let start = if app.log_lines.len() > visible_height {
-        app.log_scroll
-            .saturating_sub(visible_height / 2)
-            .min(app.log_lines.len().saturating_sub(visible_height))
-    } else {
-        0
-    };
+    app.log_height.set(visible_height);
+    let start = app.log_view_start(visible_height);
 
+    let query = app.search.as_ref().map_or("", |s| s.query.as_str());
+    let selection = app.selected_lines();
     let visible_lines: Vec<Line> = app
         .log_lines
         .iter()
+        .enumerate()
         .skip(start)
         .take(visible_height)
-        .map(|l| Line::from(Span::raw(l.as_str())))
+        .map(|(i, l)| {
+            let mut line = render_log_line(l, query);
+            if selection.as_ref().is_some_and(|r| r.contains(&i)) {
+                line.style = Style::default().bg(Color::DarkGray);
+            }
+            if app.bookmarks.binary_search(&i).is_ok() {
+                line.spans.insert(0, Span::styled("» ", Style::default().fg(Color::Magenta)));
+            }
+            line
+        })
         .collect();
 
-    let log_title = if let Some(exec) = app.executions.get(app.selected_execution) {
+    let mut log_title = if let Some(exec) = app.executions.get(app.selected_execution) {
         let dt = Utc.timestamp_opt(exec.started_at, 0).unwrap().with_timezone(&Local);
-        format!(" Log - {} ", dt.format("%Y-%m-%d %H:%M"))
+        fill(text.log_of, &[&dt.format("%Y-%m-%d %H:%M")])
     } else {
-        " Log ".to_string()
+        text.log.to_string()
     };
+    if !query.is_empty() {
+        let matches = app.match_lines();
+        let current = match matches.iter().position(|&i| i == app.log_scroll) {
+            Some(pos) => (pos + 1).to_string(),
+            None => "-".to_string(),
+        };
+        log_title.push_str(&format!("- /{} {}/{} ", query, current, matches.len()));
+    }
+    if let Some(range) = &selection {
+        let count = text.lines.of(range.clone().count());
+        log_title.push_str(&fill(text.log_selected, &[&count]));
+    }
+    if !app.bookmarks.is_empty() {
+        log_title.push_str(&format!("- {} ", text.bookmarks.of(app.bookmarks.len())));
+    }
+
+    let mut title = vec![Span::raw(log_title)];
+    if app.follow {
+        title.push(Span::styled(
+            text.follow,
+            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+        ));
+    }
 
     let paragraph = Paragraph::new(visible_lines)
         .block(
             Block::default()
-                .title(log_title)
+                .title(Line::from(title))
                 .borders(Borders::ALL)
                 .border_style(border_style),
         )
@@ -205,28 +439,742 @@ fn draw_log(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
-fn format_countdown(next: DateTime<Utc>) -> String {
+/// The line's ANSI colors, with search matches highlighted on top.
+fn render_log_line<'a>(line: &'a LogLine, query: &str) -> Line<'a> {
+    let matches = find_matches(&line.text, query);
+    if matches.is_empty() && line.styles.is_empty() {
+        return Line::from(line.text.as_str());
+    }
+
+    let mut cuts = vec![0, line.text.len()];
+    cuts.extend(line.styles.iter().flat_map(|(r, _)| [r.start, r.end]));
+    cuts.extend(matches.iter().flat_map(|&(s, e)| [s, e]));
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let highlight = Style::default().fg(Color::Black).bg(Color::Yellow);
+    let spans = cuts
+        .windows(2)
+        .map(|w| {
+            let (start, end) = (w[0], w[1]);
+            let style = if matches.iter().any(|&(s, e)| s <= start && end <= e) {
+                highlight
+            } else {
+                line.styles
+                    .iter()
+                    .find(|(r, _)| r.start <= start && end <= r.end)
+                    .map_or(Style::default(), |(_, style)| *style)
+            };
+            Span::styled(&line.text[start..end], style)
+        })
+        .collect::<Vec<_>>();
+    Line::from(spans)
+}
+
+fn draw_durations(frame: &mut Frame, text: &Strings, view: &DurationsView, area: Rect) {
+    frame.render_widget(Clear, area);
+    let title = panel_title(text, &fill(text.durations_of, &[&view.workflow]), &view.status);
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    if let Some(error) = &view.status.error {
+        let msg = Paragraph::new(format!("⚠ {}", error)).style(Style::default().fg(Color::Red));
+        frame.render_widget(msg, rows[0]);
+        return;
+    }
+    if view.points.is_empty() {
+        let msg = if view.status.loading { "" } else { text.no_finished_runs };
+        let msg = Paragraph::new(msg).style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(msg, rows[0]);
+        return;
+    }
+
+    let all: Vec<(f64, f64)> = view
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i as f64, p.duration_secs as f64))
+        .collect();
+    let failed: Vec<(f64, f64)> = view
+        .points
+        .iter()
+        .zip(&all)
+        .filter(|(p, _)| matches!(p.status.as_str(), "failed" | "config_error"))
+        .map(|(_, xy)| *xy)
+        .collect();
+
+    let mut sorted: Vec<i64> = view.points.iter().map(|p| p.duration_secs).collect();
+    sorted.sort_unstable();
+    let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+    let median = sorted[sorted.len() / 2];
+    let last_x = (all.len() - 1).max(1) as f64;
+    let median_line = [(0.0, median as f64), (last_x, median as f64)];
+    let y_max = (max as f64 * 1.1).max(1.0);
+
+    let datasets = vec![
+        Dataset::default()
+            .name(text.median)
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::DarkGray))
+            .data(&median_line),
+        Dataset::default()
+            .name(text.duration)
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&all),
+        Dataset::default()
+            .name(text.failed)
+            .marker(Marker::Dot)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Red))
+            .data(&failed),
+    ];
+
+    let time_label = |i: usize| {
+        let dt = Utc.timestamp_opt(view.points[i].started_at, 0).unwrap().with_timezone(&Local);
+        Span::raw(dt.format("%m-%d %H:%M").to_string())
+    };
+    let chart = Chart::new(datasets)
+        .x_axis(
+            Axis::default()
+                .bounds([0.0, last_x])
+                .labels(vec![time_label(0), time_label(all.len() - 1)])
+                .style(Style::default().fg(Color::DarkGray)),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, y_max])
+                .labels(vec![Span::raw("0s"), Span::raw(secs_label(y_max as i64))])
+                .style(Style::default().fg(Color::DarkGray)),
+        );
+    frame.render_widget(chart, rows[0]);
+
+    let summary = fill(
+        text.durations_summary,
+        &[&view.points.len(), &secs_label(min), &secs_label(median), &secs_label(max)],
+    );
+    let summary = Paragraph::new(summary).style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(summary, rows[1]);
+}
+
+fn draw_decisions(frame: &mut Frame, text: &Strings, view: &DecisionsView, area: Rect) {
+    frame.render_widget(Clear, area);
+    let title = match &view.workflow {
+        Some(workflow) => fill(text.decisions_of, &[workflow]),
+        None => text.decisions.to_string(),
+    };
+    let scope = match view.workflow {
+        Some(_) => text.all_workflows,
+        None => text.selected_workflow,
+    };
+    let footer = fill(text.decisions_footer, &[&scope]);
+    let block = Block::default()
+        .title(panel_title(text, &title, &view.status))
+        .title_bottom(Line::from(footer).right_aligned())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    view.height.set(inner.height as usize);
+
+    if let Some(error) = &view.status.error {
+        let msg = Paragraph::new(format!("⚠ {}", error)).style(Style::default().fg(Color::Red));
+        frame.render_widget(msg, inner);
+        return;
+    }
+    if view.decisions.is_empty() {
+        let msg = if view.status.loading { "" } else { text.no_decisions };
+        let msg = Paragraph::new(msg).style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(msg, inner);
+        return;
+    }
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let lines: Vec<Line> = view
+        .decisions
+        .iter()
+        .skip(view.scroll)
+        .take(inner.height as usize)
+        .map(|d| {
+            let at = Utc.timestamp_opt(d.at, 0).unwrap().with_timezone(&Local);
+            let color = match d.kind.as_str() {
+                "fired" => Color::Green,
+                "queued" => Color::Magenta,
+                "deferred" => Color::Yellow,
+                _ => Color::Red,
+            };
+            let mut spans = vec![Span::styled(at.format("%m-%d %H:%M:%S  ").to_string(), dim)];
+            if view.workflow.is_none() {
+                spans.push(Span::raw(format!("{:<20} ", d.workflow)));
+            }
+            spans.push(Span::styled(format!("{:<9}", d.kind), Style::default().fg(color)));
+            spans.push(Span::styled(format!("{:<10}", d.trigger), dim));
+            spans.push(Span::raw(d.reason.clone()));
+            Line::from(spans)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_explain(frame: &mut Frame, text: &Strings, view: &ExplainView, area: Rect) {
+    frame.render_widget(Clear, area);
+    let title = panel_title(text, &fill(text.next_run_of, &[&view.workflow]), &view.status);
+    let block = Block::default()
+        .title(title)
+        .title_bottom(Line::from(text.explain_close).right_aligned())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let Some(ex) = &view.explanation else {
+        let msg = match &view.status.error {
+            Some(error) => {
+                Paragraph::new(format!("⚠ {}", error)).style(Style::default().fg(Color::Red))
+            }
+            None => Paragraph::new(""),
+        };
+        frame.render_widget(msg.block(block), area);
+        return;
+    };
+
+    let label = |name: &str| Span::styled(pad(name, 20), Style::default().fg(Color::DarkGray));
+    let row = |name: &str, value: String| Line::from(vec![label(name), Span::raw(value)]);
+    let local = |at: i64| {
+        let at = Utc.timestamp_opt(at, 0).unwrap();
+        let day = at.with_timezone(&Local).format("%a %Y-%m-%d %H:%M:%S");
+        format!("{} ({})", day, format_countdown(text, at))
+    };
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = Vec::new();
+
+    // the answer first, then what it follows from
+    let gated = !ex.should_run.is_empty() || !ex.conditions.is_empty();
+    let summary = if !ex.schedule_error.is_empty() {
+        let invalid = fill(text.invalid_schedule, &[&ex.schedule_error]);
+        Line::styled(invalid, bold.fg(Color::Red))
+    } else if ex.paused {
+        Line::styled(text.explain_paused, bold.fg(Color::Yellow))
+    } else if let Some(&next) = ex.next_fire_at.first() {
+        let mut summary = fill(text.next_run_at, &[&local(next)]);
+        if !ex.schedule_override.is_empty() {
+            summary += &format!(" ({})", ex.schedule_override);
+        }
+        if ex.jitter_secs > 0 {
+            summary += &fill(text.up_to_later, &[&secs_label(ex.jitter_secs)]);
+        }
+        if gated {
+            summary += text.if_checks_allow;
+        }
+        if ex.running {
+            summary += text.after_current_run;
+        }
+        Line::styled(summary, bold.fg(Color::Green))
+    } else {
+        let manual = !ex.triggers.is_empty() && ex.triggers.iter().all(|t| t.kind == "at");
+        let summary = if manual { text.one_off_passed } else { text.no_scheduled_time };
+        Line::styled(summary, bold)
+    };
+    lines.push(summary);
+    lines.push(Line::from(""));
+
+    for (i, t) in ex.triggers.iter().enumerate() {
+        let name = if i == 0 { text.schedule } else { "" };
+        let spec = match t.description == t.spec {
+            true => t.spec.clone(),
+            false => format!("{}  ({})", t.spec, t.description),
+        };
+        lines.push(row(name, spec));
+    }
+    let timezone = match ex.timezone.as_str() {
+        "" => text.runner_local_time.to_string(),
+        tz => tz.to_string(),
+    };
+    lines.push(row(text.timezone, timezone));
+    for (i, &at) in ex.next_fire_at.iter().enumerate() {
+        lines.push(row(if i == 0 { text.upcoming } else { "" }, local(at)));
+    }
+    let jitter = match ex.jitter_secs {
+        0 => text.no_jitter.to_string(),
+        secs => fill(text.jitter_up_to, &[&secs_label(secs)]),
+    };
+    lines.push(row(text.jitter, jitter));
+    if ex.when_idle_secs > 0 {
+        let value = fill(text.when_idle_waits, &[&secs_label(ex.when_idle_secs)]);
+        lines.push(row(text.when_idle, value));
+    }
+    let paused = if ex.paused { text.paused_yes } else { text.paused_no };
+    lines.push(row(text.paused_label, paused.to_string()));
+    if !ex.schedule_override.is_empty() {
+        let value = fill(text.override_in_place, &[&ex.schedule_override]);
+        lines.push(row(text.schedule_override, value));
+    }
+    if !gated {
+        lines.push(row(text.checks, text.no_checks.to_string()));
+    }
+    if !ex.should_run.is_empty() {
+        lines.push(row("should_run", fill(text.should_run_exit, &[&ex.should_run])));
+    }
+    if !ex.conditions.is_empty() {
+        let value = fill(text.conditions_agree, &[&ex.conditions.join(", ")]);
+        lines.push(row(text.conditions, value));
+    }
+    let mut overlap = match ex.overlap.as_str() {
+        "queue" => text.overlap_queue.to_string(),
+        other => other.to_string(),
+    };
+    if ex.running {
+        overlap += &fill(text.running_queued, &[&ex.queued]);
+    }
+    lines.push(row(text.overlap, overlap));
+    if !ex.lock.is_empty() {
+        lines.push(row(text.lock, fill(text.lock_waits, &[&ex.lock])));
+    }
+    if !ex.distributed_lock.is_empty() {
+        let value = fill(text.distributed_lock_waits, &[&ex.distributed_lock]);
+        lines.push(row(text.distributed_lock, value));
+    }
+    let missed = match ex.catch_up {
+        true => text.catch_up_once,
+        false => text.catch_up_reported,
+    };
+    lines.push(row(text.missed_runs, missed.to_string()));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_confirm(frame: &mut Frame, text: &Strings, workflow: &str, area: Rect) {
+    let question = Line::from(vec![
+        Span::raw(text.confirm_before),
+        Span::styled(workflow.to_string(), Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(text.confirm_after),
+        Span::styled(text.confirm_keys, Style::default().fg(Color::DarkGray)),
+    ]);
+    let width = (question.width() as u16 + 4).min(area.width);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(3) / 2,
+        width,
+        height: 3.min(area.height),
+    };
+    frame.render_widget(Clear, popup);
+    let block = Block::default()
+        .title(text.confirm)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    frame.render_widget(Paragraph::new(question).centered().block(block), popup);
+}
+
+fn draw_help(frame: &mut Frame, app: &App, scroll: u16, area: Rect) {
+    frame.render_widget(Clear, area);
+    app.help_height.set(area.height.saturating_sub(2));
+    let block = Block::default()
+        .title(app.text.help)
+        .title_bottom(Line::from(app.text.help_footer).right_aligned())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let lines = help_lines(&app.keymap, app.text);
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(paragraph, area);
+}
+
+/// Every keybinding by panel, with the configured keys for the rebindable actions.
+pub fn help_lines(keys: &Keymap, text: &Strings) -> Vec<Line<'static>> {
+    let key = |action| keys.labels(action);
+    let up_down = format!("{} {}", key(Action::Up), key(Action::Down));
+    let page = format!("{} {}", key(Action::PageUp), key(Action::PageDown));
+    let sections = [
+        (
+            text.help_everywhere,
+            vec![
+                ("?".to_string(), text.help_toggle_help),
+                (key(Action::NextPanel), text.help_next_panel),
+                ("w".to_string(), text.help_workflows),
+                (up_down.clone(), text.help_select),
+                ("Enter".to_string(), text.help_enter),
+                (key(Action::Run), text.help_run),
+                (key(Action::Pause), text.help_pause),
+                ("i".to_string(), text.help_details),
+                ("c".to_string(), text.help_durations),
+                ("s".to_string(), text.help_decisions_popup),
+                ("x".to_string(), text.help_explain),
+                ("u".to_string(), text.help_restore),
+                (key(Action::Cancel), text.help_cancel),
+                (key(Action::Quit), text.help_quit),
+            ],
+        ),
+        (
+            text.help_lists,
+            vec![
+                ("← →".to_string(), text.help_switch_list),
+                ("z".to_string(), text.help_snooze),
+                ("e".to_string(), text.help_first_error),
+                ("d".to_string(), text.help_delete),
+                ("b".to_string(), text.help_baseline),
+                ("o".to_string(), text.help_open_log),
+            ],
+        ),
+        (
+            text.help_log,
+            vec![
+                (up_down, text.help_scroll_line),
+                (page, text.help_scroll_page),
+                ("^u ^d".to_string(), text.help_scroll_half),
+                ("g G".to_string(), text.help_top_bottom),
+                ("f".to_string(), text.help_follow),
+                (key(Action::Search), text.help_search),
+                ("n N".to_string(), text.help_matches),
+                ("e E".to_string(), text.help_errors),
+                ("m".to_string(), text.help_bookmark),
+                ("b B".to_string(), text.help_bookmarks),
+                ("v".to_string(), text.help_selection),
+                (">".to_string(), text.help_save),
+                ("|".to_string(), text.help_pipe),
+                ("o".to_string(), text.help_open_log),
+            ],
+        ),
+        (
+            text.help_decisions,
+            vec![
+                ("a".to_string(), text.help_decisions_scope),
+                ("g G".to_string(), text.help_newest_oldest),
+            ],
+        ),
+    ];
+
+    let mut lines = Vec::new();
+    for (title, rows) in sections {
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(Line::styled(
+            title,
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+        for (keys, action) in rows {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<16}", keys), Style::default().fg(Color::Cyan)),
+                Span::raw(action),
+            ]));
+        }
+    }
+    lines
+}
+
+fn draw_detail(frame: &mut Frame, app: &App, view: &DetailView, area: Rect) {
+    let text = app.text;
+    frame.render_widget(Clear, area);
+    let title = panel_title(text, &format!(" {} ", view.workflow), &view.status);
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let label = |name: &str| Span::styled(pad(name, 24), Style::default().fg(Color::DarkGray));
+    let row = |name: &str, value: String| Line::from(vec![label(name), Span::raw(value)]);
+    let mut lines = Vec::new();
+
+    if let Some(wf) = app.workflows.iter().find(|w| w.name == view.workflow) {
+        if !wf.aliases.is_empty() {
+            lines.push(row(text.aliases, wf.aliases.join(", ")));
+        }
+        if wf.schedule_override.is_empty() {
+            lines.push(row(text.schedule, wf.cron.clone()));
+        } else {
+            lines.push(Line::from(vec![
+                label(text.schedule),
+                Span::styled(wf.cron.clone(), Style::default().fg(Color::Yellow)),
+                Span::raw(format!("  {}", text.temporary_override)),
+            ]));
+        }
+        if !wf.schedule_description.is_empty() && wf.schedule_description != wf.cron {
+            lines.push(Line::from(vec![
+                label(""),
+                Span::styled(wf.schedule_description.clone(), Style::default().fg(Color::Cyan)),
+            ]));
+        }
+        if !wf.script_inline.is_empty() {
+            let count = text.lines.of(wf.script_inline.lines().count());
+            let first = wf.script_inline.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+            lines.push(row(text.script, fill(text.inline_script, &[&count, &first])));
+        } else if wf.steps.is_empty() {
+            let script = if wf.script_path.is_empty() { &wf.script } else { &wf.script_path };
+            lines.push(row(text.script, script.clone()));
+        } else {
+            lines.push(row(text.steps, wf.steps.join(" → ")));
+        }
+        let optional = [
+            (text.message_script, wf.message_script.clone()),
+            (text.interpreter, wf.interpreter.clone()),
+            (text.working_directory, wf.cwd.clone()),
+            (text.arguments, wf.args.join(" ")),
+            (text.stdin, wf.stdin_file.clone()),
+            (text.lock, wf.lock.clone()),
+            (text.baseline_label, wf.baseline_execution_id.clone()),
+        ];
+        for (name, value) in optional.into_iter().filter(|(_, v)| !v.is_empty()) {
+            lines.push(row(name, value));
+        }
+        let next = if wf.paused {
+            text.paused.to_string()
+        } else if wf.next_run_at > 0 {
+            let next = Utc.timestamp_opt(wf.next_run_at, 0).unwrap();
+            let local = next.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+            format!("{} ({})", local, format_countdown(text, next))
+        } else {
+            "-".to_string()
+        };
+        lines.push(row(text.next_run, next));
+        let last = if wf.last_run_at > 0 {
+            let at = Utc.timestamp_opt(wf.last_run_at, 0).unwrap().with_timezone(&Local);
+            let status = text.run_status(&wf.last_run_status);
+            format!("{} {}", at.format("%Y-%m-%d %H:%M"), status)
+        } else {
+            "-".to_string()
+        };
+        lines.push(row(text.last_run, last));
+        if !wf.health.is_empty() {
+            let (icon, style) = health_icon(&wf.health);
+            let health = format!("{}: {}", text.health_state(&wf.health), wf.health_detail);
+            lines.push(Line::from(vec![
+                label(text.health),
+                Span::styled(format!("{} ", icon), style),
+                Span::raw(health),
+            ]));
+        }
+        lines.push(Line::from(""));
+    }
+
+    if let Some(error) = &view.status.error {
+        lines.push(Line::styled(format!("⚠ {}", error), Style::default().fg(Color::Red)));
+    } else if let Some(stats) = &view.stats {
+        let succeeded = stats.total_runs - stats.failures;
+        let success_rate = match stats.total_runs {
+            0 => "-".to_string(),
+            n => {
+                let percent = format!("{:.0}", succeeded as f64 * 100.0 / n as f64);
+                fill(text.success_of, &[&percent, &succeeded, &n])
+            }
+        };
+        lines.push(row(text.success_rate, success_rate));
+        let mut recent = vec![label(text.recent_durations)];
+        for (i, point) in stats.recent.iter().enumerate() {
+            if i > 0 {
+                recent.push(Span::raw("  "));
+            }
+            let (mark, color) = match point.status.as_str() {
+                "success" => ("✓", Color::Green),
+                _ => ("✗", Color::Red),
+            };
+            recent.push(Span::styled(format!("{} ", mark), Style::default().fg(color)));
+            recent.push(Span::raw(secs_label(point.duration_secs)));
+        }
+        if stats.recent.is_empty() {
+            recent.push(Span::raw("-"));
+        }
+        lines.push(Line::from(recent));
+        let streak_style = if stats.current_failure_streak > 0 {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default().fg(Color::Green)
+        };
+        lines.push(Line::from(vec![
+            label(text.current_failure_streak),
+            Span::styled(stats.current_failure_streak.to_string(), streak_style),
+        ]));
+        let longest = stats.longest_failure_streak.to_string();
+        lines.push(row(text.longest_failure_streak, longest));
+        let mtbf = match stats.mtbf_secs {
+            0 => "-".to_string(),
+            secs => secs_label(secs),
+        };
+        lines.push(row(text.mean_time_between_failures, mtbf));
+        for (i, change) in stats.config_changes.iter().enumerate() {
+            let at = Utc.timestamp_opt(change.changed_at, 0).unwrap().with_timezone(&Local);
+            let value = format!("{}  {}", at.format("%Y-%m-%d %H:%M"), change.summary);
+            lines.push(row(if i == 0 { text.config_changes } else { "" }, value));
+        }
+        if stats.config_changes.is_empty() {
+            lines.push(row(text.config_changes, "-".to_string()));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::styled(text.detail_close, Style::default().fg(Color::DarkGray)));
+
+    frame.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: false }), area);
+}
+
+/// How a run was started, for the Executions panel. Scheduled runs (and ones recorded before
+/// sources were) get no label, so the others stand out.
+fn trigger_label(text: &Strings, trigger: &str) -> Option<&'static str> {
+    match trigger {
+        "startup" => Some(text.trigger_on_start),
+        "catch_up" => Some(text.trigger_catch_up),
+        "watch" => Some(text.trigger_file_change),
+        "tui" => Some(text.trigger_manual),
+        "cli" => Some(text.trigger_manual_cli),
+        "webhook" => Some(text.trigger_webhook),
+        "api" => Some(text.trigger_api),
+        _ => None,
+    }
+}
+
+/// `text` padded with spaces to `width` columns, counting a Hangul character as the two it
+/// takes.
+fn pad(text: &str, width: usize) -> String {
+    let used = Span::raw(text).width();
+    format!("{}{}", text, " ".repeat(width.saturating_sub(used)))
+}
+
+fn secs_label(secs: i64) -> String {
+    if secs <= 0 {
+        return "0s".to_string();
+    }
+    format_duration(&std::time::Duration::from_secs(secs.max(0) as u64))
+}
+
+/// A rectangle of `percent_x` by `percent_y` of `area`, centered in it.
+fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+fn format_countdown(text: &Strings, next: DateTime<Utc>) -> String {
     let secs = (next - Utc::now()).num_seconds();
     if secs <= 0 {
-        return "now".to_string();
+        return text.now.to_string();
     }
     let h = secs / 3600;
     let m = (secs % 3600) / 60;
     let s = secs % 60;
     if h > 0 {
-        format!("in {}h {}m", h, m)
+        fill(text.in_hours, &[&h, &m])
     } else if m > 0 {
-        format!("in {}m {}s", m, s)
+        fill(text.in_minutes, &[&m, &s])
     } else {
-        format!("in {}s", s)
+        fill(text.in_seconds, &[&s])
+    }
+}
+
+/// `Disconnected from the runner for 12s, retrying in 4s` while it does not answer.
+fn connection_banner(app: &App) -> Option<Line<'static>> {
+    let text = app.text;
+    let disconnected = app.disconnected.as_ref()?;
+    let now = Instant::now();
+    let retry = match disconnected.retry_at.checked_duration_since(now) {
+        Some(wait) if !wait.is_zero() => {
+            fill(text.retrying_in, &[&secs_label(wait.as_secs() as i64 + 1)])
+        }
+        _ => text.reconnecting.to_string(),
+    };
+    let down = secs_label(now.duration_since(disconnected.since).as_secs() as i64);
+    let banner = fill(text.disconnected, &[&down, &retry]);
+    Some(Line::styled(banner, Style::default().fg(Color::White).bg(Color::Red)))
+}
+
+fn offline_banner(app: &App) -> Option<Line<'static>> {
+    if !app.client.is_offline() {
+        return None;
     }
+    Some(Line::styled(app.text.offline, Style::default().fg(Color::Black).bg(Color::Cyan)))
+}
+
+/// Schedules that fired while the runner was down, e.g.
+/// `Missed while the runner was down: backup (10-13 03:00), report (10-13 09:00)`.
+fn missed_banner(app: &App) -> Option<Line<'static>> {
+    if app.missed.is_empty() {
+        return None;
+    }
+    let runs: Vec<String> = app
+        .missed
+        .iter()
+        .map(|run| {
+            let at = Utc.timestamp_opt(run.missed_at, 0).unwrap().with_timezone(&Local);
+            let catching_up = if run.caught_up { app.text.catching_up } else { "" };
+            format!("{} ({}{})", run.workflow, at.format("%m-%d %H:%M"), catching_up)
+        })
+        .collect();
+    let banner = fill(app.text.missed, &[&runs.join(", ")]);
+    Some(Line::styled(banner, Style::default().fg(Color::Black).bg(Color::Yellow)))
 }
 
 fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
-    let help = if app.status_message.is_empty() {
-        "[←→] switch panel  [w] workflows  [↑↓] select  [r] run now  [q] quit"
+    let text = app.text;
+    if let Some(search) = app.search.as_ref().filter(|s| s.editing) {
+        let input = Paragraph::new(format!("/{}█", search.query));
+        frame.render_widget(input, area);
+        return;
+    }
+    if let Some(prompt) = &app.export {
+        let selected = app.selection_start.is_some();
+        let what = if selected { text.export_selection } else { text.export_log };
+        let target = match prompt.kind {
+            ExportKind::File => text.export_file,
+            ExportKind::Pipe => text.export_command,
+        };
+        let hint = fill(text.export_hint, &[&what, &target, &app.keymap.label(Action::Cancel)]);
+        let input = format!("{} {}█   {}", prompt.kind.prompt(), prompt.input, hint);
+        let input = Paragraph::new(input);
+        frame.render_widget(input, area);
+        return;
+    }
+
+    let keys = &app.keymap;
+    let key = |action| keys.label(action);
+    let help = if let Some(label) = app.busy_with() {
+        fill(text.busy, &[&label, &key(Action::Cancel)])
+    } else if !app.status_message.is_empty() {
+        app.status_message.clone()
+    } else if app.search.is_some() {
+        fill(text.search_hint, &[&key(Action::Search), &key(Action::Cancel)])
+    } else if app.active_panel == Panel::Log {
+        fill(
+            text.log_hint,
+            &[
+                &key(Action::Up),
+                &key(Action::Down),
+                &key(Action::PageUp),
+                &key(Action::PageDown),
+                &key(Action::Search),
+                &key(Action::NextPanel),
+                &key(Action::Quit),
+            ],
+        )
     } else {
-        &app.status_message
+        fill(
+            text.lists_hint,
+            &[
+                &key(Action::Up),
+                &key(Action::Down),
+                &key(Action::Run),
+                &key(Action::Pause),
+                &key(Action::Quit),
+            ],
+        )
     };
 
     let paragraph = Paragraph::new(help).style(Style::default().fg(Color::DarkGray));